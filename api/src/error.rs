@@ -5,7 +5,7 @@ use axum::{
     response::{IntoResponse, Response},
     BoxError, Json,
 };
-use jito_merkle_tree::error::MerkleTreeError;
+use jito_merkle_tree::error::DistributorError;
 use serde_derive::{Deserialize, Serialize};
 use serde_json::json;
 use solana_program::pubkey::ParsePubkeyError;
@@ -15,8 +15,8 @@ use tracing::log::error;
 
 #[derive(Error, Debug)]
 pub enum ApiError {
-    #[error("Merkle Tree Validation Error: {0}")]
-    MerkleTreeError(#[from] MerkleTreeError),
+    #[error("Distributor Error: {0}")]
+    DistributorError(#[from] DistributorError),
 
     #[error("User {0} not found")]
     UserNotFound(String),
@@ -47,8 +47,8 @@ pub struct Error {
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
         let (status, error_message) = match self {
-            ApiError::MerkleTreeError(_) => {
-                error!("Merkle Tree Error: {:?}", self);
+            ApiError::DistributorError(_) => {
+                error!("Distributor Error: {:?}", self);
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error")
             }
             ApiError::UserNotFound(s) => {
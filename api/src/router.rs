@@ -147,6 +147,12 @@ async fn get_claim_status(
 
     let claim_status_account = accounts.pop().ok_or_else(|| ApiError::InternalError)?;
 
+    // nodes with a custom unlock schedule vest on their own window
+    let (start_ts, end_ts) = node.unlock_schedule.map_or(
+        (distributor.start_ts, distributor.end_ts),
+        |schedule| (schedule.start_ts, schedule.end_ts),
+    );
+
     if distributor.clawed_back {
         match claim_status_account {
             Some(claim_status_account) => {
@@ -217,8 +223,10 @@ async fn get_claim_status(
                                     .duration_since(UNIX_EPOCH)
                                     .unwrap()
                                     .as_secs() as i64,
-                                distributor.start_ts,
-                                distributor.end_ts,
+                                start_ts,
+                                end_ts,
+                                distributor.cliff_ts,
+                                distributor.vesting_interval_secs,
                             )
                             .unwrap(),
                         amount_locked_withdrawn: claim_status.locked_amount_withdrawn,
@@ -251,8 +259,10 @@ async fn get_claim_status(
                                     .duration_since(UNIX_EPOCH)
                                     .unwrap()
                                     .as_secs() as i64,
-                                distributor.start_ts,
-                                distributor.end_ts,
+                                start_ts,
+                                end_ts,
+                                distributor.cliff_ts,
+                                distributor.vesting_interval_secs,
                             )
                             .unwrap(),
                             amount_locked_withdrawn: 0, /* never withdrew any because account doesn't exist */
@@ -287,8 +297,10 @@ async fn get_claim_status(
                             .duration_since(UNIX_EPOCH)
                             .unwrap()
                             .as_secs() as i64,
-                        distributor.start_ts,
-                        distributor.end_ts,
+                        start_ts,
+                        end_ts,
+                        distributor.cliff_ts,
+                        distributor.vesting_interval_secs,
                     )
                     .unwrap(),
                     amount_locked_withdrawn: 0, // never withdrew any because account doesn't exist
@@ -303,22 +315,21 @@ pub async fn read_distributor(
     rpc_client: &RpcClient,
     distributor: &Pubkey,
 ) -> Result<MerkleDistributor> {
-    return match rpc_client.get_account_data(distributor).await {
-        Ok(data) => {
-            return match MerkleDistributor::try_deserialize(&mut data.as_slice()) {
+    return match rpc_client
+        .get_account_with_commitment(distributor, rpc_client.commitment())
+        .await
+        .map_err(ApiError::RpcError)?
+        .value
+    {
+        Some(account) => {
+            return match MerkleDistributor::try_deserialize(&mut account.data.as_slice()) {
                 Ok(distributor) => Ok(distributor),
                 Err(e) => Err(ApiError::MerkleDistributorError(e.to_string())),
             };
         }
-        Err(e) => {
-            if e.kind.to_string().contains("AccountNotFound") {
-                Err(ApiError::MerkleDistributorError(
-                    "Merkle Distributor not found".to_string(),
-                ))
-            } else {
-                Err(ApiError::RpcError(e))
-            }
-        }
+        None => Err(ApiError::MerkleDistributorError(
+            "Merkle Distributor not found".to_string(),
+        )),
     };
 }
 
@@ -359,6 +370,8 @@ struct Distributor {
     pub start_ts: i64,
     /// Lockup time end (Unix Timestamp)
     pub end_ts: i64,
+    /// Cliff (Unix Timestamp), no locked tokens unlock before it
+    pub cliff_ts: i64,
     /// Clawback start (Unix Timestamp)
     pub clawback_start_ts: i64,
     /// Clawback receiver
@@ -367,6 +380,10 @@ struct Distributor {
     pub admin: Pubkey,
     /// Whether or not the distributor has been clawed back
     pub clawed_back: bool,
+    /// Minimum seconds between two claim_locked of the same claimant
+    pub min_claim_interval: i64,
+    /// Locked tokens unlock in steps of this many seconds, 1 for linear vesting
+    pub vesting_interval_secs: i64,
 }
 
 async fn get_distributor(State(state): State<Arc<RouterState>>) -> Result<Json<Distributor>> {
@@ -384,10 +401,13 @@ async fn get_distributor(State(state): State<Arc<RouterState>>) -> Result<Json<D
         num_nodes_claimed: d.num_nodes_claimed,
         start_ts: d.start_ts,
         end_ts: d.end_ts,
+        cliff_ts: d.cliff_ts,
         clawback_start_ts: d.clawback_start_ts,
         clawback_receiver: d.clawback_receiver,
         admin: d.admin,
         clawed_back: d.clawed_back,
+        min_claim_interval: d.min_claim_interval,
+        vesting_interval_secs: d.vesting_interval_secs,
     }))
 }
 
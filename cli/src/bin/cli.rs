@@ -1,42 +1,172 @@
 extern crate jito_merkle_tree;
 extern crate merkle_distributor;
 
-use std::path::PathBuf;
+mod batch;
+mod config;
+mod failover;
+mod instruction_json;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod monitor;
+mod network;
+mod output;
+mod progress;
+mod proofs;
+mod reconcile;
+mod send;
+mod snapshot;
+mod watch;
+
+use std::{
+    collections::{HashMap, HashSet},
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
 use anchor_lang::{
-    prelude::Pubkey, AccountDeserialize, AnchorDeserialize, InstructionData, Key, ToAccountMetas,
+    prelude::Pubkey, AccountDeserialize, AnchorDeserialize, Discriminator, InstructionData,
+    ToAccountMetas,
 };
 use anchor_spl::token;
-use clap::{Parser, Subcommand};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use clap::{CommandFactory, ErrorKind, Parser, Subcommand, ValueEnum};
+use futures::StreamExt;
 use jito_merkle_tree::{
-    airdrop_merkle_tree::AirdropMerkleTree,
-    utils::{get_claim_status_pda, get_merkle_distributor_pda},
+    airdrop_merkle_tree::{
+        check_empty_nodes, check_unlock_schedules_end_by, AirdropMerkleTree, AllocationOptions,
+    },
+    csv_entry::AirdropCategory,
+    error::DistributorError,
+    tree_node::TreeNode,
+    utils::{
+        get_claim_status_address, get_merkle_distributor_pda, get_sol_vault_pda, next_free_version,
+        TreeBuildProgress, VersionScheme,
+    },
 };
 use light_client::{
-    indexer::{AddressWithTree, Indexer},
-    rpc::{LightClient, LightClientConfig, Rpc},
+    indexer::{AddressWithTree, CompressedAccount, Indexer, IndexerError, TreeInfo},
+    rpc::{LightClient, LightClientConfig, Rpc, RpcError},
 };
-use light_sdk::instruction::{
-    account_meta::CompressedAccountMeta, PackedAccounts, PackedStateTreeInfo,
-    SystemAccountMetaConfig,
+use light_compressed_account::TreeType;
+use light_sdk::{
+    constants::ACCOUNT_COMPRESSION_PROGRAM_ID,
+    instruction::{
+        account_meta::CompressedAccountMeta, PackedAccounts, PackedStateTreeInfo,
+        SystemAccountMetaConfig, ValidityProof,
+    },
 };
 use merkle_distributor::state::{
-    claim_status::{ClaimStatus, ClaimStatusInstructionData},
-    merkle_distributor::MerkleDistributor,
+    claim_status::{
+        CategoryBreakdown, ClaimStatus, ClaimStatusInstructionData, LegacyClaimStatus,
+        UnlockSchedule,
+    },
+    merkle_distributor::{DistributionMode, MerkleDistributor},
+    merkle_distributor_view::MerkleDistributorView,
+};
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_nonce::{state::State, versions::Versions};
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_pubsub_client::nonblocking::pubsub_client::PubsubClient;
+use solana_remote_wallet::{
+    locator::{Locator, Manufacturer},
+    remote_keypair::{generate_remote_keypair, RemoteKeypair},
+    remote_wallet::{maybe_wallet_manager, RemoteWalletError},
+};
+use solana_rpc_client::rpc_client::{RpcClient, RpcClientConfig};
+use solana_rpc_client_api::{
+    client_error::Error as ClientError,
+    config::{
+        RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSimulateTransactionConfig,
+        RpcTransactionLogsConfig, RpcTransactionLogsFilter,
+    },
+    filter::{Memcmp, RpcFilterType},
+    response::RpcSimulateTransactionResult,
 };
-use solana_program::instruction::Instruction;
-use solana_rpc_client::rpc_client::RpcClient;
 use solana_sdk::{
-    account::Account, commitment_config::CommitmentConfig,
-    compute_budget::ComputeBudgetInstruction, signature::read_keypair_file, signer::Signer,
-    transaction::Transaction,
+    account::Account,
+    commitment_config::CommitmentConfig,
+    compute_budget::ComputeBudgetInstruction,
+    derivation_path::DerivationPath,
+    hash::Hash,
+    instruction::InstructionError,
+    signature::{read_keypair_file, Keypair, Signature},
+    signer::Signer,
+    sysvar::clock::{self, Clock},
+    transaction::{Transaction, TransactionError},
 };
+use solana_system_interface::instruction::{advance_nonce_account, transfer};
 use spl_associated_token_account::{
     get_associated_token_address, instruction::create_associated_token_account,
 };
+use tracing::{debug, error, info, trace, warn, Level};
+use tracing_subscriber::EnvFilter;
+
+use crate::{
+    batch::{
+        batch_instructions, pack_claims, read_keypair_paths, WalletClaim, MAX_COMPUTE_UNIT_LIMIT,
+    },
+    config::{config_env, config_path, read_config},
+    failover::FailoverSender,
+    instruction_json::instructions_to_json,
+    monitor::{Alert, DistributorSnapshot, HealthMonitor, MonitorThresholds},
+    network::Network,
+    output::{
+        print_json, say, set_output_format, BatchClaimOutput, BatchClaimWallet, ClaimLoopOutput,
+        ClaimOutput, ClaimStatusAddressEntry, ClaimStatusAddressFile, ClaimStatusView,
+        ClaimantEntry, ClawbackOutput, CloseDistributorOutput, CreateMerkleTreeOutput,
+        DeriveAddressesOutput, DistributorInfoOutput, DistributorSummary, EligibilityOutput,
+        ErrorOutput, ExportClaimsOutput, FundVaultOutput, ListClaimantsOutput,
+        ListDistributorsOutput, MergeOutput, MigrateClaimStatusOutput, MigrateDistributorOutput,
+        NewDistributorOutput, OutputFormat, PartialClawbackOutput, ReassignClaimOutput,
+        ReconcileOutput, RenounceAdminOutput, SetAdminOutput, SetClawbackReceiverOutput,
+        SetPausedOutput, SimulateClaimOutput, SimulatedClaimEvent, StatusOutput,
+        UnsignedTransactionOutput, UpdateClawbackStartOutput, UpdateRootOutput, VerifyClaimOutput,
+        VerifyProofOutput, VerifyTreeOutput, WatchClaimOutput, WithdrawableOutput,
+    },
+    progress::ProgressBar,
+    proofs::{ProofFetcher, ProofKey, PROOF_BATCH_SIZE},
+    reconcile::{
+        is_reassigned, read_reassignments, reconcile, write_claims_csv, ClaimExportRow,
+        DistributorTotals, NodeReconciliation, Reassignment,
+    },
+    send::{
+        parse_escalation_multiplier, send_with_retry, with_compute_budget, PriorityFeeEscalation,
+    },
+    snapshot::{diff_snapshots, read_snapshot, ClaimSnapshot, SnapshotHeader, SnapshotWriter},
+    watch::{claim_events, websocket_url, ClaimKind},
+};
 
 const NEW_CLAIM_COMPUTE_UNITS: u32 = 400_000;
 const CLAIM_LOCKED_COMPUTE_UNITS: u32 = 500_000;
+const REASSIGN_CLAIM_COMPUTE_UNITS: u32 = 600_000;
+
+/// SPL token program `TokenError::InsufficientFunds`, surfaced as a custom instruction error
+/// when the vault cannot cover a transfer.
+const TOKEN_INSUFFICIENT_FUNDS: u32 = 1;
+
+/// Distributor program `InsufficientVaultBalance`, checked before the vault pays out.
+const VAULT_INSUFFICIENT_BALANCE: u32 = anchor_lang::error::ERROR_CODE_OFFSET
+    + merkle_distributor::error::ErrorCode::InsufficientVaultBalance as u32;
+
+/// Distributor program `RecipientAccountFrozen`, checked before a claim pays out.
+const RECIPIENT_ACCOUNT_FROZEN: u32 = anchor_lang::error::ERROR_CODE_OFFSET
+    + merkle_distributor::error::ErrorCode::RecipientAccountFrozen as u32;
+
+/// Account discriminator of v2 (batched) merkle trees in the account compression program.
+const BATCHED_MERKLE_TREE_DISCRIMINATOR: [u8; 8] = *b"BatchMta";
+
+/// Default ceiling for escalated priority fees, in microlamports.
+const DEFAULT_MAX_PRIORITY_FEE: u64 = 1_000_000;
+
+/// First delay when polling the indexer for a new compressed account, doubled per attempt.
+const INDEXER_POLL_INITIAL_DELAY: Duration = Duration::from_millis(250);
+const INDEXER_POLL_MAX_DELAY: Duration = Duration::from_secs(4);
+
+/// Claim status addresses requested from the indexer per call in `dump-state` and `reconcile`.
+const CLAIM_STATUS_BATCH_SIZE: usize = 100;
+
+type Result<T> = std::result::Result<T, DistributorError>;
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
@@ -44,7 +174,14 @@ pub struct Args {
     #[clap(subcommand)]
     pub command: Commands,
 
-    /// Airdrop version
+    /// TOML file, or JSON file ending in `.json`, with defaults for the flags below, keyed by
+    /// flag name, e.g. `mint = "..."` and `rpc_url = "..."`. Command line flags and
+    /// environment variables take precedence over it.
+    #[clap(long, env)]
+    pub config: Option<PathBuf>,
+
+    /// Airdrop version, part of the distributor PDA seeds. Any u64 works: a counter,
+    /// a YYYYMMDD date, a unix timestamp or a campaign id. See `list-distributors`.
     #[clap(long, env, default_value_t = 0)]
     pub airdrop_version: u64,
 
@@ -52,11 +189,12 @@ pub struct Args {
     #[clap(long, env)]
     pub mint: Pubkey,
 
-    /// RPC url
-    #[clap(long, env)]
-    pub rpc_url: String,
+    /// RPC url. Give it several times, or comma-separated, to fail over to the next endpoint
+    /// when one cannot be reached or times out
+    #[clap(long, env, required = true, use_value_delimiter = true)]
+    pub rpc_url: Vec<String>,
 
-    /// Photon indexer URL (defaults to RPC url if not specified)
+    /// Photon indexer URL (defaults to the first RPC url if not specified)
     #[clap(long, env)]
     pub photon_url: Option<String>,
 
@@ -65,12 +203,154 @@ pub struct Args {
     pub program_id: Pubkey,
 
     /// Payer keypair
+    #[clap(long, env, required_unless_present_any = &["authority", "ledger"])]
+    pub keypair_path: Option<PathBuf>,
+
+    /// Sign as the payer with a Ledger connected over USB instead of the `--keypair-path`
+    /// keypair, approving each transaction on the device. Takes an optional
+    /// `<account>/<change>` key path, e.g. `--ledger=1/0`, and uses the wallet's first key,
+    /// m/44'/501', without one
+    #[clap(
+        long,
+        value_name = "KEY_PATH",
+        min_values = 0,
+        require_equals = true,
+        conflicts_with = "keypair_path"
+    )]
+    pub ledger: Option<Option<String>>,
+
+    /// Pubkey to build transactions for in place of the `--keypair-path` keypair, for signers
+    /// whose key is not on this machine. Combine with `--offline`.
+    #[clap(long, env)]
+    pub authority: Option<Pubkey>,
+
+    /// Keypair paying the transaction fees and token account rent of claims instead of the
+    /// claimant, e.g. a relayer. The claimant still signs as the token account authority.
     #[clap(long, env)]
-    pub keypair_path: PathBuf,
+    pub fee_payer: Option<PathBuf>,
+
+    /// Cluster whose Light Protocol address and state trees to use. `--address-tree` and
+    /// `--state-tree` override its trees.
+    #[clap(long, env, value_enum, default_value = "mainnet")]
+    pub network: Network,
+
+    /// Address tree holding the claim status accounts, overriding the `--network` one. The
+    /// program only accepts the address tree it was built with, so change this together with
+    /// the program deployment.
+    #[clap(long, env, alias = "address-merkle-tree")]
+    pub address_tree: Option<Pubkey>,
+
+    /// v2 state tree receiving new and updated claim status accounts, one of the `--network`
+    /// state trees. Defaults to a random one for new claims and the account's current tree for
+    /// claim_locked.
+    /// Set it to spread writes across trees or to move accounts off a tree being rolled over.
+    #[clap(long, env, alias = "output-tree")]
+    pub state_tree: Option<Pubkey>,
 
     /// Priority fee
     #[clap(long, env)]
     pub priority: Option<u64>,
+
+    /// Compute unit limit of `claim` transactions. Without it the transaction is simulated
+    /// and the limit set to the units consumed plus `--compute-unit-margin`, falling back to
+    /// a fixed limit when the simulation fails
+    #[clap(long, env)]
+    pub compute_units: Option<u32>,
+
+    /// Percentage added to the simulated compute units
+    #[clap(long, env, default_value_t = 20)]
+    pub compute_unit_margin: u32,
+
+    /// Times to resend a transaction that expired before landing or hit a lagging RPC node.
+    /// Transactions on a durable nonce or a given --blockhash are sent once
+    #[clap(long, env, default_value_t = 3)]
+    pub max_retries: u32,
+
+    /// Multiply the priority fee by this factor on each retry, at least 1
+    #[clap(long, env, default_value_t = 1.5, value_parser = parse_escalation_multiplier)]
+    pub priority_escalation: f64,
+
+    /// Ceiling for the escalated priority fee, in microlamports
+    #[clap(long, env, default_value_t = DEFAULT_MAX_PRIORITY_FEE)]
+    pub max_priority: u64,
+
+    /// How long to wait for the indexer to pick up a newly created claim
+    #[clap(long, env, default_value_t = 30)]
+    pub indexer_timeout_secs: u64,
+
+    /// Slots the indexer may trail the RPC node by before claim commands refuse to start
+    #[clap(long, env, default_value_t = 150)]
+    pub max_indexer_lag: u64,
+
+    /// Skip comparing the indexer's slot with the RPC node's before claim commands, e.g. for
+    /// indexers without `getIndexerSlot`
+    #[clap(long, env)]
+    pub skip_indexer_check: bool,
+
+    /// Log filter such as `debug`, or `trace` to log the Light system program inputs built for
+    /// each claim. Progress goes to stderr at `info`
+    #[clap(long, env = "RUST_LOG", default_value = "info")]
+    pub log_level: String,
+
+    /// Print the instructions of action commands as JSON instead of signing and sending them
+    #[clap(long)]
+    pub output_instructions: bool,
+
+    /// Skip the confirmation prompt of new-distributor and clawback, for automation
+    #[clap(long, env)]
+    pub yes: bool,
+
+    /// Print the transactions of action commands unsigned as base64, for signing offline,
+    /// instead of signing and sending them
+    #[clap(long)]
+    pub offline: bool,
+
+    /// Write the transaction of action commands unsigned as base64 to this file instead of
+    /// signing and sending it, e.g. to import it into a Squads multisig
+    #[clap(long, env)]
+    pub export_tx: Option<PathBuf>,
+
+    /// Blockhash to build transactions with instead of fetching the latest one. With
+    /// `--nonce-account` it must be the nonce stored in that account.
+    #[clap(long, env)]
+    pub blockhash: Option<Hash>,
+
+    /// Durable nonce account. Transactions advance it in their first instruction and use its
+    /// nonce as the blockhash, so they stay valid until they are signed and sent.
+    #[clap(long, env)]
+    pub nonce_account: Option<Pubkey>,
+
+    /// Authority of `--nonce-account`, defaults to the transaction fee payer
+    #[clap(long, env, requires = "nonce_account")]
+    pub nonce_authority: Option<Pubkey>,
+
+    /// Output format. `json` prints one JSON object per command on stdout and sends
+    /// everything else to stderr
+    #[clap(long, value_enum, default_value = "text")]
+    pub output: OutputFormat,
+
+    /// Commitment of every account read and transaction confirmation. `finalized` waits
+    /// longer but cannot be rolled back, worth it before acting on what was read, e.g. for
+    /// new-distributor, clawback and admin changes in scripts
+    #[clap(long, env, value_enum, default_value = "confirmed")]
+    pub commitment: Commitment,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Commitment {
+    Processed,
+    Confirmed,
+    Finalized,
+}
+
+impl Commitment {
+    fn config(self) -> CommitmentConfig {
+        match self {
+            Commitment::Processed => CommitmentConfig::processed(),
+            Commitment::Confirmed => CommitmentConfig::confirmed(),
+            Commitment::Finalized => CommitmentConfig::finalized(),
+        }
+    }
 }
 
 // Subcommands
@@ -78,14 +358,81 @@ pub struct Args {
 pub enum Commands {
     /// Claim unlocked tokens
     Claim(ClaimArgs),
+    /// Claim for many wallets at once, packing several claims into each transaction
+    BatchClaim(BatchClaimArgs),
     /// Create a new instance of a merkle distributor
     NewDistributor(NewDistributorArgs),
+    /// Mint or transfer tokens into the distributor's token vault
+    FundVault(FundVaultArgs),
     /// Clawback tokens from merkle distributor
     #[clap(hide = true)]
     Clawback(ClawbackArgs),
+    /// Claw back part of the vault, keeping what unclaimed allocations still need
+    PartialClawback(PartialClawbackArgs),
     /// Create a Merkle tree, given a CSV of recipients
     CreateMerkleTree(CreateMerkleTreeArgs),
+    /// Combine several CSVs and tree files into one tree, summing claimants found in more than
+    /// one of them
+    Merge(MergeArgs),
+    /// Print the claimants and amounts in a merkle tree file
+    ListClaimants(ListClaimantsArgs),
+    /// Check offline whether a wallet is in a tree file and what it is allocated
+    Eligibility(EligibilityArgs),
     SetAdmin(SetAdminArgs),
+    /// Pause or resume claims on the distributor (admin only)
+    SetPaused(SetPausedArgs),
+    /// Change the token account clawback sends the remaining vault balance to (admin only)
+    SetClawbackReceiver(SetClawbackReceiverArgs),
+    /// Postpone the time from which clawback is allowed (admin only)
+    UpdateClawbackStart(UpdateClawbackStartArgs),
+    /// Replace the distributor's root with that of a tree adding new recipients (admin only)
+    UpdateRoot(UpdateRootArgs),
+    /// Move an unclaimed allocation to a new wallet, e.g. after a lost key (admin only)
+    ReassignClaim(ReassignClaimArgs),
+    /// Close a clawed back distributor and its empty vault, returning the rent to the admin
+    CloseDistributor,
+    /// Give up admin control of the distributor for good (admin only)
+    RenounceAdmin,
+    /// Extend a distributor created by an earlier program release to the current account
+    /// layout, so the other instructions can load it
+    MigrateDistributor,
+    /// Rewrite a claim status created by an earlier program release in the current layout, so
+    /// claim_locked can spend it
+    MigrateClaimStatus(MigrateClaimStatusArgs),
+    /// Poll the distributor and alert on vault shortfalls, claim spikes and clawbacks
+    Monitor(MonitorArgs),
+    /// Stream the distributor's claims as they land, until Ctrl-C
+    Watch(WatchArgs),
+    /// Check that a claimant's tokens arrived and how much remains locked
+    VerifyClaim(VerifyClaimArgs),
+    /// List every distributor for the mint and suggest the next free airdrop version
+    ListDistributors,
+    /// Print the distributor's on-chain state and its vault balance
+    DistributorInfo,
+    /// Show how much of the payer's allocation has been claimed and what is still vesting
+    Status(StatusArgs),
+    /// Print the locked amount claim_locked would withdraw right now
+    Withdrawable(WithdrawableArgs),
+    /// Keep withdrawing the payer's locked tokens as they vest, until all are withdrawn
+    ClaimLoop(ClaimLoopArgs),
+    /// Write the distributor, its vault balance and every claim status to a snapshot file
+    DumpState(DumpStateArgs),
+    /// Check offline that a claimant's proof in the tree file verifies against its root
+    VerifyProof(VerifyProofArgs),
+    /// Check offline that a tree file's root and every proof in it match its nodes
+    VerifyTree(VerifyTreeArgs),
+    /// Compare two snapshots written by dump-state
+    DiffState(DiffStateArgs),
+    /// Report which nodes of the tree file have been claimed and check the distributor's totals
+    Reconcile(ReconcileArgs),
+    /// Write a CSV of what every node of the tree file is eligible for and has claimed, for
+    /// accounting
+    ExportClaims(ExportClaimsArgs),
+    /// Write the claim status address of every claimant in the tree file, offline
+    DeriveAddresses(DeriveAddressesArgs),
+    /// Simulate the payer's next claim transaction and report its result, compute units and
+    /// logs, without sending it
+    SimulateClaim(SimulateClaimArgs),
 }
 
 // NewClaim and Claim subcommand args
@@ -94,15 +441,57 @@ pub struct ClaimArgs {
     /// Merkle distributor path
     #[clap(long, env)]
     pub merkle_tree_path: PathBuf,
+
+    /// Skip claim_locked while less than this many locked tokens are withdrawable, so bots
+    /// do not spend more on fees than they claim
+    #[clap(long, env, default_value_t = 0)]
+    pub min_unlock_to_claim: u64,
+
+    /// Wallet to send the tokens to instead of the claimant's, e.g. a cold wallet. Its token
+    /// account is created when missing. The claim itself stays the claimant's
+    #[clap(long, env)]
+    pub recipient: Option<Pubkey>,
+}
+
+#[derive(Parser, Debug)]
+pub struct SimulateClaimArgs {
+    /// Merkle distributor path
+    #[clap(long, env)]
+    pub merkle_tree_path: PathBuf,
+
+    /// Wallet the claim would send the tokens to instead of the claimant's
+    #[clap(long, env)]
+    pub recipient: Option<Pubkey>,
+}
+
+#[derive(Parser, Debug)]
+pub struct BatchClaimArgs {
+    /// Claimant keypairs: a directory of `.json` keypair files, or a file listing one keypair
+    /// path per line
+    #[clap(long, env)]
+    pub keypairs: PathBuf,
+
+    /// Merkle distributor path
+    #[clap(long, env)]
+    pub merkle_tree_path: PathBuf,
+
+    /// Skip claim_locked for wallets with less than this many locked tokens withdrawable
+    #[clap(long, env, default_value_t = 0)]
+    pub min_unlock_to_claim: u64,
 }
 
 // NewDistributor subcommand args
 #[derive(Parser, Debug)]
 pub struct NewDistributorArgs {
-    /// Clawback receiver token account
+    /// Clawback receiver token account, or wallet with --native
     #[clap(long, env)]
     pub clawback_receiver_token_account: Pubkey,
 
+    /// Distribute native SOL held in a SOL vault instead of tokens. Requires --mint to be the
+    /// native mint, So11111111111111111111111111111111111111112
+    #[clap(long, env)]
+    pub native: bool,
+
     /// Lockup timestamp start
     #[clap(long, env)]
     pub start_vesting_ts: i64,
@@ -111,6 +500,11 @@ pub struct NewDistributorArgs {
     #[clap(long, env)]
     pub end_vesting_ts: i64,
 
+    /// Cliff (unix timestamp): no locked tokens unlock before it, then everything vested since
+    /// the start unlocks at once. Defaults to the start, i.e. plain linear vesting
+    #[clap(long, env)]
+    pub cliff_ts: Option<i64>,
+
     /// Merkle distributor path
     #[clap(long, env)]
     pub merkle_tree_path: PathBuf,
@@ -118,6 +512,33 @@ pub struct NewDistributorArgs {
     /// When to make the clawback period start. Must be at least a day after the end_vesting_ts
     #[clap(long, env)]
     pub clawback_start_ts: i64,
+
+    /// Minimum seconds a claimant has to wait between two withdrawals of locked tokens
+    #[clap(long, env, default_value_t = 0)]
+    pub min_claim_interval: i64,
+
+    /// Unlock locked tokens in steps of this many seconds since the start, e.g. 2592000 for
+    /// 30 day tranches. Defaults to 1, i.e. linear vesting
+    #[clap(long, env, default_value_t = 1)]
+    pub vesting_interval_secs: i64,
+
+    /// Let the admin move unclaimed allocations to other wallets with reassign-claim. Cannot
+    /// be changed after creation, nor combined with --native
+    #[clap(long, env)]
+    pub allow_reassignment: bool,
+}
+
+impl NewDistributorArgs {
+    fn cliff_ts(&self) -> i64 {
+        self.cliff_ts.unwrap_or(self.start_vesting_ts)
+    }
+
+    fn distribution_mode(&self) -> DistributionMode {
+        match self.native {
+            true => DistributionMode::Native,
+            false => DistributionMode::Token,
+        }
+    }
 }
 
 #[derive(Parser, Debug)]
@@ -126,532 +547,6650 @@ pub struct ClawbackArgs {
     pub clawback_keypair_path: PathBuf,
 }
 
+#[derive(Parser, Debug)]
+pub struct PartialClawbackArgs {
+    #[clap(long, env)]
+    pub clawback_keypair_path: PathBuf,
+    /// Tokens to claw back, in base units. At most the vault balance above the allocations
+    /// not claimed yet
+    #[clap(long, env)]
+    pub amount: u64,
+}
+
 #[derive(Parser, Debug)]
 pub struct CreateMerkleTreeArgs {
     /// CSV path
+    #[clap(
+        long,
+        env,
+        required_unless_present = "json_path",
+        conflicts_with = "json_path"
+    )]
+    pub csv_path: Option<PathBuf>,
+
+    /// JSON path, an array of objects with the CSV columns as fields
     #[clap(long, env)]
-    pub csv_path: PathBuf,
+    pub json_path: Option<PathBuf>,
 
     /// Merkle tree out path
     #[clap(long, env)]
     pub merkle_tree_path: PathBuf,
-}
 
-#[derive(Parser, Debug)]
-pub struct SetAdminArgs {
+    /// Mint decimals. When set, CSV amounts are decimal UI amounts such as `10.5`; otherwise
+    /// they are whole tokens of a 9 decimal mint
     #[clap(long, env)]
-    pub new_admin: Pubkey,
-}
-
-#[tokio::main]
-async fn main() {
-    let args = Args::parse();
+    pub decimals: Option<u32>,
 
-    match &args.command {
-        Commands::NewDistributor(new_distributor_args) => {
-            process_new_distributor(&args, new_distributor_args);
-        }
-        Commands::Claim(claim_args) => {
-            process_claim(&args, claim_args).await;
-        }
-        Commands::Clawback(clawback_args) => process_clawback(&args, clawback_args),
-        Commands::CreateMerkleTree(merkle_tree_args) => {
-            process_create_merkle_tree(merkle_tree_args);
-        }
-        Commands::SetAdmin(set_admin_args) => {
-            process_set_admin(&args, set_admin_args);
-        }
-    }
-}
+    /// Sum the amounts of claimants listed more than once into a single node. Without it,
+    /// repeated claimants are an error naming the lines they appear on
+    #[clap(long)]
+    pub merge_duplicates: bool,
 
-async fn process_new_claim(args: &Args, claim_args: &ClaimArgs) {
-    let keypair = read_keypair_file(&args.keypair_path).expect("Failed reading keypair file");
-    let claimant = keypair.pubkey();
-    println!("Claiming tokens for user {}...", claimant);
+    /// Keep claimants whose amounts are all zero. Without it they are an error, since each
+    /// takes a `max_num_nodes` slot and claiming it pays out nothing
+    #[clap(long)]
+    pub allow_empty_nodes: bool,
 
-    let merkle_tree = AirdropMerkleTree::new_from_file(&claim_args.merkle_tree_path)
-        .expect("failed to load merkle tree from file");
+    /// Clawback start the distributor will get. Per-node unlock schedules ending after it are
+    /// an error, since clawback takes whatever they have not unlocked yet
+    #[clap(long, env)]
+    pub clawback_start_ts: Option<i64>,
 
-    let (distributor, _bump) =
-        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+    /// Merkle tree file format. Commands reading the file detect it automatically
+    #[clap(long, value_enum, default_value = "json")]
+    pub format: TreeFileFormat,
 
-    // Get user's node in claim
-    let node = merkle_tree.get_node(&claimant);
-    let (claim_status_address, _address_seed) = get_claim_status_pda(
-        &args.program_id,
-        &claimant,
-        &distributor,
-    );
-    let address_tree = Pubkey::new_from_array(light_sdk::constants::ADDRESS_TREE_V2);
+    /// Also write a `<pubkey>.json` file per claimant into this directory, holding only that
+    /// claimant's amounts, proof and the merkle root
+    #[clap(long, env)]
+    pub export_proofs: Option<PathBuf>,
+}
 
-    let photon_url = args.photon_url.clone().unwrap_or_else(|| args.rpc_url.clone());
-    let config = LightClientConfig {
-        url: args.rpc_url.to_string(),
-        photon_url: Some(photon_url),
-        commitment_config: None,
-        fetch_active_tree: true,
-        api_key: None,
-    };
-    let mut client = LightClient::new(config).await.expect("failed to create client");
+#[derive(Parser, Debug)]
+pub struct MergeArgs {
+    /// CSV of recipients to merge, may be given more than once
+    #[clap(long, required_unless_present = "merkle_tree_path")]
+    pub csv_path: Vec<PathBuf>,
 
-    let claimant_ata = get_associated_token_address(&claimant, &args.mint);
+    /// Merkle tree file to merge, JSON or bincode, may be given more than once
+    #[clap(long)]
+    pub merkle_tree_path: Vec<PathBuf>,
 
-    let mut ixs = vec![ComputeBudgetInstruction::set_compute_unit_limit(NEW_CLAIM_COMPUTE_UNITS)];
-    let proof = client
-        .get_validity_proof(
-            vec![],
-            vec![AddressWithTree {
-                address: claim_status_address,
-                tree: address_tree,
-            }],
-            None,
-        )
-        .await
-        .expect("failed to get validity proof")
-        .value;
+    /// Merged merkle tree out path
+    #[clap(long)]
+    pub out_path: PathBuf,
 
-    let mut packed_accounts = PackedAccounts::default();
-    packed_accounts.add_system_accounts_v2(SystemAccountMetaConfig::new(merkle_distributor::ID))
-        .expect("add system accounts");
+    /// Mint decimals of the CSV amounts, as for `create-merkle-tree`. Tree files already hold
+    /// native amounts
+    #[clap(long, env)]
+    pub decimals: Option<u32>,
 
-    // Pack address tree info for v2
-    let address_tree_info = proof.pack_tree_infos(&mut packed_accounts).address_trees[0];
-    let output_state_tree_index = client
-        .get_random_state_tree_info()
-        .expect("failed to get state tree info")
-        .pack_output_tree_index(&mut packed_accounts)
-        .expect("failed to pack output tree");
+    /// Keep claimants whose amounts add up to zero across the inputs, as for
+    /// `create-merkle-tree`
+    #[clap(long)]
+    pub allow_empty_nodes: bool,
 
-    match client.get_account(claimant_ata).await {
-        Ok(_) => {}
-        Err(e) => {
-            if e.to_string().contains("AccountNotFound") {
-                println!("PDA does not exist. creating.");
-                let ix =
-                    create_associated_token_account(&claimant, &claimant, &args.mint, &token::ID);
-                ixs.push(ix);
-            } else {
-                eprintln!("Error fetching PDA: {e}");
-                std::process::exit(1);
-            }
-        }
-    }
-    let (packed_account_metas, _, _) = packed_accounts.to_account_metas();
+    /// Merged merkle tree file format
+    #[clap(long, value_enum, default_value = "json")]
+    pub format: TreeFileFormat,
+}
 
-    let new_claim_ix = Instruction {
-        program_id: args.program_id,
-        accounts: [
-            merkle_distributor::accounts::NewClaim {
-                distributor,
-                from: get_associated_token_address(&distributor, &args.mint),
-                to: claimant_ata,
-                claimant,
-                token_program: token::ID,
-            }
-            .to_account_metas(None),
-            packed_account_metas,
-        ]
-        .concat(),
-        data: merkle_distributor::instruction::NewClaim {
-            amount_unlocked: node.amount_unlocked(),
-            amount_locked: node.amount_locked(),
-            proof: node.proof.expect("proof not found"),
-            validity_proof: proof.proof,
-            address_tree_info,
-            output_state_tree_index,
-        }
-        .data(),
-    };
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TreeFileFormat {
+    Json,
+    /// Compact binary format for large airdrops
+    Bincode,
+}
 
-    ixs.push(new_claim_ix);
+#[derive(Parser, Debug)]
+pub struct ListClaimantsArgs {
+    /// Merkle distributor path
+    #[clap(long, env)]
+    pub merkle_tree_path: PathBuf,
 
-    let blockhash = client.get_latest_blockhash().await.unwrap().0;
-    let tx =
-        Transaction::new_signed_with_payer(&ixs, Some(&claimant.key()), &[&keypair], blockhash);
+    /// Number of claimants to skip
+    #[clap(long, default_value_t = 0)]
+    pub offset: usize,
 
-    match client.client.send_and_confirm_transaction_with_spinner(&tx) {
-        Ok(signature) => {
-            println!("Created new claim: {signature}");
-        }
-        Err(e) => {
-            let error_str = e.to_string();
-            if error_str.contains("insufficient funds") {
-                let token_vault = get_associated_token_address(&distributor, &args.mint);
-                eprintln!("Error: Token vault has insufficient funds.");
-                eprintln!("  Vault address: {token_vault}");
-                eprintln!("  Mint tokens to the vault before claiming:");
-                eprintln!("  spl-token mint {} <amount> {}", args.mint, token_vault);
-            } else {
-                eprintln!("Error creating claim: {e}");
-            }
-            std::process::exit(1);
-        }
-    }
+    /// Maximum number of claimants to print
+    #[clap(long)]
+    pub limit: Option<usize>,
 }
 
-async fn process_claim(args: &Args, claim_args: &ClaimArgs) {
-    let keypair = read_keypair_file(&args.keypair_path).expect("Failed reading keypair file");
-    let claimant = keypair.pubkey();
+#[derive(Parser, Debug)]
+pub struct EligibilityArgs {
+    /// Merkle distributor path
+    #[clap(long, env)]
+    pub merkle_tree_path: PathBuf,
 
-    let priority_fee = args.priority.unwrap_or(0);
+    /// Wallet to look up
+    #[clap(long)]
+    pub wallet: Pubkey,
+}
 
-    let (distributor, _bump) =
-        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+#[derive(Parser, Debug)]
+pub struct SetAdminArgs {
+    #[clap(long, env)]
+    pub new_admin: Pubkey,
+}
 
-    let (claim_status_address, _) = get_claim_status_pda(
-        &args.program_id,
-        &claimant,
-        &distributor,
-    );
+#[derive(Parser, Debug)]
+pub struct SetClawbackReceiverArgs {
+    /// Token account for the distributor's mint that receives clawed back tokens
+    #[clap(long, env)]
+    pub new_clawback_receiver_token_account: Pubkey,
+}
 
-    let photon_url = args.photon_url.clone().unwrap_or_else(|| args.rpc_url.clone());
-    let config = LightClientConfig {
-        url: args.rpc_url.to_string(),
-        photon_url: Some(photon_url),
-        commitment_config: None,
-        fetch_active_tree: false,
-        api_key: None,
-    };
-    let mut client = LightClient::new(config).await.expect("failed to create client");
+#[derive(Parser, Debug)]
+pub struct SetPausedArgs {
+    /// `true` rejects new claims and locked withdrawals until set back to `false`
+    #[clap(long, env, action = clap::ArgAction::Set)]
+    pub paused: bool,
+}
 
-    let claim_status_compressed_account = match client
-        .get_compressed_account(claim_status_address, None)
-        .await
-    {
-        Ok(response) => match response.value {
-            Some(compressed_account) => compressed_account,
-            None => {
-                println!("PDA does not exist. creating.");
-                process_new_claim(args, claim_args).await;
-                // Wait a bit for indexer to catch up
-                tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-                client
-                    .get_compressed_account(claim_status_address, None)
-                    .await
-                    .expect("Fetching account failed.")
-                    .value
-                    .expect("Account still not found after creation")
-            }
-        },
-        Err(e) => {
-            panic!("error getting PDA: {e}")
-        }
-    };
+#[derive(Parser, Debug)]
+pub struct MigrateClaimStatusArgs {
+    /// Claimant whose claim status to migrate, defaults to the keypair's pubkey
+    #[clap(long, env)]
+    pub claimant: Option<Pubkey>,
+}
 
-    let claim_status = ClaimStatus::deserialize(
-        &mut claim_status_compressed_account
-            .data
-            .as_ref()
-            .unwrap()
-            .data
-            .as_slice(),
-    )
-    .expect("Claim status compressed account data deserialization failed");
+#[derive(Parser, Debug)]
+pub struct UpdateClawbackStartArgs {
+    /// New clawback start, later than the current one and at least a day after end_vesting_ts
+    #[clap(long, env)]
+    pub clawback_start_ts: i64,
+}
 
-    let validity_proof = client
-        .get_validity_proof(
-            vec![claim_status_compressed_account.hash],
-            vec![],
-            None,
-        )
-        .await
-        .expect("get validity proof failed")
-        .value;
+#[derive(Parser, Debug)]
+pub struct UpdateRootArgs {
+    /// Tree file of the new root, containing every node of the previous tree unchanged
+    #[clap(long, env)]
+    pub merkle_tree_path: PathBuf,
 
-    // Build v2 PackedStateTreeInfo from the compressed account merkle context
-    let mut packed_accounts = PackedAccounts::default();
-    packed_accounts.add_system_accounts_v2(SystemAccountMetaConfig::new(merkle_distributor::ID))
-        .expect("add system accounts");
+    /// Tree file the distributor currently uses
+    #[clap(long, env)]
+    pub previous_merkle_tree_path: PathBuf,
+}
 
-    // Add state tree and queue to packed accounts
-    let merkle_tree_index = packed_accounts.insert_or_get(claim_status_compressed_account.tree_info.tree);
-    let queue_index = packed_accounts.insert_or_get(claim_status_compressed_account.tree_info.queue);
+#[derive(Parser, Debug)]
+pub struct ReassignClaimArgs {
+    /// Merkle distributor path
+    #[clap(long, env)]
+    pub merkle_tree_path: PathBuf,
 
-    let tree_info = PackedStateTreeInfo {
-        root_index: validity_proof.accounts[0].root_index.root_index().unwrap_or_default(),
-        prove_by_index: validity_proof.accounts[0].root_index.proof_by_index(),
-        merkle_tree_pubkey_index: merkle_tree_index,
-        queue_pubkey_index: queue_index,
-        leaf_index: claim_status_compressed_account.leaf_index,
-    };
+    /// Claimant in the tree whose allocation has not been claimed
+    #[clap(long, env)]
+    pub old_claimant: Pubkey,
 
-    let input_account_meta = CompressedAccountMeta {
-        tree_info,
-        address: claim_status_address,
-        output_state_tree_index: queue_index,
-    };
+    /// Wallet receiving the allocation. Must not be in the tree
+    #[clap(long, env)]
+    pub new_claimant: Pubkey,
+}
 
-    let claimant_ata = get_associated_token_address(&claimant, &args.mint);
+#[derive(Parser, Debug)]
+pub struct FundVaultArgs {
+    /// Tokens to add to the vault, in base units, or lamports for a native SOL distributor
+    #[clap(long, env)]
+    pub amount: u64,
+    /// Token account to transfer from, owned by the payer. Native SOL distributors are funded
+    /// from the payer's wallet and take neither this nor --use-mint-authority
+    #[clap(long, env)]
+    pub from_token_account: Option<Pubkey>,
+    /// Mint the tokens instead, the payer being the mint authority
+    #[clap(long, env, conflicts_with = "from_token_account")]
+    pub use_mint_authority: bool,
+}
 
-    let mut ixs = vec![ComputeBudgetInstruction::set_compute_unit_limit(CLAIM_LOCKED_COMPUTE_UNITS)];
+#[derive(Parser, Debug)]
+pub struct VerifyClaimArgs {
+    /// Claimant wallet to check
+    #[clap(long, env)]
+    pub claimant: Pubkey,
+}
 
-    let (packed_account_metas, _, _) = packed_accounts.to_account_metas();
+#[derive(Parser, Debug)]
+pub struct StatusArgs {
+    /// Merkle distributor path
+    #[clap(long, env)]
+    pub merkle_tree_path: PathBuf,
+}
 
-    let claim_ix = Instruction {
-        program_id: args.program_id,
-        accounts: [
-            merkle_distributor::accounts::ClaimLocked {
-                distributor,
-                from: get_associated_token_address(&distributor, &args.mint),
-                to: claimant_ata,
-                claimant,
-                token_program: token::ID,
-            }
-            .to_account_metas(None),
-            packed_account_metas,
-        ]
-        .concat(),
-        data: merkle_distributor::instruction::ClaimLocked {
-            claim_status_data: ClaimStatusInstructionData {
-                locked_amount: claim_status.locked_amount,
-                locked_amount_withdrawn: claim_status.locked_amount_withdrawn,
-                unlocked_amount: claim_status.unlocked_amount,
-            },
-            validity_proof: validity_proof.proof,
-            input_account_meta,
-        }
-        .data(),
-    };
-    ixs.push(claim_ix);
+#[derive(Parser, Debug)]
+pub struct WithdrawableArgs {
+    /// Claimant wallet to check, defaults to the payer
+    #[clap(long, env)]
+    pub claimant: Option<Pubkey>,
+}
 
-    if priority_fee > 0 {
-        let instruction = ComputeBudgetInstruction::set_compute_unit_price(priority_fee);
-        ixs.push(instruction);
-        println!(
-            "Added priority fee instruction of {} microlamports",
-            priority_fee
-        );
-    } else {
-        println!("No priority fee added. Add one with --priority <microlamports u64>");
-    }
+#[derive(Parser, Debug)]
+pub struct ClaimLoopArgs {
+    /// Seconds between checks of the withdrawable amount
+    #[clap(
+        long,
+        env = "CLAIM_LOOP_INTERVAL_SECS",
+        default_value_t = 3600,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub interval_secs: u64,
 
-    let (blockhash, _) = client.get_latest_blockhash().await.unwrap();
-    let tx =
-        Transaction::new_signed_with_payer(&ixs, Some(&claimant.key()), &[&keypair], blockhash);
+    /// Wait until at least this many locked tokens are withdrawable before claiming
+    #[clap(long, env, default_value_t = 0)]
+    pub min_unlock_to_claim: u64,
 
-    match client.client.send_and_confirm_transaction_with_spinner(&tx) {
-        Ok(signature) => {
-            println!("Claimed tokens: {signature}");
-        }
-        Err(e) => {
-            let error_str = e.to_string();
-            if error_str.contains("insufficient funds") {
-                let token_vault = get_associated_token_address(&distributor, &args.mint);
-                eprintln!("Error: Token vault has insufficient funds.");
-                eprintln!("  Vault address: {token_vault}");
-                eprintln!("  Mint tokens to the vault before claiming:");
-                eprintln!("  spl-token mint {} <amount> {}", args.mint, token_vault);
-            } else {
-                eprintln!("Error claiming tokens: {e}");
+    /// Stop at this unix timestamp even if locked tokens remain
+    #[clap(long, env)]
+    pub until_ts: Option<i64>,
+}
+
+#[derive(Parser, Debug)]
+pub struct VerifyProofArgs {
+    /// Merkle distributor path
+    #[clap(long, env)]
+    pub merkle_tree_path: PathBuf,
+
+    /// Claimant to check, defaults to the keypair's pubkey
+    #[clap(long, env)]
+    pub claimant: Option<Pubkey>,
+}
+
+#[derive(Parser, Debug)]
+pub struct VerifyTreeArgs {
+    /// Merkle distributor path
+    #[clap(long, env)]
+    pub merkle_tree_path: PathBuf,
+}
+
+#[derive(Parser, Debug)]
+pub struct DumpStateArgs {
+    /// Merkle distributor path, lists the claimants to look up
+    #[clap(long, env)]
+    pub merkle_tree_path: PathBuf,
+
+    /// Snapshot out path
+    #[clap(long)]
+    pub path: PathBuf,
+}
+
+#[derive(Parser, Debug)]
+pub struct ReconcileArgs {
+    /// Merkle distributor path
+    #[clap(long, env)]
+    pub merkle_tree_path: PathBuf,
+
+    /// Only list the nodes that have not been claimed yet. Totals still cover every node.
+    #[clap(long)]
+    pub only_unclaimed: bool,
+
+    /// CSV with `old_claimant,new_claimant` columns for the nodes moved with reassign-claim,
+    /// so their new claimant's withdrawals are counted
+    #[clap(long, env)]
+    pub reassignments: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct ExportClaimsArgs {
+    /// Merkle distributor path, lists the claimants to look up
+    #[clap(long, env)]
+    pub merkle_tree_path: PathBuf,
+
+    /// CSV out path
+    #[clap(long)]
+    pub out_path: PathBuf,
+
+    /// CSV with `old_claimant,new_claimant` columns for the nodes moved with reassign-claim,
+    /// so they are exported with their new claimant's amounts
+    #[clap(long, env)]
+    pub reassignments: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct DeriveAddressesArgs {
+    /// Merkle distributor path
+    #[clap(long, env)]
+    pub merkle_tree_path: PathBuf,
+
+    /// Out path
+    #[clap(long)]
+    pub out_path: PathBuf,
+
+    /// Out file format
+    #[clap(long, value_enum, default_value = "json")]
+    pub format: AddressFileFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum AddressFileFormat {
+    /// Program, distributor and address tree, and a list of claimants and addresses
+    Json,
+    /// `claimant,address` rows
+    Csv,
+}
+
+#[derive(Parser, Debug)]
+pub struct DiffStateArgs {
+    /// Earlier snapshot
+    #[clap(long)]
+    pub before: PathBuf,
+
+    /// Later snapshot
+    #[clap(long)]
+    pub after: PathBuf,
+}
+
+#[derive(Parser, Debug)]
+pub struct MonitorArgs {
+    /// Seconds between polls
+    #[clap(long, env, default_value_t = 60, value_parser = clap::value_parser!(u64).range(1..))]
+    pub interval_secs: u64,
+
+    /// Post alerts as JSON to this URL in addition to printing them
+    #[clap(long, env)]
+    pub webhook_url: Option<String>,
+
+    /// Alert when claims in one poll exceed the recent average by this factor
+    #[clap(long, env, default_value_t = 5.0)]
+    pub velocity_spike_factor: f64,
+
+    /// Minimum claims in one poll before a velocity spike is reported
+    #[clap(long, env, default_value_t = 10)]
+    pub velocity_min_claims: u64,
+
+    /// Serve Prometheus metrics on this address, e.g. 0.0.0.0:9100
+    #[cfg(feature = "metrics")]
+    #[clap(long, env)]
+    pub metrics_addr: Option<std::net::SocketAddr>,
+}
+
+#[derive(Parser, Debug)]
+pub struct WatchArgs {
+    /// Websocket url of the RPC node, derived from --rpc-url if not set
+    #[clap(long, env)]
+    pub ws_url: Option<String>,
+}
+
+/// Sets the environment variables of the `--config` file values that are not set yet, so
+/// clap reads them with lower precedence than the command line. Exits on an invalid file.
+fn apply_config_file() {
+    let Some(path) = config_path(&std::env::args_os().collect::<Vec<_>>()) else {
+        return;
+    };
+    let mut command = Args::command();
+    match read_config(&path).and_then(|config| config_env(&command, config)) {
+        Ok(env) => {
+            for (name, value) in env {
+                if std::env::var_os(&name).is_none() {
+                    std::env::set_var(name, value);
+                }
             }
-            std::process::exit(1);
         }
+        Err(e) => command
+            .error(
+                ErrorKind::ValueValidation,
+                format!("invalid config file {}: {e}", path.display()),
+            )
+            .exit(),
     }
 }
 
-fn check_distributor_onchain_matches(
-    account: &Account,
-    merkle_tree: &AirdropMerkleTree,
-    new_distributor_args: &NewDistributorArgs,
-    pubkey: Pubkey,
-) -> Result<(), &'static str> {
-    if let Ok(distributor) = MerkleDistributor::try_deserialize(&mut account.data.as_slice()) {
-        if distributor.root != merkle_tree.merkle_root {
-            return Err("root mismatch");
+#[tokio::main]
+async fn main() {
+    apply_config_file();
+    let args = Args::parse();
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::new(&args.log_level))
+        .with_writer(std::io::stderr)
+        .with_target(false)
+        .without_time()
+        .init();
+    set_output_format(args.output);
+
+    let result = match &args.command {
+        Commands::NewDistributor(new_distributor_args) => {
+            process_new_distributor(&args, new_distributor_args)
         }
-        if distributor.max_total_claim != merkle_tree.max_total_claim {
-            return Err("max_total_claim mismatch");
+        Commands::FundVault(fund_vault_args) => process_fund_vault(&args, fund_vault_args),
+        Commands::Claim(claim_args) => process_claim(&args, claim_args).await,
+        Commands::BatchClaim(batch_claim_args) => {
+            process_batch_claim(&args, batch_claim_args).await
         }
-        if distributor.max_num_nodes != merkle_tree.max_num_nodes {
-            return Err("max_num_nodes mismatch");
+        Commands::Clawback(clawback_args) => process_clawback(&args, clawback_args),
+        Commands::PartialClawback(partial_clawback_args) => {
+            process_partial_clawback(&args, partial_clawback_args)
         }
-
-        if distributor.start_ts != new_distributor_args.start_vesting_ts {
-            return Err("start_ts mismatch");
+        Commands::CreateMerkleTree(merkle_tree_args) => {
+            process_create_merkle_tree(merkle_tree_args)
         }
-        if distributor.end_ts != new_distributor_args.end_vesting_ts {
-            return Err("end_ts mismatch");
+        Commands::Merge(merge_args) => process_merge(merge_args),
+        Commands::SetAdmin(set_admin_args) => process_set_admin(&args, set_admin_args),
+        Commands::SetPaused(set_paused_args) => process_set_paused(&args, set_paused_args),
+        Commands::UpdateClawbackStart(update_clawback_start_args) => {
+            process_update_clawback_start(&args, update_clawback_start_args)
         }
-        if distributor.clawback_start_ts != new_distributor_args.clawback_start_ts {
-            return Err("clawback_start_ts mismatch");
+        Commands::UpdateRoot(update_root_args) => process_update_root(&args, update_root_args),
+        Commands::ReassignClaim(reassign_claim_args) => {
+            process_reassign_claim(&args, reassign_claim_args).await
         }
-        if distributor.clawback_receiver != new_distributor_args.clawback_receiver_token_account {
-            return Err("clawback_receiver mismatch");
+        Commands::SetClawbackReceiver(set_clawback_receiver_args) => {
+            process_set_clawback_receiver(&args, set_clawback_receiver_args)
         }
-        if distributor.admin != pubkey {
-            return Err("admin mismatch");
+        Commands::CloseDistributor => process_close_distributor(&args),
+        Commands::RenounceAdmin => process_renounce_admin(&args),
+        Commands::MigrateDistributor => process_migrate_distributor(&args),
+        Commands::MigrateClaimStatus(migrate_claim_status_args) => {
+            process_migrate_claim_status(&args, migrate_claim_status_args).await
+        }
+        Commands::Monitor(monitor_args) => process_monitor(&args, monitor_args).await,
+        Commands::Watch(watch_args) => process_watch(&args, watch_args).await,
+        Commands::VerifyClaim(verify_claim_args) => {
+            process_verify_claim(&args, verify_claim_args).await
         }
+        Commands::ListDistributors => process_list_distributors(&args),
+        Commands::DistributorInfo => process_distributor_info(&args),
+        Commands::ListClaimants(list_claimants_args) => process_list_claimants(list_claimants_args),
+        Commands::Eligibility(eligibility_args) => process_eligibility(eligibility_args),
+        Commands::Status(status_args) => process_status(&args, status_args).await,
+        Commands::Withdrawable(withdrawable_args) => {
+            process_withdrawable(&args, withdrawable_args).await
+        }
+        Commands::ClaimLoop(claim_loop_args) => process_claim_loop(&args, claim_loop_args).await,
+        Commands::DumpState(dump_state_args) => process_dump_state(&args, dump_state_args).await,
+        Commands::DiffState(diff_state_args) => process_diff_state(diff_state_args),
+        Commands::VerifyProof(verify_proof_args) => process_verify_proof(&args, verify_proof_args),
+        Commands::VerifyTree(verify_tree_args) => process_verify_tree(verify_tree_args),
+        Commands::Reconcile(reconcile_args) => process_reconcile(&args, reconcile_args).await,
+        Commands::ExportClaims(export_claims_args) => {
+            process_export_claims(&args, export_claims_args).await
+        }
+        Commands::DeriveAddresses(derive_addresses_args) => {
+            process_derive_addresses(&args, derive_addresses_args)
+        }
+        Commands::SimulateClaim(simulate_claim_args) => {
+            process_simulate_claim(&args, simulate_claim_args).await
+        }
+    };
+
+    if let Err(e) = result {
+        let _ = print_json(&ErrorOutput {
+            code: e.code(),
+            message: e.to_string(),
+        });
+        error!("[{}] {e}", e.code());
+        std::process::exit(e.code() as i32);
     }
-    Ok(())
 }
 
-fn process_new_distributor(args: &Args, new_distributor_args: &NewDistributorArgs) {
-    let client = RpcClient::new_with_commitment(&args.rpc_url, CommitmentConfig::finalized());
+/// Maps an error returned by the Solana RPC client onto a [DistributorError].
+fn map_client_error(e: ClientError) -> DistributorError {
+    match e.get_transaction_error() {
+        Some(tx_error) => map_transaction_error(tx_error),
+        None => DistributorError::RpcError(e.to_string()),
+    }
+}
 
-    let keypair = read_keypair_file(&args.keypair_path).expect("Failed reading keypair file");
-    let merkle_tree = AirdropMerkleTree::new_from_file(&new_distributor_args.merkle_tree_path)
-        .expect("failed to read");
-    let (distributor_pubkey, _bump) =
-        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
-    let token_vault = get_associated_token_address(&distributor_pubkey, &args.mint);
+/// Maps a failed transaction onto a [DistributorError], keeping the custom program error code.
+fn map_transaction_error(e: TransactionError) -> DistributorError {
+    match e {
+        TransactionError::InsufficientFundsForFee
+        | TransactionError::InsufficientFundsForRent { .. } => {
+            DistributorError::InsufficientFunds(e.to_string())
+        }
+        TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(TOKEN_INSUFFICIENT_FUNDS),
+        ) => DistributorError::InsufficientFunds("token vault has insufficient funds".to_string()),
+        TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(VAULT_INSUFFICIENT_BALANCE),
+        ) => DistributorError::InsufficientFunds(
+            "vault balance is below the amount to pay out".to_string(),
+        ),
+        TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(RECIPIENT_ACCOUNT_FROZEN),
+        ) => recipient_frozen_error(),
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            DistributorError::ProgramError {
+                code,
+                message: e.to_string(),
+            }
+        }
+        _ => DistributorError::RpcError(e.to_string()),
+    }
+}
 
-    if let Some(account) = client
-        .get_account_with_commitment(&distributor_pubkey, CommitmentConfig::confirmed())
-        .unwrap()
-        .value
-    {
-        println!("merkle distributor account exists, checking parameters...");
-        check_distributor_onchain_matches(
-            &account,
-            &merkle_tree,
-            new_distributor_args,
-            keypair.pubkey(),
-        ).expect("merkle root on-chain does not match provided arguments! Confirm admin and clawback parameters to avoid loss of funds!");
+/// `RecipientAccountFrozen`, with what the claimant can do about it.
+fn recipient_frozen_error() -> DistributorError {
+    DistributorError::ProgramError {
+        code: RECIPIENT_ACCOUNT_FROZEN,
+        message: "the token account receiving the claim is frozen; ask the mint's freeze \
+                  authority to thaw it, or claim to another wallet with --recipient"
+            .to_string(),
     }
+}
 
-    println!("creating new distributor with args: {new_distributor_args:#?}");
+/// Maps an error returned by the photon indexer onto a [DistributorError].
+fn map_indexer_error(e: IndexerError) -> DistributorError {
+    match e {
+        IndexerError::IndexerNotSyncedToSlot => DistributorError::IndexerLag(e.to_string()),
+        _ => DistributorError::RpcError(e.to_string()),
+    }
+}
 
-    let new_distributor_ix = Instruction {
-        program_id: args.program_id,
-        accounts: merkle_distributor::accounts::NewDistributor {
-            clawback_receiver: new_distributor_args.clawback_receiver_token_account,
-            mint: args.mint,
-            token_vault,
-            distributor: distributor_pubkey,
-            system_program: solana_program::system_program::id(),
-            associated_token_program: spl_associated_token_account::ID,
-            token_program: token::ID,
-            admin: keypair.pubkey(),
-        }
-        .to_account_metas(None),
-        data: merkle_distributor::instruction::NewDistributor {
-            version: args.airdrop_version,
-            root: merkle_tree.merkle_root,
-            max_total_claim: merkle_tree.max_total_claim,
-            max_num_nodes: merkle_tree.max_num_nodes,
-            start_vesting_ts: new_distributor_args.start_vesting_ts,
-            end_vesting_ts: new_distributor_args.end_vesting_ts,
-            clawback_start_ts: new_distributor_args.clawback_start_ts,
-        }
-        .data(),
+/// Maps an error returned by the [LightClient] onto a [DistributorError].
+fn map_rpc_error(e: RpcError) -> DistributorError {
+    match e {
+        RpcError::ClientError(e) => map_client_error(e),
+        RpcError::TransactionError(e) => map_transaction_error(e),
+        RpcError::IndexerError(e) => map_indexer_error(e),
+        _ => DistributorError::RpcError(e.to_string()),
+    }
+}
+
+/// Fetch an account, None only if the RPC reports that it does not exist.
+async fn fetch_account(client: &LightClient, address: Pubkey) -> Result<Option<Account>> {
+    match client.get_account(address).await {
+        Ok(account) => Ok(account),
+        Err(RpcError::AccountDoesNotExist(_)) => Ok(None),
+        Err(e) => Err(map_rpc_error(e)),
+    }
+}
+
+/// Instruction creating the claimant's token account, funded by `funder`, when it does not exist.
+/// Native SOL distributors pay the claimant's wallet and need none.
+async fn create_claimant_ata_if_missing(
+    client: &LightClient,
+    args: &Args,
+    mode: DistributionMode,
+    claimant: &Pubkey,
+    funder: &Pubkey,
+) -> Result<Option<Instruction>> {
+    if mode == DistributionMode::Native {
+        return Ok(None);
+    }
+    let claimant_ata = get_associated_token_address(claimant, &args.mint);
+    if fetch_account(client, claimant_ata).await?.is_some() {
+        return Ok(None);
+    }
+    info!("Claimant token account {claimant_ata} does not exist, creating it");
+    Ok(Some(create_associated_token_account(
+        funder,
+        claimant,
+        &args.mint,
+        &token::ID,
+    )))
+}
+
+/// Tree info of `--state-tree`, or a random active v2 state tree when it is not set.
+fn output_state_tree_info(client: &LightClient, args: &Args) -> Result<TreeInfo> {
+    let Some(state_tree) = args.state_tree else {
+        return client.get_random_state_tree_info().map_err(map_rpc_error);
     };
+    client
+        .get_state_tree_infos()
+        .into_iter()
+        .find(|info| info.tree == state_tree && info.tree_type == TreeType::StateV2)
+        .ok_or_else(|| {
+            DistributorError::TreeMismatch(format!("{state_tree} is not an active v2 state tree"))
+        })
+}
 
-    let blockhash = client.get_latest_blockhash().unwrap();
-    let tx = Transaction::new_signed_with_payer(
-        &[new_distributor_ix],
-        Some(&keypair.pubkey()),
-        &[&keypair],
-        blockhash,
-    );
+/// Checks that `address_tree` exists and is a batched merkle tree of the account compression
+/// program, so a mistyped tree fails before fetching proofs.
+async fn check_address_tree(client: &LightClient, address_tree: &Pubkey) -> Result<()> {
+    let account = fetch_account(client, *address_tree).await?.ok_or_else(|| {
+        DistributorError::TreeMismatch(format!("address tree {address_tree} does not exist"))
+    })?;
+    if account.owner != Pubkey::new_from_array(ACCOUNT_COMPRESSION_PROGRAM_ID)
+        || !account.data.starts_with(&BATCHED_MERKLE_TREE_DISCRIMINATOR)
+    {
+        return Err(DistributorError::TreeMismatch(format!(
+            "{address_tree} is not a v2 address tree"
+        )));
+    }
+    Ok(())
+}
 
-    // See comments on new_distributor instruction inside the program to ensure this transaction
-    // didn't get frontrun.
-    // If this fails, make sure to run it again.
-    match client.send_and_confirm_transaction_with_spinner(&tx) {
-        Ok(sig) => {
-            println!("\nDistributor created: {sig}");
-            println!("  Distributor: {distributor_pubkey}");
-            println!("  Token vault: {token_vault}");
-            println!("\nNext step: mint tokens to the vault:");
-            println!("  spl-token mint {} {} {}", args.mint, merkle_tree.max_total_claim, token_vault);
-        }
-        Err(e) => {
-            println!("Failed to create MerkleDistributor: {:?}", e);
+/// Fails with [DistributorError::IndexerLag] when the indexer trails the RPC node by more than
+/// `--max-indexer-lag` slots. A lagging indexer misses recent claims and serves stale roots, so
+/// claims would otherwise fail halfway with less telling errors.
+async fn check_indexer(client: &LightClient, args: &Args) -> Result<()> {
+    if args.skip_indexer_check {
+        return Ok(());
+    }
+    let indexer_slot = client
+        .get_indexer_slot(None)
+        .await
+        .map_err(map_indexer_error)?;
+    let rpc_slot = client.get_slot().await.map_err(map_rpc_error)?;
+    debug!("indexer slot {indexer_slot}, RPC slot {rpc_slot}");
+    check_indexer_lag(rpc_slot, indexer_slot, args.max_indexer_lag)
+}
 
-            // double check someone didn't frontrun this transaction with a malicious merkle root
-            if let Some(account) = client
-                .get_account_with_commitment(&distributor_pubkey, CommitmentConfig::processed())
-                .unwrap()
-                .value
-            {
-                check_distributor_onchain_matches(
-                    &account,
-                    &merkle_tree,
-                    new_distributor_args,
-                    keypair.pubkey(),
-                ).expect("merkle root on-chain does not match provided arguments! Confirm admin and clawback parameters to avoid loss of funds!");
-            }
-        }
+fn check_indexer_lag(rpc_slot: u64, indexer_slot: u64, max_indexer_lag: u64) -> Result<()> {
+    let lag = rpc_slot.saturating_sub(indexer_slot);
+    if lag > max_indexer_lag {
+        return Err(DistributorError::IndexerLag(format!(
+            "the indexer is {lag} slots behind the RPC node (slot {indexer_slot} vs \
+             {rpc_slot}), try again shortly"
+        )));
     }
+    Ok(())
 }
 
-fn process_clawback(args: &Args, clawback_args: &ClawbackArgs) {
-    let payer_keypair = read_keypair_file(&args.keypair_path).expect("Failed reading keypair file");
-    let clawback_keypair = read_keypair_file(&clawback_args.clawback_keypair_path)
-        .expect("Failed reading keypair file");
+/// Fetch a compressed account by address, None only if the indexer reports that it does not
+/// exist. Timeouts and malformed responses are returned as errors, never treated as missing.
+async fn fetch_compressed_account(
+    client: &LightClient,
+    address: [u8; 32],
+) -> Result<Option<CompressedAccount>> {
+    match client.get_compressed_account(address, None).await {
+        Ok(response) => Ok(response.value),
+        Err(IndexerError::AccountNotFound) => Ok(None),
+        Err(e) => Err(map_indexer_error(e)),
+    }
+}
 
-    let clawback_ata = get_associated_token_address(&clawback_keypair.pubkey(), &args.mint);
+/// Decodes ClaimStatus account data. Indexers return it either as raw borsh bytes or as
+/// the base64 text of those bytes, depending on the version; both are accepted. Data in the
+/// [LegacyClaimStatus] layout is an error pointing at migrate-claim-status.
+fn decode_claim_status(data: &[u8]) -> Result<ClaimStatus> {
+    if let Ok(claim_status) = ClaimStatus::try_from_slice(data) {
+        return Ok(claim_status);
+    }
+    if decode_legacy_claim_status(data).is_some() {
+        return Err(legacy_claim_status_error());
+    }
+    let decoded = std::str::from_utf8(data)
+        .ok()
+        .and_then(|encoded| BASE64.decode(encoded.trim()).ok())
+        .ok_or_else(|| {
+            DistributorError::RpcError(
+                "claim status data is neither borsh nor base64 encoded".to_string(),
+            )
+        })?;
+    ClaimStatus::try_from_slice(&decoded).map_err(|e| {
+        DistributorError::RpcError(format!(
+            "claim status compressed account data deserialization failed: {e}"
+        ))
+    })
+}
 
-    let client = RpcClient::new_with_commitment(&args.rpc_url, CommitmentConfig::confirmed());
+fn legacy_claim_status_error() -> DistributorError {
+    DistributorError::RpcError(
+        "claim status has the layout of an earlier program release, run migrate-claim-status \
+         first"
+            .to_string(),
+    )
+}
 
-    let (distributor, _bump) =
-        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+/// Like [decode_claim_status] for data in the [LegacyClaimStatus] layout, None for any other.
+fn decode_legacy_claim_status(data: &[u8]) -> Option<LegacyClaimStatus> {
+    if let Ok(legacy) = LegacyClaimStatus::try_from_slice(data) {
+        return Some(legacy);
+    }
+    let decoded = BASE64.decode(std::str::from_utf8(data).ok()?.trim()).ok()?;
+    LegacyClaimStatus::try_from_slice(&decoded).ok()
+}
 
-    let from = get_associated_token_address(&distributor, &args.mint);
-    println!("from: {from}");
+fn parse_claim_status(compressed_account: &CompressedAccount) -> Result<ClaimStatus> {
+    let data = compressed_account.data.as_ref().ok_or_else(|| {
+        DistributorError::RpcError("claim status account has no data".to_string())
+    })?;
+    decode_claim_status(&data.data)
+}
 
-    let clawback_ix = Instruction {
-        program_id: args.program_id,
-        accounts: merkle_distributor::accounts::Clawback {
-            distributor,
-            from,
-            to: clawback_ata,
-            claimant: clawback_keypair.pubkey(),
-            system_program: solana_program::system_program::ID,
-            token_program: token::ID,
-        }
-        .to_account_metas(None),
-        data: merkle_distributor::instruction::Clawback {}.data(),
-    };
+fn next_indexer_poll_delay(delay: Duration) -> Duration {
+    (delay * 2).min(INDEXER_POLL_MAX_DELAY)
+}
 
-    let tx = Transaction::new_signed_with_payer(
-        &[clawback_ix],
-        Some(&payer_keypair.pubkey()),
-        &[&payer_keypair, &clawback_keypair],
-        client.get_latest_blockhash().unwrap(),
-    );
+/// Polls the indexer with exponential backoff until the compressed account at `address`
+/// is indexed, or fails with [DistributorError::IndexerLag] once `timeout` elapses.
+async fn wait_for_compressed_account(
+    client: &LightClient,
+    address: [u8; 32],
+    timeout: Duration,
+) -> Result<CompressedAccount> {
+    let start = Instant::now();
+    let mut delay = INDEXER_POLL_INITIAL_DELAY;
+    let mut attempt = 1;
+    loop {
+        match fetch_compressed_account(client, address).await {
+            Ok(Some(account)) => return Ok(account),
+            Ok(None) => debug!(attempt, "compressed account not indexed yet"),
+            Err(DistributorError::IndexerLag(e)) => debug!(attempt, "indexer is behind: {e}"),
+            Err(e) => return Err(e),
+        }
 
-    let signature = client
-        .send_and_confirm_transaction_with_spinner(&tx)
-        .unwrap();
+        let elapsed = start.elapsed();
+        if elapsed >= timeout {
+            return Err(DistributorError::IndexerLag(format!(
+                "compressed account {} not indexed after {}s ({attempt} attempts)",
+                Pubkey::new_from_array(address),
+                timeout.as_secs()
+            )));
+        }
+        tokio::time::sleep(delay.min(timeout - elapsed)).await;
+        delay = next_indexer_poll_delay(delay);
+        attempt += 1;
+    }
+}
 
-    println!("Successfully clawed back funds! signature: {signature:#?}");
+/// Signers of a claim transaction. The first one pays the fees: the `--fee-payer` keypair
+/// when set, otherwise the claimant.
+fn claim_signers<'a>(
+    claimant: &'a dyn Signer,
+    fee_payer: Option<&'a Keypair>,
+) -> Vec<&'a dyn Signer> {
+    match fee_payer {
+        Some(fee_payer) => vec![fee_payer, claimant],
+        None => vec![claimant],
+    }
 }
 
-fn process_create_merkle_tree(merkle_tree_args: &CreateMerkleTreeArgs) {
-    let merkle_tree = AirdropMerkleTree::new_from_csv(&merkle_tree_args.csv_path).unwrap();
-    merkle_tree.write_to_file(&merkle_tree_args.merkle_tree_path);
+fn read_keypair(path: &Path) -> Result<Keypair> {
+    read_keypair_file(path).map_err(|e| {
+        DistributorError::IoError(std::io::Error::other(format!(
+            "failed reading keypair file {}: {e}",
+            path.display()
+        )))
+    })
 }
 
-fn process_set_admin(args: &Args, set_admin_args: &SetAdminArgs) {
-    let keypair = read_keypair_file(&args.keypair_path).expect("Failed reading keypair file");
+/// The `--keypair-path` keypair.
+fn read_payer_keypair(args: &Args) -> Result<Keypair> {
+    match &args.keypair_path {
+        Some(path) => read_keypair(path),
+        None => Err(DistributorError::IoError(std::io::Error::other(
+            "--keypair-path or --ledger is required to sign transactions, pass --offline to \
+             print them",
+        ))),
+    }
+}
 
-    let client = RpcClient::new_with_commitment(&args.rpc_url, CommitmentConfig::confirmed());
+/// The payer, needed whenever a transaction is signed here: the `--ledger` key when set,
+/// otherwise the `--keypair-path` keypair.
+fn payer_signer(args: &Args) -> Result<Box<dyn Signer>> {
+    match &args.ledger {
+        Some(key_path) => Ok(Box::new(ledger_signer(key_path.as_deref())?)),
+        None => Ok(Box::new(read_payer_keypair(args)?)),
+    }
+}
 
-    let (distributor, _bump) =
-        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+/// Derivation path of the `--ledger` key path, the wallet's first key when there is none.
+fn ledger_derivation_path(key_path: Option<&str>) -> Result<DerivationPath> {
+    let Some(key_path) = key_path else {
+        return Ok(DerivationPath::default());
+    };
+    DerivationPath::from_key_str(key_path).map_err(|e| {
+        DistributorError::IoError(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("--ledger: {e}"),
+        ))
+    })
+}
 
-    let set_admin_ix = Instruction {
-        program_id: args.program_id,
-        accounts: merkle_distributor::accounts::SetAdmin {
-            distributor,
-            admin: keypair.pubkey(),
-            new_admin: set_admin_args.new_admin,
-        }
-        .to_account_metas(None),
-        data: merkle_distributor::instruction::SetAdmin {}.data(),
+/// The key at `key_path` of the first Ledger connected over USB, with the Solana app open.
+fn ledger_signer(key_path: Option<&str>) -> Result<RemoteKeypair> {
+    let derivation_path = ledger_derivation_path(key_path)?;
+    let ledger_error = |e: RemoteWalletError| {
+        DistributorError::IoError(std::io::Error::other(format!("Ledger: {e}")))
+    };
+    let wallet_manager = maybe_wallet_manager()
+        .map_err(ledger_error)?
+        .ok_or_else(|| {
+            DistributorError::IoError(std::io::Error::other(
+                "no Ledger found, connect it and open the Solana app",
+            ))
+        })?;
+    let locator = Locator {
+        manufacturer: Manufacturer::Ledger,
+        pubkey: None,
     };
+    let signer = generate_remote_keypair(locator, derivation_path, &wallet_manager, false, "payer")
+        .map_err(ledger_error)?;
+    info!("Signing with Ledger key {}", signer.pubkey());
+    Ok(signer)
+}
 
-    let tx = Transaction::new_signed_with_payer(
-        &[set_admin_ix],
-        Some(&keypair.pubkey()),
-        &[&keypair],
-        client.get_latest_blockhash().unwrap(),
-    );
+/// Pubkey the command acts for: `--authority` when set, otherwise the payer.
+fn payer_pubkey(args: &Args) -> Result<Pubkey> {
+    match args.authority {
+        Some(authority) => Ok(authority),
+        None => Ok(payer_signer(args)?.pubkey()),
+    }
+}
 
-    let signature = client
-        .send_and_confirm_transaction_with_spinner(&tx)
-        .unwrap();
+/// Whether action commands hand out their transactions unsigned instead of sending them.
+fn exports_transactions(args: &Args) -> bool {
+    args.offline || args.export_tx.is_some()
+}
+
+/// Whether transactions are built on a blockhash given on the command line or exported for
+/// offline signing, where resending with a fresh blockhash is not possible.
+fn uses_fixed_blockhash(args: &Args) -> bool {
+    exports_transactions(args) || args.blockhash.is_some() || args.nonce_account.is_some()
+}
+
+/// Durable nonce stored in `account`, None if it is not an initialized nonce account.
+fn nonce_blockhash(account: &Account) -> Option<Hash> {
+    if account.owner != solana_program::system_program::ID {
+        return None;
+    }
+    match bincode::deserialize::<Versions>(&account.data)
+        .ok()?
+        .state()
+    {
+        State::Initialized(data) => Some(data.blockhash()),
+        State::Uninitialized => None,
+    }
+}
+
+/// Instructions to prepend and the blockhash to build a transaction paid by `fee_payer` with:
+/// advancing `--nonce-account` and its stored nonce, else `--blockhash`, else the latest one.
+fn transaction_blockhash(
+    client: &RpcClient,
+    args: &Args,
+    fee_payer: &Pubkey,
+) -> Result<(Vec<Instruction>, Hash)> {
+    let Some(nonce_account) = args.nonce_account else {
+        let blockhash = match args.blockhash {
+            Some(blockhash) => blockhash,
+            None => client.get_latest_blockhash().map_err(map_client_error)?,
+        };
+        return Ok((vec![], blockhash));
+    };
+    let blockhash = match args.blockhash {
+        Some(blockhash) => blockhash,
+        None => {
+            let account = client
+                .get_account(&nonce_account)
+                .map_err(map_client_error)?;
+            nonce_blockhash(&account).ok_or_else(|| {
+                DistributorError::RpcError(format!(
+                    "{nonce_account} is not an initialized nonce account"
+                ))
+            })?
+        }
+    };
+    let authority = args.nonce_authority.unwrap_or(*fee_payer);
+    Ok((
+        vec![advance_nonce_account(&nonce_account, &authority)],
+        blockhash,
+    ))
+}
+
+/// Sends `instructions` paid by `fee_payer` when set and by the payer otherwise, signed by the
+/// payer and `co_signers`. Retries with [send_with_retry], escalating the priority fee.
+/// With a fixed blockhash the transaction is built once on [transaction_blockhash] instead: with
+/// `--offline` or `--export-tx` it is handed out unsigned and None is returned, otherwise it is
+/// sent without retries.
+fn send_or_print_transaction(
+    client: &RpcClient,
+    args: &Args,
+    instructions: &[Instruction],
+    fee_payer: Option<&Keypair>,
+    co_signers: &[&Keypair],
+) -> Result<Option<Signature>> {
+    let fee_payer_pubkey = match fee_payer {
+        Some(fee_payer) => fee_payer.pubkey(),
+        None => payer_pubkey(args)?,
+    };
+    if !uses_fixed_blockhash(args) {
+        let payer = payer_signer(args)?;
+        let mut signers = claim_signers(payer.as_ref(), fee_payer);
+        signers.extend(co_signers.iter().map(|&co_signer| co_signer as &dyn Signer));
+        return send_with_retry(
+            client,
+            instructions,
+            &fee_payer_pubkey,
+            &signers,
+            &priority_fee_escalation(args),
+            args.max_retries,
+        )
+        .map(Some)
+        .map_err(map_client_error);
+    }
+
+    let (mut ixs, blockhash) = transaction_blockhash(client, args, &fee_payer_pubkey)?;
+    ixs.extend(with_priority_fee(args, instructions));
+    let mut tx = Transaction::new_with_payer(&ixs, Some(&fee_payer_pubkey));
+    tx.message.recent_blockhash = blockhash;
+
+    if exports_transactions(args) {
+        let signers =
+            tx.message.account_keys[..tx.message.header.num_required_signatures as usize].to_vec();
+        let transaction = BASE64.encode(
+            bincode::serialize(&tx)
+                .map_err(|e| DistributorError::IoError(std::io::Error::other(e)))?,
+        );
+        match &args.export_tx {
+            Some(path) => {
+                std::fs::write(path, &transaction)?;
+                say!("Wrote unsigned transaction to {}", path.display());
+            }
+            None => say!("{transaction}"),
+        }
+        info!(
+            "Unsigned transaction on blockhash {blockhash}, to be signed by: {}",
+            signers
+                .iter()
+                .map(Pubkey::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        print_json(&UnsignedTransactionOutput {
+            transaction,
+            blockhash: blockhash.to_string(),
+            signers: signers.iter().map(Pubkey::to_string).collect(),
+        })?;
+        return Ok(None);
+    }
+
+    let payer = payer_signer(args)?;
+    let mut signers = claim_signers(payer.as_ref(), fee_payer);
+    signers.extend(co_signers.iter().map(|&co_signer| co_signer as &dyn Signer));
+    tx.try_sign(&signers[..], blockhash)
+        .map_err(|e| DistributorError::RpcError(format!("failed to sign transaction: {e}")))?;
+    client
+        .send_and_confirm_transaction_with_spinner(&tx)
+        .map(Some)
+        .map_err(map_client_error)
+}
+
+/// Whether a confirmation prompt answer accepts.
+fn is_confirmed(answer: &str) -> bool {
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// `ts` followed by how far it is from `now`, e.g. `1700000000 (in 2d 3h)`, so a timestamp in
+/// milliseconds or off by a year stands out.
+fn describe_ts(ts: i64, now: i64) -> String {
+    let secs = ts.abs_diff(now);
+    let (days, hours, minutes) = (secs / 86_400, secs % 86_400 / 3_600, secs % 3_600 / 60);
+    let span = match (days, hours) {
+        (0, 0) => format!("{minutes}m"),
+        (0, _) => format!("{hours}h {minutes}m"),
+        _ => format!("{days}d {hours}h"),
+    };
+    match ts >= now {
+        true => format!("{ts} (in {span})"),
+        false => format!("{ts} ({span} ago)"),
+    }
+}
+
+/// Prints `summary` and asks to proceed before a high-stakes transaction is sent. Skipped with
+/// `--yes`, and when the transaction is only printed or exported. Anything but `y` aborts,
+/// including a closed stdin, so unattended runs need `--yes`.
+fn confirm(args: &Args, summary: &[String]) -> Result<()> {
+    prompt(args, summary, "Proceed? [y/N] ", is_confirmed)
+}
+
+/// [confirm] with a custom question and the answers it `accepts`.
+fn prompt(
+    args: &Args,
+    summary: &[String],
+    question: &str,
+    accepts: impl Fn(&str) -> bool,
+) -> Result<()> {
+    if args.yes || args.output_instructions || exports_transactions(args) {
+        return Ok(());
+    }
+    for line in summary {
+        eprintln!("{line}");
+    }
+    eprint!("{question}");
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    if !accepts(&answer) {
+        return Err(DistributorError::IoError(std::io::Error::new(
+            std::io::ErrorKind::Interrupted,
+            "aborted, nothing was sent",
+        )));
+    }
+    Ok(())
+}
+
+/// `--address-tree`, or the address tree of `--network`.
+fn address_tree(args: &Args) -> Pubkey {
+    args.address_tree
+        .unwrap_or_else(|| args.network.preset().address_tree)
+}
+
+/// RPC client reading and confirming at `--commitment`, failing over between the
+/// `--rpc-url` endpoints.
+fn rpc_client(args: &Args) -> RpcClient {
+    RpcClient::new_sender(
+        FailoverSender::new(&args.rpc_url),
+        RpcClientConfig::with_commitment(args.commitment.config()),
+    )
+}
+
+/// Light client with the `--network` state trees as its active trees, sending its RPC requests
+/// through [rpc_client].
+async fn new_light_client(args: &Args) -> Result<LightClient> {
+    let photon_url = args
+        .photon_url
+        .clone()
+        .unwrap_or_else(|| args.rpc_url[0].clone());
+    let config = LightClientConfig {
+        url: args.rpc_url[0].clone(),
+        photon_url: Some(photon_url),
+        commitment_config: Some(args.commitment.config()),
+        fetch_active_tree: false,
+        api_key: None,
+    };
+    let mut client = LightClient::new(config).await.map_err(map_rpc_error)?;
+    client.client = rpc_client(args);
+    client.state_merkle_trees = args.network.preset().state_trees;
+    Ok(client)
+}
+
+fn priority_fee_escalation(args: &Args) -> PriorityFeeEscalation {
+    PriorityFeeEscalation {
+        initial: args.priority.unwrap_or(0),
+        multiplier: args.priority_escalation,
+        ceiling: args.max_priority,
+    }
+}
+
+fn print_vault_funding_hint(args: &Args, distributor: &Pubkey, mode: DistributionMode) {
+    let vault = vault_address(args, distributor, mode);
+    error!("Vault {vault} has insufficient funds");
+    match mode {
+        DistributionMode::Token => info!(
+            "Fund it before claiming: cli --mint {} fund-vault --amount <amount> \
+             --use-mint-authority",
+            args.mint
+        ),
+        DistributionMode::Native => info!(
+            "Fund it before claiming: cli --mint {} fund-vault --amount <lamports>",
+            args.mint
+        ),
+    }
+}
+
+/// Logs the remaining accounts passed to the program at trace level, labelling the section each
+/// belongs to.
+/// Packed indices in instruction data resolve to `packed_accounts_offset + index`.
+fn trace_cpi_remaining_accounts(
+    account_metas: &[AccountMeta],
+    system_accounts_offset: usize,
+    packed_accounts_offset: usize,
+) {
+    trace!(
+        "{} remaining accounts, system offset {system_accounts_offset}, packed offset {packed_accounts_offset}",
+        account_metas.len()
+    );
+    for (i, meta) in account_metas.iter().enumerate() {
+        let section = if i >= packed_accounts_offset {
+            format!("packed[{}]", i - packed_accounts_offset)
+        } else if i >= system_accounts_offset {
+            format!("system[{}]", i - system_accounts_offset)
+        } else {
+            "pre".to_string()
+        };
+        trace!(
+            "  remaining_accounts[{i}] {section} {} writable={} signer={}",
+            meta.pubkey,
+            meta.is_writable,
+            meta.is_signer
+        );
+    }
+}
+
+/// Prints `instructions` as a JSON array on stdout for `--output-instructions`, including the
+/// compute unit price instruction when a priority fee is set.
+fn print_instructions(args: &Args, instructions: &[Instruction]) -> Result<()> {
+    println!(
+        "{}",
+        instructions_to_json(&with_priority_fee(args, instructions))?
+    );
+    Ok(())
+}
+
+/// `instructions` behind their compute budget instructions and the compute unit price of
+/// `--priority`, see [with_compute_budget].
+fn with_priority_fee(args: &Args, instructions: &[Instruction]) -> Vec<Instruction> {
+    with_compute_budget(instructions, args.priority.unwrap_or(0))
+}
+
+/// `units_consumed` plus `margin_percent` of it, capped at the transaction maximum.
+fn compute_unit_limit_with_margin(units_consumed: u64, margin_percent: u32) -> u32 {
+    let limit = units_consumed.saturating_mul(100 + u64::from(margin_percent)) / 100;
+    u32::try_from(limit).map_or(MAX_COMPUTE_UNIT_LIMIT, |limit| {
+        limit.min(MAX_COMPUTE_UNIT_LIMIT)
+    })
+}
+
+/// `ixs` preceded by a compute unit limit: `--compute-units`, or the units a simulation of
+/// `ixs` paid by `payer` consumes plus `--compute-unit-margin`. Falls back to `default_limit`
+/// when offline or when the simulation fails, leaving the error to the real transaction.
+fn with_compute_unit_limit(
+    client: &RpcClient,
+    args: &Args,
+    payer: &Pubkey,
+    ixs: Vec<Instruction>,
+    default_limit: u32,
+) -> Vec<Instruction> {
+    let limit = match args.compute_units {
+        Some(limit) => limit,
+        None if args.offline => default_limit,
+        None => simulate_compute_units(client, payer, &ixs)
+            .map(|units| compute_unit_limit_with_margin(units, args.compute_unit_margin))
+            .unwrap_or_else(|e| {
+                warn!("Compute unit simulation failed, using {default_limit}: {e}");
+                default_limit
+            }),
+    };
+    debug!("Compute unit limit {limit}");
+    [
+        vec![ComputeBudgetInstruction::set_compute_unit_limit(limit)],
+        ixs,
+    ]
+    .concat()
+}
+
+/// Units consumed by `ixs` in a simulation under the maximum compute unit limit, without
+/// signatures or a real blockhash.
+fn simulate_compute_units(
+    client: &RpcClient,
+    payer: &Pubkey,
+    ixs: &[Instruction],
+) -> std::result::Result<u64, String> {
+    let result = simulate_instructions(client, payer, ixs)?;
+    if let Some(err) = result.err {
+        return Err(err.to_string());
+    }
+    result
+        .units_consumed
+        .ok_or_else(|| "the RPC node did not report consumed units".to_string())
+}
+
+/// Simulates `ixs` under the maximum compute unit limit, without signatures and against the
+/// latest blockhash. A failing transaction is reported in the result, not as an error.
+fn simulate_instructions(
+    client: &RpcClient,
+    payer: &Pubkey,
+    ixs: &[Instruction],
+) -> std::result::Result<RpcSimulateTransactionResult, String> {
+    let simulated_ixs = [
+        vec![ComputeBudgetInstruction::set_compute_unit_limit(
+            MAX_COMPUTE_UNIT_LIMIT,
+        )],
+        ixs.to_vec(),
+    ]
+    .concat();
+    let tx = Transaction::new_with_payer(&simulated_ixs, Some(payer));
+    client
+        .simulate_transaction_with_config(
+            &tx,
+            RpcSimulateTransactionConfig {
+                sig_verify: false,
+                replace_recent_blockhash: true,
+                commitment: Some(client.commitment()),
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )
+        .map(|response| response.value)
+        .map_err(|e| e.to_string())
+}
+
+/// Sends `new_claim` for the payer. Returns None when only printing the instructions.
+async fn process_new_claim(args: &Args, claim_args: &ClaimArgs) -> Result<Option<Signature>> {
+    let claimant = payer_pubkey(args)?;
+    let fee_payer = args.fee_payer.as_deref().map(read_keypair).transpose()?;
+    let fee_payer_pubkey = fee_payer
+        .as_ref()
+        .map_or(claimant, |fee_payer| fee_payer.pubkey());
+    info!("Claiming tokens for user {claimant}");
+
+    let merkle_tree = AirdropMerkleTree::new_from_file(&claim_args.merkle_tree_path)?;
+
+    let (distributor, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+    let (claim_status_address, _address_seed) = get_claim_status_address(
+        &args.program_id,
+        &claimant,
+        &distributor,
+        &address_tree(args),
+    );
+
+    let client = new_light_client(args).await?;
+    check_address_tree(&client, &address_tree(args)).await?;
+    let mode = distribution_mode(&client.client, args, &distributor)?;
+    let owner = claim_args.recipient.unwrap_or(claimant);
+
+    let mut ixs: Vec<Instruction> =
+        create_claimant_ata_if_missing(&client, args, mode, &owner, &fee_payer_pubkey)
+            .await?
+            .into_iter()
+            .collect();
+    let mut proofs = ProofFetcher::new(&client, PROOF_BATCH_SIZE);
+    ixs.push(
+        new_claim_instruction(
+            &client,
+            &mut proofs,
+            args,
+            &merkle_tree,
+            &claimant,
+            mode,
+            claim_args.recipient,
+        )
+        .await?,
+    );
+    let ixs = with_compute_unit_limit(
+        &client.client,
+        args,
+        &fee_payer_pubkey,
+        ixs,
+        NEW_CLAIM_COMPUTE_UNITS,
+    );
+
+    if args.output_instructions {
+        print_instructions(args, &ixs)?;
+        return Ok(None);
+    }
+
+    match send_or_print_transaction(&client.client, args, &ixs, fee_payer.as_ref(), &[]) {
+        Ok(None) => Ok(None),
+        Ok(Some(signature)) => {
+            say!("Created new claim: {signature}");
+            Ok(Some(signature))
+        }
+        Err(e) => {
+            if let DistributorError::InsufficientFunds(_) = e {
+                print_vault_funding_hint(args, &distributor, mode);
+            }
+            Err(check_already_claimed(&client, claim_status_address, &claimant, e).await)
+        }
+    }
+}
+
+/// The node's amounts per airdrop category, recorded in the claim status for reporting.
+fn category_breakdown(node: &TreeNode) -> CategoryBreakdown {
+    CategoryBreakdown {
+        unlocked_staker: node.total_unlocked_staker,
+        locked_staker: node.total_locked_staker,
+        unlocked_searcher: node.total_unlocked_searcher,
+        locked_searcher: node.total_locked_searcher,
+        unlocked_validator: node.total_unlocked_validator,
+        locked_validator: node.total_locked_validator,
+    }
+}
+
+/// Native SOL claims always pay the claimant's wallet, so they take no `--recipient`.
+fn check_recipient(mode: DistributionMode, recipient: Option<Pubkey>) -> Result<()> {
+    if mode == DistributionMode::Native && recipient.is_some() {
+        return Err(DistributorError::IoError(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "--recipient is not supported by native SOL distributors",
+        )));
+    }
+    Ok(())
+}
+
+/// `new_claim` for `claimant`, proving that its claim status address does not exist yet.
+/// `new_claim_to` when the tokens go to the token account of `recipient`, `new_claim_native`
+/// for a native SOL distributor.
+#[allow(clippy::too_many_arguments)]
+async fn new_claim_instruction(
+    client: &LightClient,
+    proofs: &mut ProofFetcher<'_, LightClient>,
+    args: &Args,
+    merkle_tree: &AirdropMerkleTree,
+    claimant: &Pubkey,
+    mode: DistributionMode,
+    recipient: Option<Pubkey>,
+) -> Result<Instruction> {
+    check_recipient(mode, recipient)?;
+    let (distributor, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+
+    // Get user's node in claim
+    let node = merkle_tree
+        .get_node(claimant)
+        .ok_or(DistributorError::ProofNotFound(*claimant))?;
+    let node_proof = node_proof(node)?;
+    let address_tree = address_tree(args);
+    let (claim_status_address, _address_seed) =
+        get_claim_status_address(&args.program_id, claimant, &distributor, &address_tree);
+
+    // No non-inclusion proof exists for an address that was already created
+    let proof = match proofs
+        .get(ProofKey::Address {
+            address: claim_status_address,
+            tree: address_tree,
+        })
+        .await
+    {
+        Ok(proof) => proof,
+        Err(e) => {
+            let e = map_indexer_error(e);
+            return Err(check_already_claimed(client, claim_status_address, claimant, e).await);
+        }
+    };
+
+    let mut packed_accounts = PackedAccounts::default();
+    packed_accounts
+        .add_system_accounts_v2(SystemAccountMetaConfig::new(merkle_distributor::ID))
+        .map_err(|e| DistributorError::RpcError(format!("failed to add system accounts: {e}")))?;
+
+    // Pack address tree info for v2
+    let address_tree_info = proof.pack_tree_infos(&mut packed_accounts).address_trees[0];
+    let output_state_tree_index = output_state_tree_info(client, args)?
+        .pack_output_tree_index(&mut packed_accounts)
+        .map_err(map_indexer_error)?;
+
+    let (packed_account_metas, system_accounts_offset, packed_accounts_offset) =
+        packed_accounts.to_account_metas();
+
+    if tracing::enabled!(Level::TRACE) {
+        trace!(
+            "new claim status address {}",
+            Pubkey::new_from_array(claim_status_address)
+        );
+        trace!("address tree {address_tree}");
+        trace!("address_tree_info {address_tree_info:?}");
+        trace!("output_state_tree_index {output_state_tree_index}");
+        trace!(
+            "address root indices {:?}",
+            proof
+                .addresses
+                .iter()
+                .map(|a| a.root_index)
+                .collect::<Vec<_>>()
+        );
+        trace!("validity proof {:?}", proof.proof);
+        trace_cpi_remaining_accounts(
+            &packed_account_metas,
+            system_accounts_offset,
+            packed_accounts_offset,
+        );
+    }
+
+    let unlock_schedule = node.unlock_schedule.map(|schedule| UnlockSchedule {
+        start_ts: schedule.start_ts,
+        end_ts: schedule.end_ts,
+    });
+    let (accounts, data) = match (mode, recipient) {
+        (DistributionMode::Token, Some(recipient)) => (
+            merkle_distributor::accounts::NewClaimTo {
+                distributor,
+                from: vault_address(args, &distributor, mode),
+                to: get_associated_token_address(&recipient, &args.mint),
+                claimant: *claimant,
+                mint: args.mint,
+                token_program: token::ID,
+            }
+            .to_account_metas(None),
+            merkle_distributor::instruction::NewClaimTo {
+                amount_unlocked: node.amount_unlocked(),
+                amount_locked: node.amount_locked(),
+                proof: node_proof,
+                unlock_schedule,
+                category_breakdown: Some(category_breakdown(node)),
+                validity_proof: proof.proof,
+                address_tree_info,
+                output_state_tree_index,
+            }
+            .data(),
+        ),
+        (DistributionMode::Token, None) => (
+            merkle_distributor::accounts::NewClaim {
+                distributor,
+                from: vault_address(args, &distributor, mode),
+                to: get_associated_token_address(claimant, &args.mint),
+                claimant: *claimant,
+                mint: args.mint,
+                token_program: token::ID,
+            }
+            .to_account_metas(None),
+            merkle_distributor::instruction::NewClaim {
+                amount_unlocked: node.amount_unlocked(),
+                amount_locked: node.amount_locked(),
+                proof: node_proof,
+                unlock_schedule,
+                category_breakdown: Some(category_breakdown(node)),
+                validity_proof: proof.proof,
+                address_tree_info,
+                output_state_tree_index,
+            }
+            .data(),
+        ),
+        (DistributionMode::Native, _) => (
+            merkle_distributor::accounts::NewClaimNative {
+                distributor,
+                from: vault_address(args, &distributor, mode),
+                claimant: *claimant,
+                system_program: solana_program::system_program::ID,
+            }
+            .to_account_metas(None),
+            merkle_distributor::instruction::NewClaimNative {
+                amount_unlocked: node.amount_unlocked(),
+                amount_locked: node.amount_locked(),
+                proof: node_proof,
+                unlock_schedule,
+                category_breakdown: Some(category_breakdown(node)),
+                validity_proof: proof.proof,
+                address_tree_info,
+                output_state_tree_index,
+            }
+            .data(),
+        ),
+    };
+
+    Ok(Instruction {
+        program_id: args.program_id,
+        accounts: [accounts, packed_account_metas].concat(),
+        data,
+    })
+}
+
+/// Turns a failed new_claim into [DistributorError::AlreadyClaimed] when the claimant's claim
+/// status exists, e.g. when an earlier claim was not indexed yet by the time `claim` looked for
+/// it. The lookup only happens on failure, so a first claim costs no extra round trip.
+async fn check_already_claimed(
+    client: &LightClient,
+    claim_status_address: [u8; 32],
+    claimant: &Pubkey,
+    e: DistributorError,
+) -> DistributorError {
+    let claim_status_exists = matches!(
+        fetch_compressed_account(client, claim_status_address).await,
+        Ok(Some(_))
+    );
+    already_claimed_or(e, claim_status_exists, claimant)
+}
+
+/// [DistributorError::AlreadyClaimed] if the claim status exists, `e` otherwise.
+fn already_claimed_or(
+    e: DistributorError,
+    claim_status_exists: bool,
+    claimant: &Pubkey,
+) -> DistributorError {
+    match claim_status_exists {
+        true => DistributorError::AlreadyClaimed(*claimant),
+        false => e,
+    }
+}
+
+/// What `claim` sends for the claimant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ClaimSteps {
+    /// Create the claim status, transferring the unlocked amount
+    new_claim: bool,
+    /// Withdraw the currently unlocked part of the locked amount
+    claim_locked: bool,
+}
+
+/// Decides the claim steps from whether the claim status exists and the locked amount
+/// withdrawable right now, which must reach `min_unlock_to_claim`. When that amount could not
+/// be computed claim_locked is still sent and the program decides.
+fn claim_steps(
+    claim_status_exists: bool,
+    locked_withdrawable: Option<u64>,
+    min_unlock_to_claim: u64,
+) -> ClaimSteps {
+    ClaimSteps {
+        new_claim: !claim_status_exists,
+        claim_locked: match locked_withdrawable {
+            Some(amount) => amount > 0 && amount >= min_unlock_to_claim,
+            None => true,
+        },
+    }
+}
+
+/// Claims whatever the payer can claim right now: creates the claim status with the unlocked
+/// amount on the first run, then withdraws any locked tokens that have vested.
+async fn process_claim(args: &Args, claim_args: &ClaimArgs) -> Result<()> {
+    let claimant = payer_pubkey(args)?;
+    let fee_payer = args.fee_payer.as_deref().map(read_keypair).transpose()?;
+    let fee_payer_pubkey = fee_payer
+        .as_ref()
+        .map_or(claimant, |fee_payer| fee_payer.pubkey());
+
+    let (distributor, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+
+    let (claim_status_address, _) = get_claim_status_address(
+        &args.program_id,
+        &claimant,
+        &distributor,
+        &address_tree(args),
+    );
+
+    let client = new_light_client(args).await?;
+    check_indexer(&client, args).await?;
+    let mode = distribution_mode(&client.client, args, &distributor)?;
+    check_recipient(mode, claim_args.recipient)?;
+    let owner = claim_args.recipient.unwrap_or(claimant);
+    // The program would reject the claim, after the fee is paid
+    if mode == DistributionMode::Token {
+        let recipient_ata = get_associated_token_address(&owner, &args.mint);
+        if fetch_token_account(&client.client, &recipient_ata)?
+            .is_some_and(|account| account.is_frozen())
+        {
+            return Err(recipient_frozen_error());
+        }
+    }
+
+    let balance_before = payout_balance(&client.client, args, mode, &owner)?;
+
+    let mut new_claim_signature = None;
+    let existing = fetch_compressed_account(&client, claim_status_address).await?;
+    let claim_status_existed = existing.is_some();
+    let claim_status_compressed_account = match existing {
+        Some(compressed_account) => compressed_account,
+        None => {
+            info!("Claim status does not exist, creating it");
+            new_claim_signature = process_new_claim(args, claim_args).await?;
+            if args.output_instructions || exports_transactions(args) {
+                // claim_locked needs the claim status account to exist before it can be built
+                info!("Run again once new_claim has landed to build claim_locked");
+                return Ok(());
+            }
+            // claim_locked spends the new account, so it has to be indexed first
+            wait_for_compressed_account(
+                &client,
+                claim_status_address,
+                Duration::from_secs(args.indexer_timeout_secs),
+            )
+            .await?
+        }
+    };
+
+    let claim_status = parse_claim_status(&claim_status_compressed_account)?;
+    let distributor_state = fetch_distributor(&client.client, &distributor)?;
+    let locked_withdrawable = locked_withdrawable_now(&claim_status, &distributor_state);
+    let steps = claim_steps(
+        claim_status_existed,
+        locked_withdrawable,
+        claim_args.min_unlock_to_claim,
+    );
+    debug!("claim steps {steps:?}, locked withdrawable {locked_withdrawable:?}");
+
+    if !steps.claim_locked {
+        let locked_remaining = claim_status
+            .locked_amount
+            .saturating_sub(claim_status.locked_amount_withdrawn);
+        let withdrawable = locked_withdrawable.unwrap_or_default();
+        if withdrawable > 0 {
+            say!(
+                "Skipping claim_locked: {withdrawable} locked tokens withdrawable, below \
+                 --min-unlock-to-claim {}",
+                claim_args.min_unlock_to_claim
+            );
+        } else if steps.new_claim {
+            say!(
+                "Claimed the unlocked amount, {locked_remaining} locked tokens are not \
+                 withdrawable yet"
+            );
+        } else {
+            say!(
+                "Nothing to claim: {locked_remaining} of {} locked tokens remaining, none \
+                 withdrawable yet",
+                claim_status.locked_amount
+            );
+        }
+        if args.output_instructions {
+            return print_instructions(args, &[]);
+        }
+        let balance_after = payout_balance(&client.client, args, mode, &owner)?;
+        print_json(&ClaimOutput {
+            distributor: distributor.to_string(),
+            claim_status: Pubkey::new_from_array(claim_status_address).to_string(),
+            new_claim_signature: new_claim_signature.map(|sig| sig.to_string()),
+            signature: None,
+            amount_claimed: balance_after.saturating_sub(balance_before),
+        })?;
+        return Ok(());
+    }
+
+    // The claimant may have closed the token account since new_claim
+    let mut ixs: Vec<Instruction> =
+        create_claimant_ata_if_missing(&client, args, mode, &owner, &fee_payer_pubkey)
+            .await?
+            .into_iter()
+            .collect();
+    ixs.push(
+        claim_locked_instruction(
+            &client,
+            &mut ProofFetcher::new(&client, PROOF_BATCH_SIZE),
+            args,
+            &claimant,
+            &claim_status_compressed_account,
+            &claim_status,
+            mode,
+            claim_args.recipient,
+        )
+        .await?,
+    );
+    let ixs = with_compute_unit_limit(
+        &client.client,
+        args,
+        &fee_payer_pubkey,
+        ixs,
+        CLAIM_LOCKED_COMPUTE_UNITS,
+    );
+
+    if args.output_instructions {
+        return print_instructions(args, &ixs);
+    }
+
+    if args.priority.is_none() {
+        say!("No priority fee added. Add one with --priority <microlamports u64>");
+    }
+
+    match send_or_print_transaction(&client.client, args, &ixs, fee_payer.as_ref(), &[]) {
+        Ok(None) => Ok(()),
+        Ok(Some(signature)) => {
+            say!("Claimed tokens: {signature}");
+            let balance_after = payout_balance(&client.client, args, mode, &owner)?;
+            print_json(&ClaimOutput {
+                distributor: distributor.to_string(),
+                claim_status: Pubkey::new_from_array(claim_status_address).to_string(),
+                new_claim_signature: new_claim_signature.map(|sig| sig.to_string()),
+                signature: Some(signature.to_string()),
+                amount_claimed: balance_after.saturating_sub(balance_before),
+            })?;
+            Ok(())
+        }
+        Err(e) => {
+            if let DistributorError::InsufficientFunds(_) = e {
+                print_vault_funding_hint(args, &distributor, mode);
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Simulates the transaction `claim` would send next for the payer: `new_claim` while the
+/// claim status does not exist, `claim_locked` once it does. Nothing is signed or sent, so no
+/// account is created.
+async fn process_simulate_claim(args: &Args, simulate_args: &SimulateClaimArgs) -> Result<()> {
+    let claimant = payer_pubkey(args)?;
+    let fee_payer_pubkey = match args.fee_payer.as_deref() {
+        Some(path) => read_keypair(path)?.pubkey(),
+        None => claimant,
+    };
+
+    let (distributor, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+    let (claim_status_address, _) = get_claim_status_address(
+        &args.program_id,
+        &claimant,
+        &distributor,
+        &address_tree(args),
+    );
+
+    let client = new_light_client(args).await?;
+    check_indexer(&client, args).await?;
+    let mode = distribution_mode(&client.client, args, &distributor)?;
+    check_recipient(mode, simulate_args.recipient)?;
+    let owner = simulate_args.recipient.unwrap_or(claimant);
+
+    let mut ixs: Vec<Instruction> =
+        create_claimant_ata_if_missing(&client, args, mode, &owner, &fee_payer_pubkey)
+            .await?
+            .into_iter()
+            .collect();
+    let mut proofs = ProofFetcher::new(&client, PROOF_BATCH_SIZE);
+    let kind = match fetch_compressed_account(&client, claim_status_address).await? {
+        None => {
+            let merkle_tree = AirdropMerkleTree::new_from_file(&simulate_args.merkle_tree_path)?;
+            ixs.push(
+                new_claim_instruction(
+                    &client,
+                    &mut proofs,
+                    args,
+                    &merkle_tree,
+                    &claimant,
+                    mode,
+                    simulate_args.recipient,
+                )
+                .await?,
+            );
+            ClaimKind::NewClaim
+        }
+        Some(compressed_account) => {
+            let claim_status = parse_claim_status(&compressed_account)?;
+            ixs.push(
+                claim_locked_instruction(
+                    &client,
+                    &mut proofs,
+                    args,
+                    &claimant,
+                    &compressed_account,
+                    &claim_status,
+                    mode,
+                    simulate_args.recipient,
+                )
+                .await?,
+            );
+            ClaimKind::ClaimLocked
+        }
+    };
+
+    let result = simulate_instructions(&client.client, &fee_payer_pubkey, &ixs)
+        .map_err(DistributorError::RpcError)?;
+    let logs = result.logs.unwrap_or_default();
+    let events = claim_events(&logs, &args.program_id);
+    let error = result.err.map(|err| err.to_string());
+
+    match &error {
+        None => say!("Simulated {} for {claimant}: success", kind.name()),
+        Some(err) => say!("Simulated {} for {claimant}: failed, {err}", kind.name()),
+    }
+    if let Some(units) = result.units_consumed {
+        say!("Compute units consumed: {units}");
+    }
+    for event in &events {
+        say!(
+            "Event {}: {} claimed {}",
+            event.kind.name(),
+            event.claimant,
+            event.amount
+        );
+    }
+    say!("Logs:");
+    for line in &logs {
+        say!("  {line}");
+    }
+
+    print_json(&SimulateClaimOutput {
+        distributor: distributor.to_string(),
+        claimant: claimant.to_string(),
+        instruction: kind.name().to_string(),
+        success: error.is_none(),
+        error,
+        units_consumed: result.units_consumed,
+        events: events
+            .iter()
+            .map(|event| SimulatedClaimEvent {
+                kind: event.kind.name().to_string(),
+                claimant: event.claimant.to_string(),
+                amount: event.amount,
+            })
+            .collect(),
+        logs,
+    })?;
+    Ok(())
+}
+
+/// `claim_locked` for `claimant`, spending its existing claim status account.
+/// `claim_locked_to` when the tokens go to the token account of `recipient`,
+/// `claim_locked_native` for a native SOL distributor.
+#[allow(clippy::too_many_arguments)]
+async fn claim_locked_instruction(
+    client: &LightClient,
+    proofs: &mut ProofFetcher<'_, LightClient>,
+    args: &Args,
+    claimant: &Pubkey,
+    claim_status_compressed_account: &CompressedAccount,
+    claim_status: &ClaimStatus,
+    mode: DistributionMode,
+    recipient: Option<Pubkey>,
+) -> Result<Instruction> {
+    check_recipient(mode, recipient)?;
+    let (distributor, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+    let (claim_status_address, _) = get_claim_status_address(
+        &args.program_id,
+        claimant,
+        &distributor,
+        &address_tree(args),
+    );
+
+    let (input_account_meta, validity_proof, packed_account_metas) = claim_status_input(
+        client,
+        proofs,
+        args,
+        claim_status_address,
+        claim_status_compressed_account,
+    )
+    .await?;
+
+    let claim_status_data = ClaimStatusInstructionData {
+        locked_amount: claim_status.locked_amount,
+        locked_amount_withdrawn: claim_status.locked_amount_withdrawn,
+        unlocked_amount: claim_status.unlocked_amount,
+        unlock_schedule: claim_status.unlock_schedule,
+        last_claimed_ts: claim_status.last_claimed_ts,
+        category_breakdown: claim_status.category_breakdown,
+    };
+    let (accounts, data) = match (mode, recipient) {
+        (DistributionMode::Token, Some(recipient)) => (
+            merkle_distributor::accounts::ClaimLockedTo {
+                distributor,
+                from: vault_address(args, &distributor, mode),
+                to: get_associated_token_address(&recipient, &args.mint),
+                claimant: *claimant,
+                mint: args.mint,
+                token_program: token::ID,
+            }
+            .to_account_metas(None),
+            merkle_distributor::instruction::ClaimLockedTo {
+                claim_status_data,
+                validity_proof,
+                input_account_meta,
+            }
+            .data(),
+        ),
+        (DistributionMode::Token, None) => (
+            merkle_distributor::accounts::ClaimLocked {
+                distributor,
+                from: vault_address(args, &distributor, mode),
+                to: get_associated_token_address(claimant, &args.mint),
+                claimant: *claimant,
+                mint: args.mint,
+                token_program: token::ID,
+            }
+            .to_account_metas(None),
+            merkle_distributor::instruction::ClaimLocked {
+                claim_status_data,
+                validity_proof,
+                input_account_meta,
+            }
+            .data(),
+        ),
+        (DistributionMode::Native, _) => (
+            merkle_distributor::accounts::ClaimLockedNative {
+                distributor,
+                from: vault_address(args, &distributor, mode),
+                claimant: *claimant,
+                system_program: solana_program::system_program::ID,
+            }
+            .to_account_metas(None),
+            merkle_distributor::instruction::ClaimLockedNative {
+                claim_status_data,
+                validity_proof,
+                input_account_meta,
+            }
+            .data(),
+        ),
+    };
+
+    Ok(Instruction {
+        program_id: args.program_id,
+        accounts: [accounts, packed_account_metas].concat(),
+        data,
+    })
+}
+
+/// Input account meta, validity proof and Light remaining accounts spending the claim status
+/// `claim_status_compressed_account` at `claim_status_address`, for claim_locked and
+/// migrate_claim_status.
+async fn claim_status_input(
+    client: &LightClient,
+    proofs: &mut ProofFetcher<'_, LightClient>,
+    args: &Args,
+    claim_status_address: [u8; 32],
+    claim_status_compressed_account: &CompressedAccount,
+) -> Result<(CompressedAccountMeta, ValidityProof, Vec<AccountMeta>)> {
+    let validity_proof = proofs
+        .get(ProofKey::Account(claim_status_compressed_account.hash))
+        .await
+        .map_err(map_indexer_error)?;
+
+    // Build v2 PackedStateTreeInfo from the compressed account merkle context
+    let mut packed_accounts = PackedAccounts::default();
+    packed_accounts
+        .add_system_accounts_v2(SystemAccountMetaConfig::new(merkle_distributor::ID))
+        .map_err(|e| DistributorError::RpcError(format!("failed to add system accounts: {e}")))?;
+
+    // The queue comes from the indexer's context for the account's own tree, so any
+    // deployment works. Reject incomplete tree info rather than fail on-chain.
+    let claim_status_tree_info = &claim_status_compressed_account.tree_info;
+    if claim_status_tree_info.tree == Pubkey::default()
+        || claim_status_tree_info.queue == Pubkey::default()
+    {
+        return Err(DistributorError::TreeMismatch(format!(
+            "indexer returned no state tree or queue for claim status {}",
+            Pubkey::new_from_array(claim_status_address)
+        )));
+    }
+
+    // Add state tree and queue to packed accounts
+    let merkle_tree_index =
+        packed_accounts.insert_or_get(claim_status_compressed_account.tree_info.tree);
+    let queue_index =
+        packed_accounts.insert_or_get(claim_status_compressed_account.tree_info.queue);
+
+    let tree_info = PackedStateTreeInfo {
+        root_index: validity_proof.accounts[0]
+            .root_index
+            .root_index()
+            .unwrap_or_default(),
+        prove_by_index: validity_proof.accounts[0].root_index.proof_by_index(),
+        merkle_tree_pubkey_index: merkle_tree_index,
+        queue_pubkey_index: queue_index,
+        leaf_index: claim_status_compressed_account.leaf_index,
+    };
+
+    let output_state_tree_index = match args.state_tree {
+        Some(_) => output_state_tree_info(client, args)?
+            .pack_output_tree_index(&mut packed_accounts)
+            .map_err(map_indexer_error)?,
+        None => queue_index,
+    };
+    let input_account_meta = CompressedAccountMeta {
+        tree_info,
+        address: claim_status_address,
+        output_state_tree_index,
+    };
+
+    let (packed_account_metas, system_accounts_offset, packed_accounts_offset) =
+        packed_accounts.to_account_metas();
+
+    if tracing::enabled!(Level::TRACE) {
+        trace!(
+            "claim status address {}",
+            Pubkey::new_from_array(claim_status_address)
+        );
+        trace!(
+            "claim status hash {:?} tree {} queue {}",
+            claim_status_compressed_account.hash,
+            claim_status_compressed_account.tree_info.tree,
+            claim_status_compressed_account.tree_info.queue
+        );
+        trace!("input_account_meta {input_account_meta:?}");
+        trace!("validity proof {:?}", validity_proof.proof);
+        trace_cpi_remaining_accounts(
+            &packed_account_metas,
+            system_accounts_offset,
+            packed_accounts_offset,
+        );
+    }
+
+    Ok((
+        input_account_meta,
+        validity_proof.proof,
+        packed_account_metas,
+    ))
+}
+
+/// new_claim for `claimant`, creating its token account when missing, paid by `funder`.
+async fn wallet_new_claim(
+    client: &LightClient,
+    proofs: &mut ProofFetcher<'_, LightClient>,
+    args: &Args,
+    merkle_tree: &AirdropMerkleTree,
+    claimant: &Pubkey,
+    funder: &Pubkey,
+    mode: DistributionMode,
+) -> Result<WalletClaim> {
+    let mut instructions: Vec<Instruction> =
+        create_claimant_ata_if_missing(client, args, mode, claimant, funder)
+            .await?
+            .into_iter()
+            .collect();
+    instructions.push(
+        new_claim_instruction(client, proofs, args, merkle_tree, claimant, mode, None).await?,
+    );
+    Ok(WalletClaim {
+        instructions,
+        compute_units: NEW_CLAIM_COMPUTE_UNITS,
+    })
+}
+
+/// claim_locked for `claimant`, None while fewer than `min_unlock_to_claim` locked tokens are
+/// withdrawable.
+#[allow(clippy::too_many_arguments)]
+async fn wallet_claim_locked(
+    client: &LightClient,
+    proofs: &mut ProofFetcher<'_, LightClient>,
+    args: &Args,
+    distributor: &MerkleDistributor,
+    claimant: &Pubkey,
+    funder: &Pubkey,
+    claim_status_compressed_account: &CompressedAccount,
+    min_unlock_to_claim: u64,
+) -> Result<Option<WalletClaim>> {
+    let claim_status = parse_claim_status(claim_status_compressed_account)?;
+    let locked_withdrawable = locked_withdrawable_now(&claim_status, distributor);
+    if !claim_steps(true, locked_withdrawable, min_unlock_to_claim).claim_locked {
+        return Ok(None);
+    }
+    let mode = distributor.distribution_mode;
+    let mut instructions: Vec<Instruction> =
+        create_claimant_ata_if_missing(client, args, mode, claimant, funder)
+            .await?
+            .into_iter()
+            .collect();
+    instructions.push(
+        claim_locked_instruction(
+            client,
+            proofs,
+            args,
+            claimant,
+            claim_status_compressed_account,
+            &claim_status,
+            mode,
+            None,
+        )
+        .await?,
+    );
+    Ok(Some(WalletClaim {
+        instructions,
+        compute_units: CLAIM_LOCKED_COMPUTE_UNITS,
+    }))
+}
+
+/// Instructions [send_or_print_transaction] adds to every transaction, counted when packing
+/// claims. The compute unit price only stands in for its size.
+fn batch_reserved_instructions(args: &Args, payer: &Pubkey) -> Vec<Instruction> {
+    let mut ixs = vec![ComputeBudgetInstruction::set_compute_unit_price(
+        args.max_priority,
+    )];
+    if let Some(nonce_account) = args.nonce_account {
+        let authority = args.nonce_authority.unwrap_or(*payer);
+        ixs.push(advance_nonce_account(&nonce_account, &authority));
+    }
+    ixs
+}
+
+/// Sends `claims` in as few transactions as fit, each co-signed by the `claimants` whose claims
+/// it carries, and returns the outcome of every claim. A failed transaction fails all of its
+/// claims. With `--output-instructions` each transaction is printed instead, without signature.
+fn send_wallet_claims(
+    client: &RpcClient,
+    args: &Args,
+    payer: &Pubkey,
+    claimants: &[&Keypair],
+    claims: &[WalletClaim],
+) -> Result<Vec<std::result::Result<Option<Signature>, String>>> {
+    let batches = pack_claims(payer, &batch_reserved_instructions(args, payer), claims);
+    let mut outcomes = Vec::with_capacity(claims.len());
+    for (i, batch) in batches.iter().enumerate() {
+        let ixs = batch_instructions(&claims[batch.clone()]);
+        if args.output_instructions {
+            print_instructions(args, &ixs)?;
+            outcomes.extend(batch.clone().map(|_| Ok(None)));
+            continue;
+        }
+        info!(
+            "Sending transaction {}/{} with {} claims",
+            i + 1,
+            batches.len(),
+            batch.len()
+        );
+        let co_signers: Vec<&Keypair> = claimants[batch.clone()]
+            .iter()
+            .copied()
+            .filter(|claimant| claimant.pubkey() != *payer)
+            .collect();
+        let outcome = send_or_print_transaction(client, args, &ixs, None, &co_signers)
+            .map_err(|e| e.to_string());
+        if let Err(e) = &outcome {
+            warn!("Transaction {}/{} failed: {e}", i + 1, batches.len());
+        }
+        outcomes.extend(batch.clone().map(|_| outcome.clone()));
+    }
+    Ok(outcomes)
+}
+
+/// Claims for every wallet of `--keypairs` like `claim` does, packing the claims of several
+/// wallets into each transaction: new_claim for wallets without a claim status, then
+/// claim_locked for wallets with vested locked tokens. The `--keypair-path` keypair pays the
+/// fees and any missing token accounts. Failures are reported per wallet.
+async fn process_batch_claim(args: &Args, batch_claim_args: &BatchClaimArgs) -> Result<()> {
+    if exports_transactions(args) {
+        return Err(DistributorError::IoError(std::io::Error::other(
+            "batch-claim signs with the claimant keypairs, --offline and --export-tx are not \
+             supported",
+        )));
+    }
+    let payer = payer_signer(args)?.pubkey();
+    let merkle_tree = AirdropMerkleTree::new_from_file(&batch_claim_args.merkle_tree_path)?;
+
+    let (distributor, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+    let claim_status_address = |claimant: &Pubkey| {
+        get_claim_status_address(
+            &args.program_id,
+            claimant,
+            &distributor,
+            &address_tree(args),
+        )
+        .0
+    };
+
+    let mut claimants: Vec<Keypair> = vec![];
+    let mut unreadable = vec![];
+    for path in read_keypair_paths(&batch_claim_args.keypairs)? {
+        match read_keypair(&path) {
+            Ok(keypair) if claimants.iter().any(|c| c.pubkey() == keypair.pubkey()) => {
+                warn!(
+                    "Skipping {}, {} is listed twice",
+                    path.display(),
+                    keypair.pubkey()
+                );
+            }
+            Ok(keypair) => claimants.push(keypair),
+            Err(e) => unreadable.push(BatchClaimWallet {
+                wallet: path.display().to_string(),
+                new_claim_signature: None,
+                claim_locked_signature: None,
+                error: Some(e.to_string()),
+            }),
+        }
+    }
+    info!("Claiming for {} wallets", claimants.len());
+    let mut wallets: Vec<BatchClaimWallet> = claimants
+        .iter()
+        .map(|claimant| BatchClaimWallet {
+            wallet: claimant.pubkey().to_string(),
+            new_claim_signature: None,
+            claim_locked_signature: None,
+            error: None,
+        })
+        .collect();
+
+    let client = new_light_client(args).await?;
+    check_indexer(&client, args).await?;
+    check_address_tree(&client, &address_tree(args)).await?;
+
+    // new_claim for the wallets without a claim status
+    let mut existing = vec![];
+    for claimant in &claimants {
+        existing.push(
+            fetch_compressed_account(&client, claim_status_address(&claimant.pubkey())).await,
+        );
+    }
+    let mut proofs = ProofFetcher::new(&client, PROOF_BATCH_SIZE);
+    let new_addresses: Vec<ProofKey> = claimants
+        .iter()
+        .zip(&existing)
+        .filter(|(_, existing)| matches!(existing, Ok(None)))
+        .map(|(claimant, _)| ProofKey::Address {
+            address: claim_status_address(&claimant.pubkey()),
+            tree: address_tree(args),
+        })
+        .collect();
+    proofs.prefetch(&new_addresses).await;
+    let mode = distribution_mode(&client.client, args, &distributor)?;
+    let mut claim_statuses = vec![];
+    let mut pending = vec![];
+    let mut claims = vec![];
+    for (i, (claimant, existing)) in claimants.iter().zip(existing).enumerate() {
+        let claim = match existing {
+            Ok(Some(compressed_account)) => {
+                claim_statuses.push(Some(compressed_account));
+                continue;
+            }
+            Ok(None) => {
+                wallet_new_claim(
+                    &client,
+                    &mut proofs,
+                    args,
+                    &merkle_tree,
+                    &claimant.pubkey(),
+                    &payer,
+                    mode,
+                )
+                .await
+            }
+            Err(e) => Err(e),
+        };
+        claim_statuses.push(None);
+        match claim {
+            Ok(claim) => {
+                pending.push(i);
+                claims.push(claim);
+            }
+            Err(e) => wallets[i].error = Some(e.to_string()),
+        }
+    }
+    let signers: Vec<&Keypair> = pending.iter().map(|&i| &claimants[i]).collect();
+    let outcomes = send_wallet_claims(&client.client, args, &payer, &signers, &claims)?;
+    for (&i, outcome) in pending.iter().zip(outcomes) {
+        match outcome {
+            Ok(signature) => wallets[i].new_claim_signature = signature.map(|s| s.to_string()),
+            Err(e) => wallets[i].error = Some(e),
+        }
+    }
+
+    // claim_locked for the wallets with enough vested locked tokens
+    let distributor_state = fetch_distributor(&client.client, &distributor)?;
+    let mut claim_locked_accounts = vec![];
+    for (i, claimant) in claimants.iter().enumerate() {
+        if wallets[i].error.is_some() {
+            continue;
+        }
+        let claim_status_compressed_account = match claim_statuses[i].take() {
+            Some(compressed_account) => compressed_account,
+            // new_claim was only printed, its claim status does not exist yet
+            None if wallets[i].new_claim_signature.is_none() => continue,
+            // claim_locked spends the new account, so it has to be indexed first
+            None => match wait_for_compressed_account(
+                &client,
+                claim_status_address(&claimant.pubkey()),
+                Duration::from_secs(args.indexer_timeout_secs),
+            )
+            .await
+            {
+                Ok(compressed_account) => compressed_account,
+                Err(e) => {
+                    wallets[i].error = Some(e.to_string());
+                    continue;
+                }
+            },
+        };
+        claim_locked_accounts.push((i, claim_status_compressed_account));
+    }
+    let spent_accounts: Vec<ProofKey> = claim_locked_accounts
+        .iter()
+        .filter(|(_, compressed_account)| {
+            parse_claim_status(compressed_account).is_ok_and(|claim_status| {
+                let locked_withdrawable =
+                    locked_withdrawable_now(&claim_status, &distributor_state);
+                claim_steps(
+                    true,
+                    locked_withdrawable,
+                    batch_claim_args.min_unlock_to_claim,
+                )
+                .claim_locked
+            })
+        })
+        .map(|(_, compressed_account)| ProofKey::Account(compressed_account.hash))
+        .collect();
+    proofs.prefetch(&spent_accounts).await;
+    let mut pending = vec![];
+    let mut claims = vec![];
+    for (i, claim_status_compressed_account) in claim_locked_accounts {
+        let claim = wallet_claim_locked(
+            &client,
+            &mut proofs,
+            args,
+            &distributor_state,
+            &claimants[i].pubkey(),
+            &payer,
+            &claim_status_compressed_account,
+            batch_claim_args.min_unlock_to_claim,
+        )
+        .await;
+        match claim {
+            Ok(Some(claim)) => {
+                pending.push(i);
+                claims.push(claim);
+            }
+            Ok(None) => {}
+            Err(e) => wallets[i].error = Some(e.to_string()),
+        }
+    }
+    let signers: Vec<&Keypair> = pending.iter().map(|&i| &claimants[i]).collect();
+    let outcomes = send_wallet_claims(&client.client, args, &payer, &signers, &claims)?;
+    for (&i, outcome) in pending.iter().zip(outcomes) {
+        match outcome {
+            Ok(signature) => wallets[i].claim_locked_signature = signature.map(|s| s.to_string()),
+            Err(e) => wallets[i].error = Some(e),
+        }
+    }
+    debug!(
+        "Fetched validity proofs with {} indexer requests",
+        proofs.requests()
+    );
+
+    if args.output_instructions {
+        return Ok(());
+    }
+
+    wallets.extend(unreadable);
+    for wallet in &wallets {
+        match (
+            &wallet.error,
+            &wallet.new_claim_signature,
+            &wallet.claim_locked_signature,
+        ) {
+            (Some(e), _, _) => say!("{}: failed, {e}", wallet.wallet),
+            (None, None, None) => say!("{}: nothing to claim", wallet.wallet),
+            (None, new_claim, claim_locked) => say!(
+                "{}: claimed, signatures: {}",
+                wallet.wallet,
+                new_claim
+                    .iter()
+                    .chain(claim_locked)
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+    let failed = wallets
+        .iter()
+        .filter(|wallet| wallet.error.is_some())
+        .count();
+    say!("{failed} of {} wallets failed", wallets.len());
+    print_json(&BatchClaimOutput {
+        distributor: distributor.to_string(),
+        failed,
+        wallets,
+    })?;
+    Ok(())
+}
+
+fn check_distributor_onchain_matches(
+    account: &Account,
+    merkle_tree: &AirdropMerkleTree,
+    new_distributor_args: &NewDistributorArgs,
+    pubkey: Pubkey,
+) -> Result<()> {
+    let mismatch = |field: &str| {
+        DistributorError::TreeMismatch(format!(
+            "{field} mismatch: on-chain distributor does not match provided arguments! \
+             Confirm admin and clawback parameters to avoid loss of funds!"
+        ))
+    };
+
+    if let Ok(distributor) = MerkleDistributor::try_deserialize(&mut account.data.as_slice()) {
+        if distributor.root != merkle_tree.merkle_root {
+            return Err(mismatch("root"));
+        }
+        if distributor.max_total_claim != merkle_tree.max_total_claim {
+            return Err(mismatch("max_total_claim"));
+        }
+        if distributor.max_num_nodes != merkle_tree.max_num_nodes {
+            return Err(mismatch("max_num_nodes"));
+        }
+        // Migrated distributors do not know their depth
+        if distributor.tree_depth != 0 && distributor.tree_depth != merkle_tree.tree_depth() {
+            return Err(mismatch("tree_depth"));
+        }
+
+        if distributor.start_ts != new_distributor_args.start_vesting_ts {
+            return Err(mismatch("start_ts"));
+        }
+        if distributor.end_ts != new_distributor_args.end_vesting_ts {
+            return Err(mismatch("end_ts"));
+        }
+        if distributor.cliff_ts != new_distributor_args.cliff_ts() {
+            return Err(mismatch("cliff_ts"));
+        }
+        if distributor.clawback_start_ts != new_distributor_args.clawback_start_ts {
+            return Err(mismatch("clawback_start_ts"));
+        }
+        if distributor.min_claim_interval != new_distributor_args.min_claim_interval {
+            return Err(mismatch("min_claim_interval"));
+        }
+        if distributor.vesting_interval_secs != new_distributor_args.vesting_interval_secs {
+            return Err(mismatch("vesting_interval_secs"));
+        }
+        if distributor.clawback_receiver != new_distributor_args.clawback_receiver_token_account {
+            return Err(mismatch("clawback_receiver"));
+        }
+        if distributor.distribution_mode != new_distributor_args.distribution_mode() {
+            return Err(mismatch("distribution_mode"));
+        }
+        if distributor.allow_reassignment != new_distributor_args.allow_reassignment {
+            return Err(mismatch("allow_reassignment"));
+        }
+        if distributor.admin != pubkey {
+            return Err(mismatch("admin"));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that `max_total_claim` and `max_num_nodes`, which new_distributor and update_root take
+/// from the tree file, match its nodes. Caps below the tree leave the last claimants unable to claim, and a
+/// `max_total_claim` above it makes the vault look underfunded.
+fn check_tree_totals(merkle_tree: &AirdropMerkleTree) -> Result<()> {
+    let total_amount = merkle_tree
+        .tree_nodes()
+        .iter()
+        .try_fold(0u64, |total, node| {
+            total
+                .checked_add(node.amount_unlocked())?
+                .checked_add(node.amount_locked())
+        })
+        .ok_or_else(|| DistributorError::TreeMismatch("node amounts overflow a u64".to_string()))?;
+    if total_amount != merkle_tree.max_total_claim {
+        return Err(DistributorError::TreeMismatch(format!(
+            "node amounts add up to {total_amount} but the tree file's max_total_claim is {}, \
+             rebuild it with create-merkle-tree",
+            merkle_tree.max_total_claim
+        )));
+    }
+    if merkle_tree.tree_nodes().len() as u64 != merkle_tree.max_num_nodes {
+        return Err(DistributorError::TreeMismatch(format!(
+            "the tree file has {} nodes but its max_num_nodes is {}, rebuild it with \
+             create-merkle-tree",
+            merkle_tree.tree_nodes().len(),
+            merkle_tree.max_num_nodes
+        )));
+    }
+    Ok(())
+}
+
+fn process_new_distributor(args: &Args, new_distributor_args: &NewDistributorArgs) -> Result<()> {
+    let client = rpc_client(args);
+
+    let admin = payer_pubkey(args)?;
+    let merkle_tree = AirdropMerkleTree::new_from_file(&new_distributor_args.merkle_tree_path)?;
+    // The tree file may have been edited by hand since create-merkle-tree
+    check_tree_totals(&merkle_tree)?;
+    check_unlock_schedules_end_by(
+        merkle_tree.tree_nodes(),
+        new_distributor_args.clawback_start_ts,
+    )?;
+    let mode = new_distributor_args.distribution_mode();
+    if mode == DistributionMode::Native && args.mint != token::spl_token::native_mint::ID {
+        return Err(DistributorError::IoError(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "native SOL distributors belong to the native mint, pass --mint {}",
+                token::spl_token::native_mint::ID
+            ),
+        )));
+    }
+    if mode == DistributionMode::Native && new_distributor_args.allow_reassignment {
+        return Err(DistributorError::IoError(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "--allow-reassignment is not supported by native SOL distributors",
+        )));
+    }
+    let (distributor_pubkey, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+    let token_vault = vault_address(args, &distributor_pubkey, mode);
+
+    if let Some(account) = client
+        .get_account_with_commitment(&distributor_pubkey, client.commitment())
+        .map_err(map_client_error)?
+        .value
+    {
+        info!("Distributor {distributor_pubkey} exists, checking its parameters");
+        // Guard against reusing a version that already holds a different airdrop
+        if let Ok(existing) = MerkleDistributor::try_deserialize(&mut account.data.as_slice()) {
+            if existing.root != merkle_tree.merkle_root {
+                let used_versions: Vec<u64> = fetch_distributors(&client, args)?
+                    .iter()
+                    .map(|(_, distributor)| distributor.version)
+                    .collect();
+                let hint = next_free_version(&used_versions, args.airdrop_version)
+                    .map(|version| format!(", next free version is {version}"))
+                    .unwrap_or_default();
+                return Err(DistributorError::TreeMismatch(format!(
+                    "airdrop version {} is already used by distributor {distributor_pubkey} \
+                     with a different merkle root{hint}",
+                    args.airdrop_version
+                )));
+            }
+        }
+        check_distributor_onchain_matches(&account, &merkle_tree, new_distributor_args, admin)?;
+        info!("Distributor {distributor_pubkey} already exists with these parameters");
+        print_json(&NewDistributorOutput {
+            distributor: distributor_pubkey.to_string(),
+            token_vault: token_vault.to_string(),
+            signature: None,
+        })?;
+        return Ok(());
+    }
+
+    info!("Creating distributor {distributor_pubkey}");
+    debug!("new-distributor args: {new_distributor_args:?}");
+
+    let new_distributor_ix = match mode {
+        DistributionMode::Token => Instruction {
+            program_id: args.program_id,
+            accounts: merkle_distributor::accounts::NewDistributor {
+                clawback_receiver: new_distributor_args.clawback_receiver_token_account,
+                mint: args.mint,
+                token_vault,
+                distributor: distributor_pubkey,
+                system_program: solana_program::system_program::id(),
+                associated_token_program: spl_associated_token_account::ID,
+                token_program: token::ID,
+                admin,
+            }
+            .to_account_metas(None),
+            data: merkle_distributor::instruction::NewDistributor {
+                version: args.airdrop_version,
+                root: merkle_tree.merkle_root,
+                max_total_claim: merkle_tree.max_total_claim,
+                max_num_nodes: merkle_tree.max_num_nodes,
+                start_vesting_ts: new_distributor_args.start_vesting_ts,
+                end_vesting_ts: new_distributor_args.end_vesting_ts,
+                cliff_ts: new_distributor_args.cliff_ts(),
+                clawback_start_ts: new_distributor_args.clawback_start_ts,
+                min_claim_interval: new_distributor_args.min_claim_interval,
+                vesting_interval_secs: new_distributor_args.vesting_interval_secs,
+                allow_reassignment: new_distributor_args.allow_reassignment,
+                tree_depth: merkle_tree.tree_depth(),
+            }
+            .data(),
+        },
+        DistributionMode::Native => Instruction {
+            program_id: args.program_id,
+            accounts: merkle_distributor::accounts::NewNativeDistributor {
+                distributor: distributor_pubkey,
+                clawback_receiver: new_distributor_args.clawback_receiver_token_account,
+                sol_vault: token_vault,
+                admin,
+                system_program: solana_program::system_program::id(),
+            }
+            .to_account_metas(None),
+            data: merkle_distributor::instruction::NewNativeDistributor {
+                version: args.airdrop_version,
+                root: merkle_tree.merkle_root,
+                max_total_claim: merkle_tree.max_total_claim,
+                max_num_nodes: merkle_tree.max_num_nodes,
+                start_vesting_ts: new_distributor_args.start_vesting_ts,
+                end_vesting_ts: new_distributor_args.end_vesting_ts,
+                cliff_ts: new_distributor_args.cliff_ts(),
+                clawback_start_ts: new_distributor_args.clawback_start_ts,
+                min_claim_interval: new_distributor_args.min_claim_interval,
+                vesting_interval_secs: new_distributor_args.vesting_interval_secs,
+                tree_depth: merkle_tree.tree_depth(),
+            }
+            .data(),
+        },
+    };
+
+    if args.output_instructions {
+        return print_instructions(args, &[new_distributor_ix]);
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    confirm(
+        args,
+        &[
+            format!("Creating distributor {distributor_pubkey}"),
+            format!("  Airdrop version: {}", args.airdrop_version),
+            format!("  Distributes: {mode:?}"),
+            format!("  Merkle root: {}", hex::encode(merkle_tree.merkle_root)),
+            format!(
+                "  Total claim: {} over {} nodes",
+                merkle_tree.max_total_claim, merkle_tree.max_num_nodes
+            ),
+            format!(
+                "  Vesting start: {}",
+                describe_ts(new_distributor_args.start_vesting_ts, now)
+            ),
+            format!(
+                "  Cliff: {}",
+                describe_ts(new_distributor_args.cliff_ts(), now)
+            ),
+            format!(
+                "  Vesting end: {}",
+                describe_ts(new_distributor_args.end_vesting_ts, now)
+            ),
+            format!(
+                "  Vesting interval: {}s",
+                new_distributor_args.vesting_interval_secs
+            ),
+            format!(
+                "  Claim reassignment: {}",
+                match new_distributor_args.allow_reassignment {
+                    true => "allowed",
+                    false => "not allowed",
+                }
+            ),
+            format!(
+                "  Clawback start: {}",
+                describe_ts(new_distributor_args.clawback_start_ts, now)
+            ),
+            format!(
+                "  Clawback receiver: {}",
+                new_distributor_args.clawback_receiver_token_account
+            ),
+            format!("  Admin: {admin}"),
+        ],
+    )?;
+
+    // See comments on new_distributor instruction inside the program to ensure this transaction
+    // didn't get frontrun.
+    // If this fails, make sure to run it again.
+    match send_or_print_transaction(&client, args, &[new_distributor_ix], None, &[]) {
+        Ok(None) => Ok(()),
+        Ok(Some(sig)) => {
+            say!("\nDistributor created: {sig}");
+            say!("  Distributor: {distributor_pubkey}");
+            say!("  Token vault: {token_vault}");
+            if mode == DistributionMode::Native {
+                say!("\nNext step: send the lamports to the SOL vault:");
+                say!(
+                    "  cli --mint {} fund-vault --amount {}",
+                    args.mint,
+                    merkle_tree.max_total_claim
+                );
+            } else {
+                say!("\nNext step: mint tokens to the vault:");
+                say!(
+                    "  cli --mint {} fund-vault --amount {} --use-mint-authority",
+                    args.mint,
+                    merkle_tree.max_total_claim
+                );
+                say!(
+                    "  or: spl-token mint {} {} {}",
+                    args.mint,
+                    merkle_tree.max_total_claim,
+                    token_vault
+                );
+            }
+            print_json(&NewDistributorOutput {
+                distributor: distributor_pubkey.to_string(),
+                token_vault: token_vault.to_string(),
+                signature: Some(sig.to_string()),
+            })?;
+            Ok(())
+        }
+        Err(e) => {
+            say!("Failed to create MerkleDistributor: {:?}", e);
+
+            // double check someone didn't frontrun this transaction with a malicious merkle root
+            if let Some(account) = client
+                .get_account_with_commitment(&distributor_pubkey, CommitmentConfig::processed())
+                .map_err(map_client_error)?
+                .value
+            {
+                check_distributor_onchain_matches(
+                    &account,
+                    &merkle_tree,
+                    new_distributor_args,
+                    admin,
+                )?;
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Offset of `MerkleDistributor::mint`: discriminator, bump, version, root.
+const DISTRIBUTOR_MINT_OFFSET: usize = 8 + 1 + 8 + 32;
+
+/// Fetch every distributor of `args.program_id` for `args.mint`, sorted by version.
+fn fetch_distributors(client: &RpcClient, args: &Args) -> Result<Vec<(Pubkey, MerkleDistributor)>> {
+    let accounts = client
+        .get_program_accounts_with_config(
+            &args.program_id,
+            RpcProgramAccountsConfig {
+                filters: Some(vec![
+                    RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                        0,
+                        MerkleDistributor::DISCRIMINATOR.to_vec(),
+                    )),
+                    RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                        DISTRIBUTOR_MINT_OFFSET,
+                        args.mint.to_bytes().to_vec(),
+                    )),
+                ]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    ..RpcAccountInfoConfig::default()
+                },
+                ..RpcProgramAccountsConfig::default()
+            },
+        )
+        .map_err(map_client_error)?;
+
+    let mut distributors = accounts
+        .into_iter()
+        .filter_map(|(pubkey, account)| {
+            MerkleDistributor::try_deserialize(&mut account.data.as_slice())
+                .ok()
+                .map(|distributor| (pubkey, distributor))
+        })
+        .collect::<Vec<_>>();
+    distributors.sort_by_key(|(_, distributor)| distributor.version);
+    Ok(distributors)
+}
+
+fn process_list_distributors(args: &Args) -> Result<()> {
+    let client = rpc_client(args);
+    let distributors = fetch_distributors(&client, args)?;
+
+    if distributors.is_empty() {
+        say!("No distributors found for mint {}", args.mint);
+    }
+    for (pubkey, distributor) in distributors.iter() {
+        say!(
+            "version {} ({}): {pubkey}",
+            distributor.version,
+            VersionScheme::classify(distributor.version)
+        );
+        say!(
+            "  claimed {}/{} nodes, {}/{} tokens, clawed back: {}",
+            distributor.num_nodes_claimed,
+            distributor.max_num_nodes,
+            distributor.total_amount_claimed,
+            distributor.max_total_claim,
+            distributor.clawed_back
+        );
+    }
+
+    let used_versions: Vec<u64> = distributors
+        .iter()
+        .map(|(_, distributor)| distributor.version)
+        .collect();
+    // Sequential airdrops continue after the highest counter in use
+    let from = used_versions
+        .iter()
+        .copied()
+        .filter(|version| VersionScheme::classify(*version) == VersionScheme::Sequential)
+        .max()
+        .map_or(0, |version| version.saturating_add(1));
+    let next_free_version = next_free_version(&used_versions, from);
+    if let Some(version) = next_free_version {
+        say!("Next free sequential version: {version}");
+    }
+    print_json(&ListDistributorsOutput {
+        distributors: distributors
+            .iter()
+            .map(|(pubkey, distributor)| DistributorSummary {
+                distributor: pubkey.to_string(),
+                version: distributor.version,
+                version_scheme: VersionScheme::classify(distributor.version).to_string(),
+                num_nodes_claimed: distributor.num_nodes_claimed,
+                max_num_nodes: distributor.max_num_nodes,
+                total_amount_claimed: distributor.total_amount_claimed,
+                max_total_claim: distributor.max_total_claim,
+                clawed_back: distributor.clawed_back,
+            })
+            .collect(),
+        next_free_version,
+    })?;
+    Ok(())
+}
+
+/// Builds the mint_to or transfer_checked instruction moving `--amount` into `token_vault`.
+fn fund_vault_instruction(
+    args: &Args,
+    fund_vault_args: &FundVaultArgs,
+    authority: &Pubkey,
+    token_vault: &Pubkey,
+    decimals: u8,
+) -> Result<Instruction> {
+    let instruction = match fund_vault_args.from_token_account {
+        Some(source) => token::spl_token::instruction::transfer_checked(
+            &token::ID,
+            &source,
+            &args.mint,
+            token_vault,
+            authority,
+            &[],
+            fund_vault_args.amount,
+            decimals,
+        ),
+        None => token::spl_token::instruction::mint_to(
+            &token::ID,
+            &args.mint,
+            token_vault,
+            authority,
+            &[],
+            fund_vault_args.amount,
+        ),
+    };
+    instruction.map_err(|e| {
+        DistributorError::IoError(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("failed to build the funding instruction: {e}"),
+        ))
+    })
+}
+
+fn process_fund_vault(args: &Args, fund_vault_args: &FundVaultArgs) -> Result<()> {
+    let authority = payer_pubkey(args)?;
+
+    let client = rpc_client(args);
+
+    let (distributor, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+    let mode = distribution_mode(&client, args, &distributor)?;
+    let token_vault = vault_address(args, &distributor, mode);
+    let invalid_input = |message: &str| {
+        DistributorError::IoError(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            message.to_string(),
+        ))
+    };
+
+    let fund_vault_ix = match mode {
+        DistributionMode::Native => {
+            if fund_vault_args.from_token_account.is_some() || fund_vault_args.use_mint_authority {
+                return Err(invalid_input(
+                    "native SOL distributors are funded from the payer's wallet, drop \
+                     --from-token-account and --use-mint-authority",
+                ));
+            }
+            let balance = client.get_balance(&authority).map_err(map_client_error)?;
+            if balance < fund_vault_args.amount {
+                return Err(DistributorError::InsufficientFunds(format!(
+                    "{authority} holds {balance} lamports, {} needed",
+                    fund_vault_args.amount
+                )));
+            }
+            transfer(&authority, &token_vault, fund_vault_args.amount)
+        }
+        DistributionMode::Token => {
+            if fund_vault_args.from_token_account.is_none() && !fund_vault_args.use_mint_authority {
+                return Err(invalid_input(
+                    "one of --from-token-account or --use-mint-authority is required",
+                ));
+            }
+            let mint_account = client.get_account(&args.mint).map_err(map_client_error)?;
+            let mint = token::Mint::try_deserialize(&mut mint_account.data.as_slice())
+                .map_err(|e| DistributorError::RpcError(format!("failed to parse mint: {e}")))?;
+            match fund_vault_args.from_token_account {
+                Some(source) => {
+                    let balance = client
+                        .get_token_account_balance(&source)
+                        .map_err(map_client_error)?
+                        .amount
+                        .parse::<u64>()
+                        .map_err(|e| DistributorError::RpcError(format!("invalid balance: {e}")))?;
+                    if balance < fund_vault_args.amount {
+                        return Err(DistributorError::InsufficientFunds(format!(
+                            "{source} holds {balance}, {} needed",
+                            fund_vault_args.amount
+                        )));
+                    }
+                }
+                None => {
+                    if Option::<Pubkey>::from(mint.mint_authority) != Some(authority) {
+                        return Err(DistributorError::InsufficientFunds(format!(
+                            "{authority} is not the mint authority of {}",
+                            args.mint
+                        )));
+                    }
+                }
+            }
+            fund_vault_instruction(
+                args,
+                fund_vault_args,
+                &authority,
+                &token_vault,
+                mint.decimals,
+            )?
+        }
+    };
+
+    let snapshot = fetch_distributor_snapshot(&client, &distributor)?;
+    let new_balance = snapshot
+        .vault_balance
+        .saturating_add(fund_vault_args.amount);
+    if new_balance < snapshot.outstanding() {
+        warn!(
+            "The vault will hold {new_balance}, below the {} still owed to claimants \
+             (max_total_claim {} minus {} claimed)",
+            snapshot.outstanding(),
+            snapshot.max_total_claim,
+            snapshot.total_amount_claimed
+        );
+    }
+
+    if args.output_instructions {
+        return print_instructions(args, &[fund_vault_ix]);
+    }
+
+    let Some(signature) = send_or_print_transaction(&client, args, &[fund_vault_ix], None, &[])?
+    else {
+        return Ok(());
+    };
+
+    let vault_balance = fetch_distributor_snapshot(&client, &distributor)?.vault_balance;
+    say!("Funded vault {token_vault}, it now holds {vault_balance}. signature: {signature}");
+    print_json(&FundVaultOutput {
+        distributor: distributor.to_string(),
+        token_vault: token_vault.to_string(),
+        amount: fund_vault_args.amount,
+        vault_balance,
+        signature: signature.to_string(),
+    })?;
+    Ok(())
+}
+
+fn process_clawback(args: &Args, clawback_args: &ClawbackArgs) -> Result<()> {
+    let clawback_keypair = read_keypair(&clawback_args.clawback_keypair_path)?;
+
+    let client = rpc_client(args);
+
+    let (distributor, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+    let distributor_state = fetch_distributor(&client, &distributor)?;
+    check_clawback_window(&client, args, &distributor, &distributor_state)?;
+    let mode = distributor_state.distribution_mode;
+
+    let from = vault_address(args, &distributor, mode);
+    debug!("clawing back from vault {from}");
+
+    let (clawback_ix, clawback_ata) = match mode {
+        DistributionMode::Token => {
+            let clawback_ata = get_associated_token_address(&clawback_keypair.pubkey(), &args.mint);
+            let clawback_ix = Instruction {
+                program_id: args.program_id,
+                accounts: merkle_distributor::accounts::Clawback {
+                    distributor,
+                    from,
+                    to: clawback_ata,
+                    claimant: clawback_keypair.pubkey(),
+                    system_program: solana_program::system_program::ID,
+                    mint: args.mint,
+                    token_program: token::ID,
+                }
+                .to_account_metas(None),
+                data: merkle_distributor::instruction::Clawback {}.data(),
+            };
+            (clawback_ix, clawback_ata)
+        }
+        DistributionMode::Native => {
+            // The lamports go to the receiver wallet set at creation
+            let clawback_receiver = distributor_state.clawback_receiver;
+            let clawback_ix = Instruction {
+                program_id: args.program_id,
+                accounts: merkle_distributor::accounts::ClawbackNative {
+                    distributor,
+                    from,
+                    to: clawback_receiver,
+                    claimant: clawback_keypair.pubkey(),
+                    system_program: solana_program::system_program::ID,
+                }
+                .to_account_metas(None),
+                data: merkle_distributor::instruction::ClawbackNative {}.data(),
+            };
+            (clawback_ix, clawback_receiver)
+        }
+    };
+
+    if args.output_instructions {
+        return print_instructions(args, &[clawback_ix]);
+    }
+
+    if !args.yes && !exports_transactions(args) {
+        let vault_balance = match mode {
+            DistributionMode::Token => {
+                client
+                    .get_token_account_balance(&from)
+                    .map_err(map_client_error)?
+                    .amount
+            }
+            DistributionMode::Native => format!(
+                "{} lamports",
+                client.get_balance(&from).map_err(map_client_error)?
+            ),
+        };
+        confirm(
+            args,
+            &[
+                format!("Clawing back distributor {distributor}"),
+                format!("  Amount: {vault_balance}, the whole vault {from}"),
+                format!("  Destination: {clawback_ata}"),
+            ],
+        )?;
+    }
+
+    let Some(signature) =
+        send_or_print_transaction(&client, args, &[clawback_ix], None, &[&clawback_keypair])?
+    else {
+        return Ok(());
+    };
+
+    say!("Successfully clawed back funds! signature: {signature:#?}");
+    print_json(&ClawbackOutput {
+        distributor: distributor.to_string(),
+        signature: signature.to_string(),
+    })?;
+    Ok(())
+}
+
+/// Chain time per the clock sysvar, the time the program checks timestamps against.
+fn chain_time(client: &RpcClient) -> Result<i64> {
+    let account = client.get_account(&clock::ID).map_err(map_client_error)?;
+    bincode::deserialize::<Clock>(&account.data)
+        .map(|clock| clock.unix_timestamp)
+        .map_err(|e| DistributorError::RpcError(format!("failed to parse clock sysvar: {e}")))
+}
+
+/// Why the clawback window of `distributor` is closed at `now`, None once it is open.
+fn clawback_window_closed(distributor: &MerkleDistributor, now: i64) -> Option<String> {
+    if distributor.clawed_back {
+        return Some("the distributor has already been clawed back".to_string());
+    }
+    (now < distributor.clawback_start_ts).then(|| {
+        format!(
+            "clawback opens at {}",
+            describe_ts(distributor.clawback_start_ts, now)
+        )
+    })
+}
+
+/// Aborts a clawback before its transaction is built when the program would reject it. A
+/// transaction printed or exported for later is only warned about while the window is yet to
+/// open.
+fn check_clawback_window(
+    client: &RpcClient,
+    args: &Args,
+    distributor_pubkey: &Pubkey,
+    distributor: &MerkleDistributor,
+) -> Result<()> {
+    let now = chain_time(client)?;
+    let Some(reason) = clawback_window_closed(distributor, now) else {
+        say!(
+            "Clawback window open since {}",
+            describe_ts(distributor.clawback_start_ts, now)
+        );
+        return Ok(());
+    };
+    if !distributor.clawed_back && (args.output_instructions || exports_transactions(args)) {
+        warn!("{reason}, the transaction fails until then");
+        return Ok(());
+    }
+    Err(DistributorError::IoError(std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        format!("cannot claw back distributor {distributor_pubkey}: {reason}"),
+    )))
+}
+
+/// Vault balance `partial_clawback` may take: whatever exceeds the allocations not claimed yet.
+fn clawback_surplus(vault_balance: u64, distributor: &MerkleDistributor) -> u64 {
+    let obligation = distributor
+        .max_total_claim
+        .saturating_sub(distributor.total_amount_claimed);
+    vault_balance.saturating_sub(obligation)
+}
+
+fn process_partial_clawback(
+    args: &Args,
+    partial_clawback_args: &PartialClawbackArgs,
+) -> Result<()> {
+    let clawback_keypair = read_keypair(&partial_clawback_args.clawback_keypair_path)?;
+
+    let client = rpc_client(args);
+
+    let (distributor, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+    let distributor_state = fetch_distributor(&client, &distributor)?;
+    if distributor_state.distribution_mode != DistributionMode::Token {
+        return Err(DistributorError::IoError(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "partial-clawback only supports token distributors",
+        )));
+    }
+    check_clawback_window(&client, args, &distributor, &distributor_state)?;
+
+    let from = distributor_state.token_vault;
+    let vault_balance = client
+        .get_token_account_balance(&from)
+        .map_err(map_client_error)?
+        .amount
+        .parse::<u64>()
+        .map_err(|e| DistributorError::RpcError(format!("failed to parse vault balance: {e}")))?;
+    let surplus = clawback_surplus(vault_balance, &distributor_state);
+    if partial_clawback_args.amount > surplus {
+        return Err(DistributorError::IoError(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "vault {from} holds {vault_balance}, of which {} is owed to unclaimed \
+                 allocations, so at most {surplus} can be clawed back",
+                vault_balance - surplus
+            ),
+        )));
+    }
+
+    let clawback_ix = Instruction {
+        program_id: args.program_id,
+        accounts: merkle_distributor::accounts::PartialClawback {
+            distributor,
+            from,
+            to: distributor_state.clawback_receiver,
+            claimant: clawback_keypair.pubkey(),
+            system_program: solana_program::system_program::ID,
+            mint: args.mint,
+            token_program: token::ID,
+        }
+        .to_account_metas(None),
+        data: merkle_distributor::instruction::PartialClawback {
+            amount: partial_clawback_args.amount,
+        }
+        .data(),
+    };
+
+    if args.output_instructions {
+        return print_instructions(args, &[clawback_ix]);
+    }
+
+    if !args.yes && !exports_transactions(args) {
+        confirm(
+            args,
+            &[
+                format!("Partially clawing back distributor {distributor}"),
+                format!(
+                    "  Amount: {} of the {vault_balance} in vault {from}",
+                    partial_clawback_args.amount
+                ),
+                format!("  Destination: {}", distributor_state.clawback_receiver),
+            ],
+        )?;
+    }
+
+    let Some(signature) =
+        send_or_print_transaction(&client, args, &[clawback_ix], None, &[&clawback_keypair])?
+    else {
+        return Ok(());
+    };
+
+    say!(
+        "Successfully clawed back {} tokens! signature: {signature:#?}",
+        partial_clawback_args.amount
+    );
+    print_json(&PartialClawbackOutput {
+        distributor: distributor.to_string(),
+        amount: partial_clawback_args.amount,
+        signature: signature.to_string(),
+    })?;
+    Ok(())
+}
+
+fn process_create_merkle_tree(merkle_tree_args: &CreateMerkleTreeArgs) -> Result<()> {
+    let progress_bar = ProgressBar::on_stderr();
+    let progress = |progress: TreeBuildProgress| progress_bar.update(progress);
+    let options = AllocationOptions {
+        decimals: merkle_tree_args.decimals,
+        merge_duplicates: merkle_tree_args.merge_duplicates,
+        progress: Some(&progress),
+        allow_empty_nodes: merkle_tree_args.allow_empty_nodes,
+        clawback_start_ts: merkle_tree_args.clawback_start_ts,
+    };
+    let merkle_tree = match &merkle_tree_args.json_path {
+        Some(json_path) => AirdropMerkleTree::new_from_json_with_options(json_path, options),
+        None => {
+            // clap requires exactly one of --csv-path and --json-path
+            let csv_path = merkle_tree_args.csv_path.as_ref().ok_or_else(|| {
+                DistributorError::IoError(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "one of --csv-path or --json-path is required",
+                ))
+            })?;
+            AirdropMerkleTree::new_from_csv_with_options(csv_path, options)
+        }
+    };
+    progress_bar.finish();
+    let merkle_tree = merkle_tree?;
+    write_tree(
+        &merkle_tree,
+        &merkle_tree_args.merkle_tree_path,
+        merkle_tree_args.format,
+    )?;
+    if let Some(dir) = &merkle_tree_args.export_proofs {
+        let written = merkle_tree.write_proofs_to_dir(dir)?;
+        say!("wrote {written} proof files to {}", dir.display());
+    }
+    print_json(&CreateMerkleTreeOutput {
+        merkle_tree_path: merkle_tree_args.merkle_tree_path.display().to_string(),
+        merkle_root: merkle_tree.merkle_root,
+        max_num_nodes: merkle_tree.max_num_nodes,
+        max_total_claim: merkle_tree.max_total_claim,
+    })?;
+    Ok(())
+}
+
+fn write_tree(
+    merkle_tree: &AirdropMerkleTree,
+    path: &PathBuf,
+    format: TreeFileFormat,
+) -> Result<()> {
+    match format {
+        TreeFileFormat::Json => merkle_tree.write_to_file(path),
+        TreeFileFormat::Bincode => merkle_tree.write_to_file_bincode(path)?,
+    }
+    Ok(())
+}
+
+/// Claimants present in more than one of `sources`.
+fn overlapping_claimants(sources: &[Vec<TreeNode>]) -> usize {
+    let mut source_counts: HashMap<Pubkey, usize> = HashMap::new();
+    for nodes in sources {
+        let claimants: HashSet<Pubkey> = nodes.iter().map(|node| node.claimant).collect();
+        for claimant in claimants {
+            *source_counts.entry(claimant).or_default() += 1;
+        }
+    }
+    source_counts.values().filter(|count| **count > 1).count()
+}
+
+fn process_merge(merge_args: &MergeArgs) -> Result<()> {
+    let mut sources = vec![];
+    for csv_path in &merge_args.csv_path {
+        sources.push(AirdropMerkleTree::nodes_from_csv(
+            csv_path,
+            merge_args.decimals,
+        )?);
+    }
+    for tree_path in &merge_args.merkle_tree_path {
+        sources.push(AirdropMerkleTree::new_from_file(tree_path)?.into_tree_nodes());
+    }
+    let inputs = sources.len();
+    let overlapping_claimants = overlapping_claimants(&sources);
+    if !merge_args.allow_empty_nodes {
+        check_empty_nodes(&sources.concat())?;
+    }
+    let merkle_tree = AirdropMerkleTree::merge(sources)?;
+    write_tree(&merkle_tree, &merge_args.out_path, merge_args.format)?;
+    say!("Merged {inputs} inputs, {overlapping_claimants} claimants found in more than one");
+    print_json(&MergeOutput {
+        merkle_tree_path: merge_args.out_path.display().to_string(),
+        inputs,
+        overlapping_claimants,
+        merkle_root: merkle_tree.merkle_root,
+        max_num_nodes: merkle_tree.max_num_nodes,
+        max_total_claim: merkle_tree.max_total_claim,
+    })?;
+    Ok(())
+}
+
+fn claimant_entry(node: &TreeNode) -> ClaimantEntry {
+    ClaimantEntry {
+        claimant: node.claimant.to_string(),
+        amount_unlocked: node.amount_unlocked(),
+        amount_locked: node.amount_locked(),
+        staker_unlocked: node.total_unlocked_staker,
+        staker_locked: node.total_locked_staker,
+        searcher_unlocked: node.total_unlocked_searcher,
+        searcher_locked: node.total_locked_searcher,
+        validator_unlocked: node.total_unlocked_validator,
+        validator_locked: node.total_locked_validator,
+    }
+}
+
+/// Unlocked and locked amounts of each airdrop category the node has an allocation in.
+fn node_categories(node: &TreeNode) -> Vec<(AirdropCategory, u64, u64)> {
+    [
+        (
+            AirdropCategory::Staker,
+            node.total_unlocked_staker,
+            node.total_locked_staker,
+        ),
+        (
+            AirdropCategory::Searcher,
+            node.total_unlocked_searcher,
+            node.total_locked_searcher,
+        ),
+        (
+            AirdropCategory::Validator,
+            node.total_unlocked_validator,
+            node.total_locked_validator,
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, unlocked, locked)| *unlocked > 0 || *locked > 0)
+    .collect()
+}
+
+/// Looks the wallet up in the tree file. A wallet missing from it is an answer, not an error.
+fn process_eligibility(eligibility_args: &EligibilityArgs) -> Result<()> {
+    let merkle_tree = AirdropMerkleTree::new_from_file(&eligibility_args.merkle_tree_path)?;
+    let wallet = eligibility_args.wallet;
+    let Some(node) = merkle_tree.get_node(&wallet) else {
+        say!("{wallet} is not eligible: it is not in the merkle tree");
+        print_json(&EligibilityOutput {
+            wallet: wallet.to_string(),
+            eligible: false,
+            categories: vec![],
+            allocation: None,
+        })?;
+        return Ok(());
+    };
+
+    let categories = node_categories(node);
+    say!(
+        "{wallet} is eligible: {} unlocked, {} locked",
+        node.amount_unlocked(),
+        node.amount_locked()
+    );
+    for (category, unlocked, locked) in &categories {
+        say!("  {category:?}: {unlocked} unlocked, {locked} locked");
+    }
+    print_json(&EligibilityOutput {
+        wallet: wallet.to_string(),
+        eligible: true,
+        categories: categories
+            .iter()
+            .map(|(category, _, _)| category.clone())
+            .collect(),
+        allocation: Some(claimant_entry(node)),
+    })?;
+    Ok(())
+}
+
+fn process_list_claimants(list_claimants_args: &ListClaimantsArgs) -> Result<()> {
+    let merkle_tree = AirdropMerkleTree::new_from_file(&list_claimants_args.merkle_tree_path)?;
+    let claimants: Vec<ClaimantEntry> = merkle_tree
+        .tree_nodes()
+        .iter()
+        .skip(list_claimants_args.offset)
+        .take(list_claimants_args.limit.unwrap_or(usize::MAX))
+        .map(claimant_entry)
+        .collect();
+
+    for entry in &claimants {
+        say!(
+            "{}: {} unlocked, {} locked (staker {}/{}, searcher {}/{}, validator {}/{})",
+            entry.claimant,
+            entry.amount_unlocked,
+            entry.amount_locked,
+            entry.staker_unlocked,
+            entry.staker_locked,
+            entry.searcher_unlocked,
+            entry.searcher_locked,
+            entry.validator_unlocked,
+            entry.validator_locked
+        );
+    }
+    say!(
+        "Showing {} of {} claimants",
+        claimants.len(),
+        merkle_tree.tree_nodes().len()
+    );
+    print_json(&ListClaimantsOutput {
+        total: merkle_tree.tree_nodes().len(),
+        claimants,
+    })?;
+    Ok(())
+}
+
+fn process_set_admin(args: &Args, set_admin_args: &SetAdminArgs) -> Result<()> {
+    let admin = payer_pubkey(args)?;
+
+    let client = rpc_client(args);
+
+    let (distributor, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+
+    let set_admin_ix = Instruction {
+        program_id: args.program_id,
+        accounts: merkle_distributor::accounts::SetAdmin {
+            distributor,
+            admin,
+            new_admin: set_admin_args.new_admin,
+        }
+        .to_account_metas(None),
+        data: merkle_distributor::instruction::SetAdmin {}.data(),
+    };
+
+    if args.output_instructions {
+        return print_instructions(args, &[set_admin_ix]);
+    }
+
+    let Some(signature) = send_or_print_transaction(&client, args, &[set_admin_ix], None, &[])?
+    else {
+        return Ok(());
+    };
+
+    say!("Successfully set admin! signature: {signature:#?}");
+    print_json(&SetAdminOutput {
+        distributor: distributor.to_string(),
+        new_admin: set_admin_args.new_admin.to_string(),
+        signature: signature.to_string(),
+    })?;
+    Ok(())
+}
+
+fn process_close_distributor(args: &Args) -> Result<()> {
+    let admin = payer_pubkey(args)?;
+
+    let client = rpc_client(args);
+
+    let (distributor, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+    let mode = distribution_mode(&client, args, &distributor)?;
+    let token_vault = vault_address(args, &distributor, mode);
+
+    let close_distributor_ix = match mode {
+        DistributionMode::Token => Instruction {
+            program_id: args.program_id,
+            accounts: merkle_distributor::accounts::CloseDistributor {
+                distributor,
+                token_vault,
+                admin,
+                token_program: token::ID,
+            }
+            .to_account_metas(None),
+            data: merkle_distributor::instruction::CloseDistributor {}.data(),
+        },
+        // Clawback already closed the SOL vault
+        DistributionMode::Native => Instruction {
+            program_id: args.program_id,
+            accounts: merkle_distributor::accounts::CloseNativeDistributor { distributor, admin }
+                .to_account_metas(None),
+            data: merkle_distributor::instruction::CloseNativeDistributor {}.data(),
+        },
+    };
+
+    if args.output_instructions {
+        return print_instructions(args, &[close_distributor_ix]);
+    }
+
+    let Some(signature) =
+        send_or_print_transaction(&client, args, &[close_distributor_ix], None, &[])?
+    else {
+        return Ok(());
+    };
+
+    say!("Closed distributor {distributor} and vault {token_vault}, signature: {signature}");
+    print_json(&CloseDistributorOutput {
+        distributor: distributor.to_string(),
+        token_vault: token_vault.to_string(),
+        signature: signature.to_string(),
+    })?;
+    Ok(())
+}
+
+/// Renouncing takes the admin phrase typed out in full, a stray `y` is not enough.
+fn is_renounce_confirmed(answer: &str) -> bool {
+    answer.trim() == "RENOUNCE"
+}
+
+fn process_renounce_admin(args: &Args) -> Result<()> {
+    let admin = payer_pubkey(args)?;
+
+    let client = rpc_client(args);
+
+    let (distributor, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+
+    let renounce_admin_ix = Instruction {
+        program_id: args.program_id,
+        accounts: merkle_distributor::accounts::RenounceAdmin { distributor, admin }
+            .to_account_metas(None),
+        data: merkle_distributor::instruction::RenounceAdmin {}.data(),
+    };
+
+    if args.output_instructions {
+        return print_instructions(args, &[renounce_admin_ix]);
+    }
+
+    prompt(
+        args,
+        &[
+            format!("Renouncing admin {admin} of distributor {distributor}"),
+            "  This cannot be undone: set-admin, set-paused, set-clawback-receiver, \
+             update-clawback-start, update-root, reassign-claim and close-distributor will fail \
+             for good"
+                .to_string(),
+        ],
+        "Type RENOUNCE to proceed: ",
+        is_renounce_confirmed,
+    )?;
+
+    let Some(signature) =
+        send_or_print_transaction(&client, args, &[renounce_admin_ix], None, &[])?
+    else {
+        return Ok(());
+    };
+
+    say!("Renounced admin of distributor {distributor}, signature: {signature}");
+    print_json(&RenounceAdminOutput {
+        distributor: distributor.to_string(),
+        admin: admin.to_string(),
+        signature: signature.to_string(),
+    })?;
+    Ok(())
+}
+
+fn process_migrate_distributor(args: &Args) -> Result<()> {
+    let payer = payer_pubkey(args)?;
+
+    let client = rpc_client(args);
+
+    let (distributor, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+
+    let migrate_distributor_ix = Instruction {
+        program_id: args.program_id,
+        accounts: merkle_distributor::accounts::MigrateDistributor {
+            distributor,
+            payer,
+            system_program: solana_program::system_program::id(),
+        }
+        .to_account_metas(None),
+        data: merkle_distributor::instruction::MigrateDistributor {}.data(),
+    };
+
+    if args.output_instructions {
+        return print_instructions(args, &[migrate_distributor_ix]);
+    }
+
+    let Some(signature) =
+        send_or_print_transaction(&client, args, &[migrate_distributor_ix], None, &[])?
+    else {
+        return Ok(());
+    };
+
+    say!("Migrated distributor {distributor} to the current layout, signature: {signature}");
+    print_json(&MigrateDistributorOutput {
+        distributor: distributor.to_string(),
+        signature: signature.to_string(),
+    })?;
+    Ok(())
+}
+
+async fn process_migrate_claim_status(
+    args: &Args,
+    migrate_claim_status_args: &MigrateClaimStatusArgs,
+) -> Result<()> {
+    let payer = payer_pubkey(args)?;
+    let claimant = migrate_claim_status_args.claimant.unwrap_or(payer);
+
+    let (distributor, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+    let (claim_status_address, _) = get_claim_status_address(
+        &args.program_id,
+        &claimant,
+        &distributor,
+        &address_tree(args),
+    );
+
+    let client = new_light_client(args).await?;
+    check_indexer(&client, args).await?;
+    let claim_status_compressed_account = fetch_compressed_account(&client, claim_status_address)
+        .await?
+        .ok_or_else(|| {
+            DistributorError::RpcError(format!("{claimant} has no claim status to migrate"))
+        })?;
+    let data = claim_status_compressed_account
+        .data
+        .as_ref()
+        .ok_or_else(|| {
+            DistributorError::RpcError("claim status account has no data".to_string())
+        })?;
+    let Some(claim_status_data) = decode_legacy_claim_status(&data.data) else {
+        // Fails for data in neither layout
+        parse_claim_status(&claim_status_compressed_account)?;
+        say!("Claim status of {claimant} already has the current layout");
+        return Ok(());
+    };
+
+    let (input_account_meta, validity_proof, packed_account_metas) = claim_status_input(
+        &client,
+        &mut ProofFetcher::new(&client, PROOF_BATCH_SIZE),
+        args,
+        claim_status_address,
+        &claim_status_compressed_account,
+    )
+    .await?;
+    let migrate_claim_status_ix = Instruction {
+        program_id: args.program_id,
+        accounts: [
+            merkle_distributor::accounts::MigrateClaimStatus { payer }.to_account_metas(None),
+            packed_account_metas,
+        ]
+        .concat(),
+        data: merkle_distributor::instruction::MigrateClaimStatus {
+            input_account_meta,
+            claim_status_data,
+            validity_proof,
+        }
+        .data(),
+    };
+
+    if args.output_instructions {
+        return print_instructions(args, &[migrate_claim_status_ix]);
+    }
+
+    let Some(signature) =
+        send_or_print_transaction(&client.client, args, &[migrate_claim_status_ix], None, &[])?
+    else {
+        return Ok(());
+    };
+
+    say!("Migrated claim status of {claimant} to the current layout, signature: {signature}");
+    print_json(&MigrateClaimStatusOutput {
+        distributor: distributor.to_string(),
+        claimant: claimant.to_string(),
+        claim_status: Pubkey::new_from_array(claim_status_address).to_string(),
+        signature: signature.to_string(),
+    })?;
+    Ok(())
+}
+
+fn process_set_clawback_receiver(
+    args: &Args,
+    set_clawback_receiver_args: &SetClawbackReceiverArgs,
+) -> Result<()> {
+    let admin = payer_pubkey(args)?;
+
+    let client = rpc_client(args);
+
+    let (distributor, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+    let new_clawback_receiver = set_clawback_receiver_args.new_clawback_receiver_token_account;
+
+    let set_clawback_receiver_ix = Instruction {
+        program_id: args.program_id,
+        accounts: merkle_distributor::accounts::SetClawbackReceiver {
+            distributor,
+            new_clawback_account: new_clawback_receiver,
+            admin,
+        }
+        .to_account_metas(None),
+        data: merkle_distributor::instruction::SetClawbackReceiver {}.data(),
+    };
+
+    if args.output_instructions {
+        return print_instructions(args, &[set_clawback_receiver_ix]);
+    }
+
+    let Some(signature) =
+        send_or_print_transaction(&client, args, &[set_clawback_receiver_ix], None, &[])?
+    else {
+        return Ok(());
+    };
+
+    say!("Successfully set clawback receiver to {new_clawback_receiver}! signature: {signature}");
+    print_json(&SetClawbackReceiverOutput {
+        distributor: distributor.to_string(),
+        new_clawback_receiver: new_clawback_receiver.to_string(),
+        signature: signature.to_string(),
+    })?;
+    Ok(())
+}
+
+fn process_set_paused(args: &Args, set_paused_args: &SetPausedArgs) -> Result<()> {
+    let admin = payer_pubkey(args)?;
+
+    let client = rpc_client(args);
+
+    let (distributor, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+
+    let set_paused_ix = Instruction {
+        program_id: args.program_id,
+        accounts: merkle_distributor::accounts::SetPaused { distributor, admin }
+            .to_account_metas(None),
+        data: merkle_distributor::instruction::SetPaused {
+            paused: set_paused_args.paused,
+        }
+        .data(),
+    };
+
+    if args.output_instructions {
+        return print_instructions(args, &[set_paused_ix]);
+    }
+
+    let Some(signature) = send_or_print_transaction(&client, args, &[set_paused_ix], None, &[])?
+    else {
+        return Ok(());
+    };
+
+    match set_paused_args.paused {
+        true => say!("Distributor paused, signature: {signature}"),
+        false => say!("Distributor resumed, signature: {signature}"),
+    }
+    print_json(&SetPausedOutput {
+        distributor: distributor.to_string(),
+        paused: set_paused_args.paused,
+        signature: signature.to_string(),
+    })?;
+    Ok(())
+}
+
+fn process_update_clawback_start(
+    args: &Args,
+    update_clawback_start_args: &UpdateClawbackStartArgs,
+) -> Result<()> {
+    let admin = payer_pubkey(args)?;
+
+    let client = rpc_client(args);
+
+    let (distributor, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+
+    let update_clawback_start_ix = Instruction {
+        program_id: args.program_id,
+        accounts: merkle_distributor::accounts::UpdateClawbackStartTs { distributor, admin }
+            .to_account_metas(None),
+        data: merkle_distributor::instruction::UpdateClawbackStartTs {
+            clawback_start_ts: update_clawback_start_args.clawback_start_ts,
+        }
+        .data(),
+    };
+
+    if args.output_instructions {
+        return print_instructions(args, &[update_clawback_start_ix]);
+    }
+
+    let Some(signature) =
+        send_or_print_transaction(&client, args, &[update_clawback_start_ix], None, &[])?
+    else {
+        return Ok(());
+    };
+
+    say!(
+        "Clawback start moved to {}, signature: {signature}",
+        update_clawback_start_args.clawback_start_ts
+    );
+    print_json(&UpdateClawbackStartOutput {
+        distributor: distributor.to_string(),
+        clawback_start_ts: update_clawback_start_args.clawback_start_ts,
+        signature: signature.to_string(),
+    })?;
+    Ok(())
+}
+
+/// Checks that `new_tree` keeps every node of `previous_tree` unchanged, so no recipient of the
+/// old root loses or changes its allocation. Returns how many nodes it adds.
+fn check_superset(
+    previous_tree: &AirdropMerkleTree,
+    new_tree: &AirdropMerkleTree,
+) -> Result<usize> {
+    let mut missing = vec![];
+    let mut changed = vec![];
+    for node in previous_tree.tree_nodes().iter() {
+        match new_tree.get_node(&node.claimant) {
+            None => missing.push(node.claimant),
+            Some(new_node) if new_node.hash() != node.hash() => changed.push(node.claimant),
+            Some(_) => {}
+        }
+    }
+    if !missing.is_empty() || !changed.is_empty() {
+        for claimant in missing.iter() {
+            say!("{claimant} is missing from the new tree");
+        }
+        for claimant in changed.iter() {
+            say!("{claimant} has a different allocation in the new tree");
+        }
+        return Err(DistributorError::TreeMismatch(format!(
+            "the new tree drops {} and changes {} nodes of the previous tree, build it from the \
+             previous recipients plus the new ones",
+            missing.len(),
+            changed.len()
+        )));
+    }
+    Ok(new_tree.tree_nodes().len() - previous_tree.tree_nodes().len())
+}
+
+fn process_update_root(args: &Args, update_root_args: &UpdateRootArgs) -> Result<()> {
+    let admin = payer_pubkey(args)?;
+
+    let merkle_tree = AirdropMerkleTree::new_from_file(&update_root_args.merkle_tree_path)?;
+    let previous_tree =
+        AirdropMerkleTree::new_from_file(&update_root_args.previous_merkle_tree_path)?;
+    check_tree_totals(&merkle_tree)?;
+    let new_nodes = check_superset(&previous_tree, &merkle_tree)?;
+    let added_amount = merkle_tree
+        .max_total_claim
+        .checked_sub(previous_tree.max_total_claim)
+        .ok_or_else(|| {
+            DistributorError::TreeMismatch(format!(
+                "the new tree's max_total_claim {} is below the previous tree's {}, although \
+                 it keeps every previous node",
+                merkle_tree.max_total_claim, previous_tree.max_total_claim
+            ))
+        })?;
+
+    let client = rpc_client(args);
+
+    let (distributor, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+    let distributor_state = fetch_distributor(&client, &distributor)?;
+    if distributor_state.root != previous_tree.merkle_root {
+        return Err(DistributorError::TreeMismatch(format!(
+            "the distributor's root is {}, not the root {} of --previous-merkle-tree-path",
+            hex::encode(distributor_state.root),
+            hex::encode(previous_tree.merkle_root)
+        )));
+    }
+    check_unlock_schedules_end_by(
+        merkle_tree.tree_nodes(),
+        distributor_state.clawback_start_ts,
+    )?;
+
+    let update_root_ix = Instruction {
+        program_id: args.program_id,
+        accounts: merkle_distributor::accounts::UpdateRoot { distributor, admin }
+            .to_account_metas(None),
+        data: merkle_distributor::instruction::UpdateRoot {
+            root: merkle_tree.merkle_root,
+            max_total_claim: merkle_tree.max_total_claim,
+            max_num_nodes: merkle_tree.max_num_nodes,
+            tree_depth: merkle_tree.tree_depth(),
+        }
+        .data(),
+    };
+
+    if args.output_instructions {
+        return print_instructions(args, &[update_root_ix]);
+    }
+
+    confirm(
+        args,
+        &[
+            format!("Updating the root of distributor {distributor}"),
+            format!(
+                "  Merkle root: {} to {}",
+                hex::encode(previous_tree.merkle_root),
+                hex::encode(merkle_tree.merkle_root)
+            ),
+            format!(
+                "  Total claim: {} to {}",
+                distributor_state.max_total_claim, merkle_tree.max_total_claim
+            ),
+            format!(
+                "  Nodes: {} to {}, {new_nodes} new",
+                distributor_state.max_num_nodes, merkle_tree.max_num_nodes
+            ),
+        ],
+    )?;
+
+    let Some(signature) = send_or_print_transaction(&client, args, &[update_root_ix], None, &[])?
+    else {
+        return Ok(());
+    };
+
+    say!(
+        "Root updated to {}, adding {new_nodes} nodes, signature: {signature}",
+        hex::encode(merkle_tree.merkle_root)
+    );
+    if added_amount > 0 {
+        say!(
+            "Fund the vault with the {added_amount} tokens the new nodes add, e.g. with fund-vault"
+        );
+    }
+    print_json(&UpdateRootOutput {
+        distributor: distributor.to_string(),
+        merkle_root: hex::encode(merkle_tree.merkle_root),
+        max_total_claim: merkle_tree.max_total_claim,
+        max_num_nodes: merkle_tree.max_num_nodes,
+        tree_depth: merkle_tree.tree_depth(),
+        new_nodes: new_nodes as u64,
+        signature: signature.to_string(),
+    })?;
+    Ok(())
+}
+
+/// The node of `old_claimant`, which reassign-claim moves to `new_claimant`. A `new_claimant`
+/// in the tree is refused: its own allocation would be claimable only until the reassignment
+/// creates its claim status, and lost afterwards.
+fn reassigned_node<'a>(
+    merkle_tree: &'a AirdropMerkleTree,
+    old_claimant: &Pubkey,
+    new_claimant: &Pubkey,
+) -> Result<&'a TreeNode> {
+    let node = merkle_tree
+        .get_node(old_claimant)
+        .ok_or(DistributorError::ProofNotFound(*old_claimant))?;
+    if old_claimant == new_claimant {
+        return Err(DistributorError::IoError(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "--new-claimant must differ from --old-claimant",
+        )));
+    }
+    if merkle_tree.get_node(new_claimant).is_some() {
+        return Err(DistributorError::IoError(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("{new_claimant} has an allocation of its own in the tree"),
+        )));
+    }
+    Ok(node)
+}
+
+async fn process_reassign_claim(
+    args: &Args,
+    reassign_claim_args: &ReassignClaimArgs,
+) -> Result<()> {
+    let admin = payer_pubkey(args)?;
+    let old_claimant = reassign_claim_args.old_claimant;
+    let new_claimant = reassign_claim_args.new_claimant;
+
+    let merkle_tree = AirdropMerkleTree::new_from_file(&reassign_claim_args.merkle_tree_path)?;
+    let node = reassigned_node(&merkle_tree, &old_claimant, &new_claimant)?;
+    let node_proof = node_proof(node)?;
+
+    let (distributor, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+    let client = new_light_client(args).await?;
+    let address_tree = address_tree(args);
+    check_address_tree(&client, &address_tree).await?;
+    let mode = distribution_mode(&client.client, args, &distributor)?;
+    if mode == DistributionMode::Native {
+        return Err(DistributorError::IoError(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "native SOL distributors do not support claim reassignment",
+        )));
+    }
+
+    let (old_address, _) =
+        get_claim_status_address(&args.program_id, &old_claimant, &distributor, &address_tree);
+    let (new_address, _) =
+        get_claim_status_address(&args.program_id, &new_claimant, &distributor, &address_tree);
+    // One proof that neither claim status exists, as the program creates both in one CPI
+    let proof = match client
+        .get_validity_proof(
+            vec![],
+            vec![
+                AddressWithTree {
+                    address: old_address,
+                    tree: address_tree,
+                },
+                AddressWithTree {
+                    address: new_address,
+                    tree: address_tree,
+                },
+            ],
+            None,
+        )
+        .await
+    {
+        Ok(proof) => proof.value,
+        Err(e) => {
+            let e = map_indexer_error(e);
+            return Err(check_already_claimed(&client, old_address, &old_claimant, e).await);
+        }
+    };
+
+    let mut packed_accounts = PackedAccounts::default();
+    packed_accounts
+        .add_system_accounts_v2(SystemAccountMetaConfig::new(merkle_distributor::ID))
+        .map_err(|e| DistributorError::RpcError(format!("failed to add system accounts: {e}")))?;
+    let address_tree_infos = proof.pack_tree_infos(&mut packed_accounts).address_trees;
+    let output_state_tree_index = output_state_tree_info(&client, args)?
+        .pack_output_tree_index(&mut packed_accounts)
+        .map_err(map_indexer_error)?;
+    let (packed_account_metas, _, _) = packed_accounts.to_account_metas();
+
+    let reassign_claim_ix = Instruction {
+        program_id: args.program_id,
+        accounts: [
+            merkle_distributor::accounts::ReassignClaim {
+                distributor,
+                from: vault_address(args, &distributor, mode),
+                to: get_associated_token_address(&new_claimant, &args.mint),
+                admin,
+                mint: args.mint,
+                token_program: token::ID,
+            }
+            .to_account_metas(None),
+            packed_account_metas,
+        ]
+        .concat(),
+        data: merkle_distributor::instruction::ReassignClaim {
+            old_claimant,
+            new_claimant,
+            amount_unlocked: node.amount_unlocked(),
+            amount_locked: node.amount_locked(),
+            proof: node_proof,
+            unlock_schedule: node.unlock_schedule.map(|schedule| UnlockSchedule {
+                start_ts: schedule.start_ts,
+                end_ts: schedule.end_ts,
+            }),
+            category_breakdown: Some(category_breakdown(node)),
+            validity_proof: proof.proof,
+            old_address_tree_info: address_tree_infos[0],
+            new_address_tree_info: address_tree_infos[1],
+            output_state_tree_index,
+        }
+        .data(),
+    };
+
+    let mut ixs: Vec<Instruction> =
+        create_claimant_ata_if_missing(&client, args, mode, &new_claimant, &admin)
+            .await?
+            .into_iter()
+            .collect();
+    ixs.push(reassign_claim_ix);
+    let ixs = with_compute_unit_limit(
+        &client.client,
+        args,
+        &admin,
+        ixs,
+        REASSIGN_CLAIM_COMPUTE_UNITS,
+    );
+
+    if args.output_instructions {
+        return print_instructions(args, &ixs);
+    }
+
+    confirm(
+        args,
+        &[
+            format!("Reassigning the claim of {old_claimant} to {new_claimant}"),
+            format!("  Distributor: {distributor}"),
+            format!(
+                "  Allocation: {} unlocked, {} locked",
+                node.amount_unlocked(),
+                node.amount_locked()
+            ),
+        ],
+    )?;
+
+    let Some(signature) = send_or_print_transaction(&client.client, args, &ixs, None, &[])? else {
+        return Ok(());
+    };
+
+    say!("Reassigned the claim of {old_claimant} to {new_claimant}, signature: {signature}");
+    print_json(&ReassignClaimOutput {
+        distributor: distributor.to_string(),
+        old_claimant: old_claimant.to_string(),
+        new_claimant: new_claimant.to_string(),
+        amount_unlocked: node.amount_unlocked(),
+        amount_locked: node.amount_locked(),
+        signature: signature.to_string(),
+    })?;
+    Ok(())
+}
+
+async fn process_monitor(args: &Args, monitor_args: &MonitorArgs) -> Result<()> {
+    let client = rpc_client(args);
+    let http = reqwest::Client::new();
+
+    let (distributor, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+
+    let mut monitor = HealthMonitor::new(MonitorThresholds {
+        velocity_spike_factor: monitor_args.velocity_spike_factor,
+        velocity_min_claims: monitor_args.velocity_min_claims,
+    });
+
+    #[cfg(feature = "metrics")]
+    let metrics = std::sync::Arc::new(metrics::Metrics::default());
+    #[cfg(feature = "metrics")]
+    if let Some(metrics_addr) = monitor_args.metrics_addr {
+        let listener = tokio::net::TcpListener::bind(metrics_addr).await?;
+        say!("Serving metrics on {metrics_addr}");
+        let metrics = metrics.clone();
+        tokio::spawn(async move {
+            if let Err(e) = metrics::serve(listener, metrics).await {
+                error!("Metrics server stopped: {e}");
+            }
+        });
+    }
+
+    say!(
+        "Monitoring distributor {distributor} every {}s",
+        monitor_args.interval_secs
+    );
+    let mut interval = tokio::time::interval(Duration::from_secs(monitor_args.interval_secs));
+    loop {
+        interval.tick().await;
+
+        // A failed poll is reported but does not stop the monitor
+        let poll_start = Instant::now();
+        let snapshot = match fetch_distributor_snapshot(&client, &distributor) {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                error!("[{}] failed to poll distributor: {e}", e.code());
+                #[cfg(feature = "metrics")]
+                metrics.record_failure();
+                continue;
+            }
+        };
+        #[cfg(feature = "metrics")]
+        metrics.record_snapshot(&snapshot, poll_start.elapsed());
+        debug!("polled distributor in {:?}", poll_start.elapsed());
+        say!(
+            "vault: {} outstanding: {} claimed nodes: {} clawed back: {} paused: {}",
+            snapshot.vault_balance,
+            snapshot.outstanding(),
+            snapshot.num_nodes_claimed,
+            snapshot.clawed_back,
+            snapshot.paused
+        );
+
+        for alert in monitor.observe(snapshot) {
+            say!("ALERT: {alert}");
+            // Long running, so JSON mode prints one line per alert rather than a single object
+            print_json(&alert_payload(&distributor, &alert))?;
+            if let Some(webhook_url) = &monitor_args.webhook_url {
+                if let Err(e) = post_alert(&http, webhook_url, &distributor, &alert).await {
+                    error!("Failed to post alert to webhook: {e}");
+                }
+            }
+        }
+    }
+}
+
+async fn process_watch(args: &Args, watch_args: &WatchArgs) -> Result<()> {
+    let (distributor, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+    let ws_url = match &watch_args.ws_url {
+        Some(ws_url) => ws_url.clone(),
+        None => websocket_url(&args.rpc_url[0]).map_err(|message| {
+            DistributorError::IoError(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                message,
+            ))
+        })?,
+    };
+
+    let pubsub = PubsubClient::new(&ws_url)
+        .await
+        .map_err(|e| DistributorError::RpcError(format!("failed to connect to {ws_url}: {e}")))?;
+    // Every claim writes the distributor, so its transactions are the ones mentioning it
+    let (mut notifications, unsubscribe) = pubsub
+        .logs_subscribe(
+            RpcTransactionLogsFilter::Mentions(vec![distributor.to_string()]),
+            RpcTransactionLogsConfig {
+                commitment: Some(args.commitment.config()),
+            },
+        )
+        .await
+        .map_err(|e| DistributorError::RpcError(format!("failed to subscribe to logs: {e}")))?;
+
+    say!("Watching claims of distributor {distributor}, Ctrl-C to stop");
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    loop {
+        let notification = tokio::select! {
+            _ = &mut ctrl_c => {
+                say!("Interrupted, stopping");
+                break;
+            }
+            notification = notifications.next() => notification,
+        };
+        let Some(notification) = notification else {
+            return Err(DistributorError::RpcError(
+                "log subscription closed by the RPC node".to_string(),
+            ));
+        };
+        let logs = notification.value;
+        // Failed transactions still log, but claimed nothing
+        if logs.err.is_some() {
+            continue;
+        }
+        for claim in claim_events(&logs.logs, &args.program_id) {
+            let timestamp = claim.timestamp.unwrap_or_else(|| {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs() as i64
+            });
+            let kind = claim.kind.name();
+            say!(
+                "[{timestamp}] {kind}: {} claimed {} (slot {}, {})",
+                claim.claimant,
+                claim.amount,
+                notification.context.slot,
+                logs.signature
+            );
+            // Long running, so JSON mode prints one line per claim rather than a single object
+            print_json(&WatchClaimOutput {
+                distributor: distributor.to_string(),
+                signature: logs.signature.clone(),
+                slot: notification.context.slot,
+                kind: kind.to_string(),
+                claimant: claim.claimant.to_string(),
+                amount: claim.amount,
+                timestamp,
+            })?;
+        }
+    }
+    drop(notifications);
+    unsubscribe().await;
+    Ok(())
+}
+
+fn fetch_distributor_snapshot(
+    client: &RpcClient,
+    distributor: &Pubkey,
+) -> Result<DistributorSnapshot> {
+    let account = client.get_account(distributor).map_err(map_client_error)?;
+    let distributor_state = MerkleDistributor::try_deserialize(&mut account.data.as_slice())
+        .map_err(|e| DistributorError::RpcError(format!("failed to parse distributor: {e}")))?;
+
+    let vault_balance = match distributor_state.distribution_mode {
+        DistributionMode::Token => client
+            .get_token_account_balance(&distributor_state.token_vault)
+            .map_err(map_client_error)?
+            .amount
+            .parse::<u64>()
+            .map_err(|e| DistributorError::RpcError(format!("invalid vault balance: {e}")))?,
+        // Claims never touch the rent-exempt minimum of the SOL vault
+        DistributionMode::Native => client
+            .get_balance(&distributor_state.token_vault)
+            .map_err(map_client_error)?
+            .saturating_sub(
+                client
+                    .get_minimum_balance_for_rent_exemption(0)
+                    .map_err(map_client_error)?,
+            ),
+    };
+
+    Ok(DistributorSnapshot {
+        vault_balance,
+        max_total_claim: distributor_state.max_total_claim,
+        total_amount_claimed: distributor_state.total_amount_claimed,
+        num_nodes_claimed: distributor_state.num_nodes_claimed,
+        clawed_back: distributor_state.clawed_back,
+        paused: distributor_state.paused,
+    })
+}
+
+fn alert_payload(distributor: &Pubkey, alert: &Alert) -> serde_json::Value {
+    serde_json::json!({
+        "distributor": distributor.to_string(),
+        "message": alert.to_string(),
+        "alert": alert,
+    })
+}
+
+async fn post_alert(
+    http: &reqwest::Client,
+    webhook_url: &str,
+    distributor: &Pubkey,
+    alert: &Alert,
+) -> reqwest::Result<()> {
+    http.post(webhook_url)
+        .json(&alert_payload(distributor, alert))
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+/// Tokens the claimant's ATA should hold if nothing was moved out since claiming.
+fn expected_claimed_balance(claim_status: &ClaimStatus) -> u64 {
+    claim_status
+        .unlocked_amount
+        .saturating_add(claim_status.locked_amount_withdrawn)
+}
+
+/// A token account, None if it does not exist.
+fn fetch_token_account(
+    client: &RpcClient,
+    token_account: &Pubkey,
+) -> Result<Option<token::TokenAccount>> {
+    match client
+        .get_account_with_commitment(token_account, client.commitment())
+        .map_err(map_client_error)?
+        .value
+    {
+        Some(account) => Ok(Some(
+            token::TokenAccount::try_deserialize(&mut account.data.as_slice()).map_err(|e| {
+                DistributorError::RpcError(format!("failed to parse token account: {e}"))
+            })?,
+        )),
+        None => Ok(None),
+    }
+}
+
+/// Balance of a token account, None if it does not exist.
+fn fetch_token_balance(client: &RpcClient, token_account: &Pubkey) -> Result<Option<u64>> {
+    Ok(fetch_token_account(client, token_account)?.map(|account| account.amount))
+}
+
+/// How `distributor` pays out. Only distributors of the native mint can pay out native SOL, so
+/// the account is not fetched for other mints.
+fn distribution_mode(
+    client: &RpcClient,
+    args: &Args,
+    distributor: &Pubkey,
+) -> Result<DistributionMode> {
+    if args.mint != token::spl_token::native_mint::ID {
+        return Ok(DistributionMode::Token);
+    }
+    Ok(fetch_distributor(client, distributor)?.distribution_mode)
+}
+
+/// The vault of `distributor`: its associated token account, or its SOL vault.
+fn vault_address(args: &Args, distributor: &Pubkey, mode: DistributionMode) -> Pubkey {
+    match mode {
+        DistributionMode::Token => get_associated_token_address(distributor, &args.mint),
+        DistributionMode::Native => get_sol_vault_pda(&args.program_id, distributor).0,
+    }
+}
+
+/// What `claimant` holds of the airdropped asset: its token account balance, or its lamports
+/// for a native SOL distributor.
+fn payout_balance(
+    client: &RpcClient,
+    args: &Args,
+    mode: DistributionMode,
+    claimant: &Pubkey,
+) -> Result<u64> {
+    match mode {
+        DistributionMode::Token => Ok(fetch_token_balance(
+            client,
+            &get_associated_token_address(claimant, &args.mint),
+        )?
+        .unwrap_or_default()),
+        DistributionMode::Native => client.get_balance(claimant).map_err(map_client_error),
+    }
+}
+
+fn fetch_distributor(client: &RpcClient, distributor_pubkey: &Pubkey) -> Result<MerkleDistributor> {
+    let distributor_account = client
+        .get_account(distributor_pubkey)
+        .map_err(map_client_error)?;
+    if distributor_account
+        .data
+        .starts_with(MerkleDistributor::DISCRIMINATOR)
+        && distributor_account.data.len() < MerkleDistributor::LEN
+    {
+        return Err(DistributorError::RpcError(format!(
+            "distributor {distributor_pubkey} has the layout of an earlier program release, \
+             run migrate-distributor first"
+        )));
+    }
+    MerkleDistributor::try_deserialize(&mut distributor_account.data.as_slice())
+        .map_err(|e| DistributorError::RpcError(format!("failed to parse distributor: {e}")))
+}
+
+/// Locked tokens `claim_locked` would transfer right now, per the claimant's unlock window.
+fn locked_withdrawable_now(
+    claim_status: &ClaimStatus,
+    distributor: &MerkleDistributor,
+) -> Option<u64> {
+    let curr_ts = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    locked_withdrawable_at(claim_status, distributor, curr_ts)
+}
+
+/// The locked amount claim_locked withdraws at `curr_ts`, ignoring `min_claim_interval`. None
+/// when the program would fail with an arithmetic error.
+fn locked_withdrawable_at(
+    claim_status: &ClaimStatus,
+    distributor: &MerkleDistributor,
+    curr_ts: i64,
+) -> Option<u64> {
+    let (start_ts, end_ts) = claim_status.unlock_window(distributor.start_ts, distributor.end_ts);
+    claim_status
+        .amount_withdrawable(
+            curr_ts,
+            start_ts,
+            end_ts,
+            distributor.cliff_ts,
+            distributor.vesting_interval_secs,
+        )
+        .ok()
+}
+
+/// Full days and remaining seconds until `end_ts`, as claim_locked logs them.
+fn lockup_remaining(curr_ts: i64, end_ts: i64) -> (i64, i64) {
+    let remaining_seconds = end_ts.saturating_sub(curr_ts).max(0);
+    (remaining_seconds / 86_400, remaining_seconds % 86_400)
+}
+
+/// When claim_locked is accepted again after a withdrawal at `last_claimed_ts`, None when it
+/// already is.
+fn next_withdrawal_ts(last_claimed_ts: i64, min_claim_interval: i64, curr_ts: i64) -> Option<i64> {
+    let next_ts = last_claimed_ts.saturating_add(min_claim_interval);
+    (last_claimed_ts > 0 && next_ts > curr_ts).then_some(next_ts)
+}
+
+fn claim_status_view(
+    claim_status: &ClaimStatus,
+    distributor: &MerkleDistributor,
+) -> ClaimStatusView {
+    ClaimStatusView {
+        unlocked_amount: claim_status.unlocked_amount,
+        locked_amount: claim_status.locked_amount,
+        locked_amount_withdrawn: claim_status.locked_amount_withdrawn,
+        locked_amount_withdrawable: locked_withdrawable_now(claim_status, distributor),
+        last_claimed_ts: (claim_status.last_claimed_ts > 0).then_some(claim_status.last_claimed_ts),
+    }
+}
+
+async fn process_status(args: &Args, status_args: &StatusArgs) -> Result<()> {
+    let claimant = payer_pubkey(args)?;
+    let merkle_tree = AirdropMerkleTree::new_from_file(&status_args.merkle_tree_path)?;
+    let node = merkle_tree
+        .get_node(&claimant)
+        .ok_or(DistributorError::ProofNotFound(claimant))?;
+
+    let client = new_light_client(args).await?;
+    let (distributor_pubkey, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+    let (claim_status_address, _) = get_claim_status_address(
+        &args.program_id,
+        &claimant,
+        &distributor_pubkey,
+        &address_tree(args),
+    );
+
+    say!("Claimant: {claimant}");
+    let mut output = StatusOutput {
+        claimant: claimant.to_string(),
+        claim_status_address: Pubkey::new_from_array(claim_status_address).to_string(),
+        allocation_unlocked: node.amount_unlocked(),
+        allocation_locked: node.amount_locked(),
+        claim_status: None,
+    };
+    let claim_status = match fetch_compressed_account(&client, claim_status_address).await? {
+        Some(compressed_account) => parse_claim_status(&compressed_account)?,
+        None => {
+            say!("  not yet claimed");
+            say!(
+                "  Allocation: {} unlocked, {} locked",
+                node.amount_unlocked(),
+                node.amount_locked()
+            );
+            print_json(&output)?;
+            return Ok(());
+        }
+    };
+    let distributor = fetch_distributor(&client.client, &distributor_pubkey)?;
+    let view = claim_status_view(&claim_status, &distributor);
+
+    say!("  Unlocked amount: {}", view.unlocked_amount);
+    say!("  Locked amount: {}", view.locked_amount);
+    say!(
+        "  Locked amount withdrawn: {}",
+        view.locked_amount_withdrawn
+    );
+    match view.locked_amount_withdrawable {
+        Some(withdrawable) => say!("  Locked amount withdrawable now: {withdrawable}"),
+        None => say!("  Locked amount withdrawable now: could not be computed"),
+    }
+    match view.last_claimed_ts {
+        Some(ts) => say!("  Last locked withdrawal: {ts}"),
+        None => say!("  Last locked withdrawal: never"),
+    }
+    output.claim_status = Some(view);
+    print_json(&output)?;
+    Ok(())
+}
+
+async fn process_withdrawable(args: &Args, withdrawable_args: &WithdrawableArgs) -> Result<()> {
+    let claimant = match withdrawable_args.claimant {
+        Some(claimant) => claimant,
+        None => payer_pubkey(args)?,
+    };
+    let client = new_light_client(args).await?;
+    let (distributor_pubkey, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+    let (claim_status_address, _) = get_claim_status_address(
+        &args.program_id,
+        &claimant,
+        &distributor_pubkey,
+        &address_tree(args),
+    );
+    let mut output = WithdrawableOutput {
+        claimant: claimant.to_string(),
+        claim_status_address: Pubkey::new_from_array(claim_status_address).to_string(),
+        claimed: false,
+        locked_amount_withdrawable: None,
+        locked_amount_remaining: 0,
+        lockup_remaining_secs: 0,
+        next_withdrawal_ts: None,
+    };
+    let Some(compressed_account) = fetch_compressed_account(&client, claim_status_address).await?
+    else {
+        say!("{claimant} has not claimed yet, run claim to receive the unlocked amount first");
+        print_json(&output)?;
+        return Ok(());
+    };
+    let claim_status = parse_claim_status(&compressed_account)?;
+    let distributor = fetch_distributor(&client.client, &distributor_pubkey)?;
+    let curr_ts = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let (_, end_ts) = claim_status.unlock_window(distributor.start_ts, distributor.end_ts);
+    let (days, seconds_after_days) = lockup_remaining(curr_ts, end_ts);
+
+    output.claimed = true;
+    output.locked_amount_withdrawable =
+        locked_withdrawable_at(&claim_status, &distributor, curr_ts);
+    output.locked_amount_remaining = claim_status
+        .locked_amount
+        .saturating_sub(claim_status.locked_amount_withdrawn);
+    output.lockup_remaining_secs = days * 86_400 + seconds_after_days;
+    output.next_withdrawal_ts = next_withdrawal_ts(
+        claim_status.last_claimed_ts,
+        distributor.min_claim_interval,
+        curr_ts,
+    );
+
+    match output.locked_amount_withdrawable {
+        Some(amount) => say!(
+            "{amount} withdrawable now with {days} days and {seconds_after_days} seconds left \
+             in lockup"
+        ),
+        None => say!("Withdrawable amount could not be computed"),
+    }
+    say!(
+        "{} locked tokens not withdrawn yet",
+        output.locked_amount_remaining
+    );
+    if let Some(next_ts) = output.next_withdrawal_ts {
+        say!(
+            "The next withdrawal is accepted from {}",
+            describe_ts(next_ts, curr_ts)
+        );
+    }
+    print_json(&output)?;
+    Ok(())
+}
+
+/// What `claim-loop` does on one check of the claimant's locked tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClaimLoopStep {
+    /// Withdraw this many locked tokens
+    Claim(u64),
+    /// Check again at the next interval
+    Wait,
+    /// Every locked token has been withdrawn
+    Done,
+    /// The distributor was clawed back, the remaining locked tokens cannot be withdrawn
+    ClawedBack,
+}
+
+/// Decides the `claim-loop` step at `curr_ts`. Waits while the distributor is paused, while
+/// its `min_claim_interval` since the last withdrawal has not passed, and while less than
+/// `min_unlock_to_claim` is withdrawable, unless that is everything left.
+fn claim_loop_step(
+    claim_status: &ClaimStatus,
+    distributor: &MerkleDistributor,
+    curr_ts: i64,
+    min_unlock_to_claim: u64,
+) -> ClaimLoopStep {
+    let locked_remaining = claim_status
+        .locked_amount
+        .saturating_sub(claim_status.locked_amount_withdrawn);
+    if locked_remaining == 0 {
+        return ClaimLoopStep::Done;
+    }
+    if distributor.clawed_back {
+        return ClaimLoopStep::ClawedBack;
+    }
+    if distributor.paused
+        || next_withdrawal_ts(
+            claim_status.last_claimed_ts,
+            distributor.min_claim_interval,
+            curr_ts,
+        )
+        .is_some()
+    {
+        return ClaimLoopStep::Wait;
+    }
+    match locked_withdrawable_at(claim_status, distributor, curr_ts) {
+        Some(amount) if amount > 0 && amount >= min_unlock_to_claim.min(locked_remaining) => {
+            ClaimLoopStep::Claim(amount)
+        }
+        _ => ClaimLoopStep::Wait,
+    }
+}
+
+/// Checks the payer's locked tokens every interval and withdraws them once enough has vested.
+/// Stops when all are withdrawn, the distributor is clawed back, `--until-ts` passes or on
+/// Ctrl-C. A failed check or claim is reported and retried at the next interval.
+async fn process_claim_loop(args: &Args, claim_loop_args: &ClaimLoopArgs) -> Result<()> {
+    if args.output_instructions || exports_transactions(args) {
+        return Err(DistributorError::IoError(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "claim-loop sends its own transactions, use claim to build one",
+        )));
+    }
+    let claimant = payer_pubkey(args)?;
+    let fee_payer = args.fee_payer.as_deref().map(read_keypair).transpose()?;
+    let (distributor, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+    let (claim_status_address, _) = get_claim_status_address(
+        &args.program_id,
+        &claimant,
+        &distributor,
+        &address_tree(args),
+    );
+
+    let client = new_light_client(args).await?;
+    check_indexer(&client, args).await?;
+    let mode = distribution_mode(&client.client, args, &distributor)?;
+    if fetch_compressed_account(&client, claim_status_address)
+        .await?
+        .is_none()
+    {
+        return Err(DistributorError::IoError(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "{claimant} has not claimed yet, run claim to receive the unlocked amount first"
+            ),
+        )));
+    }
+
+    say!(
+        "Checking the locked tokens of {claimant} every {}s",
+        claim_loop_args.interval_secs
+    );
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    let mut interval = tokio::time::interval(Duration::from_secs(claim_loop_args.interval_secs));
+    loop {
+        tokio::select! {
+            _ = &mut ctrl_c => {
+                say!("Interrupted, stopping");
+                return Ok(());
+            }
+            _ = interval.tick() => {}
+        }
+        let curr_ts = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        if claim_loop_args
+            .until_ts
+            .is_some_and(|until_ts| curr_ts >= until_ts)
+        {
+            say!("Reached --until-ts, stopping");
+            return Ok(());
+        }
+
+        // A failed check or claim is reported but does not stop the loop
+        let claim = claim_loop_once(
+            args,
+            &client,
+            &claimant,
+            fee_payer.as_ref(),
+            claim_status_address,
+            mode,
+            curr_ts,
+            claim_loop_args.min_unlock_to_claim,
+        )
+        .await;
+        match claim {
+            Ok(ClaimLoopStep::Done) => {
+                say!("All locked tokens have been withdrawn");
+                return Ok(());
+            }
+            Ok(ClaimLoopStep::ClawedBack) => {
+                say!("The distributor was clawed back, no locked tokens are left to withdraw");
+                return Ok(());
+            }
+            Ok(_) => {}
+            Err(e) => {
+                error!("[{}] claim-loop check failed: {e}", e.code());
+                if let DistributorError::InsufficientFunds(_) = e {
+                    print_vault_funding_hint(args, &distributor, mode);
+                }
+            }
+        }
+    }
+}
+
+/// One `claim-loop` check at `curr_ts`: fetches the claim status and the distributor, and sends
+/// claim_locked when [claim_loop_step] decides to claim. Returns the step taken.
+#[allow(clippy::too_many_arguments)]
+async fn claim_loop_once(
+    args: &Args,
+    client: &LightClient,
+    claimant: &Pubkey,
+    fee_payer: Option<&Keypair>,
+    claim_status_address: [u8; 32],
+    mode: DistributionMode,
+    curr_ts: i64,
+    min_unlock_to_claim: u64,
+) -> Result<ClaimLoopStep> {
+    let (distributor, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+    // The account existed when the loop started, so a missing one is the indexer's doing
+    let compressed_account = fetch_compressed_account(client, claim_status_address)
+        .await?
+        .ok_or_else(|| {
+            DistributorError::IndexerLag(format!(
+                "claim status {} not returned",
+                Pubkey::new_from_array(claim_status_address)
+            ))
+        })?;
+    let claim_status = parse_claim_status(&compressed_account)?;
+    let distributor_state = fetch_distributor(&client.client, &distributor)?;
+    let step = claim_loop_step(
+        &claim_status,
+        &distributor_state,
+        curr_ts,
+        min_unlock_to_claim,
+    );
+    let ClaimLoopStep::Claim(withdrawable) = step else {
+        debug!("claim-loop step {step:?}");
+        return Ok(step);
+    };
+
+    let fee_payer_pubkey = fee_payer.map_or(*claimant, |fee_payer| fee_payer.pubkey());
+    let balance_before = payout_balance(&client.client, args, mode, claimant)?;
+    let mut ixs: Vec<Instruction> =
+        create_claimant_ata_if_missing(client, args, mode, claimant, &fee_payer_pubkey)
+            .await?
+            .into_iter()
+            .collect();
+    ixs.push(
+        claim_locked_instruction(
+            client,
+            &mut ProofFetcher::new(client, PROOF_BATCH_SIZE),
+            args,
+            claimant,
+            &compressed_account,
+            &claim_status,
+            mode,
+            None,
+        )
+        .await?,
+    );
+    let ixs = with_compute_unit_limit(
+        &client.client,
+        args,
+        &fee_payer_pubkey,
+        ixs,
+        CLAIM_LOCKED_COMPUTE_UNITS,
+    );
+    let Some(signature) = send_or_print_transaction(&client.client, args, &ixs, fee_payer, &[])?
+    else {
+        return Ok(step);
+    };
+
+    let amount_claimed =
+        payout_balance(&client.client, args, mode, claimant)?.saturating_sub(balance_before);
+    // More may have vested by the time the transaction landed than was withdrawable at
+    // `curr_ts`, and a native payout is net of the fees the claimant paid
+    let locked_amount_remaining = claim_status
+        .locked_amount
+        .saturating_sub(claim_status.locked_amount_withdrawn)
+        .saturating_sub(amount_claimed.max(withdrawable));
+    say!(
+        "Claimed {amount_claimed} locked tokens, {locked_amount_remaining} remaining: {signature}"
+    );
+    // Long running, so JSON mode prints one line per claim rather than a single object
+    print_json(&ClaimLoopOutput {
+        claimant: claimant.to_string(),
+        distributor: distributor.to_string(),
+        signature: signature.to_string(),
+        amount_claimed,
+        locked_amount_remaining,
+    })?;
+    Ok(step)
+}
+
+async fn process_verify_claim(args: &Args, verify_claim_args: &VerifyClaimArgs) -> Result<()> {
+    let claimant = verify_claim_args.claimant;
+    let client = new_light_client(args).await?;
+
+    let (distributor_pubkey, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+    let (claim_status_address, _) = get_claim_status_address(
+        &args.program_id,
+        &claimant,
+        &distributor_pubkey,
+        &address_tree(args),
+    );
+
+    let claimant_ata = get_associated_token_address(&claimant, &args.mint);
+    let mut output = VerifyClaimOutput {
+        claimant: claimant.to_string(),
+        claim_status_address: Pubkey::new_from_array(claim_status_address).to_string(),
+        claim_status: None,
+        token_account: claimant_ata.to_string(),
+        token_account_balance: None,
+        expected_balance: None,
+    };
+
+    let claim_status = match fetch_compressed_account(&client, claim_status_address).await? {
+        Some(compressed_account) => parse_claim_status(&compressed_account)?,
+        None => {
+            say!("Claimant {claimant} has not claimed from distributor {distributor_pubkey}");
+            print_json(&output)?;
+            return Ok(());
+        }
+    };
+
+    let distributor = fetch_distributor(&client.client, &distributor_pubkey)?;
+
+    let ata_balance = fetch_token_balance(&client.client, &claimant_ata)?;
+
+    let withdrawable = locked_withdrawable_now(&claim_status, &distributor);
+
+    say!("Claimant: {claimant}");
+    say!(
+        "  Claim status: {}",
+        Pubkey::new_from_array(claim_status_address)
+    );
+    say!("  Unlocked amount: {}", claim_status.unlocked_amount);
+    say!("  Locked amount: {}", claim_status.locked_amount);
+    say!(
+        "  Locked amount withdrawn: {}",
+        claim_status.locked_amount_withdrawn
+    );
+    say!(
+        "  Locked amount remaining: {}",
+        claim_status
+            .locked_amount
+            .saturating_sub(claim_status.locked_amount_withdrawn)
+    );
+    if let Some(withdrawable) = withdrawable {
+        say!("  Locked amount withdrawable now: {withdrawable}");
+    }
+
+    let expected = expected_claimed_balance(&claim_status);
+    match ata_balance {
+        Some(balance) if balance >= expected => {
+            say!(
+                "  Token account {claimant_ata} holds {balance}, expected at least {expected}: OK"
+            );
+        }
+        Some(balance) => {
+            say!(
+                "  Token account {claimant_ata} holds {balance}, expected at least {expected}: \
+                 tokens may have been transferred out since claiming"
+            );
+        }
+        None => {
+            say!(
+                "  Token account {claimant_ata} does not exist: tokens were sent to a different \
+                 account or the ATA was closed"
+            );
+        }
+    }
+
+    output.claim_status = Some(claim_status_view(&claim_status, &distributor));
+    output.token_account_balance = ata_balance;
+    output.expected_balance = Some(expected);
+    print_json(&output)?;
+    Ok(())
+}
+
+/// The node's merkle proof, or an error explaining how to fix a tree file saved without them.
+fn node_proof(node: &TreeNode) -> Result<Vec<[u8; 32]>> {
+    node.proof.clone().ok_or_else(|| {
+        DistributorError::TreeMismatch(
+            "this merkle tree file has no proofs; rebuild it with create-merkle-tree".to_string(),
+        )
+    })
+}
+
+/// Whether `node`'s proof verifies against the root of `merkle_tree`.
+fn node_proof_is_valid(node: &TreeNode, merkle_tree: &AirdropMerkleTree) -> Result<bool> {
+    let proof = node_proof(node)?;
+    Ok(merkle_tree.verify_proof_with_schedule(
+        &node.claimant,
+        node.amount_unlocked(),
+        node.amount_locked(),
+        node.unlock_schedule,
+        &proof,
+    ))
+}
+
+fn process_verify_proof(args: &Args, verify_proof_args: &VerifyProofArgs) -> Result<()> {
+    let claimant = match verify_proof_args.claimant {
+        Some(claimant) => claimant,
+        None => payer_pubkey(args)?,
+    };
+    let merkle_tree = AirdropMerkleTree::new_from_file(&verify_proof_args.merkle_tree_path)?;
+    let node = merkle_tree
+        .get_node(&claimant)
+        .ok_or(DistributorError::ProofNotFound(claimant))?;
+
+    if !node_proof_is_valid(node, &merkle_tree)? {
+        return Err(DistributorError::TreeMismatch(format!(
+            "proof for {claimant} does not verify against merkle root {:?}",
+            merkle_tree.merkle_root
+        )));
+    }
+
+    say!("Proof for {claimant} is valid");
+    say!(
+        "  Allocation: {} unlocked, {} locked",
+        node.amount_unlocked(),
+        node.amount_locked()
+    );
+    print_json(&VerifyProofOutput {
+        claimant: claimant.to_string(),
+        merkle_root: merkle_tree.merkle_root,
+        amount_unlocked: node.amount_unlocked(),
+        amount_locked: node.amount_locked(),
+    })?;
+    Ok(())
+}
+
+/// Reads the distributor and its vault balance in one call, so both reflect the returned slot.
+/// Both possible vaults are read since the distribution mode is only known once the distributor
+/// is. A missing vault counts as empty.
+fn fetch_distributor_and_vault(
+    client: &RpcClient,
+    args: &Args,
+    distributor_pubkey: &Pubkey,
+) -> Result<(u64, MerkleDistributor, u64)> {
+    let token_vault = vault_address(args, distributor_pubkey, DistributionMode::Token);
+    let sol_vault = vault_address(args, distributor_pubkey, DistributionMode::Native);
+    let response = client
+        .get_multiple_accounts_with_commitment(
+            &[*distributor_pubkey, token_vault, sol_vault],
+            client.commitment(),
+        )
+        .map_err(map_client_error)?;
+    let slot = response.context.slot;
+    let [distributor_account, token_vault_account, sol_vault_account] =
+        <[Option<Account>; 3]>::try_from(response.value)
+            .map_err(|_| DistributorError::RpcError("expected three accounts".to_string()))?;
+    let distributor_account = distributor_account.ok_or_else(|| {
+        DistributorError::RpcError(format!("distributor {distributor_pubkey} does not exist"))
+    })?;
+    let distributor = MerkleDistributor::try_deserialize(&mut distributor_account.data.as_slice())
+        .map_err(|e| DistributorError::RpcError(format!("failed to parse distributor: {e}")))?;
+    let vault_balance = match (
+        distributor.distribution_mode,
+        token_vault_account,
+        sol_vault_account,
+    ) {
+        (DistributionMode::Token, Some(account), _) => {
+            token::TokenAccount::try_deserialize(&mut account.data.as_slice())
+                .map_err(|e| {
+                    DistributorError::RpcError(format!("failed to parse token vault: {e}"))
+                })?
+                .amount
+        }
+        // Claims never touch the rent-exempt minimum of the SOL vault
+        (DistributionMode::Native, _, Some(account)) => account.lamports.saturating_sub(
+            client
+                .get_minimum_balance_for_rent_exemption(0)
+                .map_err(map_client_error)?,
+        ),
+        _ => 0,
+    };
+    Ok((slot, distributor, vault_balance))
+}
+
+fn process_distributor_info(args: &Args) -> Result<()> {
+    let client = rpc_client(args);
+    let (distributor_pubkey, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+    let (slot, distributor, vault_balance) =
+        fetch_distributor_and_vault(&client, args, &distributor_pubkey)?;
+
+    let state = MerkleDistributorView::from(&distributor);
+    say!("Distributor {distributor_pubkey} at slot {slot}");
+    say!("  Version: {}", distributor.version);
+    say!("  Root: {}", state.root);
+    say!("  Tree depth: {}", distributor.tree_depth);
+    say!("  Mint: {}", distributor.mint);
+    say!("  Token vault: {}", distributor.token_vault);
+    say!("  Vault balance: {vault_balance}");
+    say!(
+        "  Claimed: {}/{} nodes, {}/{} tokens",
+        distributor.num_nodes_claimed,
+        distributor.max_num_nodes,
+        distributor.total_amount_claimed,
+        distributor.max_total_claim
+    );
+    say!(
+        "  Vesting: {} to {}, cliff {}",
+        distributor.start_ts,
+        distributor.end_ts,
+        distributor.cliff_ts
+    );
+    say!("  Vesting interval: {}s", distributor.vesting_interval_secs);
+    say!("  Min claim interval: {}s", distributor.min_claim_interval);
+    say!("  Clawback start: {}", distributor.clawback_start_ts);
+    say!("  Clawback receiver: {}", distributor.clawback_receiver);
+    say!("  Admin: {}", distributor.admin);
+    say!("  Paused: {}", distributor.paused);
+    say!("  Admin renounced: {}", distributor.admin_renounced);
+    say!("  Claim reassignment: {}", distributor.allow_reassignment);
+    say!("  Clawed back: {}", distributor.clawed_back);
+    print_json(&DistributorInfoOutput {
+        distributor: distributor_pubkey.to_string(),
+        slot,
+        vault_balance,
+        state,
+    })?;
+    Ok(())
+}
+
+/// Claimants whose stored proof is missing or does not verify against the tree's root.
+fn nodes_with_invalid_proofs(merkle_tree: &AirdropMerkleTree) -> Vec<Pubkey> {
+    merkle_tree
+        .tree_nodes()
+        .iter()
+        .filter(|node| !node_proof_is_valid(node, merkle_tree).unwrap_or(false))
+        .map(|node| node.claimant)
+        .collect()
+}
+
+fn process_verify_tree(verify_tree_args: &VerifyTreeArgs) -> Result<()> {
+    let mut merkle_tree = AirdropMerkleTree::new_from_file(&verify_tree_args.merkle_tree_path)?;
+    let stored_root = merkle_tree.merkle_root;
+    let computed_root = merkle_tree.compute_root()?;
+    // Proofs are checked against the root of the nodes, not the stored one
+    merkle_tree.merkle_root = computed_root;
+    let invalid_proofs = nodes_with_invalid_proofs(&merkle_tree);
+
+    let root_matches = computed_root == stored_root;
+    if !root_matches {
+        say!(
+            "Stored root {} differs from the root of the nodes {}",
+            hex::encode(stored_root),
+            hex::encode(computed_root)
+        );
+    }
+    for claimant in invalid_proofs.iter() {
+        say!("Proof for {claimant} is missing or invalid");
+    }
+    if !root_matches || !invalid_proofs.is_empty() {
+        return Err(DistributorError::TreeMismatch(format!(
+            "root {}, {} of {} proofs invalid; do not create a distributor from this file",
+            if root_matches { "matches" } else { "differs" },
+            invalid_proofs.len(),
+            merkle_tree.tree_nodes().len()
+        )));
+    }
+
+    say!(
+        "Tree file is consistent: root {} and all {} proofs match its nodes",
+        hex::encode(computed_root),
+        merkle_tree.tree_nodes().len()
+    );
+    print_json(&VerifyTreeOutput {
+        merkle_root: hex::encode(computed_root),
+        num_nodes: merkle_tree.tree_nodes().len() as u64,
+        max_total_claim: merkle_tree.max_total_claim,
+    })?;
+    Ok(())
+}
+
+async fn process_dump_state(args: &Args, dump_state_args: &DumpStateArgs) -> Result<()> {
+    let merkle_tree = AirdropMerkleTree::new_from_file(&dump_state_args.merkle_tree_path)?;
+    let client = new_light_client(args).await?;
+    let (distributor_pubkey, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+
+    let (slot, distributor, vault_balance) =
+        fetch_distributor_and_vault(&client.client, args, &distributor_pubkey)?;
+
+    let header = SnapshotHeader {
+        distributor: distributor_pubkey.to_string(),
+        slot,
+        version: distributor.version,
+        root: distributor.root,
+        mint: distributor.mint.to_string(),
+        token_vault: distributor.token_vault.to_string(),
+        vault_balance,
+        max_total_claim: distributor.max_total_claim,
+        max_num_nodes: distributor.max_num_nodes,
+        total_amount_claimed: distributor.total_amount_claimed,
+        num_nodes_claimed: distributor.num_nodes_claimed,
+        start_ts: distributor.start_ts,
+        end_ts: distributor.end_ts,
+        clawback_start_ts: distributor.clawback_start_ts,
+        clawback_receiver: distributor.clawback_receiver.to_string(),
+        admin: distributor.admin.to_string(),
+        clawed_back: distributor.clawed_back,
+    };
+    let mut writer = SnapshotWriter::create(&dump_state_args.path, &header)?;
+
+    // Each batch is written out as soon as it arrives
+    let mut num_claims = 0;
+    for nodes in merkle_tree.tree_nodes().chunks(CLAIM_STATUS_BATCH_SIZE) {
+        let claim_statuses =
+            fetch_claim_statuses(&client, args, &distributor_pubkey, nodes).await?;
+        for (node, (address, claim_status)) in nodes.iter().zip(claim_statuses) {
+            let Some(claim_status) = claim_status else {
+                continue;
+            };
+            writer.write_claim(&ClaimSnapshot {
+                claimant: node.claimant.to_string(),
+                claim_status: Pubkey::new_from_array(address).to_string(),
+                unlocked_amount: claim_status.unlocked_amount,
+                locked_amount: claim_status.locked_amount,
+                locked_amount_withdrawn: claim_status.locked_amount_withdrawn,
+            })?;
+            num_claims += 1;
+        }
+    }
+    writer.finish()?;
+
+    say!(
+        "Wrote distributor {distributor_pubkey} at slot {slot} with {num_claims} claims to {}",
+        dump_state_args.path.display()
+    );
+    if num_claims != distributor.num_nodes_claimed {
+        warn!(
+            "Distributor reports {} claimed nodes but {num_claims} claim statuses \
+             were found, the indexer may be behind the RPC node",
+            distributor.num_nodes_claimed
+        );
+    }
+    print_json(&header)?;
+    Ok(())
+}
+
+/// Claim status address of each of `nodes` and the claim status, if it exists, fetched in a single
+/// indexer call. Claim statuses do not reference their distributor, so they are found by deriving
+/// the address of every claimant.
+async fn fetch_claim_statuses(
+    client: &LightClient,
+    args: &Args,
+    distributor: &Pubkey,
+    nodes: &[TreeNode],
+) -> Result<Vec<([u8; 32], Option<ClaimStatus>)>> {
+    let claimants: Vec<Pubkey> = nodes.iter().map(|node| node.claimant).collect();
+    fetch_claim_statuses_of(client, args, distributor, &claimants).await
+}
+
+/// [fetch_claim_statuses] for wallets that need not be in the tree, such as the new claimants
+/// of reassigned nodes.
+async fn fetch_claim_statuses_of(
+    client: &LightClient,
+    args: &Args,
+    distributor: &Pubkey,
+    claimants: &[Pubkey],
+) -> Result<Vec<([u8; 32], Option<ClaimStatus>)>> {
+    let addresses: Vec<[u8; 32]> = claimants
+        .iter()
+        .map(|claimant| {
+            get_claim_status_address(&args.program_id, claimant, distributor, &address_tree(args)).0
+        })
+        .collect();
+    let accounts = client
+        .get_multiple_compressed_accounts(Some(addresses.clone()), None, None)
+        .await
+        .map_err(map_indexer_error)?
+        .value
+        .items;
+    if accounts.len() != claimants.len() {
+        return Err(DistributorError::RpcError(format!(
+            "indexer returned {} accounts for {} addresses",
+            accounts.len(),
+            claimants.len()
+        )));
+    }
+    addresses
+        .into_iter()
+        .zip(&accounts)
+        .map(|(address, account)| {
+            Ok((
+                address,
+                account.as_ref().map(parse_claim_status).transpose()?,
+            ))
+        })
+        .collect()
+}
+
+/// Reads a `--reassignments` file, no reassignments without one.
+fn load_reassignments(path: Option<&PathBuf>) -> Result<HashMap<Pubkey, Pubkey>> {
+    let Some(path) = path else {
+        return Ok(HashMap::new());
+    };
+    read_reassignments(std::fs::File::open(path)?).map_err(|message| {
+        DistributorError::IoError(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("invalid reassignments file {}: {message}", path.display()),
+        ))
+    })
+}
+
+/// The new claimant and its claim status for each node of `nodes` that reassign-claim emptied
+/// and `new_claimants` lists, keyed by the node's claimant.
+async fn fetch_reassignments(
+    client: &LightClient,
+    args: &Args,
+    distributor: &Pubkey,
+    nodes: &[TreeNode],
+    claim_statuses: &[([u8; 32], Option<ClaimStatus>)],
+    new_claimants: &HashMap<Pubkey, Pubkey>,
+) -> Result<HashMap<Pubkey, Reassignment>> {
+    let reassigned: Vec<(Pubkey, Pubkey)> = nodes
+        .iter()
+        .zip(claim_statuses)
+        .filter(|(node, (_, claim_status))| {
+            claim_status.as_ref().is_some_and(|status| {
+                is_reassigned(status, node.amount_unlocked(), node.amount_locked())
+            })
+        })
+        .filter_map(|(node, _)| Some((node.claimant, *new_claimants.get(&node.claimant)?)))
+        .collect();
+    if reassigned.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let new_claimant_keys: Vec<Pubkey> = reassigned.iter().map(|(_, new)| *new).collect();
+    let new_claim_statuses =
+        fetch_claim_statuses_of(client, args, distributor, &new_claimant_keys).await?;
+    Ok(reassigned
+        .into_iter()
+        .zip(new_claim_statuses)
+        .map(|((old_claimant, new_claimant), (_, claim_status))| {
+            (
+                old_claimant,
+                Reassignment {
+                    new_claimant,
+                    claim_status,
+                },
+            )
+        })
+        .collect())
+}
+
+async fn process_reconcile(args: &Args, reconcile_args: &ReconcileArgs) -> Result<()> {
+    let merkle_tree = AirdropMerkleTree::new_from_file(&reconcile_args.merkle_tree_path)?;
+    let new_claimants = load_reassignments(reconcile_args.reassignments.as_ref())?;
+    let client = new_light_client(args).await?;
+    let (distributor_pubkey, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+    let distributor = fetch_distributor(&client.client, &distributor_pubkey)?;
+
+    let mut nodes = Vec::with_capacity(merkle_tree.tree_nodes().len());
+    for tree_nodes in merkle_tree.tree_nodes().chunks(CLAIM_STATUS_BATCH_SIZE) {
+        let claim_statuses =
+            fetch_claim_statuses(&client, args, &distributor_pubkey, tree_nodes).await?;
+        let reassignments = fetch_reassignments(
+            &client,
+            args,
+            &distributor_pubkey,
+            tree_nodes,
+            &claim_statuses,
+            &new_claimants,
+        )
+        .await?;
+        for (node, (_, claim_status)) in tree_nodes.iter().zip(claim_statuses) {
+            nodes.push(NodeReconciliation::new(
+                &node.claimant,
+                node.amount_unlocked(),
+                node.amount_locked(),
+                claim_status.as_ref(),
+                reassignments.get(&node.claimant),
+            ));
+        }
+    }
+    let report = reconcile(
+        &nodes,
+        &DistributorTotals {
+            root_matches: distributor.root == merkle_tree.merkle_root,
+            max_total_claim: distributor.max_total_claim,
+            max_num_nodes: distributor.max_num_nodes,
+            total_amount_claimed: distributor.total_amount_claimed,
+            num_nodes_claimed: distributor.num_nodes_claimed,
+        },
+    );
+    if reconcile_args.only_unclaimed {
+        nodes.retain(|node| !node.claimed);
+    }
+
+    say!(
+        "Distributor {distributor_pubkey}: {} of {} nodes claimed, {} of {} tokens",
+        report.nodes_claimed,
+        report.nodes_total,
+        report.amount_claimed,
+        report.tree_total_amount
+    );
+    say!(
+        "  On chain: {} nodes claimed, total_amount_claimed {}",
+        report.distributor_num_nodes_claimed,
+        report.distributor_total_amount_claimed
+    );
+    for node in &nodes {
+        let status = match (node.claimed, node.reassigned, &node.new_claimant) {
+            (_, true, Some(new_claimant)) => format!("reassigned to {new_claimant}"),
+            (_, true, None) => "reassigned".to_string(),
+            (true, false, _) => "claimed".to_string(),
+            (false, false, _) => "unclaimed".to_string(),
+        };
+        say!(
+            "  {} {status}: unlocked {}, locked {}, claimed {}",
+            node.claimant,
+            node.amount_unlocked,
+            node.amount_locked,
+            node.amount_claimed
+        );
+    }
+    match report.discrepancies.is_empty() {
+        true => say!("No discrepancies"),
+        false => {
+            say!("{} discrepancies:", report.discrepancies.len());
+            for discrepancy in &report.discrepancies {
+                say!("  {discrepancy}");
+            }
+            warn!("The indexer may be behind the RPC node, run again to rule that out");
+        }
+    }
+    print_json(&ReconcileOutput {
+        distributor: distributor_pubkey.to_string(),
+        report,
+        nodes,
+    })?;
+    Ok(())
+}
+
+async fn process_export_claims(args: &Args, export_claims_args: &ExportClaimsArgs) -> Result<()> {
+    let merkle_tree = AirdropMerkleTree::new_from_file(&export_claims_args.merkle_tree_path)?;
+    let new_claimants = load_reassignments(export_claims_args.reassignments.as_ref())?;
+    let client = new_light_client(args).await?;
+    let (distributor_pubkey, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+
+    // A node without a claim status is exported as unclaimed
+    let mut rows = Vec::with_capacity(merkle_tree.tree_nodes().len());
+    let mut nodes_claimed = 0;
+    for tree_nodes in merkle_tree.tree_nodes().chunks(CLAIM_STATUS_BATCH_SIZE) {
+        let claim_statuses =
+            fetch_claim_statuses(&client, args, &distributor_pubkey, tree_nodes).await?;
+        let reassignments = fetch_reassignments(
+            &client,
+            args,
+            &distributor_pubkey,
+            tree_nodes,
+            &claim_statuses,
+            &new_claimants,
+        )
+        .await?;
+        for (node, (_, claim_status)) in tree_nodes.iter().zip(claim_statuses) {
+            nodes_claimed += u64::from(claim_status.is_some());
+            rows.push(ClaimExportRow::new(
+                &node.claimant,
+                node.amount_unlocked(),
+                node.amount_locked(),
+                claim_status.as_ref(),
+                reassignments.get(&node.claimant),
+            ));
+        }
+        debug!(
+            "Fetched {} of {} claim statuses",
+            rows.len(),
+            merkle_tree.tree_nodes().len()
+        );
+    }
+
+    let file = std::fs::File::create(&export_claims_args.out_path)?;
+    write_claims_csv(file, &rows).map_err(|e| DistributorError::IoError(e.into()))?;
+
+    say!(
+        "Wrote {} nodes, {nodes_claimed} claimed, to {}",
+        rows.len(),
+        export_claims_args.out_path.display()
+    );
+    print_json(&ExportClaimsOutput {
+        distributor: distributor_pubkey.to_string(),
+        path: export_claims_args.out_path.display().to_string(),
+        nodes_total: rows.len() as u64,
+        nodes_claimed,
+    })?;
+    Ok(())
+}
+
+/// Claim status address of each of `nodes` for `distributor`, in `address_tree`.
+fn claim_status_addresses(
+    program_id: &Pubkey,
+    distributor: &Pubkey,
+    address_tree: &Pubkey,
+    nodes: &[TreeNode],
+) -> Vec<ClaimStatusAddressEntry> {
+    nodes
+        .iter()
+        .map(|node| {
+            let (address, _) =
+                get_claim_status_address(program_id, &node.claimant, distributor, address_tree);
+            ClaimStatusAddressEntry {
+                claimant: node.claimant.to_string(),
+                address: Pubkey::new_from_array(address).to_string(),
+            }
+        })
+        .collect()
+}
+
+fn write_claim_status_addresses(
+    path: &Path,
+    format: AddressFileFormat,
+    file: &ClaimStatusAddressFile,
+) -> Result<()> {
+    match format {
+        AddressFileFormat::Json => {
+            std::fs::write(path, serde_json::to_string_pretty(file)?)?;
+        }
+        AddressFileFormat::Csv => {
+            let csv_error = |e: csv::Error| DistributorError::IoError(e.into());
+            let mut writer = csv::Writer::from_path(path).map_err(csv_error)?;
+            for entry in &file.addresses {
+                writer.serialize(entry).map_err(csv_error)?;
+            }
+            writer.flush()?;
+        }
+    }
+    Ok(())
+}
+
+fn process_derive_addresses(
+    args: &Args,
+    derive_addresses_args: &DeriveAddressesArgs,
+) -> Result<()> {
+    // The addresses depend on the tree, so a default is too easy to get wrong here
+    let Some(address_tree) = args.address_tree else {
+        return Err(DistributorError::IoError(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "derive-addresses needs --address-tree, the --network one is {}",
+                address_tree(args)
+            ),
+        )));
+    };
+    let merkle_tree = AirdropMerkleTree::new_from_file(&derive_addresses_args.merkle_tree_path)?;
+    let (distributor, _bump) =
+        get_merkle_distributor_pda(&args.program_id, &args.mint, args.airdrop_version);
+
+    let file = ClaimStatusAddressFile {
+        program_id: args.program_id.to_string(),
+        distributor: distributor.to_string(),
+        address_tree: address_tree.to_string(),
+        addresses: claim_status_addresses(
+            &args.program_id,
+            &distributor,
+            &address_tree,
+            merkle_tree.tree_nodes(),
+        ),
+    };
+    write_claim_status_addresses(
+        &derive_addresses_args.out_path,
+        derive_addresses_args.format,
+        &file,
+    )?;
+
+    say!(
+        "Wrote {} claim status addresses of distributor {distributor} in address tree \
+         {address_tree} to {}",
+        file.addresses.len(),
+        derive_addresses_args.out_path.display()
+    );
+    print_json(&DeriveAddressesOutput {
+        distributor: distributor.to_string(),
+        address_tree: address_tree.to_string(),
+        path: derive_addresses_args.out_path.display().to_string(),
+        addresses: file.addresses.len(),
+    })?;
+    Ok(())
+}
+
+fn process_diff_state(diff_state_args: &DiffStateArgs) -> Result<()> {
+    let before = read_snapshot(&diff_state_args.before)?;
+    let after = read_snapshot(&diff_state_args.after)?;
+    if before.header.distributor != after.header.distributor {
+        return Err(DistributorError::TreeMismatch(format!(
+            "snapshots are of different distributors: {} and {}",
+            before.header.distributor, after.header.distributor
+        )));
+    }
+    let diff = diff_snapshots(&before, &after);
+
+    say!(
+        "Distributor {} from slot {} to slot {}",
+        after.header.distributor,
+        diff.slot_before,
+        diff.slot_after
+    );
+    say!("  Vault balance change: {}", diff.vault_balance_change);
+    say!(
+        "  Total amount claimed change: {}",
+        diff.total_amount_claimed_change
+    );
+    say!("  New claims: {}", diff.new_claims.len());
+    for claim in &diff.new_claims {
+        say!(
+            "    {}: {} unlocked, {} locked",
+            claim.claimant,
+            claim.unlocked_amount,
+            claim.locked_amount
+        );
+    }
+    say!("  Locked withdrawals: {}", diff.withdrawals.len());
+    for withdrawal in &diff.withdrawals {
+        say!(
+            "    {}: {} -> {}",
+            withdrawal.claimant,
+            withdrawal.locked_amount_withdrawn_before,
+            withdrawal.locked_amount_withdrawn_after
+        );
+    }
+    if !diff.missing_claims.is_empty() {
+        say!(
+            "  Claims missing from the later snapshot: {}",
+            diff.missing_claims.len()
+        );
+        for claim in &diff.missing_claims {
+            say!("    {}", claim.claimant);
+        }
+    }
+    print_json(&diff)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insufficient_funds_maps_to_insufficient_funds() {
+        let error = map_transaction_error(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(TOKEN_INSUFFICIENT_FUNDS),
+        ));
+        assert!(matches!(error, DistributorError::InsufficientFunds(_)));
+
+        let error = map_transaction_error(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VAULT_INSUFFICIENT_BALANCE),
+        ));
+        assert!(matches!(error, DistributorError::InsufficientFunds(_)));
+
+        let error = map_transaction_error(TransactionError::InsufficientFundsForFee);
+        assert!(matches!(error, DistributorError::InsufficientFunds(_)));
+    }
+
+    #[test]
+    fn test_frozen_recipient_keeps_program_error_code() {
+        let error = map_transaction_error(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RECIPIENT_ACCOUNT_FROZEN),
+        ));
+        assert_eq!(error.code(), 7);
+        assert!(matches!(
+            &error,
+            DistributorError::ProgramError { code, message }
+                if *code == RECIPIENT_ACCOUNT_FROZEN && message.contains("thaw")
+        ));
+    }
+
+    #[test]
+    fn test_fee_payer_pays_claim_transaction() {
+        let claimant = Keypair::new();
+        let fee_payer = Keypair::new();
+        let ix = Instruction::new_with_bytes(
+            merkle_distributor::ID,
+            &[],
+            vec![AccountMeta::new(claimant.pubkey(), true)],
+        );
+
+        let signers = claim_signers(&claimant, Some(&fee_payer));
+        let tx = Transaction::new_signed_with_payer(
+            &[ix.clone()],
+            Some(&signers[0].pubkey()),
+            &signers,
+            Default::default(),
+        );
+        // The runtime charges the fee to the first account key
+        assert_eq!(tx.message.account_keys[0], fee_payer.pubkey());
+        assert_eq!(tx.message.header.num_required_signatures, 2);
+        assert!(tx.message.is_signer(1));
+        assert_eq!(tx.message.account_keys[1], claimant.pubkey());
+        tx.verify().unwrap();
+
+        let signers = claim_signers(&claimant, None);
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&signers[0].pubkey()),
+            &signers,
+            Default::default(),
+        );
+        assert_eq!(tx.message.account_keys[0], claimant.pubkey());
+        assert_eq!(tx.message.header.num_required_signatures, 1);
+    }
+
+    #[test]
+    fn test_nonce_blockhash_reads_durable_nonce() {
+        use solana_nonce::state::{Data, DurableNonce};
+
+        let durable_nonce = DurableNonce::from_blockhash(&Hash::new_unique());
+        let nonce_account = |state: State, owner: Pubkey| Account {
+            lamports: 1_447_680,
+            data: bincode::serialize(&Versions::new(state)).unwrap(),
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        };
+
+        let initialized = State::Initialized(Data::new(Pubkey::new_unique(), durable_nonce, 5_000));
+        assert_eq!(
+            nonce_blockhash(&nonce_account(
+                initialized.clone(),
+                solana_program::system_program::ID
+            )),
+            Some(*durable_nonce.as_hash())
+        );
+        assert_eq!(
+            nonce_blockhash(&nonce_account(
+                State::Uninitialized,
+                solana_program::system_program::ID
+            )),
+            None
+        );
+        assert_eq!(
+            nonce_blockhash(&nonce_account(initialized, Pubkey::new_unique())),
+            None
+        );
+    }
+
+    #[test]
+    fn test_nonce_account_is_advanced_first() {
+        let authority = Pubkey::new_unique();
+        let nonce_account = Pubkey::new_unique();
+        let nonce = Hash::new_unique();
+        let args = Args::try_parse_from([
+            "cli",
+            "--mint",
+            &Pubkey::new_unique().to_string(),
+            "--rpc-url",
+            "http://127.0.0.1:1",
+            "--authority",
+            &authority.to_string(),
+            "--offline",
+            "--nonce-account",
+            &nonce_account.to_string(),
+            "--blockhash",
+            &nonce.to_string(),
+            "close-distributor",
+        ])
+        .unwrap();
+        // Both the nonce and its authority are given, so no RPC request is made
+        let client = rpc_client(&args);
+        let (ixs, blockhash) = transaction_blockhash(&client, &args, &authority).unwrap();
+        assert_eq!(blockhash, nonce);
+        assert_eq!(ixs, vec![advance_nonce_account(&nonce_account, &authority)]);
+    }
+
+    #[test]
+    fn test_network_and_commitment_flags() {
+        let mint = Pubkey::new_unique().to_string();
+        let parse = |extra: &[&str]| {
+            let mut cli_args = vec![
+                "cli",
+                "--mint",
+                mint.as_str(),
+                "--rpc-url",
+                "http://127.0.0.1:1",
+            ];
+            cli_args.extend_from_slice(extra);
+            cli_args.push("close-distributor");
+            Args::try_parse_from(cli_args).unwrap()
+        };
+        let args = parse(&[]);
+        assert_eq!(args.network, Network::Mainnet);
+        assert_eq!(
+            address_tree(&args),
+            Pubkey::new_from_array(light_sdk::constants::ADDRESS_TREE_V2)
+        );
+        assert_eq!(args.commitment.config(), CommitmentConfig::confirmed());
+        let args = parse(&["--network", "devnet", "--commitment", "finalized"]);
+        assert_eq!(args.commitment.config(), CommitmentConfig::finalized());
+        assert_eq!(address_tree(&args), Network::Devnet.preset().address_tree);
+        let custom_tree = Pubkey::new_unique();
+        let args = parse(&[
+            "--network",
+            "devnet",
+            "--address-tree",
+            &custom_tree.to_string(),
+        ]);
+        assert_eq!(address_tree(&args), custom_tree);
+    }
+
+    #[test]
+    fn test_ledger_flag() {
+        let mint = Pubkey::new_unique().to_string();
+        let parse = |extra: &[&str]| {
+            let mut cli_args = vec!["cli", "--mint", mint.as_str(), "--rpc-url", "http://x"];
+            cli_args.extend_from_slice(extra);
+            cli_args.push("set-admin");
+            cli_args.extend(["--new-admin", mint.as_str()]);
+            Args::try_parse_from(cli_args)
+        };
+        // A bare --ledger leaves the subcommand alone
+        let args = parse(&["--ledger"]).unwrap();
+        assert_eq!(args.ledger, Some(None));
+        assert!(args.keypair_path.is_none());
+        let args = parse(&["--ledger=1/0"]).unwrap();
+        assert_eq!(args.ledger, Some(Some("1/0".to_string())));
+        assert!(parse(&["--ledger", "--keypair-path", "id.json"]).is_err());
+
+        assert_eq!(
+            format!("{:?}", ledger_derivation_path(None).unwrap()),
+            "m/44'/501'"
+        );
+        assert_eq!(
+            format!("{:?}", ledger_derivation_path(Some("1/0")).unwrap()),
+            "m/44'/501'/1'/0'"
+        );
+        assert!(ledger_derivation_path(Some("1/0/2")).is_err());
+    }
+
+    #[test]
+    fn test_rpc_url_list() {
+        let mint = Pubkey::new_unique().to_string();
+        let args = Args::try_parse_from([
+            "cli",
+            "--mint",
+            mint.as_str(),
+            "--keypair-path",
+            "id.json",
+            "--rpc-url",
+            "http://a,http://b",
+            "--rpc-url",
+            "http://c",
+            "distributor-info",
+        ])
+        .unwrap();
+        assert_eq!(args.rpc_url, ["http://a", "http://b", "http://c"]);
+        assert_eq!(rpc_client(&args).url(), "http://a");
+
+        assert!(Args::try_parse_from([
+            "cli",
+            "--mint",
+            mint.as_str(),
+            "--keypair-path",
+            "id.json",
+            "distributor-info",
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_interval_secs_must_be_positive() {
+        let mint = Pubkey::new_unique().to_string();
+        let parse = |command: &str, interval_secs: &str| {
+            Args::try_parse_from([
+                "cli",
+                "--mint",
+                mint.as_str(),
+                "--rpc-url",
+                "http://a",
+                command,
+                "--interval-secs",
+                interval_secs,
+            ])
+        };
+        let args = parse("monitor", "1").unwrap();
+        assert!(matches!(
+            args.command,
+            Commands::Monitor(MonitorArgs {
+                interval_secs: 1,
+                ..
+            })
+        ));
+        // tokio::time::interval panics on a zero period
+        assert!(parse("monitor", "0").is_err());
+        assert!(parse("claim-loop", "1").is_ok());
+        assert!(parse("claim-loop", "0").is_err());
+    }
+
+    #[test]
+    fn test_check_indexer_lag() {
+        assert!(check_indexer_lag(1_000, 1_000, 150).is_ok());
+        assert!(check_indexer_lag(1_000, 850, 150).is_ok());
+        // An indexer ahead of a lagging RPC node is fine
+        assert!(check_indexer_lag(1_000, 1_010, 0).is_ok());
+        let error = check_indexer_lag(1_000, 849, 150).unwrap_err();
+        assert!(matches!(error, DistributorError::IndexerLag(_)));
+        assert!(error.to_string().contains("151 slots behind"));
+    }
+
+    #[test]
+    fn test_compute_unit_limit_with_margin() {
+        assert_eq!(compute_unit_limit_with_margin(200_000, 20), 240_000);
+        assert_eq!(compute_unit_limit_with_margin(123_457, 0), 123_457);
+        assert_eq!(
+            compute_unit_limit_with_margin(1_300_000, 20),
+            MAX_COMPUTE_UNIT_LIMIT
+        );
+        assert_eq!(
+            compute_unit_limit_with_margin(u64::MAX, 20),
+            MAX_COMPUTE_UNIT_LIMIT
+        );
+    }
+
+    #[test]
+    fn test_lockup_remaining() {
+        let end_ts = 1_700_000_000;
+        assert_eq!(lockup_remaining(end_ts - 86_400 - 90, end_ts), (1, 90));
+        assert_eq!(lockup_remaining(end_ts - 59, end_ts), (0, 59));
+        assert_eq!(lockup_remaining(end_ts, end_ts), (0, 0));
+        assert_eq!(lockup_remaining(end_ts + 10, end_ts), (0, 0));
+    }
+
+    #[test]
+    fn test_next_withdrawal_ts() {
+        let now = 1_700_000_000;
+        // Never withdrawn
+        assert_eq!(next_withdrawal_ts(0, 3_600, now), None);
+        assert_eq!(next_withdrawal_ts(now - 60, 3_600, now), Some(now + 3_540));
+        assert_eq!(next_withdrawal_ts(now - 3_600, 3_600, now), None);
+        assert_eq!(next_withdrawal_ts(now - 60, 0, now), None);
+    }
+
+    #[test]
+    fn test_claim_loop_step() {
+        let mut distributor = MerkleDistributor {
+            start_ts: 1_000,
+            end_ts: 2_000,
+            cliff_ts: 1_000,
+            min_claim_interval: 100,
+            vesting_interval_secs: 1,
+            ..Default::default()
+        };
+        let mut claim_status = ClaimStatus {
+            locked_amount: 1_000,
+            ..Default::default()
+        };
+        assert_eq!(
+            claim_loop_step(&claim_status, &distributor, 500, 0),
+            ClaimLoopStep::Wait
+        );
+        assert_eq!(
+            claim_loop_step(&claim_status, &distributor, 1_300, 0),
+            ClaimLoopStep::Claim(300)
+        );
+        assert_eq!(
+            claim_loop_step(&claim_status, &distributor, 1_300, 400),
+            ClaimLoopStep::Wait
+        );
+
+        // Within min_claim_interval of the last withdrawal
+        claim_status.locked_amount_withdrawn = 300;
+        claim_status.last_claimed_ts = 1_300;
+        assert_eq!(
+            claim_loop_step(&claim_status, &distributor, 1_350, 0),
+            ClaimLoopStep::Wait
+        );
+        assert_eq!(
+            claim_loop_step(&claim_status, &distributor, 1_400, 0),
+            ClaimLoopStep::Claim(100)
+        );
+
+        // The last tokens are claimed even below min_unlock_to_claim
+        claim_status.locked_amount_withdrawn = 900;
+        claim_status.last_claimed_ts = 1_900;
+        assert_eq!(
+            claim_loop_step(&claim_status, &distributor, 2_500, 400),
+            ClaimLoopStep::Claim(100)
+        );
+        distributor.paused = true;
+        assert_eq!(
+            claim_loop_step(&claim_status, &distributor, 2_500, 0),
+            ClaimLoopStep::Wait
+        );
+        distributor.clawed_back = true;
+        assert_eq!(
+            claim_loop_step(&claim_status, &distributor, 2_500, 0),
+            ClaimLoopStep::ClawedBack
+        );
+        claim_status.locked_amount_withdrawn = 1_000;
+        assert_eq!(
+            claim_loop_step(&claim_status, &distributor, 2_500, 0),
+            ClaimLoopStep::Done
+        );
+    }
+
+    #[test]
+    fn test_clawback_window_closed() {
+        let mut distributor = MerkleDistributor {
+            clawback_start_ts: 1_700_000_000,
+            ..Default::default()
+        };
+        assert_eq!(
+            clawback_window_closed(&distributor, 1_700_000_000 - 3_600).unwrap(),
+            "clawback opens at 1700000000 (in 1h 0m)"
+        );
+        assert_eq!(clawback_window_closed(&distributor, 1_700_000_000), None);
+        assert_eq!(clawback_window_closed(&distributor, 1_700_000_001), None);
+
+        distributor.clawed_back = true;
+        assert_eq!(
+            clawback_window_closed(&distributor, 1_700_000_001).unwrap(),
+            "the distributor has already been clawed back"
+        );
+    }
+
+    #[test]
+    fn test_clawback_surplus() {
+        let mut distributor = MerkleDistributor {
+            max_total_claim: 1_000,
+            total_amount_claimed: 400,
+            ..Default::default()
+        };
+        // 600 is still owed to unclaimed allocations
+        assert_eq!(clawback_surplus(1_000, &distributor), 400);
+        assert_eq!(clawback_surplus(600, &distributor), 0);
+        assert_eq!(clawback_surplus(500, &distributor), 0);
+
+        distributor.total_amount_claimed = 1_000;
+        assert_eq!(clawback_surplus(250, &distributor), 250);
+    }
+
+    #[test]
+    fn test_is_confirmed() {
+        assert!(is_confirmed("y\n"));
+        assert!(is_confirmed(" YES \n"));
+        assert!(!is_confirmed("\n"));
+        assert!(!is_confirmed("n\n"));
+        // A closed stdin reads nothing
+        assert!(!is_confirmed(""));
+    }
+
+    #[test]
+    fn test_is_renounce_confirmed() {
+        assert!(is_renounce_confirmed("RENOUNCE\n"));
+        assert!(!is_renounce_confirmed("y\n"));
+        assert!(!is_renounce_confirmed("renounce\n"));
+        assert!(!is_renounce_confirmed(""));
+    }
+
+    #[test]
+    fn test_describe_ts() {
+        let now = 1_700_000_000;
+        assert_eq!(describe_ts(now + 90, now), "1700000090 (in 1m)");
+        assert_eq!(
+            describe_ts(now + 2 * 86_400 + 3 * 3_600, now),
+            "1700183600 (in 2d 3h)"
+        );
+        assert_eq!(describe_ts(now - 5_400, now), "1699994600 (1h 30m ago)");
+        // A timestamp in milliseconds lands tens of thousands of years out
+        assert_eq!(
+            describe_ts(now * 1_000, now),
+            "1700000000000 (in 19656250d 0h)"
+        );
+    }
+
+    #[test]
+    fn test_fund_vault_instruction() {
+        let mint = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let token_vault = Pubkey::new_unique();
+        let source = Pubkey::new_unique();
+        let parse = |fund_vault_args: &[&str]| {
+            let mut cli_args = vec![
+                "cli".to_string(),
+                "--mint".to_string(),
+                mint.to_string(),
+                "--authority".to_string(),
+                authority.to_string(),
+            ];
+            cli_args.extend(["fund-vault", "--amount", "500"].map(String::from));
+            cli_args.extend(fund_vault_args.iter().map(|arg| arg.to_string()));
+            Args::try_parse_from(cli_args)
+        };
+        let fund_vault_ix = |args: &Args| {
+            let Commands::FundVault(fund_vault_args) = &args.command else {
+                panic!("expected fund-vault");
+            };
+            fund_vault_instruction(args, fund_vault_args, &authority, &token_vault, 6).unwrap()
+        };
+
+        let args = parse(&["--from-token-account", &source.to_string()]).unwrap();
+        assert_eq!(
+            fund_vault_ix(&args),
+            token::spl_token::instruction::transfer_checked(
+                &token::ID,
+                &source,
+                &mint,
+                &token_vault,
+                &authority,
+                &[],
+                500,
+                6,
+            )
+            .unwrap()
+        );
+        let args = parse(&["--use-mint-authority"]).unwrap();
+        assert_eq!(
+            fund_vault_ix(&args),
+            token::spl_token::instruction::mint_to(
+                &token::ID,
+                &mint,
+                &token_vault,
+                &authority,
+                &[],
+                500
+            )
+            .unwrap()
+        );
+
+        // Native SOL distributors take neither, process_fund_vault checks token distributors
+        assert!(parse(&[]).is_ok());
+        assert!(parse(&[
+            "--use-mint-authority",
+            "--from-token-account",
+            &source.to_string()
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_export_tx_writes_unsigned_transaction() {
+        let authority = Pubkey::new_unique();
+        let new_admin = Pubkey::new_unique();
+        let blockhash = Hash::new_unique();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("set_admin.tx");
+        let args = Args::try_parse_from([
+            "cli",
+            "--mint",
+            &Pubkey::new_unique().to_string(),
+            "--rpc-url",
+            "http://127.0.0.1:1",
+            "--authority",
+            &authority.to_string(),
+            "--blockhash",
+            &blockhash.to_string(),
+            "--export-tx",
+            path.to_str().unwrap(),
+            "set-admin",
+            "--new-admin",
+            &new_admin.to_string(),
+        ])
+        .unwrap();
+        let ix = Instruction {
+            program_id: args.program_id,
+            accounts: merkle_distributor::accounts::SetAdmin {
+                distributor: Pubkey::new_unique(),
+                admin: authority,
+                new_admin,
+            }
+            .to_account_metas(None),
+            data: merkle_distributor::instruction::SetAdmin {}.data(),
+        };
+
+        // The blockhash is given, so no RPC request is made
+        let client = rpc_client(&args);
+        let signature =
+            send_or_print_transaction(&client, &args, &[ix.clone()], None, &[]).unwrap();
+        assert!(signature.is_none());
+
+        let encoded = std::fs::read_to_string(&path).unwrap();
+        let tx: Transaction = bincode::deserialize(&BASE64.decode(encoded).unwrap()).unwrap();
+        // Paid by the authority, with every account meta and no signatures
+        let mut expected = Transaction::new_with_payer(&[ix], Some(&authority));
+        expected.message.recent_blockhash = blockhash;
+        assert_eq!(tx, expected);
+        assert_eq!(tx.signatures, vec![Signature::default()]);
+    }
+
+    #[test]
+    fn test_failed_new_claim_with_existing_claim_status_is_already_claimed() {
+        let claimant = Pubkey::new_unique();
+        let error = already_claimed_or(
+            DistributorError::RpcError("proof failed".to_string()),
+            true,
+            &claimant,
+        );
+        assert!(matches!(error, DistributorError::AlreadyClaimed(c) if c == claimant));
+
+        let error = already_claimed_or(
+            DistributorError::RpcError("proof failed".to_string()),
+            false,
+            &claimant,
+        );
+        assert!(matches!(error, DistributorError::RpcError(_)));
+    }
+
+    #[test]
+    fn test_custom_program_error_keeps_code() {
+        let code = anchor_lang::error::ERROR_CODE_OFFSET
+            + merkle_distributor::error::ErrorCode::InvalidProof as u32;
+        let error = map_transaction_error(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(code),
+        ));
+        match error {
+            DistributorError::ProgramError { code: mapped, .. } => assert_eq!(mapped, code),
+            e => panic!("unexpected error {e:?}"),
+        }
+    }
+
+    #[test]
+    fn test_indexer_errors() {
+        let error = map_indexer_error(IndexerError::IndexerNotSyncedToSlot);
+        assert!(matches!(error, DistributorError::IndexerLag(_)));
+
+        let error = map_rpc_error(RpcError::IndexerError(IndexerError::IndexerNotSyncedToSlot));
+        assert!(matches!(error, DistributorError::IndexerLag(_)));
+
+        let error = map_rpc_error(RpcError::CustomError("boom".to_string()));
+        assert!(matches!(error, DistributorError::RpcError(_)));
+    }
+
+    #[test]
+    fn test_expected_claimed_balance() {
+        let claim_status = ClaimStatus {
+            claimant: Pubkey::new_unique(),
+            locked_amount: 500,
+            locked_amount_withdrawn: 0,
+            unlocked_amount: 1_000,
+            unlock_schedule: None,
+            last_claimed_ts: 0,
+            category_breakdown: None,
+        };
+        // right after new_claim only the unlocked amount has been transferred
+        assert_eq!(expected_claimed_balance(&claim_status), 1_000);
+
+        let claim_status = ClaimStatus {
+            locked_amount_withdrawn: 200,
+            ..claim_status
+        };
+        assert_eq!(expected_claimed_balance(&claim_status), 1_200);
+
+        let claim_status = ClaimStatus {
+            locked_amount_withdrawn: 500,
+            ..claim_status
+        };
+        assert_eq!(expected_claimed_balance(&claim_status), 1_500);
+    }
+
+    #[test]
+    fn test_indexer_poll_backoff() {
+        let mut delay = INDEXER_POLL_INITIAL_DELAY;
+        let mut delays = vec![delay];
+        for _ in 0..5 {
+            delay = next_indexer_poll_delay(delay);
+            delays.push(delay);
+        }
+        assert_eq!(
+            delays,
+            [250, 500, 1_000, 2_000, 4_000, 4_000].map(Duration::from_millis)
+        );
+    }
+
+    #[test]
+    fn test_claim_steps_fresh_claim() {
+        // Creating the claim delivers the unlocked amount, vested locked tokens follow
+        assert_eq!(
+            claim_steps(false, Some(100), 0),
+            ClaimSteps {
+                new_claim: true,
+                claim_locked: true,
+            }
+        );
+        // Before the vesting start only the unlocked amount is claimed
+        assert_eq!(
+            claim_steps(false, Some(0), 0),
+            ClaimSteps {
+                new_claim: true,
+                claim_locked: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_claim_steps_existing_claim() {
+        assert_eq!(
+            claim_steps(true, Some(100), 0),
+            ClaimSteps {
+                new_claim: false,
+                claim_locked: true,
+            }
+        );
+        assert_eq!(
+            claim_steps(true, Some(0), 0),
+            ClaimSteps {
+                new_claim: false,
+                claim_locked: false,
+            }
+        );
+        // Unknown withdrawable amount: let the program decide
+        assert_eq!(
+            claim_steps(true, None, 0),
+            ClaimSteps {
+                new_claim: false,
+                claim_locked: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_claim_steps_min_unlock_to_claim() {
+        // Dust below the threshold is left for a later run
+        assert_eq!(
+            claim_steps(true, Some(99), 100),
+            ClaimSteps {
+                new_claim: false,
+                claim_locked: false,
+            }
+        );
+        assert_eq!(
+            claim_steps(true, Some(100), 100),
+            ClaimSteps {
+                new_claim: false,
+                claim_locked: true,
+            }
+        );
+        // The threshold never holds back creating the claim
+        assert_eq!(
+            claim_steps(false, Some(99), 100),
+            ClaimSteps {
+                new_claim: true,
+                claim_locked: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_node_proof_is_valid() {
+        let node = |amount: u64| TreeNode {
+            claimant: Pubkey::new_unique(),
+            proof: None,
+            total_unlocked_staker: amount,
+            total_locked_staker: amount,
+            total_unlocked_searcher: 0,
+            total_locked_searcher: 0,
+            total_unlocked_validator: 0,
+            total_locked_validator: 0,
+            unlock_schedule: None,
+        };
+        let merkle_tree = AirdropMerkleTree::new(vec![node(1), node(2), node(3)]).unwrap();
+        for node in merkle_tree.tree_nodes() {
+            assert!(node_proof_is_valid(node, &merkle_tree).unwrap());
+        }
+
+        // A stale tree file: amounts edited after the proofs were generated
+        let mut tampered = merkle_tree.tree_nodes()[0].clone();
+        tampered.total_unlocked_staker += 1;
+        assert!(!node_proof_is_valid(&tampered, &merkle_tree).unwrap());
+    }
+
+    #[test]
+    fn test_reassigned_node() {
+        let node = |amount: u64| TreeNode {
+            claimant: Pubkey::new_unique(),
+            proof: None,
+            total_unlocked_staker: amount,
+            total_locked_staker: amount,
+            total_unlocked_searcher: 0,
+            total_locked_searcher: 0,
+            total_unlocked_validator: 0,
+            total_locked_validator: 0,
+            unlock_schedule: None,
+        };
+        let merkle_tree = AirdropMerkleTree::new(vec![node(1), node(2)]).unwrap();
+        let old_claimant = merkle_tree.tree_nodes()[0].claimant;
+        let other_claimant = merkle_tree.tree_nodes()[1].claimant;
+        let new_claimant = Pubkey::new_unique();
+
+        let reassigned = reassigned_node(&merkle_tree, &old_claimant, &new_claimant).unwrap();
+        assert_eq!(reassigned.claimant, old_claimant);
+        assert!(matches!(
+            reassigned_node(&merkle_tree, &new_claimant, &old_claimant),
+            Err(DistributorError::ProofNotFound(claimant)) if claimant == new_claimant
+        ));
+        // The new wallet would lose an allocation of its own
+        assert!(reassigned_node(&merkle_tree, &old_claimant, &other_claimant).is_err());
+        assert!(reassigned_node(&merkle_tree, &old_claimant, &old_claimant).is_err());
+    }
+
+    #[test]
+    fn test_nodes_with_invalid_proofs() {
+        let node = |amount: u64| TreeNode {
+            claimant: Pubkey::new_unique(),
+            proof: None,
+            total_unlocked_staker: amount,
+            total_locked_staker: amount,
+            total_unlocked_searcher: 0,
+            total_locked_searcher: 0,
+            total_unlocked_validator: 0,
+            total_locked_validator: 0,
+            unlock_schedule: None,
+        };
+        let mut merkle_tree =
+            AirdropMerkleTree::new(vec![node(1), node(2), node(3), node(4)]).unwrap();
+        assert!(nodes_with_invalid_proofs(&merkle_tree).is_empty());
+
+        // A swapped proof and a missing one are both reported
+        let proof = merkle_tree.tree_nodes()[1].proof.clone();
+        merkle_tree.tree_nodes_mut()[0].proof = proof;
+        merkle_tree.tree_nodes_mut()[3].proof = None;
+        assert_eq!(
+            nodes_with_invalid_proofs(&merkle_tree),
+            vec![
+                merkle_tree.tree_nodes()[0].claimant,
+                merkle_tree.tree_nodes()[3].claimant
+            ]
+        );
+
+        // An edited amount changes the root, so the other proofs stop verifying too
+        merkle_tree.tree_nodes_mut()[2].total_locked_staker += 1;
+        let root = merkle_tree.compute_root().unwrap();
+        assert_ne!(root, merkle_tree.merkle_root);
+        merkle_tree.merkle_root = root;
+        assert_eq!(nodes_with_invalid_proofs(&merkle_tree).len(), 4);
+    }
+
+    #[test]
+    fn test_node_categories() {
+        let node = TreeNode {
+            claimant: Pubkey::new_unique(),
+            proof: None,
+            total_unlocked_staker: 10,
+            total_locked_staker: 20,
+            total_unlocked_searcher: 0,
+            total_locked_searcher: 0,
+            total_unlocked_validator: 0,
+            total_locked_validator: 5,
+            unlock_schedule: None,
+        };
+        assert_eq!(
+            node_categories(&node),
+            vec![
+                (AirdropCategory::Staker, 10, 20),
+                (AirdropCategory::Validator, 0, 5)
+            ]
+        );
+        let entry = claimant_entry(&node);
+        assert_eq!((entry.amount_unlocked, entry.amount_locked), (10, 25));
+    }
+
+    #[test]
+    fn test_check_superset() {
+        let node = |amount: u64| TreeNode {
+            claimant: Pubkey::new_unique(),
+            proof: None,
+            total_unlocked_staker: amount,
+            total_locked_staker: amount,
+            total_unlocked_searcher: 0,
+            total_locked_searcher: 0,
+            total_unlocked_validator: 0,
+            total_locked_validator: 0,
+            unlock_schedule: None,
+        };
+        let previous_nodes = vec![node(1), node(2)];
+        let previous_tree = AirdropMerkleTree::new(previous_nodes.clone()).unwrap();
+
+        let superset =
+            AirdropMerkleTree::new([previous_nodes.clone(), vec![node(3)]].concat()).unwrap();
+        assert_eq!(check_superset(&previous_tree, &superset).unwrap(), 1);
+        assert_eq!(check_superset(&previous_tree, &previous_tree).unwrap(), 0);
+
+        let dropped = AirdropMerkleTree::new(vec![previous_nodes[0].clone(), node(3)]).unwrap();
+        let error = check_superset(&previous_tree, &dropped).unwrap_err();
+        assert!(error.to_string().contains("drops 1 and changes 0 nodes"));
+
+        let mut changed_nodes = previous_nodes;
+        changed_nodes[1].total_locked_staker += 1;
+        let changed = AirdropMerkleTree::new(changed_nodes).unwrap();
+        let error = check_superset(&previous_tree, &changed).unwrap_err();
+        assert!(error.to_string().contains("drops 0 and changes 1 nodes"));
+    }
+
+    #[test]
+    fn test_check_tree_totals() {
+        let node = |unlocked: u64, locked: u64| TreeNode {
+            claimant: Pubkey::new_unique(),
+            proof: None,
+            total_unlocked_staker: unlocked,
+            total_locked_staker: locked,
+            total_unlocked_searcher: 0,
+            total_locked_searcher: 0,
+            total_unlocked_validator: 0,
+            total_locked_validator: 0,
+            unlock_schedule: None,
+        };
+        let merkle_tree = AirdropMerkleTree::new(vec![node(1, 2), node(3, 0)]).unwrap();
+        check_tree_totals(&merkle_tree).unwrap();
+
+        let mut edited = merkle_tree.clone();
+        edited.max_total_claim -= 1;
+        let error = check_tree_totals(&edited).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("node amounts add up to 6 but the tree file's max_total_claim is 5"));
+
+        let mut edited = merkle_tree.clone();
+        edited.max_num_nodes = 3;
+        let error = check_tree_totals(&edited).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("the tree file has 2 nodes but its max_num_nodes is 3"));
+
+        let mut edited = merkle_tree;
+        edited.tree_nodes_mut()[0].total_unlocked_staker = u64::MAX;
+        let error = check_tree_totals(&edited).unwrap_err();
+        assert!(error.to_string().contains("overflow"));
+    }
+
+    #[test]
+    fn test_tree_file_without_proofs() {
+        let node = TreeNode {
+            claimant: Pubkey::new_unique(),
+            proof: None,
+            total_unlocked_staker: 1,
+            total_locked_staker: 1,
+            total_unlocked_searcher: 0,
+            total_locked_searcher: 0,
+            total_unlocked_validator: 0,
+            total_locked_validator: 0,
+            unlock_schedule: None,
+        };
+        let mut merkle_tree = AirdropMerkleTree::new(vec![node]).unwrap();
+        for node in merkle_tree.tree_nodes_mut().iter_mut() {
+            node.proof = None;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("merkle_tree.json");
+        merkle_tree.write_to_file(&path);
+
+        let merkle_tree = AirdropMerkleTree::new_from_file(&path).unwrap();
+        let error = node_proof(&merkle_tree.tree_nodes()[0]).unwrap_err();
+        assert!(matches!(error, DistributorError::TreeMismatch(_)));
+        assert!(error
+            .to_string()
+            .contains("rebuild it with create-merkle-tree"));
+    }
+
+    #[test]
+    fn test_merge_csv_and_tree_files() {
+        let node = |claimant: Pubkey, unlocked: u64| TreeNode {
+            claimant,
+            proof: None,
+            total_unlocked_staker: 0,
+            total_locked_staker: 0,
+            total_unlocked_searcher: 0,
+            total_locked_searcher: 0,
+            total_unlocked_validator: unlocked,
+            total_locked_validator: 0,
+            unlock_schedule: None,
+        };
+        let (shared, from_csv, from_tree) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let dir = tempfile::tempdir().unwrap();
+        let csv_path = dir.path().join("airdrop.csv");
+        std::fs::write(
+            &csv_path,
+            format!(
+                "pubkey,amount_unlocked,amount_locked,category\n\
+                 {shared},1,2,Staker\n\
+                 {from_csv},3,0,Searcher\n"
+            ),
+        )
+        .unwrap();
+        let tree_path = dir.path().join("bonus.bin");
+        AirdropMerkleTree::new(vec![node(from_tree, 5), node(shared, 7)])
+            .unwrap()
+            .write_to_file_bincode(&tree_path)
+            .unwrap();
+        let out_path = dir.path().join("merged.json");
+
+        process_merge(&MergeArgs {
+            csv_path: vec![csv_path.clone()],
+            merkle_tree_path: vec![tree_path.clone()],
+            out_path: out_path.clone(),
+            decimals: Some(0),
+            format: TreeFileFormat::Json,
+        })
+        .unwrap();
+
+        let merged = AirdropMerkleTree::new_from_file(&out_path).unwrap();
+        assert_eq!(merged.max_num_nodes, 3);
+        assert_eq!(merged.max_total_claim, 18);
+        let shared_node = merged.get_node(&shared).unwrap();
+        assert_eq!(
+            (
+                shared_node.total_unlocked_staker,
+                shared_node.total_locked_staker,
+                shared_node.total_unlocked_validator
+            ),
+            (1, 2, 7)
+        );
+        assert!(merged.verify_nodes().is_ok());
+
+        let sources = [
+            AirdropMerkleTree::nodes_from_csv(&csv_path, Some(0)).unwrap(),
+            AirdropMerkleTree::new_from_file(&tree_path)
+                .unwrap()
+                .into_tree_nodes(),
+        ];
+        assert_eq!(overlapping_claimants(&sources), 1);
+        assert_eq!(overlapping_claimants(&sources[..1]), 0);
+    }
+
+    #[test]
+    fn test_derive_claim_status_addresses() {
+        let node = |claimant: Pubkey| TreeNode {
+            claimant,
+            proof: None,
+            total_unlocked_staker: 1,
+            total_locked_staker: 0,
+            total_unlocked_searcher: 0,
+            total_locked_searcher: 0,
+            total_unlocked_validator: 0,
+            total_locked_validator: 0,
+            unlock_schedule: None,
+        };
+        let (program_id, distributor) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let address_tree = Pubkey::new_from_array(light_sdk::constants::ADDRESS_TREE_V2);
+
+        let addresses = claim_status_addresses(
+            &program_id,
+            &distributor,
+            &address_tree,
+            &[node(first), node(second)],
+        );
+        let expected_address = |claimant| {
+            let (address, _) =
+                get_claim_status_address(&program_id, claimant, &distributor, &address_tree);
+            Pubkey::new_from_array(address).to_string()
+        };
+        assert_eq!(
+            addresses,
+            vec![
+                ClaimStatusAddressEntry {
+                    claimant: first.to_string(),
+                    address: expected_address(&first),
+                },
+                ClaimStatusAddressEntry {
+                    claimant: second.to_string(),
+                    address: expected_address(&second),
+                },
+            ]
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("addresses.csv");
+        let file = ClaimStatusAddressFile {
+            program_id: program_id.to_string(),
+            distributor: distributor.to_string(),
+            address_tree: address_tree.to_string(),
+            addresses,
+        };
+        write_claim_status_addresses(&path, AddressFileFormat::Csv, &file).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            format!(
+                "claimant,address\n{first},{}\n{second},{}\n",
+                expected_address(&first),
+                expected_address(&second)
+            )
+        );
+    }
+
+    #[test]
+    fn test_distributor_info_json() {
+        let distributor = MerkleDistributor {
+            root: [0xab; 32],
+            mint: token::spl_token::native_mint::ID,
+            max_total_claim: 1_000,
+            distribution_mode: DistributionMode::Native,
+            vesting_interval_secs: 86_400,
+            ..Default::default()
+        };
+        let output = DistributorInfoOutput {
+            distributor: Pubkey::default().to_string(),
+            slot: 7,
+            vault_balance: 500,
+            state: MerkleDistributorView::from(&distributor),
+        };
+        let json = serde_json::to_value(&output).unwrap();
+        assert_eq!(json["root"], "ab".repeat(32));
+        assert_eq!(json["mint"], "So11111111111111111111111111111111111111112");
+        assert_eq!(json["max_total_claim"], 1_000);
+        assert_eq!(json["distribution_mode"], "native");
+        assert_eq!(json["vault_balance"], 500);
+
+        // Consumers can read the distributor fields back out of the output
+        let state: MerkleDistributorView = serde_json::from_value(json).unwrap();
+        assert_eq!(state, output.state);
+    }
+
+    #[test]
+    fn test_decode_claim_status_encodings() {
+        use anchor_lang::AnchorSerialize;
+
+        let claim_status = ClaimStatus {
+            claimant: Pubkey::new_unique(),
+            locked_amount: 500,
+            locked_amount_withdrawn: 100,
+            unlocked_amount: 1_000,
+            unlock_schedule: Some(UnlockSchedule {
+                start_ts: 10,
+                end_ts: 20,
+            }),
+            last_claimed_ts: 0,
+            category_breakdown: None,
+        };
+        let mut raw = vec![];
+        claim_status.serialize(&mut raw).unwrap();
+        let encoded = BASE64.encode(&raw);
+
+        let from_raw = decode_claim_status(&raw).unwrap();
+        let from_base64 = decode_claim_status(encoded.as_bytes()).unwrap();
+        for decoded in [from_raw, from_base64] {
+            assert_eq!(decoded.claimant, claim_status.claimant);
+            assert_eq!(decoded.locked_amount, claim_status.locked_amount);
+            assert_eq!(
+                decoded.locked_amount_withdrawn,
+                claim_status.locked_amount_withdrawn
+            );
+            assert_eq!(decoded.unlocked_amount, claim_status.unlocked_amount);
+            assert_eq!(decoded.unlock_schedule, claim_status.unlock_schedule);
+        }
+
+        assert!(decode_claim_status(b"not a claim status").is_err());
+        assert!(decode_legacy_claim_status(&raw).is_none());
+
+        // Claim statuses from before unlock_schedule need migrate-claim-status
+        let legacy = LegacyClaimStatus {
+            claimant: claim_status.claimant,
+            locked_amount: 500,
+            locked_amount_withdrawn: 100,
+            unlocked_amount: 1_000,
+        };
+        let mut legacy_raw = vec![];
+        legacy.serialize(&mut legacy_raw).unwrap();
+        let legacy_encoded = BASE64.encode(&legacy_raw);
+        for data in [legacy_raw.as_slice(), legacy_encoded.as_bytes()] {
+            let error = decode_claim_status(data).unwrap_err().to_string();
+            assert!(error.contains("migrate-claim-status"), "{error}");
+            let decoded = decode_legacy_claim_status(data).unwrap();
+            assert_eq!(decoded.claimant, legacy.claimant);
+            assert_eq!(decoded.locked_amount_withdrawn, 100);
+        }
+    }
+
+    #[test]
+    fn test_recipient_only_for_token_distributors() {
+        let recipient = Some(Pubkey::new_unique());
+        assert!(check_recipient(DistributionMode::Token, recipient).is_ok());
+        assert!(check_recipient(DistributionMode::Token, None).is_ok());
+        assert!(check_recipient(DistributionMode::Native, None).is_ok());
+        assert!(check_recipient(DistributionMode::Native, recipient).is_err());
 
-    println!("Successfully set admin! signature: {signature:#?}");
+        let args = Args::try_parse_from([
+            "cli",
+            "--mint",
+            &Pubkey::new_unique().to_string(),
+            "claim",
+            "--merkle-tree-path",
+            "tree.json",
+            "--recipient",
+            &recipient.unwrap().to_string(),
+        ])
+        .unwrap();
+        let Commands::Claim(claim_args) = &args.command else {
+            panic!("expected claim");
+        };
+        assert_eq!(claim_args.recipient, recipient);
+    }
 }
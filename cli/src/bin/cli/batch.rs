@@ -0,0 +1,195 @@
+//! Packing the claims of many wallets into as few transactions as fit.
+
+use std::{
+    fs, io,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use solana_program::instruction::Instruction;
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction, pubkey::Pubkey, transaction::Transaction,
+};
+
+/// Largest serialized transaction the cluster accepts, `solana_packet::PACKET_DATA_SIZE`.
+const MAX_TRANSACTION_SIZE: usize = 1232;
+/// Most compute units a transaction can request.
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+/// Most accounts a transaction can lock.
+const MAX_TRANSACTION_ACCOUNTS: usize = 64;
+
+/// Instructions claiming for one wallet and the compute units they need.
+#[derive(Debug, Clone)]
+pub struct WalletClaim {
+    pub instructions: Vec<Instruction>,
+    pub compute_units: u32,
+}
+
+/// Keypair files listed by `path`: the `.json` files of a directory, or one path per line of a
+/// file. Relative paths in a file resolve against the file's directory.
+pub fn read_keypair_paths(path: &Path) -> io::Result<Vec<PathBuf>> {
+    if path.is_dir() {
+        let mut paths = fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<_>>>()?;
+        paths.retain(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        });
+        paths.sort();
+        return Ok(paths);
+    }
+    let base = path.parent().unwrap_or(Path::new(""));
+    Ok(parse_keypair_list(&fs::read_to_string(path)?, base))
+}
+
+/// Paths in a keypair list, skipping blank lines and `#` comments.
+fn parse_keypair_list(list: &str, base: &Path) -> Vec<PathBuf> {
+    list.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| base.join(line))
+        .collect()
+}
+
+/// The instructions of one batch: a compute unit limit covering every claim, then the claims.
+pub fn batch_instructions(claims: &[WalletClaim]) -> Vec<Instruction> {
+    let mut ixs = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+        total_compute_units(claims),
+    )];
+    ixs.extend(
+        claims
+            .iter()
+            .flat_map(|claim| claim.instructions.iter().cloned()),
+    );
+    ixs
+}
+
+fn total_compute_units(claims: &[WalletClaim]) -> u32 {
+    claims
+        .iter()
+        .map(|claim| claim.compute_units)
+        .fold(0, u32::saturating_add)
+}
+
+/// Whether `claims` fit one transaction paid by `payer`, with the `reserved` instructions the
+/// sender adds on top.
+fn fits(payer: &Pubkey, reserved: &[Instruction], claims: &[WalletClaim]) -> bool {
+    if total_compute_units(claims) > MAX_COMPUTE_UNIT_LIMIT {
+        return false;
+    }
+    let ixs = [reserved.to_vec(), batch_instructions(claims)].concat();
+    let tx = Transaction::new_with_payer(&ixs, Some(payer));
+    tx.message.account_keys.len() <= MAX_TRANSACTION_ACCOUNTS
+        && bincode::serialized_size(&tx).is_ok_and(|size| size as usize <= MAX_TRANSACTION_SIZE)
+}
+
+/// Splits `claims` into consecutive ranges that each fit one transaction, within the size,
+/// account and compute unit limits. A claim too large on its own still gets its own range and
+/// fails when sent.
+pub fn pack_claims(
+    payer: &Pubkey,
+    reserved: &[Instruction],
+    claims: &[WalletClaim],
+) -> Vec<Range<usize>> {
+    let mut batches = vec![];
+    let mut start = 0;
+    for end in 1..=claims.len() {
+        if end - start > 1 && !fits(payer, reserved, &claims[start..end]) {
+            batches.push(start..end - 1);
+            start = end - 1;
+        }
+    }
+    if start < claims.len() {
+        batches.push(start..claims.len());
+    }
+    batches
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_program::instruction::AccountMeta;
+
+    use super::*;
+
+    /// A claim signed by a fresh wallet, with `data_len` bytes of instruction data.
+    fn claim(data_len: usize, compute_units: u32) -> WalletClaim {
+        WalletClaim {
+            instructions: vec![Instruction {
+                program_id: Pubkey::new_unique(),
+                accounts: vec![AccountMeta::new_readonly(Pubkey::new_unique(), true)],
+                data: vec![0; data_len],
+            }],
+            compute_units,
+        }
+    }
+
+    /// `count` claims of different wallets.
+    fn claims(count: usize, data_len: usize, compute_units: u32) -> Vec<WalletClaim> {
+        (0..count).map(|_| claim(data_len, compute_units)).collect()
+    }
+
+    #[test]
+    fn test_parse_keypair_list() {
+        let list = "a.json\n\n# treasury wallets\n  /keys/b.json  \nsub/c.json\n";
+        assert_eq!(
+            parse_keypair_list(list, Path::new("/wallets")),
+            vec![
+                PathBuf::from("/wallets/a.json"),
+                PathBuf::from("/keys/b.json"),
+                PathBuf::from("/wallets/sub/c.json"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pack_small_claims_into_one_transaction() {
+        let payer = Pubkey::new_unique();
+        let claims = claims(4, 10, 1_000);
+        assert_eq!(pack_claims(&payer, &[], &claims), vec![0..4]);
+    }
+
+    #[test]
+    fn test_pack_claims_within_transaction_size() {
+        let payer = Pubkey::new_unique();
+        let claims = claims(7, 300, 1_000);
+        let batches = pack_claims(&payer, &[], &claims);
+        assert!(batches.len() > 1);
+        assert_eq!(batches.first().unwrap().start, 0);
+        assert_eq!(batches.last().unwrap().end, claims.len());
+        for batch in &batches {
+            assert!(fits(&payer, &[], &claims[batch.clone()]));
+        }
+        // Each batch is as full as it can be
+        for pair in batches.windows(2) {
+            assert!(!fits(
+                &payer,
+                &[],
+                &claims[pair[0].start..pair[1].start + 1]
+            ));
+        }
+    }
+
+    #[test]
+    fn test_pack_claims_within_compute_limit() {
+        let payer = Pubkey::new_unique();
+        let claims = claims(5, 10, 500_000);
+        assert_eq!(pack_claims(&payer, &[], &claims), vec![0..2, 2..4, 4..5]);
+    }
+
+    #[test]
+    fn test_reserved_instructions_count_towards_size() {
+        let payer = Pubkey::new_unique();
+        let claims = claims(2, 350, 1_000);
+        assert_eq!(pack_claims(&payer, &[], &claims), vec![0..2]);
+        let reserved = [claim(300, 0).instructions[0].clone()];
+        assert_eq!(pack_claims(&payer, &reserved, &claims), vec![0..1, 1..2]);
+    }
+
+    #[test]
+    fn test_oversized_claim_gets_its_own_batch() {
+        let payer = Pubkey::new_unique();
+        let claims = vec![claim(10, 1_000), claim(2_000, 1_000), claim(10, 1_000)];
+        assert_eq!(pack_claims(&payer, &[], &claims), vec![0..1, 1..2, 2..3]);
+    }
+}
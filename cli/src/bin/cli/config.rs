@@ -0,0 +1,198 @@
+//! Defaults for the global flags read from a `--config` file, so long-lived values like the
+//! mint and program id are kept in one place.
+
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use clap::Command;
+
+/// The `--config` path among the command line arguments, or the `CONFIG` environment variable.
+/// Read before the arguments are parsed, since the file supplies some of them.
+pub fn config_path(args: &[OsString]) -> Option<PathBuf> {
+    let mut args = args.iter().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if arg == "--config" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.to_str().and_then(|arg| arg.strip_prefix("--config=")) {
+            return Some(PathBuf::from(path));
+        }
+    }
+    std::env::var_os("CONFIG").map(PathBuf::from)
+}
+
+/// Flag values of a config file, TOML or JSON when the file ends in `.json`, by key.
+pub fn read_config(path: &Path) -> Result<BTreeMap<String, String>, String> {
+    let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    if path
+        .extension()
+        .is_some_and(|extension| extension == "json")
+    {
+        parse_json_config(&contents)
+    } else {
+        parse_toml_config(&contents)
+    }
+}
+
+fn parse_toml_config(contents: &str) -> Result<BTreeMap<String, String>, String> {
+    let table = contents.parse::<toml::Table>().map_err(|e| e.to_string())?;
+    table
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                toml::Value::String(value) => value,
+                toml::Value::Integer(value) => value.to_string(),
+                toml::Value::Float(value) => value.to_string(),
+                toml::Value::Boolean(value) => value.to_string(),
+                _ => return Err(format!("`{key}` must be a string, number or boolean")),
+            };
+            Ok((key, value))
+        })
+        .collect()
+}
+
+fn parse_json_config(contents: &str) -> Result<BTreeMap<String, String>, String> {
+    let object = serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(contents)
+        .map_err(|e| e.to_string())?;
+    object
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                serde_json::Value::String(value) => value,
+                serde_json::Value::Number(value) => value.to_string(),
+                serde_json::Value::Bool(value) => value.to_string(),
+                _ => return Err(format!("`{key}` must be a string, number or boolean")),
+            };
+            Ok((key, value))
+        })
+        .collect()
+}
+
+/// The environment variable and value setting each flag of `config` on `command`. Keys are
+/// long flag names, with `_` or `-` between words. The values take effect through the
+/// environment variables, so they are only defaults: flags given on the command line and
+/// environment variables already set win. Keys naming no flag, and flags without an
+/// environment variable, are errors.
+pub fn config_env(
+    command: &Command,
+    config: BTreeMap<String, String>,
+) -> Result<Vec<(OsString, String)>, String> {
+    let mut unknown_keys = vec![];
+    let mut env = vec![];
+    for (key, value) in config {
+        let long = key.replace('_', "-");
+        let Some(arg) = command
+            .get_arguments()
+            .find(|arg| arg.get_long() == Some(long.as_str()) && long != "config")
+        else {
+            unknown_keys.push(key);
+            continue;
+        };
+        let Some(name) = arg.get_env() else {
+            return Err(format!(
+                "`{key}` cannot be set in a config file, pass --{long} instead"
+            ));
+        };
+        env.push((name.to_os_string(), value));
+    }
+    if !unknown_keys.is_empty() {
+        return Err(format!("unknown keys: {}", unknown_keys.join(", ")));
+    }
+    Ok(env)
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Arg;
+
+    use super::*;
+
+    fn command() -> Command<'static> {
+        Command::new("cli")
+            .arg(Arg::new("mint").long("mint").env("MINT").takes_value(true))
+            .arg(
+                Arg::new("rpc-url")
+                    .long("rpc-url")
+                    .env("RPC_URL")
+                    .takes_value(true),
+            )
+            .arg(Arg::new("yes").long("yes").env("YES"))
+            .arg(Arg::new("offline").long("offline"))
+            .arg(
+                Arg::new("config")
+                    .long("config")
+                    .env("CONFIG")
+                    .takes_value(true),
+            )
+    }
+
+    fn os_args(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn test_config_path() {
+        assert_eq!(
+            config_path(&os_args(&["cli", "--config", "a.toml", "claim"])),
+            Some(PathBuf::from("a.toml"))
+        );
+        assert_eq!(
+            config_path(&os_args(&[
+                "cli",
+                "--mint",
+                "m",
+                "--config=b.json",
+                "claim"
+            ])),
+            Some(PathBuf::from("b.json"))
+        );
+    }
+
+    #[test]
+    fn test_toml_and_json_configs_agree() {
+        let toml = "mint = \"So11111111111111111111111111111111111111112\"\n\
+                    max_retries = 5\nyes = true\n";
+        let json = r#"{"mint": "So11111111111111111111111111111111111111112",
+                       "max_retries": 5, "yes": true}"#;
+        let config = parse_toml_config(toml).unwrap();
+        assert_eq!(config, parse_json_config(json).unwrap());
+        assert_eq!(config["max_retries"], "5");
+        assert_eq!(config["yes"], "true");
+        assert!(parse_toml_config("mint = [1, 2]").is_err());
+    }
+
+    #[test]
+    fn test_config_env() {
+        let config =
+            parse_toml_config("mint = \"m\"\nrpc_url = \"http://rpc\"\nyes = true").unwrap();
+        assert_eq!(
+            config_env(&command(), config).unwrap(),
+            vec![
+                (OsString::from("MINT"), "m".to_string()),
+                (OsString::from("RPC_URL"), "http://rpc".to_string()),
+                (OsString::from("YES"), "true".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_config_env_rejects_unknown_keys() {
+        let config = parse_toml_config("mint = \"m\"\nrcp_url = \"x\"\nconfig = \"c\"").unwrap();
+        assert_eq!(
+            config_env(&command(), config).unwrap_err(),
+            "unknown keys: config, rcp_url"
+        );
+        let config = parse_toml_config("offline = true").unwrap();
+        assert_eq!(
+            config_env(&command(), config).unwrap_err(),
+            "`offline` cannot be set in a config file, pass --offline instead"
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! RPC transport over every `--rpc-url` endpoint: requests go to the endpoint that last
+//! answered, moving on to the next one when it cannot be reached or times out.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use async_trait::async_trait;
+use solana_rpc_client::{
+    http_sender::HttpSender,
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+use solana_rpc_client_api::{
+    client_error::{ErrorKind as ClientErrorKind, Result as ClientResult},
+    request::RpcRequest,
+};
+use tracing::{debug, warn};
+
+/// [RpcSender] failing over between HTTP endpoints.
+pub struct FailoverSender {
+    senders: Vec<Box<dyn RpcSender + Send + Sync>>,
+    /// Index of the endpoint tried first
+    current: AtomicUsize,
+}
+
+impl FailoverSender {
+    /// Sender over HTTP endpoints `urls`, tried in order. Panics without any.
+    pub fn new(urls: &[String]) -> Self {
+        Self::with_senders(
+            urls.iter()
+                .map(|url| Box::new(HttpSender::new(url)) as Box<dyn RpcSender + Send + Sync>)
+                .collect(),
+        )
+    }
+
+    fn with_senders(senders: Vec<Box<dyn RpcSender + Send + Sync>>) -> Self {
+        assert!(!senders.is_empty(), "at least one RPC endpoint is needed");
+        Self {
+            senders,
+            current: AtomicUsize::new(0),
+        }
+    }
+}
+
+/// Whether `error` means the endpoint could not serve the request at all, rather than the
+/// request failing, so another endpoint is worth trying.
+pub fn is_endpoint_failure(error: &ClientErrorKind) -> bool {
+    match error {
+        ClientErrorKind::Io(_) => true,
+        ClientErrorKind::Reqwest(e) => e.is_connect() || e.is_timeout(),
+        _ => false,
+    }
+}
+
+#[async_trait]
+impl RpcSender for FailoverSender {
+    async fn send(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> ClientResult<serde_json::Value> {
+        let first = self.current.load(Ordering::Relaxed);
+        let mut last_error = None;
+        for offset in 0..self.senders.len() {
+            let index = (first + offset) % self.senders.len();
+            let sender = &self.senders[index];
+            match sender.send(request, params.clone()).await {
+                Err(e) if is_endpoint_failure(&e.kind) => {
+                    warn!(
+                        "RPC endpoint {} failed, trying the next one: {e}",
+                        sender.url()
+                    );
+                    last_error = Some(e);
+                }
+                result => {
+                    debug!("{request} served by {}", sender.url());
+                    self.current.store(index, Ordering::Relaxed);
+                    return result;
+                }
+            }
+        }
+        Err(last_error.expect("at least one endpoint was tried"))
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.senders
+            .iter()
+            .map(|sender| sender.get_transport_stats())
+            .fold(RpcTransportStats::default(), |total, stats| {
+                RpcTransportStats {
+                    request_count: total.request_count + stats.request_count,
+                    elapsed_time: total.elapsed_time + stats.elapsed_time,
+                    rate_limited_time: total.rate_limited_time + stats.rate_limited_time,
+                }
+            })
+    }
+
+    fn url(&self) -> String {
+        self.senders[self.current.load(Ordering::Relaxed)].url()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+
+    use solana_rpc_client_api::client_error::Error as ClientError;
+
+    use super::*;
+
+    /// Answers with its url until it goes down.
+    struct TestSender {
+        url: &'static str,
+        down: AtomicBool,
+        requests: AtomicUsize,
+    }
+
+    impl TestSender {
+        fn new(url: &'static str, down: bool) -> Self {
+            Self {
+                url,
+                down: AtomicBool::new(down),
+                requests: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl RpcSender for &'static TestSender {
+        async fn send(
+            &self,
+            _request: RpcRequest,
+            _params: serde_json::Value,
+        ) -> ClientResult<serde_json::Value> {
+            self.requests.fetch_add(1, Ordering::Relaxed);
+            if self.down.load(Ordering::Relaxed) {
+                return Err(ClientError::from(ClientErrorKind::Io(std::io::Error::new(
+                    std::io::ErrorKind::ConnectionRefused,
+                    "connection refused",
+                ))));
+            }
+            Ok(serde_json::Value::String(self.url.to_string()))
+        }
+
+        fn get_transport_stats(&self) -> RpcTransportStats {
+            RpcTransportStats {
+                request_count: self.requests.load(Ordering::Relaxed),
+                ..RpcTransportStats::default()
+            }
+        }
+
+        fn url(&self) -> String {
+            self.url.to_string()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failover_sender() {
+        let first: &'static TestSender = Box::leak(Box::new(TestSender::new("first", true)));
+        let second: &'static TestSender = Box::leak(Box::new(TestSender::new("second", false)));
+        let sender = FailoverSender::with_senders(vec![Box::new(first), Box::new(second)]);
+
+        let served = sender
+            .send(RpcRequest::GetSlot, serde_json::Value::Null)
+            .await
+            .unwrap();
+        assert_eq!(served, "second");
+        assert_eq!(sender.url(), "second");
+
+        // Sticks with the endpoint that answered
+        sender
+            .send(RpcRequest::GetSlot, serde_json::Value::Null)
+            .await
+            .unwrap();
+        assert_eq!(first.requests.load(Ordering::Relaxed), 1);
+        assert_eq!(sender.get_transport_stats().request_count, 3);
+
+        // Comes back round to the first endpoint once the second goes down
+        first.down.store(false, Ordering::Relaxed);
+        second.down.store(true, Ordering::Relaxed);
+        let served = sender
+            .send(RpcRequest::GetSlot, serde_json::Value::Null)
+            .await
+            .unwrap();
+        assert_eq!(served, "first");
+
+        first.down.store(true, Ordering::Relaxed);
+        let error = sender
+            .send(RpcRequest::GetSlot, serde_json::Value::Null)
+            .await
+            .unwrap_err();
+        assert!(is_endpoint_failure(&error.kind));
+    }
+
+    #[test]
+    fn test_is_endpoint_failure() {
+        assert!(is_endpoint_failure(&ClientErrorKind::Io(
+            std::io::Error::new(std::io::ErrorKind::TimedOut, "timed out")
+        )));
+        assert!(!is_endpoint_failure(&ClientErrorKind::Custom(
+            "bad request".to_string()
+        )));
+    }
+}
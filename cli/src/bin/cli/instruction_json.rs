@@ -0,0 +1,118 @@
+use std::str::FromStr;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde_derive::{Deserialize, Serialize};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+/// JSON form of an [Instruction] for callers that build and sign transactions themselves.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstructionJson {
+    pub program_id: String,
+    pub accounts: Vec<AccountMetaJson>,
+    /// Instruction data, base64 encoded
+    pub data: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountMetaJson {
+    pub pubkey: String,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl From<&Instruction> for InstructionJson {
+    fn from(ix: &Instruction) -> Self {
+        Self {
+            program_id: ix.program_id.to_string(),
+            accounts: ix
+                .accounts
+                .iter()
+                .map(|meta| AccountMetaJson {
+                    pubkey: meta.pubkey.to_string(),
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: BASE64.encode(&ix.data),
+        }
+    }
+}
+
+impl TryFrom<InstructionJson> for Instruction {
+    type Error = String;
+
+    fn try_from(ix: InstructionJson) -> Result<Self, Self::Error> {
+        let parse_pubkey =
+            |s: &str| Pubkey::from_str(s).map_err(|e| format!("invalid pubkey {s}: {e}"));
+        Ok(Instruction {
+            program_id: parse_pubkey(&ix.program_id)?,
+            accounts: ix
+                .accounts
+                .iter()
+                .map(|meta| {
+                    Ok(AccountMeta {
+                        pubkey: parse_pubkey(&meta.pubkey)?,
+                        is_signer: meta.is_signer,
+                        is_writable: meta.is_writable,
+                    })
+                })
+                .collect::<Result<_, String>>()?,
+            data: BASE64
+                .decode(&ix.data)
+                .map_err(|e| format!("invalid instruction data: {e}"))?,
+        })
+    }
+}
+
+/// Serializes instructions to a pretty JSON array.
+pub fn instructions_to_json(ixs: &[Instruction]) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(&ixs.iter().map(InstructionJson::from).collect::<Vec<_>>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instruction_json_round_trip() {
+        let ixs = vec![
+            Instruction {
+                program_id: Pubkey::new_unique(),
+                accounts: vec![
+                    AccountMeta::new(Pubkey::new_unique(), true),
+                    AccountMeta::new(Pubkey::new_unique(), false),
+                    AccountMeta::new_readonly(Pubkey::new_unique(), true),
+                    AccountMeta::new_readonly(Pubkey::new_unique(), false),
+                ],
+                data: vec![0, 1, 2, 3, 255],
+            },
+            Instruction {
+                program_id: Pubkey::new_unique(),
+                accounts: vec![],
+                data: vec![],
+            },
+        ];
+
+        let json = instructions_to_json(&ixs).unwrap();
+        let parsed: Vec<InstructionJson> = serde_json::from_str(&json).unwrap();
+        let round_tripped = parsed
+            .into_iter()
+            .map(Instruction::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(round_tripped, ixs);
+    }
+
+    #[test]
+    fn test_invalid_instruction_json() {
+        let ix = InstructionJson {
+            program_id: "not a pubkey".to_string(),
+            accounts: vec![],
+            data: String::new(),
+        };
+        assert!(Instruction::try_from(ix).is_err());
+    }
+}
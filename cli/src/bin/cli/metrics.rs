@@ -0,0 +1,155 @@
+//! Minimal Prometheus endpoint for long running commands, built with the `metrics` feature.
+
+use std::{
+    fmt::Write as _,
+    io,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+use crate::monitor::DistributorSnapshot;
+
+/// Latest values reported by the monitor. Gauges holding fractions are stored as f64 bits.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    claims_processed: AtomicU64,
+    poll_failures: AtomicU64,
+    poll_duration_seconds: AtomicU64,
+    vault_balance: AtomicU64,
+    completion_ratio: AtomicU64,
+}
+
+impl Metrics {
+    pub fn record_snapshot(&self, snapshot: &DistributorSnapshot, poll_duration: Duration) {
+        self.claims_processed
+            .store(snapshot.num_nodes_claimed, Ordering::Relaxed);
+        self.vault_balance
+            .store(snapshot.vault_balance, Ordering::Relaxed);
+        self.poll_duration_seconds
+            .store(poll_duration.as_secs_f64().to_bits(), Ordering::Relaxed);
+        let completion = match snapshot.max_total_claim {
+            0 => 0.0,
+            max_total_claim => snapshot.total_amount_claimed as f64 / max_total_claim as f64,
+        };
+        self.completion_ratio
+            .store(completion.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn record_failure(&self) {
+        self.poll_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Renders all metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let load = |value: &AtomicU64| value.load(Ordering::Relaxed);
+        let load_f64 = |value: &AtomicU64| f64::from_bits(value.load(Ordering::Relaxed));
+
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, value: String| {
+            let _ = writeln!(out, "# HELP {name} {help}");
+            let _ = writeln!(out, "# TYPE {name} {kind}");
+            let _ = writeln!(out, "{name} {value}");
+        };
+        metric(
+            "distributor_claims_processed",
+            "gauge",
+            "Claimants that have claimed from the distributor",
+            load(&self.claims_processed).to_string(),
+        );
+        metric(
+            "distributor_poll_failures_total",
+            "counter",
+            "Polls that failed to read the distributor or vault",
+            load(&self.poll_failures).to_string(),
+        );
+        metric(
+            "distributor_poll_duration_seconds",
+            "gauge",
+            "Time taken by the last successful poll",
+            load_f64(&self.poll_duration_seconds).to_string(),
+        );
+        metric(
+            "distributor_vault_balance",
+            "gauge",
+            "Token vault balance in base units",
+            load(&self.vault_balance).to_string(),
+        );
+        metric(
+            "distributor_completion_ratio",
+            "gauge",
+            "Fraction of max_total_claim that has been claimed",
+            load_f64(&self.completion_ratio).to_string(),
+        );
+        out
+    }
+}
+
+/// Answers every HTTP request on `listener` with the current metrics, whatever the path.
+pub async fn serve(listener: TcpListener, metrics: Arc<Metrics>) -> io::Result<()> {
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let metrics = metrics.clone();
+        tokio::spawn(async move {
+            // The request itself is not needed, read it so the client sees a clean close
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request).await;
+            let body = metrics.render();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_scrape_metrics_endpoint() {
+        let metrics = Arc::new(Metrics::default());
+        metrics.record_snapshot(
+            &DistributorSnapshot {
+                vault_balance: 750,
+                max_total_claim: 1_000,
+                total_amount_claimed: 250,
+                num_nodes_claimed: 3,
+                clawed_back: false,
+                paused: false,
+            },
+            Duration::from_millis(20),
+        );
+        metrics.record_failure();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, metrics));
+
+        let body = reqwest::get(format!("http://{addr}/metrics"))
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        for name in [
+            "distributor_claims_processed 3",
+            "distributor_poll_failures_total 1",
+            "distributor_poll_duration_seconds ",
+            "distributor_vault_balance 750",
+            "distributor_completion_ratio 0.25",
+        ] {
+            assert!(body.contains(name), "missing {name} in {body}");
+        }
+    }
+}
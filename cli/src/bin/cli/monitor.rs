@@ -0,0 +1,265 @@
+use std::{collections::VecDeque, fmt};
+
+use serde_derive::Serialize;
+
+/// Number of previous polls used as the claim velocity baseline.
+const VELOCITY_HISTORY_LEN: usize = 10;
+/// Polls needed before a baseline is trusted enough to flag spikes.
+const VELOCITY_MIN_HISTORY: usize = 3;
+
+/// Point-in-time view of the on-chain state the monitor cares about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DistributorSnapshot {
+    pub vault_balance: u64,
+    pub max_total_claim: u64,
+    pub total_amount_claimed: u64,
+    pub num_nodes_claimed: u64,
+    pub clawed_back: bool,
+    pub paused: bool,
+}
+
+impl DistributorSnapshot {
+    /// Tokens still owed to claimants that have not been transferred out of the vault
+    pub fn outstanding(&self) -> u64 {
+        self.max_total_claim
+            .saturating_sub(self.total_amount_claimed)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MonitorThresholds {
+    /// Claims in a single poll must exceed the baseline by this factor to count as a spike
+    pub velocity_spike_factor: f64,
+    /// Ignore spikes below this many claims per poll
+    pub velocity_min_claims: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Alert {
+    VaultUnderfunded {
+        vault_balance: u64,
+        outstanding: u64,
+    },
+    ClaimVelocitySpike {
+        claims: u64,
+        baseline: f64,
+    },
+    ClawedBack,
+    Paused,
+    Unpaused,
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Alert::VaultUnderfunded {
+                vault_balance,
+                outstanding,
+            } => write!(
+                f,
+                "vault balance {vault_balance} is below outstanding obligations {outstanding}"
+            ),
+            Alert::ClaimVelocitySpike { claims, baseline } => write!(
+                f,
+                "{claims} claims since last poll, baseline is {baseline:.2} per poll"
+            ),
+            Alert::ClawedBack => write!(f, "distributor has been clawed back"),
+            Alert::Paused => write!(f, "distributor has been paused, claims are rejected"),
+            Alert::Unpaused => write!(f, "distributor has been unpaused"),
+        }
+    }
+}
+
+/// Tracks consecutive snapshots and decides which alerts to raise.
+/// Alerts are edge-triggered: a condition is reported when it starts, not on every poll.
+pub struct HealthMonitor {
+    thresholds: MonitorThresholds,
+    previous: Option<DistributorSnapshot>,
+    claims_per_poll: VecDeque<u64>,
+}
+
+impl HealthMonitor {
+    pub fn new(thresholds: MonitorThresholds) -> Self {
+        Self {
+            thresholds,
+            previous: None,
+            claims_per_poll: VecDeque::with_capacity(VELOCITY_HISTORY_LEN),
+        }
+    }
+
+    pub fn observe(&mut self, snapshot: DistributorSnapshot) -> Vec<Alert> {
+        let mut alerts = vec![];
+
+        let underfunded =
+            |s: &DistributorSnapshot| !s.clawed_back && s.vault_balance < s.outstanding();
+        if underfunded(&snapshot) && !self.previous.as_ref().is_some_and(underfunded) {
+            alerts.push(Alert::VaultUnderfunded {
+                vault_balance: snapshot.vault_balance,
+                outstanding: snapshot.outstanding(),
+            });
+        }
+
+        if let Some(previous) = self.previous {
+            if snapshot.clawed_back && !previous.clawed_back {
+                alerts.push(Alert::ClawedBack);
+            }
+            match (previous.paused, snapshot.paused) {
+                (false, true) => alerts.push(Alert::Paused),
+                (true, false) => alerts.push(Alert::Unpaused),
+                _ => {}
+            }
+
+            let claims = snapshot
+                .num_nodes_claimed
+                .saturating_sub(previous.num_nodes_claimed);
+            if let Some(baseline) = self.velocity_baseline() {
+                if claims >= self.thresholds.velocity_min_claims
+                    && claims as f64 > baseline * self.thresholds.velocity_spike_factor
+                {
+                    alerts.push(Alert::ClaimVelocitySpike { claims, baseline });
+                }
+            }
+
+            if self.claims_per_poll.len() == VELOCITY_HISTORY_LEN {
+                self.claims_per_poll.pop_front();
+            }
+            self.claims_per_poll.push_back(claims);
+        }
+
+        self.previous = Some(snapshot);
+        alerts
+    }
+
+    fn velocity_baseline(&self) -> Option<f64> {
+        if self.claims_per_poll.len() < VELOCITY_MIN_HISTORY {
+            return None;
+        }
+        let total: u64 = self.claims_per_poll.iter().sum();
+        Some(total as f64 / self.claims_per_poll.len() as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THRESHOLDS: MonitorThresholds = MonitorThresholds {
+        velocity_spike_factor: 5.0,
+        velocity_min_claims: 10,
+    };
+
+    fn snapshot(
+        vault_balance: u64,
+        total_amount_claimed: u64,
+        num_nodes_claimed: u64,
+    ) -> DistributorSnapshot {
+        DistributorSnapshot {
+            vault_balance,
+            max_total_claim: 1_000,
+            total_amount_claimed,
+            num_nodes_claimed,
+            clawed_back: false,
+            paused: false,
+        }
+    }
+
+    #[test]
+    fn test_vault_underfunded_alert_is_edge_triggered() {
+        let mut monitor = HealthMonitor::new(THRESHOLDS);
+
+        assert!(monitor.observe(snapshot(1_000, 0, 0)).is_empty());
+        assert_eq!(
+            monitor.observe(snapshot(500, 100, 1)),
+            vec![Alert::VaultUnderfunded {
+                vault_balance: 500,
+                outstanding: 900,
+            }]
+        );
+        // still underfunded, already reported
+        assert!(monitor.observe(snapshot(500, 100, 1)).is_empty());
+        // topped up, then drops again
+        assert!(monitor.observe(snapshot(900, 100, 1)).is_empty());
+        assert_eq!(monitor.observe(snapshot(100, 200, 2)).len(), 1);
+    }
+
+    #[test]
+    fn test_clawback_alert() {
+        let mut monitor = HealthMonitor::new(THRESHOLDS);
+        assert!(monitor.observe(snapshot(1_000, 0, 0)).is_empty());
+
+        // a clawed back vault is empty but owes nothing, so only the clawback is reported
+        let clawed_back = DistributorSnapshot {
+            vault_balance: 0,
+            clawed_back: true,
+            ..snapshot(0, 0, 0)
+        };
+        assert_eq!(monitor.observe(clawed_back), vec![Alert::ClawedBack]);
+        assert!(monitor.observe(clawed_back).is_empty());
+    }
+
+    #[test]
+    fn test_pause_alerts() {
+        let mut monitor = HealthMonitor::new(THRESHOLDS);
+        let paused = DistributorSnapshot {
+            paused: true,
+            ..snapshot(1_000, 0, 0)
+        };
+        // a distributor paused before the monitor started is not a transition
+        assert!(monitor.observe(paused).is_empty());
+        assert_eq!(
+            monitor.observe(snapshot(1_000, 0, 0)),
+            vec![Alert::Unpaused]
+        );
+        assert_eq!(monitor.observe(paused), vec![Alert::Paused]);
+        assert!(monitor.observe(paused).is_empty());
+    }
+
+    #[test]
+    fn test_claim_velocity_spike() {
+        let mut monitor = HealthMonitor::new(THRESHOLDS);
+        let mut claimed = 0;
+        assert!(monitor.observe(snapshot(1_000, 0, claimed)).is_empty());
+
+        // no alert until a baseline exists, even for a burst
+        claimed += 50;
+        assert!(monitor.observe(snapshot(1_000, 0, claimed)).is_empty());
+
+        for _ in 0..5 {
+            claimed += 4;
+            assert!(monitor.observe(snapshot(1_000, 0, claimed)).is_empty());
+        }
+
+        claimed += 200;
+        let alerts = monitor.observe(snapshot(1_000, 0, claimed));
+        assert!(matches!(
+            alerts.as_slice(),
+            [Alert::ClaimVelocitySpike { claims: 200, .. }]
+        ));
+    }
+
+    #[test]
+    fn test_claim_velocity_ignores_small_counts() {
+        let mut monitor = HealthMonitor::new(THRESHOLDS);
+        let mut claimed = 0;
+        assert!(monitor.observe(snapshot(1_000, 0, claimed)).is_empty());
+        for _ in 0..5 {
+            assert!(monitor.observe(snapshot(1_000, 0, claimed)).is_empty());
+        }
+
+        // infinitely faster than the baseline, but below velocity_min_claims
+        claimed += 9;
+        assert!(monitor.observe(snapshot(1_000, 0, claimed)).is_empty());
+    }
+
+    #[test]
+    fn test_alert_json() {
+        let json = serde_json::to_value(Alert::VaultUnderfunded {
+            vault_balance: 1,
+            outstanding: 2,
+        })
+        .unwrap();
+        assert_eq!(json["kind"], "vault_underfunded");
+        assert_eq!(json["outstanding"], 2);
+    }
+}
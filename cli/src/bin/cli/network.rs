@@ -0,0 +1,106 @@
+//! Light Protocol tree accounts of each cluster, so the tree flags only need setting to
+//! override them.
+
+use clap::ValueEnum;
+use light_client::indexer::TreeInfo;
+use light_compressed_account::TreeType;
+use solana_sdk::pubkey::Pubkey;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Network {
+    /// Mainnet-beta
+    Mainnet,
+    /// Devnet
+    Devnet,
+}
+
+/// Tree accounts claims use on one cluster.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkPreset {
+    /// Address tree of the claim status accounts
+    pub address_tree: Pubkey,
+    /// v2 state trees new claim status accounts are written to, with their queues
+    pub state_trees: Vec<TreeInfo>,
+}
+
+/// Address tree, output queue and CPI context of the v2 state trees. Both clusters have them
+/// at the same addresses.
+const V2_STATE_TREES: [(&str, &str, &str); 5] = [
+    (
+        "bmt1LryLZUMmF7ZtqESaw7wifBXLfXHQYoE4GAmrahU",
+        "oq1na8gojfdUhsfCpyjNt6h4JaDWtHf1yQj4koBWfto",
+        "cpi15BoVPKgEPw5o8wc2T816GE7b378nMXnhH3Xbq4y",
+    ),
+    (
+        "bmt2UxoBxB9xWev4BkLvkGdapsz6sZGkzViPNph7VFi",
+        "oq2UkeMsJLfXt2QHzim242SUi3nvjJs8Pn7Eac9H9vg",
+        "cpi2yGapXUR3As5SjnHBAVvmApNiLsbeZpF3euWnW6B",
+    ),
+    (
+        "bmt3ccLd4bqSVZVeCJnH1F6C8jNygAhaDfxDwePyyGb",
+        "oq3AxjekBWgo64gpauB6QtuZNesuv19xrhaC1ZM1THQ",
+        "cpi3mbwMpSX8FAGMZVP85AwxqCaQMfEk9Em1v8QK9Rf",
+    ),
+    (
+        "bmt4d3p1a4YQgk9PeZv5s4DBUmbF5NxqYpk9HGjQsd8",
+        "oq4ypwvVGzCUMoiKKHWh4S1SgZJ9vCvKpcz6RT6A8dq",
+        "cpi4yyPDc4bCgHAnsenunGA8Y77j3XEDyjgfyCKgcoc",
+    ),
+    (
+        "bmt5yU97jC88YXTuSukYHa8Z5Bi2ZDUtmzfkDTA2mG2",
+        "oq5oh5ZR3yGomuQgFduNDzjtGvVWfDRGLuDVjv9a96P",
+        "cpi5ZTjdgYpZ1Xr7B1cMLLUE81oTtJbNNAyKary2nV6",
+    ),
+];
+
+fn v2_state_trees() -> Vec<TreeInfo> {
+    V2_STATE_TREES
+        .iter()
+        .map(|(tree, queue, cpi_context)| TreeInfo {
+            tree: Pubkey::from_str_const(tree),
+            queue: Pubkey::from_str_const(queue),
+            cpi_context: Some(Pubkey::from_str_const(cpi_context)),
+            next_tree_info: None,
+            tree_type: TreeType::StateV2,
+        })
+        .collect()
+}
+
+impl Network {
+    /// The tree accounts of this cluster. Mainnet and devnet currently share them, they are
+    /// kept apart so a tree rolled over on one cluster only changes its own preset.
+    pub fn preset(self) -> NetworkPreset {
+        match self {
+            Network::Mainnet => NetworkPreset {
+                address_tree: Pubkey::new_from_array(light_sdk::constants::ADDRESS_TREE_V2),
+                state_trees: v2_state_trees(),
+            },
+            Network::Devnet => NetworkPreset {
+                address_tree: Pubkey::new_from_array(light_sdk::constants::ADDRESS_TREE_V2),
+                state_trees: v2_state_trees(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_hold_v2_trees() {
+        for network in [Network::Mainnet, Network::Devnet] {
+            let preset = network.preset();
+            assert_eq!(
+                preset.address_tree.to_string(),
+                "amt2kaJA14v3urZbZvnc5v2np8jqvc4Z8zDep5wbtzx"
+            );
+            assert_eq!(preset.state_trees.len(), 5);
+            for info in &preset.state_trees {
+                assert_eq!(info.tree_type, TreeType::StateV2);
+                assert!(info.tree.to_string().starts_with("bmt"));
+                assert!(info.queue.to_string().starts_with("oq"));
+            }
+        }
+    }
+}
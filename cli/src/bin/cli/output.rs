@@ -0,0 +1,471 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use clap::ValueEnum;
+use jito_merkle_tree::csv_entry::AirdropCategory;
+use merkle_distributor::state::merkle_distributor_view::MerkleDistributorView;
+use serde_derive::Serialize;
+
+use crate::reconcile::{NodeReconciliation, ReconcileReport};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable lines on stdout
+    Text,
+    /// A single JSON object per command on stdout, everything else on stderr
+    Json,
+}
+
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Selects the output format for the rest of the process. Call once, before any output.
+pub fn set_output_format(format: OutputFormat) {
+    JSON_OUTPUT.store(format == OutputFormat::Json, Ordering::Relaxed);
+}
+
+pub fn is_json_output() -> bool {
+    JSON_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// Prints `value` as a single line of JSON on stdout in JSON mode, does nothing in text mode.
+pub fn print_json<T: serde::Serialize>(value: &T) -> serde_json::Result<()> {
+    if is_json_output() {
+        println!("{}", serde_json::to_string(value)?);
+    }
+    Ok(())
+}
+
+/// `println!` for human-readable output, sent to stderr in JSON mode so stdout stays parseable.
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if $crate::output::is_json_output() {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
+
+pub(crate) use say;
+
+#[derive(Debug, Serialize)]
+pub struct ClaimOutput {
+    pub distributor: String,
+    pub claim_status: String,
+    /// Set when this run created the claim status account
+    pub new_claim_signature: Option<String>,
+    /// claim_locked signature, None when no locked tokens were withdrawable
+    pub signature: Option<String>,
+    /// Increase of the claimant's token account balance over the whole command, or of the
+    /// recipient's with `--recipient`. For a native SOL distributor the increase of the
+    /// claimant's lamports, net of any fees it paid
+    pub amount_claimed: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchClaimWallet {
+    /// Claimant pubkey, or the keypair path when the keypair could not be read
+    pub wallet: String,
+    pub new_claim_signature: Option<String>,
+    pub claim_locked_signature: Option<String>,
+    /// Why claiming for the wallet failed, None when it succeeded or had nothing to claim
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchClaimOutput {
+    pub distributor: String,
+    /// Number of wallets with an error
+    pub failed: usize,
+    pub wallets: Vec<BatchClaimWallet>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NewDistributorOutput {
+    pub distributor: String,
+    pub token_vault: String,
+    /// None when a distributor with the same parameters already existed
+    pub signature: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FundVaultOutput {
+    pub distributor: String,
+    pub token_vault: String,
+    pub amount: u64,
+    /// Vault balance after the transaction landed
+    pub vault_balance: u64,
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClawbackOutput {
+    pub distributor: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PartialClawbackOutput {
+    pub distributor: String,
+    pub amount: u64,
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SetAdminOutput {
+    pub distributor: String,
+    pub new_admin: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CloseDistributorOutput {
+    pub distributor: String,
+    pub token_vault: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SetClawbackReceiverOutput {
+    pub distributor: String,
+    pub new_clawback_receiver: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SetPausedOutput {
+    pub distributor: String,
+    pub paused: bool,
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UpdateClawbackStartOutput {
+    pub distributor: String,
+    pub clawback_start_ts: i64,
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UpdateRootOutput {
+    pub distributor: String,
+    /// Hex encoded merkle root of the new tree
+    pub merkle_root: String,
+    pub max_total_claim: u64,
+    pub max_num_nodes: u64,
+    pub tree_depth: u8,
+    /// Nodes the new tree adds to the previous one
+    pub new_nodes: u64,
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MigrateClaimStatusOutput {
+    pub distributor: String,
+    pub claimant: String,
+    pub claim_status: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MigrateDistributorOutput {
+    pub distributor: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReassignClaimOutput {
+    pub distributor: String,
+    pub old_claimant: String,
+    pub new_claimant: String,
+    pub amount_unlocked: u64,
+    pub amount_locked: u64,
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RenounceAdminOutput {
+    pub distributor: String,
+    pub admin: String,
+    pub signature: String,
+}
+
+/// Printed by `--offline` in place of the command's output.
+#[derive(Debug, Serialize)]
+pub struct UnsignedTransactionOutput {
+    /// Base64 of the serialized transaction, without signatures
+    pub transaction: String,
+    pub blockhash: String,
+    /// Pubkeys that have to sign, the fee payer first
+    pub signers: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateMerkleTreeOutput {
+    pub merkle_tree_path: String,
+    pub merkle_root: [u8; 32],
+    pub max_num_nodes: u64,
+    pub max_total_claim: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MergeOutput {
+    pub merkle_tree_path: String,
+    /// CSVs and tree files merged
+    pub inputs: usize,
+    /// Claimants found in more than one input, whose amounts were summed
+    pub overlapping_claimants: usize,
+    pub merkle_root: [u8; 32],
+    pub max_num_nodes: u64,
+    pub max_total_claim: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClaimantEntry {
+    pub claimant: String,
+    pub amount_unlocked: u64,
+    pub amount_locked: u64,
+    pub staker_unlocked: u64,
+    pub staker_locked: u64,
+    pub searcher_unlocked: u64,
+    pub searcher_locked: u64,
+    pub validator_unlocked: u64,
+    pub validator_locked: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EligibilityOutput {
+    pub wallet: String,
+    /// Whether the wallet is in the tree file
+    pub eligible: bool,
+    /// Airdrop categories the wallet has an allocation in
+    pub categories: Vec<AirdropCategory>,
+    /// None when the wallet is not eligible
+    pub allocation: Option<ClaimantEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListClaimantsOutput {
+    /// Claimants in the tree file, before `--offset` and `--limit`
+    pub total: usize,
+    pub claimants: Vec<ClaimantEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DistributorSummary {
+    pub distributor: String,
+    pub version: u64,
+    pub version_scheme: String,
+    pub num_nodes_claimed: u64,
+    pub max_num_nodes: u64,
+    pub total_amount_claimed: u64,
+    pub max_total_claim: u64,
+    pub clawed_back: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DistributorInfoOutput {
+    pub distributor: String,
+    /// Slot the distributor and vault were read at
+    pub slot: u64,
+    pub vault_balance: u64,
+    /// Every field of the distributor account, at the top level
+    #[serde(flatten)]
+    pub state: MerkleDistributorView,
+}
+
+/// One claim seen by `watch`.
+#[derive(Debug, Serialize)]
+pub struct WatchClaimOutput {
+    pub distributor: String,
+    pub signature: String,
+    pub slot: u64,
+    /// `new_claim` or `claim_locked`
+    pub kind: String,
+    pub claimant: String,
+    pub amount: u64,
+    /// Chain time of a new claim, the time it was received for claim_locked
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListDistributorsOutput {
+    pub distributors: Vec<DistributorSummary>,
+    pub next_free_version: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClaimStatusView {
+    pub unlocked_amount: u64,
+    pub locked_amount: u64,
+    pub locked_amount_withdrawn: u64,
+    pub locked_amount_withdrawable: Option<u64>,
+    /// None until the first claim_locked
+    pub last_claimed_ts: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatusOutput {
+    pub claimant: String,
+    pub claim_status_address: String,
+    pub allocation_unlocked: u64,
+    pub allocation_locked: u64,
+    /// None until the claimant has claimed
+    pub claim_status: Option<ClaimStatusView>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WithdrawableOutput {
+    pub claimant: String,
+    pub claim_status_address: String,
+    /// False until new_claim, claim_locked withdraws nothing before it
+    pub claimed: bool,
+    /// Locked amount claim_locked would withdraw now, None when it cannot be computed
+    pub locked_amount_withdrawable: Option<u64>,
+    /// Locked amount not withdrawn yet, vested or not
+    pub locked_amount_remaining: u64,
+    /// Seconds until the locked amount is fully vested
+    pub lockup_remaining_secs: i64,
+    /// Set while the distributor's min_claim_interval since the last withdrawal has not passed
+    pub next_withdrawal_ts: Option<i64>,
+}
+
+/// Printed by claim-loop for each claim_locked it sends, one line per claim.
+#[derive(Debug, Serialize)]
+pub struct ClaimLoopOutput {
+    pub claimant: String,
+    pub distributor: String,
+    pub signature: String,
+    /// Increase of the claimant's balance, as in [ClaimOutput]
+    pub amount_claimed: u64,
+    /// Locked amount not withdrawn yet after this claim
+    pub locked_amount_remaining: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerifyClaimOutput {
+    pub claimant: String,
+    pub claim_status_address: String,
+    /// None until the claimant has claimed
+    pub claim_status: Option<ClaimStatusView>,
+    pub token_account: String,
+    /// None when the token account does not exist
+    pub token_account_balance: Option<u64>,
+    pub expected_balance: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerifyProofOutput {
+    pub claimant: String,
+    pub merkle_root: [u8; 32],
+    pub amount_unlocked: u64,
+    pub amount_locked: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerifyTreeOutput {
+    /// Hex encoded merkle root, recomputed from the nodes
+    pub merkle_root: String,
+    pub num_nodes: u64,
+    pub max_total_claim: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReconcileOutput {
+    pub distributor: String,
+    #[serde(flatten)]
+    pub report: ReconcileReport,
+    /// Every node of the tree file, only the unclaimed ones with `--only-unclaimed`
+    pub nodes: Vec<NodeReconciliation>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportClaimsOutput {
+    pub distributor: String,
+    pub path: String,
+    pub nodes_total: u64,
+    pub nodes_claimed: u64,
+}
+
+/// Compressed address of a claimant's claim status, base58 encoded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ClaimStatusAddressEntry {
+    pub claimant: String,
+    pub address: String,
+}
+
+/// JSON file written by `derive-addresses`. The addresses only hold for this program,
+/// distributor and address tree.
+#[derive(Debug, Serialize)]
+pub struct ClaimStatusAddressFile {
+    pub program_id: String,
+    pub distributor: String,
+    pub address_tree: String,
+    pub addresses: Vec<ClaimStatusAddressEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeriveAddressesOutput {
+    pub distributor: String,
+    pub address_tree: String,
+    pub path: String,
+    pub addresses: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SimulateClaimOutput {
+    pub distributor: String,
+    pub claimant: String,
+    /// `new_claim` or `claim_locked`
+    pub instruction: String,
+    pub success: bool,
+    /// Transaction error of a failed simulation
+    pub error: Option<String>,
+    pub units_consumed: Option<u64>,
+    /// Claim events the program emitted
+    pub events: Vec<SimulatedClaimEvent>,
+    pub logs: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SimulatedClaimEvent {
+    /// `new_claim` or `claim_locked`
+    pub kind: String,
+    pub claimant: String,
+    pub amount: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ErrorOutput {
+    pub code: u32,
+    pub message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_format_values() {
+        assert_eq!(
+            OutputFormat::from_str("json", false).unwrap(),
+            OutputFormat::Json
+        );
+        assert_eq!(
+            OutputFormat::from_str("text", false).unwrap(),
+            OutputFormat::Text
+        );
+        assert!(OutputFormat::from_str("yaml", false).is_err());
+    }
+
+    #[test]
+    fn test_optional_fields_serialize_as_null() {
+        let output = NewDistributorOutput {
+            distributor: "distributor".to_string(),
+            token_vault: "vault".to_string(),
+            signature: None,
+        };
+        let json = serde_json::to_value(output).unwrap();
+        assert_eq!(json["distributor"], "distributor");
+        assert!(json["signature"].is_null());
+    }
+}
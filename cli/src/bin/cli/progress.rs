@@ -0,0 +1,144 @@
+//! Progress bar drawn on stderr while a large merkle tree is built.
+
+use std::{
+    io::{IsTerminal, Write},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use jito_merkle_tree::utils::{TreeBuildProgress, TreeBuildStage};
+
+/// Width of the bar itself, in characters.
+const BAR_WIDTH: usize = 30;
+/// Least time between two redraws.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// One line for `progress`, `elapsed` into its stage, e.g.
+/// `Hashing   [#######-----] 1200/5000 nodes, ETA 3s`.
+pub fn render_progress(progress: TreeBuildProgress, elapsed: Duration) -> String {
+    let stage = match progress.stage {
+        TreeBuildStage::Hashing => "Hashing",
+        TreeBuildStage::Proofs => "Proofs",
+        TreeBuildStage::Verifying => "Verifying",
+    };
+    let total = progress.total.max(1);
+    let filled = BAR_WIDTH * progress.done.min(total) / total;
+    let eta = match progress.done {
+        0 => "ETA ?".to_string(),
+        done => {
+            let remaining = progress.total.saturating_sub(done) as f64;
+            let secs = elapsed.as_secs_f64() / done as f64 * remaining;
+            format!("ETA {}s", secs.ceil() as u64)
+        }
+    };
+    format!(
+        "{stage:<9} [{}{}] {}/{} nodes, {eta}",
+        "#".repeat(filled),
+        "-".repeat(BAR_WIDTH - filled),
+        progress.done,
+        progress.total
+    )
+}
+
+struct BarState {
+    stage: Option<TreeBuildStage>,
+    stage_start: Instant,
+    last_draw: Option<Instant>,
+    done: usize,
+}
+
+/// Redraws [render_progress] in place as reports arrive. Draws nothing unless stderr is a
+/// terminal, so logs and pipes stay clean.
+pub struct ProgressBar {
+    enabled: bool,
+    state: Mutex<BarState>,
+}
+
+impl ProgressBar {
+    pub fn on_stderr() -> Self {
+        Self {
+            enabled: std::io::stderr().is_terminal(),
+            state: Mutex::new(BarState {
+                stage: None,
+                stage_start: Instant::now(),
+                last_draw: None,
+                done: 0,
+            }),
+        }
+    }
+
+    /// Takes a report of the tree build, redrawing at most every [REDRAW_INTERVAL] and always
+    /// when a stage completes.
+    pub fn update(&self, progress: TreeBuildProgress) {
+        if !self.enabled {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        if state.stage != Some(progress.stage) {
+            if state.stage.is_some() {
+                eprintln!();
+            }
+            state.stage = Some(progress.stage);
+            state.stage_start = now;
+            state.last_draw = None;
+            state.done = 0;
+        }
+        // Parallel builds may report a stage slightly out of order
+        if progress.done < state.done {
+            return;
+        }
+        state.done = progress.done;
+        let finished = progress.done >= progress.total;
+        if !finished
+            && state
+                .last_draw
+                .is_some_and(|last_draw| now - last_draw < REDRAW_INTERVAL)
+        {
+            return;
+        }
+        state.last_draw = Some(now);
+        eprint!("\r{}", render_progress(progress, now - state.stage_start));
+        let _ = std::io::stderr().flush();
+    }
+
+    /// Ends the bar's line, once the build is over.
+    pub fn finish(&self) {
+        if self.enabled && self.state.lock().unwrap().stage.is_some() {
+            eprintln!();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_progress() {
+        let progress = TreeBuildProgress {
+            stage: TreeBuildStage::Proofs,
+            done: 250,
+            total: 1_000,
+        };
+        assert_eq!(
+            render_progress(progress, Duration::from_secs(10)),
+            "Proofs    [#######-----------------------] 250/1000 nodes, ETA 30s"
+        );
+
+        let progress = TreeBuildProgress {
+            stage: TreeBuildStage::Hashing,
+            done: 0,
+            total: 1_000,
+        };
+        assert!(render_progress(progress, Duration::ZERO).ends_with("0/1000 nodes, ETA ?"));
+
+        let progress = TreeBuildProgress {
+            stage: TreeBuildStage::Verifying,
+            done: 1_000,
+            total: 1_000,
+        };
+        assert!(render_progress(progress, Duration::from_secs(5))
+            .starts_with(&format!("Verifying [{}]", "#".repeat(BAR_WIDTH))));
+    }
+}
@@ -0,0 +1,257 @@
+//! Validity proofs of many claim status accounts, fetched a batch at a time and cached for the
+//! rest of the command.
+//!
+//! The indexer answers a `getValidityProof` call with one proof covering every hash and address
+//! it was given, while each claim instruction needs a proof of its own account alone. A batch is
+//! therefore a set of single-account requests sent concurrently.
+
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use futures::future::join_all;
+use light_client::{
+    indexer::{AddressWithTree, Indexer, IndexerError, ValidityProofWithContext},
+    rpc::LightClient,
+};
+use solana_sdk::pubkey::Pubkey;
+use tracing::{debug, warn};
+
+/// Proof requests in flight at once.
+pub const PROOF_BATCH_SIZE: usize = 10;
+/// Times the failed requests of a batch are sent again.
+const PROOF_FETCH_RETRIES: u32 = 2;
+/// Wait before the first retry of a batch, growing with each retry.
+const PROOF_RETRY_DELAY: Duration = Duration::from_millis(250);
+
+/// Account a validity proof is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProofKey {
+    /// Inclusion of an existing compressed account, by hash
+    Account([u8; 32]),
+    /// Non-inclusion of an address not created yet, in its address tree
+    Address { address: [u8; 32], tree: Pubkey },
+}
+
+/// Where validity proofs come from: the indexer, or a stand-in in tests.
+pub trait ProofSource {
+    async fn validity_proof(&self, key: ProofKey)
+        -> Result<ValidityProofWithContext, IndexerError>;
+}
+
+impl ProofSource for LightClient {
+    async fn validity_proof(
+        &self,
+        key: ProofKey,
+    ) -> Result<ValidityProofWithContext, IndexerError> {
+        let (hashes, addresses) = match key {
+            ProofKey::Account(hash) => (vec![hash], vec![]),
+            ProofKey::Address { address, tree } => {
+                (vec![], vec![AddressWithTree { address, tree }])
+            }
+        };
+        Ok(self
+            .get_validity_proof(hashes, addresses, None)
+            .await?
+            .value)
+    }
+}
+
+/// Fetches validity proofs, each at most once per command. An account's hash changes whenever
+/// it is written, so a cached proof never outlives the state it proves.
+pub struct ProofFetcher<'a, S> {
+    source: &'a S,
+    batch_size: usize,
+    proofs: HashMap<ProofKey, ValidityProofWithContext>,
+    requests: usize,
+}
+
+impl<'a, S: ProofSource> ProofFetcher<'a, S> {
+    /// Fetcher sending up to `batch_size` requests at once.
+    pub fn new(source: &'a S, batch_size: usize) -> Self {
+        Self {
+            source,
+            batch_size: batch_size.max(1),
+            proofs: HashMap::new(),
+            requests: 0,
+        }
+    }
+
+    /// Requests sent to the source so far, retries included.
+    pub fn requests(&self) -> usize {
+        self.requests
+    }
+
+    /// Fetches the proofs of the `keys` not cached yet, a batch at a time, sending the failed
+    /// requests of a batch again up to [PROOF_FETCH_RETRIES] times. Returns the keys that still
+    /// failed; [ProofFetcher::get] tries those once more and reports the error.
+    pub async fn prefetch(&mut self, keys: &[ProofKey]) -> Vec<ProofKey> {
+        let mut seen = HashSet::new();
+        let missing: Vec<ProofKey> = keys
+            .iter()
+            .copied()
+            .filter(|key| !self.proofs.contains_key(key) && seen.insert(*key))
+            .collect();
+        let mut failed = vec![];
+        for batch in missing.chunks(self.batch_size) {
+            let mut pending = self.fetch_batch(batch).await;
+            for retry in 1..=PROOF_FETCH_RETRIES {
+                if pending.is_empty() {
+                    break;
+                }
+                debug!(
+                    "Retrying {} of {} validity proofs",
+                    pending.len(),
+                    batch.len()
+                );
+                tokio::time::sleep(PROOF_RETRY_DELAY * retry).await;
+                pending = self.fetch_batch(&pending).await;
+            }
+            failed.extend(pending);
+        }
+        if !failed.is_empty() {
+            warn!(
+                "Could not fetch {} of {} validity proofs",
+                failed.len(),
+                missing.len()
+            );
+        }
+        failed
+    }
+
+    /// Requests the proofs of `keys` concurrently and caches them. Returns the failed keys.
+    async fn fetch_batch(&mut self, keys: &[ProofKey]) -> Vec<ProofKey> {
+        let source = self.source;
+        self.requests += keys.len();
+        let results = join_all(keys.iter().map(|key| source.validity_proof(*key))).await;
+        let mut failed = vec![];
+        for (key, result) in keys.iter().zip(results) {
+            match result {
+                Ok(proof) => {
+                    self.proofs.insert(*key, proof);
+                }
+                Err(e) => {
+                    debug!("Validity proof of {key:?} failed: {e}");
+                    failed.push(*key);
+                }
+            }
+        }
+        failed
+    }
+
+    /// The proof of `key`, from the cache or fetched now.
+    pub async fn get(&mut self, key: ProofKey) -> Result<ValidityProofWithContext, IndexerError> {
+        if let Some(proof) = self.proofs.get(&key) {
+            return Ok(proof.clone());
+        }
+        self.requests += 1;
+        let proof = self.source.validity_proof(key).await?;
+        self.proofs.insert(key, proof.clone());
+        Ok(proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Mutex,
+        },
+        time::Instant,
+    };
+
+    use super::*;
+
+    /// Answers every request after `latency`, failing the first `failures` requests of each key.
+    struct MockSource {
+        latency: Duration,
+        failures: usize,
+        attempts: Mutex<HashMap<ProofKey, usize>>,
+        requests: AtomicUsize,
+    }
+
+    impl MockSource {
+        fn new(latency: Duration, failures: usize) -> Self {
+            Self {
+                latency,
+                failures,
+                attempts: Mutex::new(HashMap::new()),
+                requests: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    impl ProofSource for MockSource {
+        async fn validity_proof(
+            &self,
+            key: ProofKey,
+        ) -> Result<ValidityProofWithContext, IndexerError> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(self.latency).await;
+            let mut attempts = self.attempts.lock().unwrap();
+            let attempt = attempts.entry(key).or_default();
+            *attempt += 1;
+            if *attempt <= self.failures {
+                return Err(IndexerError::ApiError("429 Too Many Requests".to_string()));
+            }
+            Ok(ValidityProofWithContext::default())
+        }
+    }
+
+    fn account_keys(count: u8) -> Vec<ProofKey> {
+        (0..count).map(|i| ProofKey::Account([i; 32])).collect()
+    }
+
+    #[tokio::test]
+    async fn test_batched_fetch_beats_per_account_fetch() {
+        let keys = account_keys(100);
+        let source = MockSource::new(Duration::from_millis(5), 0);
+
+        let start = Instant::now();
+        for key in &keys {
+            source.validity_proof(*key).await.unwrap();
+        }
+        let naive = start.elapsed();
+
+        let mut fetcher = ProofFetcher::new(&source, PROOF_BATCH_SIZE);
+        let start = Instant::now();
+        assert!(fetcher.prefetch(&keys).await.is_empty());
+        let batched = start.elapsed();
+        assert_eq!(fetcher.requests(), 100);
+        assert!(
+            batched * 3 < naive,
+            "batched {batched:?} vs per account {naive:?}"
+        );
+
+        // Every proof is cached now
+        assert!(fetcher.prefetch(&keys).await.is_empty());
+        for key in &keys {
+            fetcher.get(*key).await.unwrap();
+        }
+        assert_eq!(fetcher.requests(), 100);
+        assert_eq!(source.requests.load(Ordering::SeqCst), 200);
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_retries_only_failed_requests() {
+        let mut keys = account_keys(3);
+        keys.push(keys[0]);
+        let source = MockSource::new(Duration::ZERO, 1);
+        let mut fetcher = ProofFetcher::new(&source, 2);
+        assert!(fetcher.prefetch(&keys).await.is_empty());
+        // Each of the 3 distinct keys failed once, then succeeded
+        assert_eq!(fetcher.requests(), 6);
+
+        let source = MockSource::new(Duration::ZERO, usize::MAX);
+        let mut fetcher = ProofFetcher::new(&source, PROOF_BATCH_SIZE);
+        let key = ProofKey::Address {
+            address: [1; 32],
+            tree: Pubkey::new_unique(),
+        };
+        assert_eq!(fetcher.prefetch(&[key]).await, vec![key]);
+        assert_eq!(fetcher.requests(), 1 + PROOF_FETCH_RETRIES as usize);
+        assert!(fetcher.get(key).await.is_err());
+    }
+}
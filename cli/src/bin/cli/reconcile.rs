@@ -0,0 +1,476 @@
+//! Audit of a tree file against the claim statuses and counters of its distributor.
+
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+    str::FromStr,
+};
+
+use anchor_lang::prelude::Pubkey;
+use merkle_distributor::state::claim_status::ClaimStatus;
+use serde_derive::{Deserialize, Serialize};
+
+/// Whether `claim_status` is the empty one reassign-claim leaves at the old claimant's address.
+/// A node allocated nothing cannot be told apart from a claimed one, so it never counts.
+pub fn is_reassigned(claim_status: &ClaimStatus, amount_unlocked: u64, amount_locked: u64) -> bool {
+    claim_status.unlocked_amount == 0
+        && claim_status.locked_amount == 0
+        && (amount_unlocked > 0 || amount_locked > 0)
+}
+
+/// The wallet a reassigned node was issued to, with its claim status if the indexer has one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reassignment {
+    pub new_claimant: Pubkey,
+    pub claim_status: Option<ClaimStatus>,
+}
+
+#[derive(Deserialize)]
+struct ReassignmentRow {
+    old_claimant: String,
+    new_claimant: String,
+}
+
+/// Reads a CSV with `old_claimant,new_claimant` columns, one row per reassign-claim, into a
+/// map from old to new claimant.
+pub fn read_reassignments(reader: impl Read) -> Result<HashMap<Pubkey, Pubkey>, String> {
+    let mut reassignments = HashMap::new();
+    for row in csv::Reader::from_reader(reader).deserialize() {
+        let row: ReassignmentRow = row.map_err(|e| e.to_string())?;
+        let parse = |pubkey: &str| {
+            Pubkey::from_str(pubkey.trim()).map_err(|e| format!("invalid pubkey {pubkey}: {e}"))
+        };
+        reassignments.insert(parse(&row.old_claimant)?, parse(&row.new_claimant)?);
+    }
+    Ok(reassignments)
+}
+
+/// A tree node and what its claim status records, if it has one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NodeReconciliation {
+    pub claimant: String,
+    pub amount_unlocked: u64,
+    pub amount_locked: u64,
+    pub claimed: bool,
+    /// Set when reassign-claim issued the node to another wallet
+    pub reassigned: bool,
+    /// The wallet the node was reassigned to, when known
+    pub new_claimant: Option<String>,
+    /// Unlocked amount plus locked amount withdrawn so far, 0 when unclaimed. For a
+    /// reassigned node, what its new claimant received, or the unlocked amount paid out by the
+    /// reassignment when the new claimant is unknown.
+    pub amount_claimed: u64,
+    /// Set when the claim status amounts differ from the tree file
+    pub discrepancy: Option<String>,
+}
+
+impl NodeReconciliation {
+    /// `reassignment` is only looked at when `claim_status` is the empty status of a
+    /// reassigned node.
+    pub fn new(
+        claimant: &Pubkey,
+        amount_unlocked: u64,
+        amount_locked: u64,
+        claim_status: Option<&ClaimStatus>,
+        reassignment: Option<&Reassignment>,
+    ) -> Self {
+        let reassigned = claim_status
+            .is_some_and(|status| is_reassigned(status, amount_unlocked, amount_locked));
+        // A reassigned node's amounts live in the new claimant's status
+        let (claim_status, discrepancy) = match (reassigned, reassignment) {
+            (false, _) => (claim_status, None),
+            (true, None) => (
+                None,
+                Some(
+                    "reassigned to an unknown claimant, pass it with --reassignments to count \
+                     its withdrawals"
+                        .to_string(),
+                ),
+            ),
+            (true, Some(reassignment)) => (
+                reassignment.claim_status.as_ref(),
+                reassignment.claim_status.is_none().then(|| {
+                    format!(
+                        "reassigned to {}, which has no claim status",
+                        reassignment.new_claimant
+                    )
+                }),
+            ),
+        };
+        let discrepancy = discrepancy.or_else(|| {
+            claim_status
+                .filter(|status| {
+                    status.unlocked_amount != amount_unlocked
+                        || status.locked_amount != amount_locked
+                })
+                .map(|status| {
+                    format!(
+                        "claim status records {} unlocked and {} locked, the tree file \
+                         {amount_unlocked} and {amount_locked}",
+                        status.unlocked_amount, status.locked_amount
+                    )
+                })
+        });
+        let amount_claimed = match claim_status {
+            Some(status) => status
+                .unlocked_amount
+                .saturating_add(status.locked_amount_withdrawn),
+            None if reassigned => amount_unlocked,
+            None => 0,
+        };
+        Self {
+            claimant: claimant.to_string(),
+            amount_unlocked,
+            amount_locked,
+            claimed: reassigned || claim_status.is_some(),
+            reassigned,
+            new_claimant: reassignment
+                .filter(|_| reassigned)
+                .map(|reassignment| reassignment.new_claimant.to_string()),
+            amount_claimed,
+            discrepancy,
+        }
+    }
+}
+
+/// A row of `export-claims`: what a tree node is eligible for and what its claim status
+/// records, zeros when it has none.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ClaimExportRow {
+    pub claimant: String,
+    pub eligible_unlocked: u64,
+    pub eligible_locked: u64,
+    pub unlocked_claimed: u64,
+    pub locked_withdrawn: u64,
+    /// Time of the last locked withdrawal, 0 when there was none
+    pub last_claimed_ts: i64,
+    pub reassigned: bool,
+    /// The wallet the node was reassigned to, empty when not reassigned or unknown
+    pub new_claimant: Option<String>,
+}
+
+impl ClaimExportRow {
+    /// Like [NodeReconciliation::new], a reassigned node is exported with its new claimant's
+    /// amounts, or the unlocked amount the reassignment paid out when it is unknown.
+    pub fn new(
+        claimant: &Pubkey,
+        amount_unlocked: u64,
+        amount_locked: u64,
+        claim_status: Option<&ClaimStatus>,
+        reassignment: Option<&Reassignment>,
+    ) -> Self {
+        let reassigned = claim_status
+            .is_some_and(|status| is_reassigned(status, amount_unlocked, amount_locked));
+        let claim_status = match reassigned {
+            true => reassignment.and_then(|reassignment| reassignment.claim_status.as_ref()),
+            false => claim_status,
+        };
+        let unknown_reassignment = reassigned && claim_status.is_none();
+        Self {
+            claimant: claimant.to_string(),
+            eligible_unlocked: amount_unlocked,
+            eligible_locked: amount_locked,
+            unlocked_claimed: claim_status.map_or(
+                if unknown_reassignment {
+                    amount_unlocked
+                } else {
+                    0
+                },
+                |status| status.unlocked_amount,
+            ),
+            locked_withdrawn: claim_status.map_or(0, |status| status.locked_amount_withdrawn),
+            last_claimed_ts: claim_status.map_or(0, |status| status.last_claimed_ts),
+            reassigned,
+            new_claimant: reassignment
+                .filter(|_| reassigned)
+                .map(|reassignment| reassignment.new_claimant.to_string()),
+        }
+    }
+}
+
+/// Writes `rows` to `writer` as CSV, after a header line naming the columns.
+pub fn write_claims_csv(writer: impl Write, rows: &[ClaimExportRow]) -> Result<(), csv::Error> {
+    let mut writer = csv::Writer::from_writer(writer);
+    for row in rows {
+        writer.serialize(row)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Distributor fields the tree file is checked against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DistributorTotals {
+    pub root_matches: bool,
+    pub max_total_claim: u64,
+    pub max_num_nodes: u64,
+    pub total_amount_claimed: u64,
+    pub num_nodes_claimed: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReconcileReport {
+    pub nodes_total: u64,
+    pub nodes_claimed: u64,
+    /// Sum of all node amounts in the tree file
+    pub tree_total_amount: u64,
+    /// Sum of [NodeReconciliation::amount_claimed]
+    pub amount_claimed: u64,
+    pub distributor_total_amount_claimed: u64,
+    pub distributor_num_nodes_claimed: u64,
+    pub discrepancies: Vec<String>,
+}
+
+/// Totals `nodes` up and lists where they disagree with each other or with `distributor`.
+pub fn reconcile(nodes: &[NodeReconciliation], distributor: &DistributorTotals) -> ReconcileReport {
+    let nodes_total = nodes.len() as u64;
+    let nodes_claimed = nodes.iter().filter(|node| node.claimed).count() as u64;
+    let tree_total_amount = nodes
+        .iter()
+        .map(|node| node.amount_unlocked.saturating_add(node.amount_locked))
+        .fold(0u64, u64::saturating_add);
+    let amount_claimed = nodes
+        .iter()
+        .map(|node| node.amount_claimed)
+        .fold(0u64, u64::saturating_add);
+
+    let mut discrepancies = vec![];
+    if !distributor.root_matches {
+        discrepancies.push("the tree file root differs from the distributor root".to_string());
+    }
+    if tree_total_amount > distributor.max_total_claim {
+        discrepancies.push(format!(
+            "node amounts add up to {tree_total_amount}, above the distributor's \
+             max_total_claim {}",
+            distributor.max_total_claim
+        ));
+    }
+    if nodes_total > distributor.max_num_nodes {
+        discrepancies.push(format!(
+            "the tree file has {nodes_total} nodes, above the distributor's max_num_nodes {}",
+            distributor.max_num_nodes
+        ));
+    }
+    for node in nodes {
+        if let Some(discrepancy) = &node.discrepancy {
+            discrepancies.push(format!("{}: {discrepancy}", node.claimant));
+        }
+    }
+    if amount_claimed != distributor.total_amount_claimed {
+        discrepancies.push(format!(
+            "claim statuses add up to {amount_claimed} claimed, the distributor's \
+             total_amount_claimed is {}",
+            distributor.total_amount_claimed
+        ));
+    }
+    if nodes_claimed != distributor.num_nodes_claimed {
+        discrepancies.push(format!(
+            "{nodes_claimed} claim statuses found, the distributor's num_nodes_claimed is {}",
+            distributor.num_nodes_claimed
+        ));
+    }
+
+    ReconcileReport {
+        nodes_total,
+        nodes_claimed,
+        tree_total_amount,
+        amount_claimed,
+        distributor_total_amount_claimed: distributor.total_amount_claimed,
+        distributor_num_nodes_claimed: distributor.num_nodes_claimed,
+        discrepancies,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claim_status(unlocked_amount: u64, locked_amount: u64, withdrawn: u64) -> ClaimStatus {
+        ClaimStatus {
+            claimant: Pubkey::new_unique(),
+            locked_amount,
+            locked_amount_withdrawn: withdrawn,
+            unlocked_amount,
+            unlock_schedule: None,
+            last_claimed_ts: 0,
+            category_breakdown: None,
+        }
+    }
+
+    fn totals(total_amount_claimed: u64, num_nodes_claimed: u64) -> DistributorTotals {
+        DistributorTotals {
+            root_matches: true,
+            max_total_claim: 600,
+            max_num_nodes: 3,
+            total_amount_claimed,
+            num_nodes_claimed,
+        }
+    }
+
+    #[test]
+    fn test_reconcile_matching_state() {
+        let nodes = vec![
+            NodeReconciliation::new(
+                &Pubkey::new_unique(),
+                100,
+                100,
+                Some(&claim_status(100, 100, 40)),
+                None,
+            ),
+            NodeReconciliation::new(&Pubkey::new_unique(), 100, 100, None, None),
+            NodeReconciliation::new(
+                &Pubkey::new_unique(),
+                0,
+                200,
+                Some(&claim_status(0, 200, 0)),
+                None,
+            ),
+        ];
+        let report = reconcile(&nodes, &totals(140, 2));
+        assert_eq!(
+            report,
+            ReconcileReport {
+                nodes_total: 3,
+                nodes_claimed: 2,
+                tree_total_amount: 600,
+                amount_claimed: 140,
+                distributor_total_amount_claimed: 140,
+                distributor_num_nodes_claimed: 2,
+                discrepancies: vec![],
+            }
+        );
+        assert!(!nodes[1].claimed);
+        assert_eq!(nodes[1].amount_claimed, 0);
+    }
+
+    #[test]
+    fn test_write_claims_csv() {
+        let (claimed, unclaimed) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut status = claim_status(100, 300, 120);
+        status.last_claimed_ts = 1_700_000_000;
+        let rows = vec![
+            ClaimExportRow::new(&claimed, 100, 300, Some(&status), None),
+            ClaimExportRow::new(&unclaimed, 50, 0, None, None),
+        ];
+
+        let mut csv = vec![];
+        write_claims_csv(&mut csv, &rows).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            format!(
+                "claimant,eligible_unlocked,eligible_locked,unlocked_claimed,locked_withdrawn,\
+                 last_claimed_ts,reassigned,new_claimant\n\
+                 {claimed},100,300,100,120,1700000000,false,\n\
+                 {unclaimed},50,0,0,0,0,false,\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_reconcile_reports_discrepancies() {
+        let claimant = Pubkey::new_unique();
+        let nodes = vec![
+            NodeReconciliation::new(&claimant, 100, 100, Some(&claim_status(50, 100, 0)), None),
+            NodeReconciliation::new(&Pubkey::new_unique(), 100, 100, None, None),
+        ];
+        let mut distributor = totals(150, 2);
+        distributor.root_matches = false;
+        let report = reconcile(&nodes, &distributor);
+        assert_eq!(
+            report.discrepancies,
+            vec![
+                "the tree file root differs from the distributor root".to_string(),
+                format!(
+                    "{claimant}: claim status records 50 unlocked and 100 locked, the tree file \
+                     100 and 100"
+                ),
+                "claim statuses add up to 50 claimed, the distributor's total_amount_claimed \
+                 is 150"
+                    .to_string(),
+                "1 claim statuses found, the distributor's num_nodes_claimed is 2".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reconcile_reassigned_nodes() {
+        let (known, unknown, new_claimant) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        // reassign-claim leaves an empty status at the old claimant's address
+        let emptied = claim_status(0, 0, 0);
+        let reassignment = Reassignment {
+            new_claimant,
+            claim_status: Some(claim_status(100, 100, 60)),
+        };
+        let nodes = vec![
+            NodeReconciliation::new(&known, 100, 100, Some(&emptied), Some(&reassignment)),
+            NodeReconciliation::new(&unknown, 200, 0, Some(&emptied), None),
+        ];
+        assert!(nodes[0].reassigned && nodes[0].claimed);
+        assert_eq!(nodes[0].new_claimant, Some(new_claimant.to_string()));
+        assert_eq!(nodes[0].amount_claimed, 160);
+        assert_eq!(nodes[0].discrepancy, None);
+        // Without the new claimant only the unlocked amount paid at reassignment is known
+        assert!(nodes[1].reassigned);
+        assert_eq!(nodes[1].new_claimant, None);
+        assert_eq!(nodes[1].amount_claimed, 200);
+
+        let report = reconcile(&nodes, &totals(360, 2));
+        assert_eq!(report.amount_claimed, 360);
+        assert_eq!(
+            report.discrepancies,
+            vec![format!(
+                "{unknown}: reassigned to an unknown claimant, pass it with --reassignments to \
+                 count its withdrawals"
+            )]
+        );
+
+        // A node allocated nothing is never taken for a reassigned one
+        let empty_node = NodeReconciliation::new(&known, 0, 0, Some(&emptied), None);
+        assert!(!empty_node.reassigned);
+        assert_eq!(empty_node.discrepancy, None);
+    }
+
+    #[test]
+    fn test_export_reassigned_node() {
+        let (claimant, new_claimant) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut new_status = claim_status(100, 300, 120);
+        new_status.last_claimed_ts = 1_700_000_000;
+        let reassignment = Reassignment {
+            new_claimant,
+            claim_status: Some(new_status),
+        };
+        let emptied = claim_status(0, 0, 0);
+
+        let row = ClaimExportRow::new(&claimant, 100, 300, Some(&emptied), Some(&reassignment));
+        assert_eq!(
+            (
+                row.unlocked_claimed,
+                row.locked_withdrawn,
+                row.last_claimed_ts
+            ),
+            (100, 120, 1_700_000_000)
+        );
+        assert!(row.reassigned);
+        assert_eq!(row.new_claimant, Some(new_claimant.to_string()));
+
+        let row = ClaimExportRow::new(&claimant, 100, 300, Some(&emptied), None);
+        assert_eq!((row.unlocked_claimed, row.locked_withdrawn), (100, 0));
+        assert!(row.reassigned);
+        assert_eq!(row.new_claimant, None);
+    }
+
+    #[test]
+    fn test_read_reassignments() {
+        let (old_claimant, new_claimant) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let csv = format!("old_claimant,new_claimant\n{old_claimant},{new_claimant}\n");
+        let reassignments = read_reassignments(csv.as_bytes()).unwrap();
+        assert_eq!(reassignments.len(), 1);
+        assert_eq!(reassignments[&old_claimant], new_claimant);
+
+        assert!(read_reassignments("old_claimant,new_claimant\nnot-a-key,x\n".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,285 @@
+use std::{thread::sleep, time::Duration};
+
+use solana_program::instruction::Instruction;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_rpc_client_api::{
+    client_error::{Error as ClientError, ErrorKind as ClientErrorKind},
+    request::{RpcError, RpcResponseErrorData},
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    compute_budget::{self, ComputeBudgetInstruction},
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::Signer,
+    transaction::{Transaction, TransactionError},
+};
+use tracing::{info, warn};
+
+/// Escalation starts from this compute unit price when no priority fee was requested,
+/// since multiplying zero never escalates.
+const MIN_ESCALATED_PRIORITY_FEE: u64 = 1_000;
+
+/// Delay between signature status checks while waiting for a transaction to land.
+const CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Compute unit price schedule for resending a transaction that expired before landing.
+#[derive(Debug, Clone, Copy)]
+pub struct PriorityFeeEscalation {
+    /// Compute unit price of the first attempt, in microlamports
+    pub initial: u64,
+    /// Factor applied to the price on each retry
+    pub multiplier: f64,
+    /// Price never escalates above this, in microlamports
+    pub ceiling: u64,
+}
+
+impl PriorityFeeEscalation {
+    /// Compute unit price for the given zero-based attempt.
+    pub fn fee_for_attempt(&self, attempt: u32) -> u64 {
+        if attempt == 0 {
+            return self.initial;
+        }
+        let fee = match self.initial {
+            0 => MIN_ESCALATED_PRIORITY_FEE as f64 * self.multiplier.powi(attempt as i32 - 1),
+            initial => initial as f64 * self.multiplier.powi(attempt as i32),
+        };
+        (fee as u64).min(self.ceiling)
+    }
+}
+
+/// Parses `--priority-escalation`. A factor below 1 would lower the fee on each retry, and NaN
+/// or infinity would turn the escalated fee into 0 or the ceiling right away.
+pub fn parse_escalation_multiplier(s: &str) -> Result<f64, String> {
+    let multiplier = s.parse::<f64>().map_err(|e| e.to_string())?;
+    if !multiplier.is_finite() || multiplier < 1.0 {
+        return Err(format!("{s} is not a finite factor of at least 1"));
+    }
+    Ok(multiplier)
+}
+
+/// `instructions` with their compute budget instructions moved to the front, followed by a
+/// compute unit price of `priority_fee` when it is not zero, so every transaction requests its
+/// limit and price before anything else. A durable nonce advance still has to be prepended.
+pub fn with_compute_budget(instructions: &[Instruction], priority_fee: u64) -> Vec<Instruction> {
+    let (mut ixs, others): (Vec<_>, Vec<_>) = instructions
+        .iter()
+        .cloned()
+        .partition(|ix| compute_budget::check_id(&ix.program_id));
+    if priority_fee > 0 {
+        ixs.push(ComputeBudgetInstruction::set_compute_unit_price(
+            priority_fee,
+        ));
+    }
+    ixs.extend(others);
+    ixs
+}
+
+/// Whether the transaction never landed and can be resent with a fresh blockhash.
+fn is_expired(e: &ClientError) -> bool {
+    e.get_transaction_error() == Some(TransactionError::BlockhashNotFound)
+}
+
+/// Sends `tx` and waits until it lands. A transaction still unknown once `blockhash` is no
+/// longer valid never lands, which is reported as `BlockhashNotFound` rather than the untyped
+/// "unable to confirm transaction" of `send_and_confirm_transaction`.
+fn send_and_confirm(
+    client: &RpcClient,
+    tx: &Transaction,
+    blockhash: &Hash,
+) -> Result<Signature, ClientError> {
+    let signature = client.send_transaction(tx)?;
+    loop {
+        match client.get_signature_status(&signature)? {
+            Some(result) => return result.map(|()| signature).map_err(Into::into),
+            None if !client.is_blockhash_valid(blockhash, CommitmentConfig::processed())? => {
+                return Err(TransactionError::BlockhashNotFound.into());
+            }
+            None => sleep(CONFIRMATION_POLL_INTERVAL),
+        }
+    }
+}
+
+/// Whether the RPC node refused the request because it lags behind the cluster.
+fn is_node_behind(e: &ClientError) -> bool {
+    matches!(
+        e.kind(),
+        ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            data: RpcResponseErrorData::NodeUnhealthy { .. },
+            ..
+        })
+    )
+}
+
+/// Why `e` is worth another attempt, None for errors that would fail again the same way,
+/// such as insufficient funds or a program error.
+fn retry_reason(e: &ClientError) -> Option<&'static str> {
+    if is_expired(e) {
+        Some("Transaction expired before landing")
+    } else if is_node_behind(e) {
+        Some("RPC node is behind")
+    } else {
+        None
+    }
+}
+
+/// Signs and sends `instructions`, resending up to `max_retries` times when the transaction
+/// expires or the RPC node lags. Each attempt uses a fresh blockhash and the next price in
+/// `escalation`.
+pub fn send_with_retry(
+    client: &RpcClient,
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    signers: &[&dyn Signer],
+    escalation: &PriorityFeeEscalation,
+    max_retries: u32,
+) -> Result<Signature, ClientError> {
+    let mut attempt = 0;
+    loop {
+        let priority_fee = escalation.fee_for_attempt(attempt);
+        let ixs = with_compute_budget(instructions, priority_fee);
+        info!(
+            "Attempt {}/{}: priority fee {priority_fee} microlamports",
+            attempt + 1,
+            max_retries + 1
+        );
+
+        let result = client.get_latest_blockhash().and_then(|blockhash| {
+            let tx = Transaction::new_signed_with_payer(&ixs, Some(payer), signers, blockhash);
+            send_and_confirm(client, &tx, &blockhash)
+        });
+        match result {
+            Ok(signature) => return Ok(signature),
+            Err(e) => match retry_reason(&e) {
+                Some(reason) if attempt < max_retries => {
+                    warn!("{reason}, retrying");
+                    attempt += 1;
+                }
+                _ => return Err(e),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escalation_schedule() {
+        let escalation = PriorityFeeEscalation {
+            initial: 10_000,
+            multiplier: 1.5,
+            ceiling: 40_000,
+        };
+        let schedule: Vec<u64> = (0..6).map(|i| escalation.fee_for_attempt(i)).collect();
+        assert_eq!(
+            schedule,
+            vec![10_000, 15_000, 22_500, 33_750, 40_000, 40_000]
+        );
+    }
+
+    #[test]
+    fn test_escalation_without_initial_fee() {
+        let escalation = PriorityFeeEscalation {
+            initial: 0,
+            multiplier: 2.0,
+            ceiling: 5_000,
+        };
+        let schedule: Vec<u64> = (0..5).map(|i| escalation.fee_for_attempt(i)).collect();
+        assert_eq!(schedule, vec![0, 1_000, 2_000, 4_000, 5_000]);
+    }
+
+    #[test]
+    fn test_compute_budget_comes_first() {
+        let claim = Instruction::new_with_bytes(Pubkey::new_unique(), &[1], vec![]);
+        let create_ata = Instruction::new_with_bytes(Pubkey::new_unique(), &[2], vec![]);
+        let limit = ComputeBudgetInstruction::set_compute_unit_limit(400_000);
+        let price = ComputeBudgetInstruction::set_compute_unit_price(5_000);
+
+        let ixs = with_compute_budget(&[create_ata.clone(), limit.clone(), claim.clone()], 5_000);
+        assert_eq!(
+            ixs,
+            vec![limit.clone(), price, create_ata.clone(), claim.clone()]
+        );
+
+        // No price instruction without a priority fee
+        let ixs = with_compute_budget(&[create_ata.clone(), limit.clone(), claim.clone()], 0);
+        assert_eq!(ixs, vec![limit, create_ata, claim]);
+    }
+
+    #[test]
+    fn test_parse_escalation_multiplier() {
+        assert_eq!(parse_escalation_multiplier("1"), Ok(1.0));
+        assert_eq!(parse_escalation_multiplier("1.5"), Ok(1.5));
+        for invalid in ["0", "0.5", "-2", "NaN", "inf", "1.5x"] {
+            assert!(parse_escalation_multiplier(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_unconfirmed_transaction_expires() {
+        use solana_rpc_client::mock_sender::Mocks;
+        use solana_rpc_client_api::request::RpcRequest;
+        use solana_sdk::signature::Keypair;
+
+        // The signature is never seen and its blockhash has expired
+        let mut mocks = Mocks::default();
+        mocks.insert(
+            RpcRequest::IsBlockhashValid,
+            serde_json::json!({"context": {"slot": 1}, "value": false}),
+        );
+        let client = RpcClient::new_mock_with_mocks("sig_not_found", mocks);
+        let payer = Keypair::new();
+        let blockhash = Hash::new_unique();
+        let tx =
+            Transaction::new_signed_with_payer(&[], Some(&payer.pubkey()), &[&payer], blockhash);
+
+        let e = send_and_confirm(&client, &tx, &blockhash).unwrap_err();
+        assert!(is_expired(&e));
+    }
+
+    #[test]
+    fn test_expired_errors_are_retried() {
+        // Only the typed error counts, not the wording of a confirmation failure
+        let unconfirmed: ClientError =
+            RpcError::ForUser("unable to confirm transaction".to_string()).into();
+        assert!(!is_expired(&unconfirmed));
+
+        let blockhash_not_found: ClientError = TransactionError::BlockhashNotFound.into();
+        assert!(is_expired(&blockhash_not_found));
+
+        let failed: ClientError = TransactionError::InsufficientFundsForFee.into();
+        assert!(!is_expired(&failed));
+    }
+
+    #[test]
+    fn test_retry_reason() {
+        let node_behind: ClientError = RpcError::RpcResponseError {
+            code: -32005,
+            message: "Node is behind by 42 slots".to_string(),
+            data: RpcResponseErrorData::NodeUnhealthy {
+                num_slots_behind: Some(42),
+            },
+        }
+        .into();
+        assert_eq!(retry_reason(&node_behind), Some("RPC node is behind"));
+
+        let blockhash_not_found: ClientError = TransactionError::BlockhashNotFound.into();
+        assert_eq!(
+            retry_reason(&blockhash_not_found),
+            Some("Transaction expired before landing")
+        );
+
+        // Deterministic failures are returned right away
+        let insufficient_funds: ClientError = TransactionError::InsufficientFundsForFee.into();
+        assert_eq!(retry_reason(&insufficient_funds), None);
+        let program_error: ClientError = TransactionError::InstructionError(
+            0,
+            solana_sdk::instruction::InstructionError::Custom(6000),
+        )
+        .into();
+        assert_eq!(retry_reason(&program_error), None);
+    }
+}
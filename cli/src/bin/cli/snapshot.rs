@@ -0,0 +1,267 @@
+//! Point-in-time distributor snapshots for audits and backups.
+//!
+//! A snapshot is a JSON lines file: the first line is a [SnapshotHeader], every following line
+//! a [ClaimSnapshot]. Claims are appended as they are fetched, so large distributors never
+//! have to be held in memory while writing.
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotHeader {
+    pub distributor: String,
+    /// Slot the RPC node was at when the snapshot started
+    pub slot: u64,
+    pub version: u64,
+    pub root: [u8; 32],
+    pub mint: String,
+    pub token_vault: String,
+    pub vault_balance: u64,
+    pub max_total_claim: u64,
+    pub max_num_nodes: u64,
+    pub total_amount_claimed: u64,
+    pub num_nodes_claimed: u64,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub clawback_start_ts: i64,
+    pub clawback_receiver: String,
+    pub admin: String,
+    pub clawed_back: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClaimSnapshot {
+    pub claimant: String,
+    pub claim_status: String,
+    pub unlocked_amount: u64,
+    pub locked_amount: u64,
+    pub locked_amount_withdrawn: u64,
+}
+
+pub struct SnapshotWriter {
+    out: BufWriter<File>,
+}
+
+impl SnapshotWriter {
+    pub fn create(path: &Path, header: &SnapshotHeader) -> io::Result<Self> {
+        let mut writer = Self {
+            out: BufWriter::new(File::create(path)?),
+        };
+        writer.write_line(header)?;
+        Ok(writer)
+    }
+
+    pub fn write_claim(&mut self, claim: &ClaimSnapshot) -> io::Result<()> {
+        self.write_line(claim)
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+
+    fn write_line<T: serde::Serialize>(&mut self, value: &T) -> io::Result<()> {
+        serde_json::to_writer(&mut self.out, value)?;
+        self.out.write_all(b"\n")
+    }
+}
+
+/// A snapshot read back from disk, claims keyed by claimant.
+#[derive(Debug)]
+pub struct Snapshot {
+    pub header: SnapshotHeader,
+    pub claims: BTreeMap<String, ClaimSnapshot>,
+}
+
+pub fn read_snapshot(path: &Path) -> io::Result<Snapshot> {
+    let mut lines = BufReader::new(File::open(path)?).lines();
+    let header_line = lines.next().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} is empty", path.display()),
+        )
+    })??;
+    let header: SnapshotHeader = serde_json::from_str(&header_line)?;
+
+    let mut claims = BTreeMap::new();
+    for line in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let claim: ClaimSnapshot = serde_json::from_str(&line)?;
+        claims.insert(claim.claimant.clone(), claim);
+    }
+    Ok(Snapshot { header, claims })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Withdrawal {
+    pub claimant: String,
+    pub locked_amount_withdrawn_before: u64,
+    pub locked_amount_withdrawn_after: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SnapshotDiff {
+    pub slot_before: u64,
+    pub slot_after: u64,
+    /// Claims present only in the later snapshot
+    pub new_claims: Vec<ClaimSnapshot>,
+    /// Claims whose withdrawn locked amount changed
+    pub withdrawals: Vec<Withdrawal>,
+    /// Claims present only in the earlier snapshot. Claim statuses are never closed, so
+    /// anything here means the snapshots do not belong together.
+    pub missing_claims: Vec<ClaimSnapshot>,
+    pub vault_balance_change: i128,
+    pub total_amount_claimed_change: i128,
+}
+
+pub fn diff_snapshots(before: &Snapshot, after: &Snapshot) -> SnapshotDiff {
+    let new_claims = after
+        .claims
+        .values()
+        .filter(|claim| !before.claims.contains_key(&claim.claimant))
+        .cloned()
+        .collect();
+    let missing_claims = before
+        .claims
+        .values()
+        .filter(|claim| !after.claims.contains_key(&claim.claimant))
+        .cloned()
+        .collect();
+    let withdrawals = after
+        .claims
+        .values()
+        .filter_map(|claim| {
+            let previous = before.claims.get(&claim.claimant)?;
+            (previous.locked_amount_withdrawn != claim.locked_amount_withdrawn).then(|| {
+                Withdrawal {
+                    claimant: claim.claimant.clone(),
+                    locked_amount_withdrawn_before: previous.locked_amount_withdrawn,
+                    locked_amount_withdrawn_after: claim.locked_amount_withdrawn,
+                }
+            })
+        })
+        .collect();
+
+    SnapshotDiff {
+        slot_before: before.header.slot,
+        slot_after: after.header.slot,
+        new_claims,
+        withdrawals,
+        missing_claims,
+        vault_balance_change: after.header.vault_balance as i128
+            - before.header.vault_balance as i128,
+        total_amount_claimed_change: after.header.total_amount_claimed as i128
+            - before.header.total_amount_claimed as i128,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(slot: u64, vault_balance: u64, total_amount_claimed: u64) -> SnapshotHeader {
+        SnapshotHeader {
+            distributor: "distributor".to_string(),
+            slot,
+            version: 0,
+            root: [1; 32],
+            mint: "mint".to_string(),
+            token_vault: "vault".to_string(),
+            vault_balance,
+            max_total_claim: 3_000,
+            max_num_nodes: 3,
+            total_amount_claimed,
+            num_nodes_claimed: 0,
+            start_ts: 100,
+            end_ts: 200,
+            clawback_start_ts: 86_600,
+            clawback_receiver: "receiver".to_string(),
+            admin: "admin".to_string(),
+            clawed_back: false,
+        }
+    }
+
+    fn claim(claimant: &str, locked_amount_withdrawn: u64) -> ClaimSnapshot {
+        ClaimSnapshot {
+            claimant: claimant.to_string(),
+            claim_status: format!("{claimant}-status"),
+            unlocked_amount: 500,
+            locked_amount: 500,
+            locked_amount_withdrawn,
+        }
+    }
+
+    fn write(path: &Path, header: &SnapshotHeader, claims: &[ClaimSnapshot]) {
+        let mut writer = SnapshotWriter::create(path, header).unwrap();
+        for claim in claims {
+            writer.write_claim(claim).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("snapshot.jsonl");
+        let header = header(10, 3_000, 0);
+        let claims = vec![claim("alice", 0), claim("bob", 250)];
+        write(&path, &header, &claims);
+
+        let snapshot = read_snapshot(&path).unwrap();
+        assert_eq!(snapshot.header, header);
+        assert_eq!(snapshot.claims.len(), 2);
+        assert_eq!(snapshot.claims["bob"], claims[1]);
+    }
+
+    #[test]
+    fn test_diff_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let before_path = dir.path().join("before.jsonl");
+        let after_path = dir.path().join("after.jsonl");
+        write(
+            &before_path,
+            &header(10, 3_000, 500),
+            &[claim("alice", 0), claim("bob", 100)],
+        );
+        write(
+            &after_path,
+            &header(20, 2_000, 1_500),
+            &[claim("alice", 0), claim("bob", 300), claim("carol", 0)],
+        );
+
+        let diff = diff_snapshots(
+            &read_snapshot(&before_path).unwrap(),
+            &read_snapshot(&after_path).unwrap(),
+        );
+        assert_eq!(diff.slot_before, 10);
+        assert_eq!(diff.slot_after, 20);
+        assert_eq!(diff.new_claims, vec![claim("carol", 0)]);
+        assert_eq!(
+            diff.withdrawals,
+            vec![Withdrawal {
+                claimant: "bob".to_string(),
+                locked_amount_withdrawn_before: 100,
+                locked_amount_withdrawn_after: 300,
+            }]
+        );
+        assert!(diff.missing_claims.is_empty());
+        assert_eq!(diff.vault_balance_change, -1_000);
+        assert_eq!(diff.total_amount_claimed_change, 1_000);
+    }
+
+    #[test]
+    fn test_read_empty_snapshot_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("empty.jsonl");
+        File::create(&path).unwrap();
+        assert!(read_snapshot(&path).is_err());
+    }
+}
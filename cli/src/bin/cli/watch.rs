@@ -0,0 +1,192 @@
+//! Claims of a distributor as they land, read from the `NewClaimEvent` and `ClaimedEvent`
+//! entries its program writes to the transaction logs.
+
+use anchor_lang::{prelude::Pubkey, AnchorDeserialize, Discriminator};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use merkle_distributor::state::claimed_event::{ClaimedEvent, NewClaimEvent};
+use reqwest::Url;
+
+/// Which instruction a watched claim came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimKind {
+    /// First claim of a claimant, paying out its unlocked tokens
+    NewClaim,
+    /// Withdrawal of locked tokens
+    ClaimLocked,
+}
+
+impl ClaimKind {
+    /// Name of the instruction, as printed by `watch` and `simulate-claim`.
+    pub fn name(self) -> &'static str {
+        match self {
+            ClaimKind::NewClaim => "new_claim",
+            ClaimKind::ClaimLocked => "claim_locked",
+        }
+    }
+}
+
+/// A claim read from the logs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchedClaim {
+    pub kind: ClaimKind,
+    pub claimant: Pubkey,
+    pub amount: u64,
+    /// Chain time of a new claim. `ClaimedEvent` carries none.
+    pub timestamp: Option<i64>,
+}
+
+/// Claim events `program_id` emitted in a transaction's `logs`. Event data is attributed to
+/// the program running when it was logged, so another program writing lookalike data is not
+/// mistaken for the distributor.
+pub fn claim_events(logs: &[String], program_id: &Pubkey) -> Vec<WatchedClaim> {
+    let program_id = program_id.to_string();
+    let mut invoked: Vec<&str> = vec![];
+    let mut claims = vec![];
+    for line in logs {
+        let Some(line) = line.strip_prefix("Program ") else {
+            continue;
+        };
+        if let Some(data) = line.strip_prefix("data: ") {
+            if invoked.last() == Some(&program_id.as_str()) {
+                claims.extend(decode_claim_event(data));
+            }
+            continue;
+        }
+        let mut words = line.split_whitespace();
+        let (Some(program), Some(status)) = (words.next(), words.next()) else {
+            continue;
+        };
+        match status {
+            "invoke" => invoked.push(program),
+            "success" | "failed:" => {
+                invoked.pop();
+            }
+            _ => {}
+        }
+    }
+    claims
+}
+
+fn decode_claim_event(data: &str) -> Option<WatchedClaim> {
+    let data = BASE64.decode(data).ok()?;
+    if let Some(mut event) = data.strip_prefix(NewClaimEvent::DISCRIMINATOR) {
+        let event = NewClaimEvent::deserialize(&mut event).ok()?;
+        return Some(WatchedClaim {
+            kind: ClaimKind::NewClaim,
+            claimant: event.claimant,
+            amount: event.amount,
+            timestamp: Some(event.timestamp),
+        });
+    }
+    let mut event = data.strip_prefix(ClaimedEvent::DISCRIMINATOR)?;
+    let event = ClaimedEvent::deserialize(&mut event).ok()?;
+    Some(WatchedClaim {
+        kind: ClaimKind::ClaimLocked,
+        claimant: event.claimant,
+        amount: event.amount,
+        timestamp: None,
+    })
+}
+
+/// Websocket endpoint of an RPC url, the way the Solana CLI derives it: `ws` or `wss` in
+/// place of `http` or `https`, and the next port when one is given, e.g. 8899 to 8900.
+pub fn websocket_url(rpc_url: &str) -> Result<String, String> {
+    let mut url = Url::parse(rpc_url).map_err(|e| format!("invalid RPC url {rpc_url}: {e}"))?;
+    let scheme = match url.scheme() {
+        "https" | "wss" => "wss",
+        _ => "ws",
+    };
+    url.set_scheme(scheme)
+        .map_err(|_| format!("cannot derive a websocket url from {rpc_url}"))?;
+    if let Some(port) = url.port() {
+        url.set_port(Some(port + 1))
+            .map_err(|_| format!("cannot derive a websocket url from {rpc_url}"))?;
+    }
+    Ok(url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::Event;
+
+    use super::*;
+
+    fn data_line(event: &impl Event) -> String {
+        format!("Program data: {}", BASE64.encode(event.data()))
+    }
+
+    #[test]
+    fn test_claim_events() {
+        let program_id = merkle_distributor::id();
+        let other_program = Pubkey::new_unique();
+        let claimant = Pubkey::new_unique();
+        let new_claim = NewClaimEvent {
+            claimant,
+            timestamp: 1_700_000_000,
+            amount: 100,
+            transfer_fee: 0,
+            category_breakdown: None,
+        };
+        let claimed = ClaimedEvent {
+            claimant,
+            amount: 50,
+            transfer_fee: 0,
+        };
+        let logs = vec![
+            format!("Program {program_id} invoke [1]"),
+            "Program log: Instruction: NewClaim".to_string(),
+            format!("Program {other_program} invoke [2]"),
+            // Logged by the inner program, not the distributor
+            data_line(&claimed),
+            format!("Program {other_program} success"),
+            data_line(&new_claim),
+            format!("Program {program_id} consumed 50000 of 200000 compute units"),
+            format!("Program {program_id} success"),
+            format!("Program {program_id} invoke [1]"),
+            data_line(&claimed),
+            "Program data: not base64".to_string(),
+            format!("Program {program_id} success"),
+            format!("Program {other_program} invoke [1]"),
+            data_line(&new_claim),
+            format!("Program {other_program} failed: custom program error: 0x1"),
+        ];
+
+        assert_eq!(
+            claim_events(&logs, &program_id),
+            vec![
+                WatchedClaim {
+                    kind: ClaimKind::NewClaim,
+                    claimant,
+                    amount: 100,
+                    timestamp: Some(1_700_000_000),
+                },
+                WatchedClaim {
+                    kind: ClaimKind::ClaimLocked,
+                    claimant,
+                    amount: 50,
+                    timestamp: None,
+                },
+            ]
+        );
+        assert!(claim_events(&logs, &Pubkey::new_unique()).is_empty());
+        assert_eq!(ClaimKind::NewClaim.name(), "new_claim");
+        assert_eq!(ClaimKind::ClaimLocked.name(), "claim_locked");
+    }
+
+    #[test]
+    fn test_websocket_url() {
+        assert_eq!(
+            websocket_url("http://127.0.0.1:8899").unwrap(),
+            "ws://127.0.0.1:8900/"
+        );
+        assert_eq!(
+            websocket_url("https://api.devnet.solana.com").unwrap(),
+            "wss://api.devnet.solana.com/"
+        );
+        assert_eq!(
+            websocket_url("https://rpc.example.com/?api-key=abc").unwrap(),
+            "wss://rpc.example.com/?api-key=abc"
+        );
+        assert!(websocket_url("not a url").is_err());
+    }
+}
@@ -0,0 +1,57 @@
+//! Serial vs parallel merkle tree construction.
+//! Run with `cargo bench -p jito-merkle-tree --features parallel`.
+
+use std::time::{Duration, Instant};
+
+use jito_merkle_tree::{
+    tree_node::TreeNode,
+    utils::{build_merkle_tree, build_merkle_tree_parallel},
+};
+use solana_program::pubkey::Pubkey;
+
+const SAMPLES: usize = 5;
+
+fn nodes(num_nodes: u64) -> Vec<TreeNode> {
+    (0..num_nodes)
+        .map(|i| TreeNode {
+            claimant: Pubkey::new_unique(),
+            proof: None,
+            total_unlocked_staker: i,
+            total_locked_staker: i,
+            total_unlocked_searcher: 0,
+            total_locked_searcher: 0,
+            total_unlocked_validator: 0,
+            total_locked_validator: 0,
+            unlock_schedule: None,
+        })
+        .collect()
+}
+
+/// Fastest of [SAMPLES] runs, each on a fresh copy of `nodes`.
+fn bench(nodes: &[TreeNode], build: impl Fn(&mut [TreeNode])) -> Duration {
+    (0..SAMPLES)
+        .map(|_| {
+            let mut nodes = nodes.to_vec();
+            let start = Instant::now();
+            build(&mut nodes);
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    for num_nodes in [10_000, 100_000, 500_000] {
+        let nodes = nodes(num_nodes);
+        let serial = bench(&nodes, |nodes| {
+            build_merkle_tree(nodes);
+        });
+        let parallel = bench(&nodes, |nodes| {
+            build_merkle_tree_parallel(nodes);
+        });
+        println!(
+            "{num_nodes:>7} nodes: serial {serial:?}, parallel {parallel:?}, speedup {:.1}x",
+            serial.as_secs_f64() / parallel.as_secs_f64()
+        );
+    }
+}
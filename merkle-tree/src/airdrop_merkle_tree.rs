@@ -1,9 +1,10 @@
 use std::{
     collections::{HashMap, HashSet},
     fs::File,
-    io::{BufReader, Write},
-    path::PathBuf,
+    io::{BufRead, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
     result,
+    sync::OnceLock,
 };
 
 use indexmap::IndexMap;
@@ -12,11 +13,14 @@ use serde::{Deserialize, Serialize};
 use solana_program::{hash::hashv, pubkey::Pubkey};
 
 use crate::{
-    csv_entry::CsvEntry,
-    error::{MerkleTreeError, MerkleTreeError::MerkleValidationError},
+    csv_entry::{check_duplicate_claimants, CsvEntry, DecimalCsvEntry},
+    error::{DistributorError, MerkleTreeError, MerkleTreeError::MerkleValidationError},
     merkle_tree::MerkleTree,
-    tree_node::TreeNode,
-    utils::{get_max_total_claim, get_proof},
+    tree_node::{TreeNode, UnlockSchedule, MINT_DECIMALS},
+    utils::{
+        get_max_total_claim, get_proof, ProgressCallback, TreeBuildProgress, TreeBuildStage,
+        PROGRESS_CHUNK,
+    },
 };
 
 // We need to discern between leaf and intermediate nodes to prevent trivial second
@@ -24,25 +28,108 @@ use crate::{
 // https://flawed.net.nz/2018/02/21/attacking-merkle-trees-with-a-second-preimage-attack
 const LEAF_PREFIX: &[u8] = &[0];
 
+/// Layout version written to JSON tree files. Files from before versioning carry no version
+/// and load as 0; [AirdropMerkleTree::new_from_file] migrates them and rejects newer versions.
+pub const TREE_FILE_VERSION: u64 = 1;
+
+/// Prefix of bincode tree files, lets [AirdropMerkleTree::new_from_file] tell them from JSON.
+const BINCODE_MAGIC: &[u8; 8] = b"AMTREE\x00\x01";
+
+/// One claimant's leaf and proof, written by [AirdropMerkleTree::write_proofs_to_dir] so a claim
+/// UI can fetch a single small file instead of the whole tree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClaimantProof {
+    /// Base58 pubkey of the claimant
+    pub claimant: String,
+    pub amount_unlocked: u64,
+    pub amount_locked: u64,
+    /// Per-node vesting window, part of the leaf when present
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unlock_schedule: Option<UnlockSchedule>,
+    pub proof: Vec<[u8; 32]>,
+    pub merkle_root: [u8; 32],
+}
+
+/// How [AirdropMerkleTree::new_from_csv_with_options] and
+/// [AirdropMerkleTree::new_from_json_with_options] read allocations.
+#[derive(Clone, Copy, Default)]
+pub struct AllocationOptions<'a> {
+    /// Mint decimals of decimal UI amounts such as `10.5`. None for whole tokens of a
+    /// [crate::tree_node::MINT_DECIMALS] mint
+    pub decimals: Option<u32>,
+    /// Sum the amounts of a claimant listed more than once instead of failing
+    pub merge_duplicates: bool,
+    /// Receives the progress of building the tree, see [AirdropMerkleTree::new_with_progress]
+    pub progress: Option<ProgressCallback<'a>>,
+    /// Keep claimants allocated nothing instead of failing, see [check_empty_nodes]
+    pub allow_empty_nodes: bool,
+    /// Clawback start of the distributor the tree is for. Per-node unlock schedules ending
+    /// after it fail, see [check_unlock_schedules_end_by]
+    pub clawback_start_ts: Option<i64>,
+}
+
+impl std::fmt::Debug for AllocationOptions<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AllocationOptions")
+            .field("decimals", &self.decimals)
+            .field("merge_duplicates", &self.merge_duplicates)
+            .field("progress", &self.progress.is_some())
+            .field("allow_empty_nodes", &self.allow_empty_nodes)
+            .field("clawback_start_ts", &self.clawback_start_ts)
+            .finish()
+    }
+}
+
 /// Merkle Tree which will be used to distribute tokens to claimants.
 /// Contains all the information necessary to verify claims against the Merkle Tree.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AirdropMerkleTree {
+    /// Layout version of the file the tree was read from, see [TREE_FILE_VERSION]
+    #[serde(default)]
+    pub version: u64,
     /// The merkle root, which is uploaded on-chain
     pub merkle_root: [u8; 32],
     pub max_num_nodes: u64,
     pub max_total_claim: u64,
-    pub tree_nodes: Vec<TreeNode>,
+    /// Tree format flag: leaves may commit to a per-node unlock schedule.
+    /// Trees without the flag use the distributor's global schedule for every node.
+    #[serde(default)]
+    pub per_node_schedules: bool,
+    /// Read with [AirdropMerkleTree::tree_nodes] and edited through
+    /// [AirdropMerkleTree::tree_nodes_mut], which keeps `claimant_indices` in step
+    tree_nodes: Vec<TreeNode>,
+    /// Position of each claimant in `tree_nodes`, built on the first lookup and dropped
+    /// whenever the nodes may change
+    #[serde(skip)]
+    claimant_indices: OnceLock<HashMap<Pubkey, usize>>,
 }
 
-pub type Result<T> = result::Result<T, MerkleTreeError>;
+pub type Result<T> = result::Result<T, DistributorError>;
 
 impl AirdropMerkleTree {
     pub fn new(tree_nodes: Vec<TreeNode>) -> Result<Self> {
+        Self::new_with_progress(tree_nodes, &|_| {})
+    }
+
+    /// [AirdropMerkleTree::new] reporting how many nodes have been hashed, proven and verified
+    /// to `progress`, so a caller can show how far a large build is.
+    pub fn new_with_progress(
+        tree_nodes: Vec<TreeNode>,
+        progress: ProgressCallback,
+    ) -> Result<Self> {
         // Combine tree nodes with the same claimant, while retaining original order
         let mut tree_nodes_map: IndexMap<Pubkey, TreeNode> = IndexMap::new();
         for tree_node in tree_nodes {
             let claimant = tree_node.claimant;
+            if let Some(existing) = tree_nodes_map.get(&claimant) {
+                if existing.unlock_schedule != tree_node.unlock_schedule {
+                    return Err(MerkleValidationError(format!(
+                        "Claimant {} has conflicting unlock schedules",
+                        claimant
+                    ))
+                    .into());
+                }
+            }
             tree_nodes_map
                 .entry(claimant)
                 .and_modify(|n| {
@@ -77,47 +164,217 @@ impl AirdropMerkleTree {
         // Convert IndexMap back to Vec while preserving the order
         let mut tree_nodes: Vec<TreeNode> = tree_nodes_map.values().cloned().collect();
 
-        let hashed_nodes = tree_nodes
-            .iter()
-            .map(|claim_info| claim_info.hash().to_bytes())
-            .collect::<Vec<_>>();
-
-        let tree = MerkleTree::new(&hashed_nodes[..], true);
-
-        for (i, tree_node) in tree_nodes.iter_mut().enumerate() {
-            tree_node.proof = Some(get_proof(&tree, i));
-        }
+        #[cfg(feature = "parallel")]
+        let tree =
+            crate::utils::build_merkle_tree_parallel_with_progress(&mut tree_nodes, progress);
+        #[cfg(not(feature = "parallel"))]
+        let tree = crate::utils::build_merkle_tree_with_progress(&mut tree_nodes, progress);
 
         let max_total_claim = get_max_total_claim(tree_nodes.as_ref());
+        let per_node_schedules = tree_nodes.iter().any(|n| n.unlock_schedule.is_some());
         let tree = AirdropMerkleTree {
+            version: TREE_FILE_VERSION,
             merkle_root: tree
                 .get_root()
                 .ok_or(MerkleTreeError::MerkleRootError)?
                 .to_bytes(),
             max_num_nodes: tree_nodes.len() as u64,
             max_total_claim,
+            per_node_schedules,
             tree_nodes,
+            claimant_indices: OnceLock::new(),
         };
 
-        tree.validate()?;
+        tree.validate(progress)?;
         Ok(tree)
     }
 
+    /// [AirdropMerkleTree::new], or [AirdropMerkleTree::new_with_progress] when `options` has
+    /// a progress callback. Fails on claimants allocated nothing unless
+    /// `options.allow_empty_nodes` is set, and on unlock schedules ending after
+    /// `options.clawback_start_ts`.
+    fn new_with_options(tree_nodes: Vec<TreeNode>, options: AllocationOptions) -> Result<Self> {
+        if !options.allow_empty_nodes {
+            check_empty_nodes(&tree_nodes)?;
+        }
+        if let Some(clawback_start_ts) = options.clawback_start_ts {
+            check_unlock_schedules_end_by(&tree_nodes, clawback_start_ts)?;
+        }
+        match options.progress {
+            Some(progress) => Self::new_with_progress(tree_nodes, progress),
+            None => Self::new(tree_nodes),
+        }
+    }
+
     /// Load a merkle tree from a csv path
     pub fn new_from_csv(path: &PathBuf) -> Result<Self> {
-        let csv_entries = CsvEntry::new_from_file(path)?;
-        let tree_nodes: Vec<TreeNode> = csv_entries.into_iter().map(TreeNode::from).collect();
-        let tree = Self::new(tree_nodes)?;
-        Ok(tree)
+        Self::new_from_csv_with_options(path, AllocationOptions::default())
+    }
+
+    /// Load a merkle tree from a csv path whose amounts are decimal UI amounts of a mint with
+    /// `decimals`, e.g. `10.5`
+    pub fn new_from_csv_with_decimals(path: &PathBuf, decimals: u32) -> Result<Self> {
+        Self::new_from_csv_with_options(
+            path,
+            AllocationOptions {
+                decimals: Some(decimals),
+                ..AllocationOptions::default()
+            },
+        )
+    }
+
+    /// Load a merkle tree from a csv path. Repeated claimants are rejected with the lines they
+    /// appear on unless `options.merge_duplicates` is set.
+    pub fn new_from_csv_with_options(path: &PathBuf, options: AllocationOptions) -> Result<Self> {
+        match options.decimals {
+            Some(decimals) => {
+                let entries = DecimalCsvEntry::new_from_file_with_lines(path)?;
+                if !options.merge_duplicates {
+                    check_duplicate_claimants(
+                        entries.iter().map(|(line, e)| (*line, e.pubkey.as_str())),
+                        "line",
+                    )?;
+                }
+                let entries = entries.into_iter().map(|(_, entry)| entry).collect();
+                Self::new_with_options(decimal_tree_nodes(entries, decimals)?, options)
+            }
+            None => {
+                let entries = CsvEntry::new_from_file_with_lines(path)?;
+                if !options.merge_duplicates {
+                    check_duplicate_claimants(
+                        entries.iter().map(|(line, e)| (*line, e.pubkey.as_str())),
+                        "line",
+                    )?;
+                }
+                let entries = entries.into_iter().map(|(_, entry)| entry).collect();
+                Self::new_with_options(tree_nodes(entries)?, options)
+            }
+        }
+    }
+
+    /// Load a merkle tree from a JSON array of entries with the CSV columns as fields.
+    /// Repeated claimants are rejected with their 1-based entry numbers unless
+    /// `options.merge_duplicates` is set.
+    pub fn new_from_json_with_options(path: &PathBuf, options: AllocationOptions) -> Result<Self> {
+        let numbered = |pubkeys: Vec<&str>| {
+            pubkeys
+                .into_iter()
+                .enumerate()
+                .map(|(i, pubkey)| (i as u64 + 1, pubkey))
+                .collect::<Vec<_>>()
+        };
+        match options.decimals {
+            Some(decimals) => {
+                let entries = DecimalCsvEntry::new_from_json_file(path)?;
+                if !options.merge_duplicates {
+                    check_duplicate_claimants(
+                        numbered(entries.iter().map(|e| e.pubkey.as_str()).collect()),
+                        "entry",
+                    )?;
+                }
+                Self::new_with_options(decimal_tree_nodes(entries, decimals)?, options)
+            }
+            None => {
+                let entries = CsvEntry::new_from_json_file(path)?;
+                if !options.merge_duplicates {
+                    check_duplicate_claimants(
+                        numbered(entries.iter().map(|e| e.pubkey.as_str()).collect()),
+                        "entry",
+                    )?;
+                }
+                Self::new_with_options(tree_nodes(entries)?, options)
+            }
+        }
+    }
+
+    /// Build a merkle tree from parsed CSV or JSON entries, rejecting claimants allocated
+    /// nothing
+    pub fn new_from_csv_entries(entries: Vec<CsvEntry>) -> Result<Self> {
+        Self::new_with_options(tree_nodes(entries)?, AllocationOptions::default())
+    }
+
+    /// Build a merkle tree from parsed entries with decimal amounts of a mint with `decimals`,
+    /// rejecting claimants allocated nothing
+    pub fn new_from_decimal_csv_entries(
+        entries: Vec<DecimalCsvEntry>,
+        decimals: u32,
+    ) -> Result<Self> {
+        Self::new_with_options(
+            decimal_tree_nodes(entries, decimals)?,
+            AllocationOptions::default(),
+        )
+    }
+
+    /// Combines several distributions into one tree. Claimants present in more than one source
+    /// get a single node summing each category, as repeated claimants within one source do;
+    /// proofs of the sources are discarded and rebuilt.
+    pub fn merge(sources: impl IntoIterator<Item = Vec<TreeNode>>) -> Result<Self> {
+        Self::new(sources.into_iter().flatten().collect())
+    }
+
+    /// The nodes of a csv path, without building a tree, for [AirdropMerkleTree::merge].
+    /// With `decimals`, amounts are decimal UI amounts of a mint with that many decimals.
+    pub fn nodes_from_csv(path: &PathBuf, decimals: Option<u32>) -> Result<Vec<TreeNode>> {
+        match decimals {
+            Some(decimals) => decimal_tree_nodes(DecimalCsvEntry::new_from_file(path)?, decimals),
+            None => tree_nodes(CsvEntry::new_from_file(path)?),
+        }
     }
 
-    /// Load a serialized merkle tree from file path
+    /// Load a serialized merkle tree from file path, JSON or bincode
     pub fn new_from_file(path: &PathBuf) -> Result<Self> {
         let file = File::open(path)?;
-        let reader = BufReader::new(file);
+        let mut reader = BufReader::new(file);
+        if reader.fill_buf()?.starts_with(BINCODE_MAGIC) {
+            return Self::read_bincode(reader);
+        }
         let tree: AirdropMerkleTree = serde_json::from_reader(reader)?;
 
-        Ok(tree)
+        tree.migrate()
+    }
+
+    /// Upgrades a tree read from an older file layout to [TREE_FILE_VERSION].
+    fn migrate(mut self) -> Result<Self> {
+        match self.version {
+            // Unversioned files predate per-node schedules, which serde already defaults
+            0 => {
+                self.version = TREE_FILE_VERSION;
+                Ok(self)
+            }
+            TREE_FILE_VERSION => Ok(self),
+            version => Err(MerkleValidationError(format!(
+                "merkle tree file version {version} is newer than this build supports \
+                 ({TREE_FILE_VERSION}); upgrade the CLI to read it"
+            ))
+            .into()),
+        }
+    }
+
+    /// Load a merkle tree written by [AirdropMerkleTree::write_to_file_bincode]
+    pub fn new_from_file_bincode(path: &PathBuf) -> Result<Self> {
+        Self::read_bincode(BufReader::new(File::open(path)?))
+    }
+
+    fn read_bincode(mut reader: impl BufRead) -> Result<Self> {
+        let mut magic = [0u8; BINCODE_MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if magic != *BINCODE_MAGIC {
+            return Err(MerkleValidationError("not a bincode merkle tree file".to_string()).into());
+        }
+        let tree: BincodeTree =
+            bincode::deserialize_from(reader).map_err(MerkleTreeError::BincodeError)?;
+        Ok(tree.into())
+    }
+
+    /// Write a merkle tree to a filepath in bincode, several times smaller and faster to load
+    /// than JSON for large airdrops
+    pub fn write_to_file_bincode(&self, path: &PathBuf) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(BINCODE_MAGIC)?;
+        bincode::serialize_into(&mut writer, &BincodeTree::from(self))
+            .map_err(MerkleTreeError::BincodeError)?;
+        writer.flush()?;
+        Ok(())
     }
 
     /// Write a merkle tree to a filepath
@@ -127,23 +384,88 @@ impl AirdropMerkleTree {
         file.write_all(serialized.as_bytes()).unwrap();
     }
 
-    pub fn get_node(&self, claimant: &Pubkey) -> TreeNode {
-        for i in self.tree_nodes.iter() {
-            if i.claimant == *claimant {
-                return i.clone();
-            }
+    /// Writes a `<claimant>.json` [ClaimantProof] per node into `dir`, creating it if needed.
+    /// Returns the number of files written.
+    pub fn write_proofs_to_dir(&self, dir: &Path) -> Result<usize> {
+        std::fs::create_dir_all(dir)?;
+        for node in &self.tree_nodes {
+            let proof = node.proof.clone().ok_or_else(|| {
+                MerkleValidationError(format!("Claimant {} has no proof", node.claimant))
+            })?;
+            let claimant_proof = ClaimantProof {
+                claimant: node.claimant.to_string(),
+                amount_unlocked: node.amount_unlocked(),
+                amount_locked: node.amount_locked(),
+                unlock_schedule: node.unlock_schedule,
+                proof,
+                merkle_root: self.merkle_root,
+            };
+            let file = File::create(dir.join(format!("{}.json", node.claimant)))?;
+            serde_json::to_writer(BufWriter::new(file), &claimant_proof)
+                .map_err(MerkleTreeError::SerdeError)?;
         }
+        Ok(self.tree_nodes.len())
+    }
+
+    /// The tree's nodes in leaf order
+    pub fn tree_nodes(&self) -> &[TreeNode] {
+        &self.tree_nodes
+    }
+
+    /// Mutable access to the nodes. Drops the claimant index, which the next lookup rebuilds
+    /// from the edited nodes.
+    pub fn tree_nodes_mut(&mut self) -> &mut [TreeNode] {
+        self.claimant_indices = OnceLock::new();
+        &mut self.tree_nodes
+    }
+
+    /// The tree's nodes in leaf order, consuming the tree
+    pub fn into_tree_nodes(self) -> Vec<TreeNode> {
+        self.tree_nodes
+    }
+
+    /// The claimant's node, None when the claimant is not part of the airdrop
+    pub fn get_node(&self, claimant: &Pubkey) -> Option<&TreeNode> {
+        self.claimant_index(claimant)
+            .map(|index| &self.tree_nodes[index])
+    }
+
+    /// The node at leaf position `index`, None past the last node
+    pub fn get_node_by_index(&self, index: usize) -> Option<&TreeNode> {
+        self.tree_nodes.get(index)
+    }
+
+    /// Leaf position of the claimant's node, None when the claimant is not part of the airdrop.
+    /// Looked up in a map built on the first call.
+    pub fn claimant_index(&self, claimant: &Pubkey) -> Option<usize> {
+        self.claimant_indices
+            .get_or_init(|| {
+                let mut indices = HashMap::with_capacity(self.tree_nodes.len());
+                for (index, node) in self.tree_nodes.iter().enumerate() {
+                    // The first node of a claimant wins, as in a scan
+                    indices.entry(node.claimant).or_insert(index);
+                }
+                indices
+            })
+            .get(claimant)
+            .copied()
+    }
 
-        panic!("Claimant not found in tree");
+    /// [AirdropMerkleTree::get_node] for claimants known to be in the tree, panics otherwise
+    pub fn get_node_unchecked(&self, claimant: &Pubkey) -> TreeNode {
+        self.get_node(claimant)
+            .expect("Claimant not found in tree")
+            .clone()
     }
 
-    fn validate(&self) -> Result<()> {
+    fn validate(&self, progress: ProgressCallback) -> Result<()> {
         // The Merkle tree can be at most height 32, implying a max node count of 2^32 - 1
         if self.max_num_nodes > 2u64.pow(32) - 1 {
             return Err(MerkleValidationError(format!(
                 "Max num nodes {} is greater than 2^32 - 1",
                 self.max_num_nodes
-            )));
+            ))
+            .into());
         }
 
         // validate that the length is equal to the max_num_nodes
@@ -152,16 +474,15 @@ impl AirdropMerkleTree {
                 "Tree nodes length {} does not match max_num_nodes {}",
                 self.tree_nodes.len(),
                 self.max_num_nodes
-            )));
+            ))
+            .into());
         }
 
         // validate that there are no duplicate claimants
         let unique_nodes: HashSet<_> = self.tree_nodes.iter().map(|n| n.claimant).collect();
 
         if unique_nodes.len() != self.tree_nodes.len() {
-            return Err(MerkleValidationError(
-                "Duplicate claimants found".to_string(),
-            ));
+            return Err(MerkleValidationError("Duplicate claimants found".to_string()).into());
         }
 
         // validate that sum is equal to max_total_claim
@@ -171,20 +492,108 @@ impl AirdropMerkleTree {
             return Err(MerkleValidationError(format!(
                 "Tree nodes sum {} does not match max_total_claim {}",
                 sum, self.max_total_claim
-            )));
+            ))
+            .into());
         }
 
-        if self.verify_proof().is_err() {
-            return Err(MerkleValidationError(
-                "Merkle root is invalid given nodes".to_string(),
-            ));
+        for node in self.tree_nodes.iter() {
+            if let Some(UnlockSchedule { start_ts, end_ts }) = node.unlock_schedule {
+                if !self.per_node_schedules {
+                    return Err(MerkleValidationError(format!(
+                        "Claimant {} has an unlock schedule but per_node_schedules is not set",
+                        node.claimant
+                    ))
+                    .into());
+                }
+                if start_ts >= end_ts {
+                    return Err(MerkleValidationError(format!(
+                        "Claimant {} unlock schedule start {} is not before end {}",
+                        node.claimant, start_ts, end_ts
+                    ))
+                    .into());
+                }
+            }
+        }
+
+        if self.verify_nodes_with_progress(progress).is_err() {
+            return Err(
+                MerkleValidationError("Merkle root is invalid given nodes".to_string()).into(),
+            );
         }
 
         Ok(())
     }
 
+    /// Height of the tree above its leaves, which is the length of every proof in it: the
+    /// last node of an odd level is paired with itself, so no leaf has a shorter path. Passed
+    /// to `new_distributor` as `tree_depth`.
+    pub fn tree_depth(&self) -> u8 {
+        let mut level_len = self.tree_nodes.len();
+        let mut depth = 0;
+        while level_len > 1 {
+            level_len = level_len.div_ceil(2);
+            depth += 1;
+        }
+        depth
+    }
+
+    /// Recomputes the merkle root from `tree_nodes`, ignoring the stored `merkle_root`
+    pub fn compute_root(&self) -> Result<[u8; 32]> {
+        let hashed_nodes: Vec<[u8; 32]> = self
+            .tree_nodes
+            .iter()
+            .map(|n| n.hash().to_bytes())
+            .collect();
+        Ok(MerkleTree::new(&hashed_nodes[..], true)
+            .get_root()
+            .ok_or(MerkleTreeError::MerkleRootError)?
+            .to_bytes())
+    }
+
+    /// Whether `proof` proves the claimant's allocation against `merkle_root`, hashing the leaf
+    /// exactly like `new_claim` does. Nodes with an unlock schedule need
+    /// [AirdropMerkleTree::verify_proof_with_schedule].
+    pub fn verify_proof(
+        &self,
+        claimant: &Pubkey,
+        amount_unlocked: u64,
+        amount_locked: u64,
+        proof: &[[u8; 32]],
+    ) -> bool {
+        self.verify_proof_with_schedule(claimant, amount_unlocked, amount_locked, None, proof)
+    }
+
+    /// [AirdropMerkleTree::verify_proof] for a node that may carry its own unlock schedule,
+    /// which is part of the leaf
+    pub fn verify_proof_with_schedule(
+        &self,
+        claimant: &Pubkey,
+        amount_unlocked: u64,
+        amount_locked: u64,
+        unlock_schedule: Option<UnlockSchedule>,
+        proof: &[[u8; 32]],
+    ) -> bool {
+        let node = TreeNode {
+            claimant: *claimant,
+            proof: None,
+            total_unlocked_staker: amount_unlocked,
+            total_locked_staker: amount_locked,
+            total_unlocked_searcher: 0,
+            total_locked_searcher: 0,
+            total_unlocked_validator: 0,
+            total_locked_validator: 0,
+            unlock_schedule,
+        };
+        let leaf = hashv(&[LEAF_PREFIX, &node.hash().to_bytes()]);
+        verify(proof.to_vec(), self.merkle_root, leaf.to_bytes())
+    }
+
     /// verify that the leaves of the merkle tree match the nodes
-    pub fn verify_proof(&self) -> Result<()> {
+    pub fn verify_nodes(&self) -> Result<()> {
+        self.verify_nodes_with_progress(&|_| {})
+    }
+
+    fn verify_nodes_with_progress(&self, progress: ProgressCallback) -> Result<()> {
         let root = self.merkle_root;
 
         // Recreate root given nodes
@@ -208,7 +617,14 @@ impl AirdropMerkleTree {
             let proof = get_proof(&mk, i);
 
             if !verify(proof, root, node.to_bytes()) {
-                return Err(MerkleValidationError("invalid merkle proof".to_string()));
+                return Err(MerkleValidationError("invalid merkle proof".to_string()).into());
+            }
+            if (i + 1) % PROGRESS_CHUNK == 0 || i + 1 == hashed_nodes.len() {
+                progress(TreeBuildProgress {
+                    stage: TreeBuildStage::Verifying,
+                    done: i + 1,
+                    total: hashed_nodes.len(),
+                });
             }
         }
 
@@ -224,9 +640,152 @@ impl AirdropMerkleTree {
     }
 }
 
+/// Fails if a claimant is allocated nothing once its nodes are combined, listing each such
+/// claimant. Such a node still takes one of the distributor's `max_num_nodes` slots, and
+/// claiming it creates a claim status while paying out nothing.
+pub fn check_empty_nodes(tree_nodes: &[TreeNode]) -> Result<()> {
+    let mut empty: IndexMap<Pubkey, bool> = IndexMap::new();
+    for node in tree_nodes {
+        *empty.entry(node.claimant).or_insert(true) &= node.is_empty();
+    }
+    let empty: Vec<String> = empty
+        .into_iter()
+        .filter(|(_, empty)| *empty)
+        .map(|(claimant, _)| claimant.to_string())
+        .collect();
+    if !empty.is_empty() {
+        return Err(MerkleValidationError(format!(
+            "Claimants allocated nothing: {}",
+            empty.join(", ")
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// Fails if a per-node unlock schedule ends after `clawback_start_ts`, listing each such
+/// claimant. Clawback empties the vault, so the part of the allocation unlocking after it can
+/// never be withdrawn.
+pub fn check_unlock_schedules_end_by(
+    tree_nodes: &[TreeNode],
+    clawback_start_ts: i64,
+) -> Result<()> {
+    let late: Vec<String> = tree_nodes
+        .iter()
+        .filter(|node| {
+            node.unlock_schedule
+                .is_some_and(|schedule| schedule.end_ts > clawback_start_ts)
+        })
+        .map(|node| node.claimant.to_string())
+        .collect();
+    if !late.is_empty() {
+        return Err(MerkleValidationError(format!(
+            "Unlock schedules ending after the clawback start {}: {}",
+            clawback_start_ts,
+            late.join(", ")
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// Nodes of entries with whole-token amounts of a [MINT_DECIMALS] mint.
+fn tree_nodes(entries: Vec<CsvEntry>) -> Result<Vec<TreeNode>> {
+    entries
+        .into_iter()
+        .map(|entry| TreeNode::from_csv_entry(entry, MINT_DECIMALS))
+        .collect()
+}
+
+fn decimal_tree_nodes(entries: Vec<DecimalCsvEntry>, decimals: u32) -> Result<Vec<TreeNode>> {
+    entries
+        .into_iter()
+        .map(|entry| TreeNode::from_decimal_csv_entry(entry, decimals))
+        .collect()
+}
+
+/// [AirdropMerkleTree] as stored in bincode files. Bincode is not self-describing, so no
+/// field may be skipped the way the JSON form skips empty unlock schedules.
+#[derive(Serialize, Deserialize)]
+struct BincodeTree {
+    merkle_root: [u8; 32],
+    max_num_nodes: u64,
+    max_total_claim: u64,
+    per_node_schedules: bool,
+    tree_nodes: Vec<BincodeTreeNode>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BincodeTreeNode {
+    claimant: Pubkey,
+    proof: Option<Vec<[u8; 32]>>,
+    total_unlocked_staker: u64,
+    total_locked_staker: u64,
+    total_unlocked_searcher: u64,
+    total_locked_searcher: u64,
+    total_unlocked_validator: u64,
+    total_locked_validator: u64,
+    unlock_schedule: Option<UnlockSchedule>,
+}
+
+impl From<&AirdropMerkleTree> for BincodeTree {
+    fn from(tree: &AirdropMerkleTree) -> Self {
+        Self {
+            merkle_root: tree.merkle_root,
+            max_num_nodes: tree.max_num_nodes,
+            max_total_claim: tree.max_total_claim,
+            per_node_schedules: tree.per_node_schedules,
+            tree_nodes: tree
+                .tree_nodes
+                .iter()
+                .map(|node| BincodeTreeNode {
+                    claimant: node.claimant,
+                    proof: node.proof.clone(),
+                    total_unlocked_staker: node.total_unlocked_staker,
+                    total_locked_staker: node.total_locked_staker,
+                    total_unlocked_searcher: node.total_unlocked_searcher,
+                    total_locked_searcher: node.total_locked_searcher,
+                    total_unlocked_validator: node.total_unlocked_validator,
+                    total_locked_validator: node.total_locked_validator,
+                    unlock_schedule: node.unlock_schedule,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<BincodeTree> for AirdropMerkleTree {
+    fn from(tree: BincodeTree) -> Self {
+        Self {
+            // Bincode files are versioned by BINCODE_MAGIC instead
+            version: TREE_FILE_VERSION,
+            merkle_root: tree.merkle_root,
+            max_num_nodes: tree.max_num_nodes,
+            max_total_claim: tree.max_total_claim,
+            per_node_schedules: tree.per_node_schedules,
+            claimant_indices: OnceLock::new(),
+            tree_nodes: tree
+                .tree_nodes
+                .into_iter()
+                .map(|node| TreeNode {
+                    claimant: node.claimant,
+                    proof: node.proof,
+                    total_unlocked_staker: node.total_unlocked_staker,
+                    total_locked_staker: node.total_locked_staker,
+                    total_unlocked_searcher: node.total_unlocked_searcher,
+                    total_locked_searcher: node.total_locked_searcher,
+                    total_unlocked_validator: node.total_unlocked_validator,
+                    total_locked_validator: node.total_locked_validator,
+                    unlock_schedule: node.unlock_schedule,
+                })
+                .collect(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::path::PathBuf;
+    use std::{path::PathBuf, str::FromStr, time::Instant};
 
     use solana_program::{pubkey, pubkey::Pubkey};
     use solana_sdk::{
@@ -264,6 +823,7 @@ mod tests {
                 total_locked_searcher: rand_balance(),
                 total_unlocked_validator: rand_balance(),
                 total_locked_validator: rand_balance(),
+                unlock_schedule: None,
             });
         }
 
@@ -283,9 +843,137 @@ mod tests {
             total_locked_searcher: 5,
             total_unlocked_validator: 6,
             total_locked_validator: 7,
+            unlock_schedule: None,
         }];
         let merkle_tree = AirdropMerkleTree::new(tree_nodes).unwrap();
-        assert!(merkle_tree.verify_proof().is_ok(), "verify failed");
+        assert!(merkle_tree.verify_nodes().is_ok(), "verify failed");
+    }
+
+    #[test]
+    fn test_verify_proof() {
+        let merkle_tree = bincode_test_tree(5);
+        for node in merkle_tree.tree_nodes.iter() {
+            let proof = node.proof.as_ref().unwrap();
+            assert!(merkle_tree.verify_proof(
+                &node.claimant,
+                node.amount_unlocked(),
+                node.amount_locked(),
+                proof
+            ));
+            // Any other amount, claimant or proof is rejected
+            assert!(!merkle_tree.verify_proof(
+                &node.claimant,
+                node.amount_unlocked(),
+                node.amount_locked() + 1,
+                proof
+            ));
+            assert!(!merkle_tree.verify_proof(
+                &Pubkey::new_unique(),
+                node.amount_unlocked(),
+                node.amount_locked(),
+                proof
+            ));
+            assert!(!merkle_tree.verify_proof(
+                &node.claimant,
+                node.amount_unlocked(),
+                node.amount_locked(),
+                &proof[1..]
+            ));
+        }
+    }
+
+    #[test]
+    fn test_verify_proof_with_schedule() {
+        let schedule = UnlockSchedule {
+            start_ts: 1_000,
+            end_ts: 2_000,
+        };
+        let merkle_tree = AirdropMerkleTree::new(vec![
+            new_scheduled_node(Some(schedule)),
+            new_scheduled_node(None),
+        ])
+        .unwrap();
+        for node in merkle_tree.tree_nodes.iter() {
+            assert!(merkle_tree.verify_proof_with_schedule(
+                &node.claimant,
+                node.amount_unlocked(),
+                node.amount_locked(),
+                node.unlock_schedule,
+                node.proof.as_ref().unwrap()
+            ));
+        }
+
+        // The schedule is part of the leaf
+        let scheduled = &merkle_tree.tree_nodes[0];
+        assert!(!merkle_tree.verify_proof(
+            &scheduled.claimant,
+            scheduled.amount_unlocked(),
+            scheduled.amount_locked(),
+            scheduled.proof.as_ref().unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_get_node() {
+        let merkle_tree = bincode_test_tree(3);
+        let node = &merkle_tree.tree_nodes[1];
+        assert_eq!(merkle_tree.get_node(&node.claimant), Some(node));
+        assert_eq!(merkle_tree.get_node_unchecked(&node.claimant), *node);
+        assert_eq!(merkle_tree.get_node(&Pubkey::new_unique()), None);
+    }
+
+    #[test]
+    fn test_claimant_index_matches_tree_nodes() {
+        let merkle_tree = bincode_test_tree(50);
+        for (index, node) in merkle_tree.tree_nodes.iter().enumerate() {
+            assert_eq!(merkle_tree.claimant_index(&node.claimant), Some(index));
+            assert_eq!(merkle_tree.get_node_by_index(index), Some(node));
+        }
+        assert_eq!(merkle_tree.get_node_by_index(50), None);
+        assert_eq!(merkle_tree.claimant_index(&Pubkey::new_unique()), None);
+
+        // A tree read back from a file builds its own index
+        let json = serde_json::to_vec(&merkle_tree).unwrap();
+        let read_back: AirdropMerkleTree = serde_json::from_slice(&json).unwrap();
+        let last = &merkle_tree.tree_nodes[49];
+        assert_eq!(read_back.claimant_index(&last.claimant), Some(49));
+    }
+
+    #[test]
+    fn test_tree_nodes_mut_rebuilds_claimant_index() {
+        let mut merkle_tree = bincode_test_tree(5);
+        let first = merkle_tree.tree_nodes()[0].claimant;
+        let replaced = merkle_tree.tree_nodes()[2].claimant;
+        // Builds the index
+        assert_eq!(merkle_tree.claimant_index(&first), Some(0));
+
+        let nodes = merkle_tree.tree_nodes_mut();
+        nodes.swap(0, 1);
+        let added = Pubkey::new_unique();
+        nodes[2].claimant = added;
+
+        assert_eq!(merkle_tree.claimant_index(&first), Some(1));
+        assert_eq!(merkle_tree.claimant_index(&added), Some(2));
+        assert_eq!(merkle_tree.claimant_index(&replaced), None);
+        assert_eq!(
+            merkle_tree.get_node(&added),
+            Some(&merkle_tree.tree_nodes()[2])
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Claimant not found in tree")]
+    fn test_get_node_unchecked_panics_on_missing_claimant() {
+        bincode_test_tree(3).get_node_unchecked(&Pubkey::new_unique());
+    }
+
+    #[test]
+    fn test_compute_root() {
+        let mut merkle_tree = bincode_test_tree(5);
+        assert_eq!(merkle_tree.compute_root().unwrap(), merkle_tree.merkle_root);
+
+        merkle_tree.tree_nodes_mut()[2].total_locked_staker += 1;
+        assert_ne!(merkle_tree.compute_root().unwrap(), merkle_tree.merkle_root);
     }
 
     #[test]
@@ -301,6 +989,7 @@ mod tests {
                 total_locked_searcher: 0,
                 total_unlocked_validator: 0,
                 total_locked_validator: 0,
+                unlock_schedule: None,
             },
             TreeNode {
                 claimant: pubkey!("EDGARWktv3nDxRYjufjdbZmryqGXceaFPoPpbUzdpqED"),
@@ -311,6 +1000,7 @@ mod tests {
                 total_locked_searcher: 0,
                 total_unlocked_validator: 0,
                 total_locked_validator: 0,
+                unlock_schedule: None,
             },
             TreeNode {
                 claimant: pubkey!("EDGARWktv3nDxRYjufjdbZmryqGXceaFPoPpbUzdpqEH"),
@@ -321,6 +1011,7 @@ mod tests {
                 total_locked_searcher: 0,
                 total_unlocked_validator: 0,
                 total_locked_validator: 0,
+                unlock_schedule: None,
             },
         ];
 
@@ -342,6 +1033,250 @@ mod tests {
     }
 
     // Test creating a merkle tree from Tree Nodes, where claimants are not unique
+    fn bincode_test_tree(num_nodes: u64) -> AirdropMerkleTree {
+        let tree_nodes = (0..num_nodes)
+            .map(|i| TreeNode {
+                claimant: Pubkey::new_unique(),
+                proof: None,
+                total_unlocked_staker: i,
+                total_locked_staker: 2 * i,
+                total_unlocked_searcher: 0,
+                total_locked_searcher: 0,
+                total_unlocked_validator: 0,
+                total_locked_validator: 0,
+                // Exercise the field JSON skips when empty
+                unlock_schedule: (i % 2 == 0).then_some(UnlockSchedule {
+                    start_ts: 100,
+                    end_ts: 200,
+                }),
+            })
+            .collect();
+        AirdropMerkleTree::new(tree_nodes).unwrap()
+    }
+
+    #[test]
+    fn test_bincode_round_trip() {
+        let merkle_tree = bincode_test_tree(10);
+        let path = PathBuf::from("merkle_tree_round_trip.bin");
+        merkle_tree.write_to_file_bincode(&path).unwrap();
+
+        // Detected by its header, whatever the extension
+        let read = AirdropMerkleTree::new_from_file(&path).unwrap();
+        let read_bincode = AirdropMerkleTree::new_from_file_bincode(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        for read in [read, read_bincode] {
+            assert_eq!(read.merkle_root, merkle_tree.merkle_root);
+            assert_eq!(read.max_total_claim, merkle_tree.max_total_claim);
+            assert_eq!(read.per_node_schedules, merkle_tree.per_node_schedules);
+            assert_eq!(read.tree_nodes, merkle_tree.tree_nodes);
+        }
+    }
+
+    #[test]
+    fn test_write_proofs_to_dir() {
+        let merkle_tree = bincode_test_tree(5);
+        let dir = PathBuf::from("proofs_test_export");
+        assert_eq!(merkle_tree.write_proofs_to_dir(&dir).unwrap(), 5);
+
+        for node in &merkle_tree.tree_nodes {
+            let file = File::open(dir.join(format!("{}.json", node.claimant))).unwrap();
+            let claimant_proof: ClaimantProof = serde_json::from_reader(file).unwrap();
+            assert_eq!(claimant_proof.merkle_root, merkle_tree.merkle_root);
+
+            // Rebuild the leaf from the file alone
+            let claimant = Pubkey::from_str(&claimant_proof.claimant).unwrap();
+            let mut fields = vec![
+                claimant.to_bytes().to_vec(),
+                claimant_proof.amount_unlocked.to_le_bytes().to_vec(),
+                claimant_proof.amount_locked.to_le_bytes().to_vec(),
+            ];
+            if let Some(schedule) = claimant_proof.unlock_schedule {
+                fields.push(schedule.start_ts.to_le_bytes().to_vec());
+                fields.push(schedule.end_ts.to_le_bytes().to_vec());
+            }
+            let fields: Vec<&[u8]> = fields.iter().map(Vec::as_slice).collect();
+            let leaf = hashv(&[LEAF_PREFIX, &hashv(&fields).to_bytes()]);
+            assert!(verify(
+                claimant_proof.proof,
+                claimant_proof.merkle_root,
+                leaf.to_bytes()
+            ));
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_new_from_file_bincode_rejects_json() {
+        let path = PathBuf::from("merkle_tree_not_bincode.json");
+        bincode_test_tree(2).write_to_file(&path);
+        let result = AirdropMerkleTree::new_from_file_bincode(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(result.is_err());
+    }
+
+    /// Size and load time of both formats for a 500k node tree. Bincode stores each proof
+    /// hash in 32 bytes where pretty JSON spends a line per byte, so files are over an order
+    /// of magnitude smaller and load correspondingly faster. Run with
+    /// `cargo test --release -p jito-merkle-tree -- --ignored --nocapture bincode_vs_json`.
+    #[test]
+    #[ignore]
+    fn test_bincode_vs_json_500k_nodes() {
+        let merkle_tree = bincode_test_tree(500_000);
+        let json_path = PathBuf::from("merkle_tree_500k.json");
+        let bincode_path = PathBuf::from("merkle_tree_500k.bin");
+        merkle_tree.write_to_file(&json_path);
+        merkle_tree.write_to_file_bincode(&bincode_path).unwrap();
+
+        let json_size = std::fs::metadata(&json_path).unwrap().len();
+        let bincode_size = std::fs::metadata(&bincode_path).unwrap().len();
+        let start = Instant::now();
+        AirdropMerkleTree::new_from_file(&json_path).unwrap();
+        let json_load = start.elapsed();
+        let start = Instant::now();
+        AirdropMerkleTree::new_from_file(&bincode_path).unwrap();
+        let bincode_load = start.elapsed();
+        std::fs::remove_file(&json_path).unwrap();
+        std::fs::remove_file(&bincode_path).unwrap();
+
+        println!("json: {json_size} bytes, loaded in {json_load:?}");
+        println!("bincode: {bincode_size} bytes, loaded in {bincode_load:?}");
+        assert!(bincode_size * 10 < json_size);
+        assert!(bincode_load < json_load);
+    }
+
+    #[test]
+    fn test_new_merkle_tree_from_decimal_csv() {
+        let path = PathBuf::from("./test_fixtures/test_csv_decimals.csv");
+        let merkle_tree = AirdropMerkleTree::new_from_csv_with_decimals(&path, 6).unwrap();
+
+        let staker = &merkle_tree.tree_nodes[0];
+        assert_eq!(staker.total_unlocked_staker, 10_500_000);
+        assert_eq!(staker.total_locked_staker, 250_000);
+        let validator = &merkle_tree.tree_nodes[1];
+        assert_eq!(validator.total_locked_validator, 1_000_000_001);
+        assert_eq!(merkle_tree.max_total_claim, 3_010_850_001);
+        assert!(merkle_tree.verify_nodes().is_ok());
+
+        // 1000.000001 needs 6 decimals
+        assert!(AirdropMerkleTree::new_from_csv_with_decimals(&path, 5).is_err());
+    }
+
+    #[test]
+    fn test_new_from_csv_rejects_duplicate_claimants() {
+        let path = PathBuf::from("./test_fixtures/test_csv_duplicates.csv");
+        let error = AirdropMerkleTree::new_from_csv(&path)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.contains("D4CDVpjBDB4L3KMm3mWPymSneQEpDgEatLbeYCMDD8Uh on lines 2, 4"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn test_new_from_csv_merges_duplicate_claimants() {
+        let path = PathBuf::from("./test_fixtures/test_csv_duplicates.csv");
+        let merkle_tree = AirdropMerkleTree::new_from_csv_with_options(
+            &path,
+            AllocationOptions {
+                merge_duplicates: true,
+                ..AllocationOptions::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(merkle_tree.tree_nodes.len(), 2);
+        let merged = &merkle_tree.tree_nodes[0];
+        assert_eq!(merged.amount_unlocked(), 1300 * u64::pow(10, 9));
+        assert_eq!(merged.amount_locked(), 700 * u64::pow(10, 9));
+        assert!(merkle_tree.verify_nodes().is_ok());
+    }
+
+    #[test]
+    fn test_new_from_csv_reports_progress() {
+        let path = PathBuf::from("./test_fixtures/test_csv_duplicates.csv");
+        let reports = std::sync::Mutex::new(vec![]);
+        let record = |progress: TreeBuildProgress| reports.lock().unwrap().push(progress);
+        AirdropMerkleTree::new_from_csv_with_options(
+            &path,
+            AllocationOptions {
+                merge_duplicates: true,
+                progress: Some(&record),
+                ..AllocationOptions::default()
+            },
+        )
+        .unwrap();
+
+        // Two claimants once merged, each stage reported when done
+        let reports = reports.into_inner().unwrap();
+        let stages: Vec<_> = reports.iter().map(|p| (p.stage, p.done, p.total)).collect();
+        assert_eq!(
+            stages,
+            vec![
+                (TreeBuildStage::Hashing, 2, 2),
+                (TreeBuildStage::Proofs, 2, 2),
+                (TreeBuildStage::Verifying, 2, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_new_from_csv_rejects_empty_nodes() {
+        let path = PathBuf::from("./test_fixtures/test_csv_empty_nodes.csv");
+        let error = AirdropMerkleTree::new_from_csv(&path)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.contains(
+                "Claimants allocated nothing: 8G9xE8awr9vA2PZWFTJSHNhS16KLnXYdV6XEaJP1a2Yx"
+            ),
+            "{error}"
+        );
+
+        let merkle_tree = AirdropMerkleTree::new_from_csv_with_options(
+            &path,
+            AllocationOptions {
+                allow_empty_nodes: true,
+                ..AllocationOptions::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(merkle_tree.max_num_nodes, 2);
+        assert!(merkle_tree.tree_nodes[1].is_empty());
+        assert!(merkle_tree.verify_nodes().is_ok());
+    }
+
+    #[test]
+    fn test_check_empty_nodes_combines_claimants() {
+        let mut empty = new_scheduled_node(None);
+        empty.total_unlocked_staker = 0;
+        empty.total_locked_staker = 0;
+        // Another node of the same claimant makes up for the empty one
+        let funded = TreeNode {
+            claimant: empty.claimant,
+            ..new_scheduled_node(None)
+        };
+        assert!(check_empty_nodes(&[empty.clone(), funded]).is_ok());
+        assert!(check_empty_nodes(&[empty.clone(), empty]).is_err());
+    }
+
+    #[test]
+    fn test_check_unlock_schedules_end_by_clawback_start() {
+        let schedule = UnlockSchedule {
+            start_ts: 1_000,
+            end_ts: 2_000,
+        };
+        let nodes = [new_scheduled_node(Some(schedule)), new_scheduled_node(None)];
+        assert!(check_unlock_schedules_end_by(&nodes, 2_000).is_ok());
+
+        let error = check_unlock_schedules_end_by(&nodes, 1_999)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains(&nodes[0].claimant.to_string()), "{error}");
+        assert!(!error.contains(&nodes[1].claimant.to_string()), "{error}");
+    }
+
     #[test]
     fn test_new_merkle_tree_duplicate_claimants() {
         let duplicate_pubkey = Pubkey::new_unique();
@@ -355,6 +1290,7 @@ mod tests {
                 total_locked_searcher: 40,
                 total_unlocked_validator: 50,
                 total_locked_validator: 60,
+                unlock_schedule: None,
             },
             TreeNode {
                 claimant: duplicate_pubkey,
@@ -365,6 +1301,7 @@ mod tests {
                 total_locked_searcher: 4,
                 total_unlocked_validator: 5,
                 total_locked_validator: 6,
+                unlock_schedule: None,
             },
             TreeNode {
                 claimant: Pubkey::new_unique(),
@@ -375,6 +1312,7 @@ mod tests {
                 total_locked_searcher: 0,
                 total_unlocked_validator: 0,
                 total_locked_validator: 0,
+                unlock_schedule: None,
             },
         ];
 
@@ -388,4 +1326,226 @@ mod tests {
         assert_eq!(tree.tree_nodes[0].total_unlocked_validator, 55);
         assert_eq!(tree.tree_nodes[0].total_locked_validator, 66);
     }
+
+    fn new_category_node(
+        claimant: Pubkey,
+        unlocked_staker: u64,
+        unlocked_validator: u64,
+    ) -> TreeNode {
+        TreeNode {
+            claimant,
+            proof: None,
+            total_unlocked_staker: unlocked_staker,
+            total_locked_staker: 0,
+            total_unlocked_searcher: 0,
+            total_locked_searcher: 0,
+            total_unlocked_validator: unlocked_validator,
+            total_locked_validator: 0,
+            unlock_schedule: None,
+        }
+    }
+
+    #[test]
+    fn test_merge_overlapping_distributions() {
+        let (shared, only_first, only_second) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let first = AirdropMerkleTree::new(vec![
+            new_category_node(shared, 100, 0),
+            new_category_node(only_first, 10, 0),
+        ])
+        .unwrap();
+        let second = vec![
+            new_category_node(only_second, 0, 7),
+            new_category_node(shared, 5, 20),
+        ];
+
+        let merged = AirdropMerkleTree::merge([first.tree_nodes.clone(), second]).unwrap();
+        assert_eq!(merged.max_num_nodes, 3);
+        assert_eq!(merged.max_total_claim, 142);
+        // Claimants keep the order they first appear in
+        let claimants: Vec<_> = merged.tree_nodes.iter().map(|n| n.claimant).collect();
+        assert_eq!(claimants, vec![shared, only_first, only_second]);
+        let shared_node = merged.get_node(&shared).unwrap();
+        assert_eq!(shared_node.total_unlocked_staker, 105);
+        assert_eq!(shared_node.total_unlocked_validator, 20);
+        assert_ne!(merged.merkle_root, first.merkle_root);
+        assert!(merged.verify_nodes().is_ok());
+    }
+
+    #[test]
+    fn test_merge_disjoint_distributions() {
+        let path = PathBuf::from("./test_fixtures/test_csv.csv");
+        let from_csv = AirdropMerkleTree::nodes_from_csv(&path, None).unwrap();
+        let csv_tree = AirdropMerkleTree::new_from_csv(&path).unwrap();
+        let other = AirdropMerkleTree::new(vec![
+            new_category_node(Pubkey::new_unique(), 1, 2),
+            new_category_node(Pubkey::new_unique(), 3, 4),
+        ])
+        .unwrap();
+
+        let merged =
+            AirdropMerkleTree::merge([from_csv.clone(), other.tree_nodes.clone()]).unwrap();
+        assert_eq!(
+            merged.max_num_nodes,
+            csv_tree.max_num_nodes + other.max_num_nodes
+        );
+        assert_eq!(
+            merged.max_total_claim,
+            csv_tree.max_total_claim + other.max_total_claim
+        );
+        for node in csv_tree.tree_nodes.iter().chain(&other.tree_nodes) {
+            let merged_node = merged.get_node(&node.claimant).unwrap();
+            assert_eq!(merged_node.amount_unlocked(), node.amount_unlocked());
+            assert_eq!(merged_node.amount_locked(), node.amount_locked());
+        }
+        assert!(merged.verify_nodes().is_ok());
+
+        // A single source merges into the tree it would build alone
+        let alone = AirdropMerkleTree::merge([from_csv]).unwrap();
+        assert_eq!(alone.merkle_root, csv_tree.merkle_root);
+    }
+
+    fn new_scheduled_node(unlock_schedule: Option<UnlockSchedule>) -> TreeNode {
+        TreeNode {
+            claimant: Pubkey::new_unique(),
+            proof: None,
+            total_unlocked_staker: 10,
+            total_locked_staker: 100,
+            total_unlocked_searcher: 0,
+            total_locked_searcher: 0,
+            total_unlocked_validator: 0,
+            total_locked_validator: 0,
+            unlock_schedule,
+        }
+    }
+
+    #[test]
+    fn test_tree_depth_matches_proof_lengths() {
+        for (num_nodes, depth) in [(1, 0), (2, 1), (3, 2), (4, 2), (5, 3), (9, 4)] {
+            let tree =
+                AirdropMerkleTree::new((0..num_nodes).map(|_| new_scheduled_node(None)).collect())
+                    .unwrap();
+            assert_eq!(tree.tree_depth(), depth, "{num_nodes} nodes");
+            for node in &tree.tree_nodes {
+                assert_eq!(node.proof.as_ref().unwrap().len(), usize::from(depth));
+            }
+        }
+    }
+
+    #[test]
+    fn test_new_merkle_tree_without_schedules_uses_global_format() {
+        let tree = AirdropMerkleTree::new(vec![new_scheduled_node(None)]).unwrap();
+        assert!(!tree.per_node_schedules);
+
+        let serialized = serde_json::to_string(&tree).unwrap();
+        let legacy = serialized.replace(",\"per_node_schedules\":false", "");
+        let deserialized: AirdropMerkleTree = serde_json::from_str(&legacy).unwrap();
+        assert!(!deserialized.per_node_schedules);
+        assert!(deserialized.verify_nodes().is_ok());
+    }
+
+    #[test]
+    fn test_new_from_file_versions() {
+        let tree = AirdropMerkleTree::new(vec![new_scheduled_node(None)]).unwrap();
+        let path = PathBuf::from("merkle_tree_versions.json");
+        tree.write_to_file(&path);
+        let written: serde_json::Value =
+            serde_json::from_reader(File::open(&path).unwrap()).unwrap();
+        assert_eq!(written["version"], TREE_FILE_VERSION);
+
+        // Files from before versioning load as the current version
+        let mut legacy = written.clone();
+        legacy.as_object_mut().unwrap().remove("version");
+        std::fs::write(&path, legacy.to_string()).unwrap();
+        let migrated = AirdropMerkleTree::new_from_file(&path).unwrap();
+        assert_eq!(migrated.version, TREE_FILE_VERSION);
+        assert_eq!(migrated.merkle_root, tree.merkle_root);
+
+        let mut future = written;
+        future["version"] = (TREE_FILE_VERSION + 1).into();
+        std::fs::write(&path, future.to_string()).unwrap();
+        let error = AirdropMerkleTree::new_from_file(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(error.to_string().contains("newer than this build supports"));
+    }
+
+    #[test]
+    fn test_new_merkle_tree_with_per_node_schedules() {
+        let team = UnlockSchedule {
+            start_ts: 1_000,
+            end_ts: 100_000,
+        };
+        let community = UnlockSchedule {
+            start_ts: 1_000,
+            end_ts: 2_000,
+        };
+        let tree_nodes = vec![
+            new_scheduled_node(Some(team)),
+            new_scheduled_node(Some(community)),
+            new_scheduled_node(None),
+        ];
+
+        let tree = AirdropMerkleTree::new(tree_nodes).unwrap();
+        assert!(tree.per_node_schedules);
+        assert_eq!(tree.tree_nodes[0].unlock_schedule, Some(team));
+        assert_eq!(tree.tree_nodes[1].unlock_schedule, Some(community));
+        assert_eq!(tree.tree_nodes[2].unlock_schedule, None);
+
+        // Every proof must verify against the leaf including its schedule
+        for node in tree.tree_nodes.iter() {
+            let leaf = hashv(&[LEAF_PREFIX, &node.hash().to_bytes()]);
+            assert!(verify(
+                node.proof.clone().unwrap(),
+                tree.merkle_root,
+                leaf.to_bytes()
+            ));
+        }
+
+        // A proof does not verify if the claimant swaps in a different schedule
+        let mut tampered = tree.tree_nodes[1].clone();
+        tampered.unlock_schedule = Some(team);
+        let leaf = hashv(&[LEAF_PREFIX, &tampered.hash().to_bytes()]);
+        assert!(!verify(
+            tampered.proof.unwrap(),
+            tree.merkle_root,
+            leaf.to_bytes()
+        ));
+    }
+
+    #[test]
+    fn test_new_merkle_tree_invalid_schedule() {
+        let tree_nodes = vec![new_scheduled_node(Some(UnlockSchedule {
+            start_ts: 2_000,
+            end_ts: 1_000,
+        }))];
+        assert!(AirdropMerkleTree::new(tree_nodes).is_err());
+    }
+
+    #[test]
+    fn test_new_merkle_tree_conflicting_duplicate_schedules() {
+        let first = new_scheduled_node(Some(UnlockSchedule {
+            start_ts: 1_000,
+            end_ts: 2_000,
+        }));
+        let mut second = first.clone();
+        second.unlock_schedule = Some(UnlockSchedule {
+            start_ts: 1_000,
+            end_ts: 3_000,
+        });
+        assert!(AirdropMerkleTree::new(vec![first, second]).is_err());
+    }
+
+    #[test]
+    fn test_schedules_require_tree_format_flag() {
+        let mut tree = AirdropMerkleTree::new(vec![new_scheduled_node(Some(UnlockSchedule {
+            start_ts: 1_000,
+            end_ts: 2_000,
+        }))])
+        .unwrap();
+        tree.per_node_schedules = false;
+        assert!(tree.validate().is_err());
+    }
 }
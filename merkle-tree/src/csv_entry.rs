@@ -1,10 +1,16 @@
-use std::{fs::File, path::PathBuf, result};
+use std::{fs::File, io::BufReader, path::PathBuf, result, str::FromStr};
 
-use serde::{Deserialize, Serialize};
+use indexmap::IndexMap;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use solana_program::pubkey::Pubkey;
 
-use crate::error::MerkleTreeError;
+use crate::error::{DistributorError, MerkleTreeError::MerkleValidationError};
 
-pub type Result<T> = result::Result<T, MerkleTreeError>;
+pub type Result<T> = result::Result<T, DistributorError>;
+
+/// Most errors reported for one allocation file. Reading stops once this many rows failed, so
+/// operators can fix many rows per pass without drowning in output for a wholly broken file.
+pub const MAX_REPORTED_ERRORS: usize = 25;
 
 /// Airdrop Category. Users can belong to multiple categories
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
@@ -19,29 +25,271 @@ pub enum AirdropCategory {
 pub struct CsvEntry {
     /// Pubkey of the claimant; will be responsible for signing the claim
     pub pubkey: String,
-    /// amount unlocked, (ui amount)
+    /// amount unlocked, (ui amount) in whole tokens, scaled to base units when the node is built
     pub amount_unlocked: u64,
-    /// amount locked, (ui amount)
+    /// amount locked, (ui amount) in whole tokens, scaled to base units when the node is built
     pub amount_locked: u64,
     /// Category
     pub category: AirdropCategory,
+    /// Optional per-node unlock start, must be set together with `end_ts`
+    #[serde(default)]
+    pub start_ts: Option<i64>,
+    /// Optional per-node unlock end, must be set together with `start_ts`
+    #[serde(default)]
+    pub end_ts: Option<i64>,
 }
 
 impl CsvEntry {
     pub fn new_from_file(path: &PathBuf) -> Result<Vec<Self>> {
-        let file = File::open(path)?;
-        let mut rdr = csv::Reader::from_reader(file);
+        let entries = Self::new_from_file_with_lines(path)?;
+        Ok(entries.into_iter().map(|(_, entry)| entry).collect())
+    }
 
-        let mut entries = Vec::new();
-        for result in rdr.deserialize() {
-            let record: CsvEntry = result.unwrap();
-            entries.push(record);
-        }
+    /// Like [CsvEntry::new_from_file], with the line each entry starts on
+    pub fn new_from_file_with_lines(path: &PathBuf) -> Result<Vec<(u64, Self)>> {
+        read_csv_records(path, |entry: &Self| {
+            check_entry(&entry.pubkey, entry.start_ts, entry.end_ts)
+        })
+    }
 
+    /// Reads a JSON array of objects with the same fields as the CSV columns
+    pub fn new_from_json_file(path: &PathBuf) -> Result<Vec<Self>> {
+        let entries: Vec<Self> = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        check_json_entries(&entries, |entry| {
+            check_entry(&entry.pubkey, entry.start_ts, entry.end_ts)
+        })?;
         Ok(entries)
     }
 }
 
+/// A CSV entry whose amounts are decimal UI amounts such as `10.5`, for mints whose decimals
+/// are passed explicitly. Same columns as [CsvEntry].
+#[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct DecimalCsvEntry {
+    /// Pubkey of the claimant; will be responsible for signing the claim
+    pub pubkey: String,
+    /// amount unlocked, (decimal ui amount)
+    pub amount_unlocked: String,
+    /// amount locked, (decimal ui amount)
+    pub amount_locked: String,
+    /// Category
+    pub category: AirdropCategory,
+    /// Optional per-node unlock start, must be set together with `end_ts`
+    #[serde(default)]
+    pub start_ts: Option<i64>,
+    /// Optional per-node unlock end, must be set together with `start_ts`
+    #[serde(default)]
+    pub end_ts: Option<i64>,
+}
+
+impl DecimalCsvEntry {
+    pub fn new_from_file(path: &PathBuf) -> Result<Vec<Self>> {
+        let entries = Self::new_from_file_with_lines(path)?;
+        Ok(entries.into_iter().map(|(_, entry)| entry).collect())
+    }
+
+    /// Like [DecimalCsvEntry::new_from_file], with the line each entry starts on
+    pub fn new_from_file_with_lines(path: &PathBuf) -> Result<Vec<(u64, Self)>> {
+        read_csv_records(path, |entry: &Self| {
+            check_entry(&entry.pubkey, entry.start_ts, entry.end_ts)
+        })
+    }
+
+    /// Reads a JSON array of objects with the same fields as the CSV columns. Amounts are
+    /// strings, e.g. `"10.5"`, so they are never rounded through a float.
+    pub fn new_from_json_file(path: &PathBuf) -> Result<Vec<Self>> {
+        let entries: Vec<Self> = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        check_json_entries(&entries, |entry| {
+            check_entry(&entry.pubkey, entry.start_ts, entry.end_ts)
+        })?;
+        Ok(entries)
+    }
+}
+
+/// Reads every record of a CSV with a header row, along with the 1-based line it starts on.
+/// Rows that fail to parse or `check` are collected, up to [MAX_REPORTED_ERRORS], and
+/// reported together with their line, column and raw value.
+fn read_csv_records<T: DeserializeOwned>(
+    path: &PathBuf,
+    check: impl Fn(&T) -> result::Result<(), String>,
+) -> Result<Vec<(u64, T)>> {
+    let file = File::open(path)?;
+    let mut rdr = csv::Reader::from_reader(file);
+    let headers = rdr
+        .headers()
+        .map_err(|e| MerkleValidationError(format!("Invalid CSV: {e}")))?
+        .clone();
+
+    let mut records = Vec::new();
+    let mut errors = Vec::new();
+    for result in rdr.records() {
+        if errors.len() == MAX_REPORTED_ERRORS {
+            break;
+        }
+        let record = match result {
+            Ok(record) => record,
+            Err(e) => {
+                errors.push(e.to_string());
+                continue;
+            }
+        };
+        let line = record.position().map_or(0, |position| position.line());
+        match record.deserialize::<T>(Some(&headers)) {
+            Ok(entry) => match check(&entry) {
+                Ok(()) => records.push((line, entry)),
+                Err(e) => errors.push(format!("line {line}: {e}")),
+            },
+            Err(e) => errors.push(format!(
+                "line {line}: {}",
+                describe_field_error(&e, &headers, &record)
+            )),
+        }
+    }
+    fail_on_errors(errors)?;
+    Ok(records)
+}
+
+/// Names the column and raw value a CSV deserialization error is about. Errors serde raises
+/// itself, such as an unknown category, carry no column but already quote the value.
+fn describe_field_error(
+    error: &csv::Error,
+    headers: &csv::StringRecord,
+    record: &csv::StringRecord,
+) -> String {
+    if let csv::ErrorKind::Deserialize { err, .. } = error.kind() {
+        return match err.field() {
+            Some(field) => {
+                let column = headers.get(field as usize).unwrap_or("?");
+                let value = record.get(field as usize).unwrap_or("");
+                format!("invalid {column} '{value}': {}", err.kind())
+            }
+            None => err.kind().to_string(),
+        };
+    }
+    error.to_string()
+}
+
+/// Runs `check` over JSON entries, collecting failures by 1-based entry number.
+fn check_json_entries<T>(
+    entries: &[T],
+    check: impl Fn(&T) -> result::Result<(), String>,
+) -> Result<()> {
+    let errors = entries
+        .iter()
+        .enumerate()
+        .filter_map(|(i, entry)| check(entry).err().map(|e| format!("entry {}: {e}", i + 1)))
+        .take(MAX_REPORTED_ERRORS)
+        .collect();
+    fail_on_errors(errors)
+}
+
+fn fail_on_errors(errors: Vec<String>) -> Result<()> {
+    if errors.is_empty() {
+        return Ok(());
+    }
+    let truncated = if errors.len() == MAX_REPORTED_ERRORS {
+        format!(", stopped after {MAX_REPORTED_ERRORS}")
+    } else {
+        String::new()
+    };
+    Err(MerkleValidationError(format!(
+        "{} invalid entries{truncated}:\n  {}",
+        errors.len(),
+        errors.join("\n  ")
+    ))
+    .into())
+}
+
+/// Fails if a pubkey appears more than once, listing each repeated pubkey with the
+/// `location`s, e.g. line numbers, it appears at.
+pub fn check_duplicate_claimants<'a>(
+    pubkeys: impl IntoIterator<Item = (u64, &'a str)>,
+    location: &str,
+) -> Result<()> {
+    let mut locations: IndexMap<&str, Vec<u64>> = IndexMap::new();
+    for (at, pubkey) in pubkeys {
+        locations.entry(pubkey).or_default().push(at);
+    }
+    let duplicates: Vec<String> = locations
+        .into_iter()
+        .filter(|(_, at)| at.len() > 1)
+        .map(|(pubkey, at)| {
+            let at: Vec<String> = at.iter().map(u64::to_string).collect();
+            format!("{pubkey} on {location}s {}", at.join(", "))
+        })
+        .collect();
+    if !duplicates.is_empty() {
+        return Err(MerkleValidationError(format!(
+            "Duplicate claimants: {}",
+            duplicates.join("; ")
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+fn check_entry(
+    pubkey: &str,
+    start_ts: Option<i64>,
+    end_ts: Option<i64>,
+) -> result::Result<(), String> {
+    if Pubkey::from_str(pubkey).is_err() {
+        return Err(format!("invalid pubkey '{pubkey}'"));
+    }
+    if start_ts.is_some() != end_ts.is_some() {
+        return Err(format!(
+            "Claimant {pubkey} must set both start_ts and end_ts or neither"
+        ));
+    }
+    Ok(())
+}
+
+/// Converts a whole-token UI amount into base units of a mint with `decimals`, failing rather
+/// than wrapping when the result does not fit a u64.
+pub fn ui_amount_to_base_units(amount: u64, decimals: u32) -> Result<u64> {
+    10u64
+        .checked_pow(decimals)
+        .and_then(|scale| amount.checked_mul(scale))
+        .ok_or_else(|| {
+            MerkleValidationError(format!(
+                "Invalid amount {amount}: too large for a u64 in base units"
+            ))
+            .into()
+        })
+}
+
+/// Converts a decimal UI amount such as `10.5` into base units of a mint with `decimals`.
+/// Amounts with more fractional digits than the mint supports are rejected, never rounded.
+pub fn parse_ui_amount(amount: &str, decimals: u32) -> Result<u64> {
+    let invalid = |reason: &str| -> DistributorError {
+        MerkleValidationError(format!("Invalid amount {amount:?}: {reason}")).into()
+    };
+    let (whole, fraction) = amount.trim().split_once('.').unwrap_or((amount.trim(), ""));
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+        return Err(invalid("expected a non-negative decimal number"));
+    }
+    if fraction.len() > decimals as usize {
+        return Err(invalid(&format!(
+            "more than {decimals} fractional digits for a mint with {decimals} decimals"
+        )));
+    }
+
+    let overflow = || invalid("too large for a u64 in base units");
+    let parse = |s: &str| match s {
+        "" => Ok(0),
+        s => s.parse::<u64>().map_err(|_| overflow()),
+    };
+    let whole = parse(whole)?;
+    let fraction_units = parse(fraction)?;
+    let scale = 10u64.checked_pow(decimals).ok_or_else(overflow)?;
+    let fraction_scale = 10u64.pow(decimals - fraction.len() as u32);
+    whole
+        .checked_mul(scale)
+        .and_then(|whole| whole.checked_add(fraction_units * fraction_scale))
+        .ok_or_else(overflow)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -60,5 +308,122 @@ mod tests {
         assert_eq!(entries[0].amount_unlocked, 1000);
         assert_eq!(entries[0].amount_locked, 500);
         assert_eq!(entries[0].category, AirdropCategory::Staker);
+        assert_eq!(entries[0].start_ts, None);
+        assert_eq!(entries[0].end_ts, None);
+    }
+
+    #[test]
+    fn test_csv_parsing_with_lines() {
+        let path = PathBuf::from("./test_fixtures/test_csv.csv");
+        let entries = CsvEntry::new_from_file_with_lines(&path).expect("Failed to parse CSV");
+        let lines: Vec<u64> = entries.iter().map(|(line, _)| *line).collect();
+        assert_eq!(lines, vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn test_csv_errors_name_line_column_and_value() {
+        let path = PathBuf::from("./test_fixtures/test_csv_invalid.csv");
+        let error = CsvEntry::new_from_file(&path).unwrap_err().to_string();
+
+        assert!(error.contains("3 invalid entries"), "{error}");
+        assert!(error.contains("line 3: invalid pubkey 'xyz'"), "{error}");
+        assert!(
+            error.contains("line 4: invalid amount_unlocked 'abc'"),
+            "{error}"
+        );
+        assert!(error.contains("line 5: unknown variant `Miner`"), "{error}");
+    }
+
+    #[test]
+    fn test_csv_errors_are_capped() {
+        let path = PathBuf::from("./test_fixtures/test_csv_many_invalid.csv");
+        let error = CsvEntry::new_from_file(&path).unwrap_err().to_string();
+
+        assert!(
+            error.contains(&format!("stopped after {MAX_REPORTED_ERRORS}")),
+            "{error}"
+        );
+        assert_eq!(error.matches("invalid pubkey").count(), MAX_REPORTED_ERRORS);
+    }
+
+    #[test]
+    fn test_check_duplicate_claimants() {
+        assert!(check_duplicate_claimants([(2, "a"), (3, "b")], "line").is_ok());
+
+        let error = check_duplicate_claimants([(2, "a"), (3, "b"), (4, "a"), (7, "a")], "line")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("a on lines 2, 4, 7"), "{error}");
+        assert!(!error.contains("b on"), "{error}");
+    }
+
+    #[test]
+    fn test_parse_ui_amount() {
+        assert_eq!(parse_ui_amount("10.5", 6).unwrap(), 10_500_000);
+        assert_eq!(parse_ui_amount("10", 6).unwrap(), 10_000_000);
+        assert_eq!(parse_ui_amount("0.000001", 6).unwrap(), 1);
+        assert_eq!(parse_ui_amount(".5", 1).unwrap(), 5);
+        assert_eq!(parse_ui_amount("7", 0).unwrap(), 7);
+
+        // More precision than the mint has
+        assert!(parse_ui_amount("0.0000001", 6).is_err());
+        assert!(parse_ui_amount("1.5", 0).is_err());
+        // Not a number
+        assert!(parse_ui_amount("", 6).is_err());
+        assert!(parse_ui_amount(".", 6).is_err());
+        assert!(parse_ui_amount("-1", 6).is_err());
+        assert!(parse_ui_amount("1e6", 6).is_err());
+        // Overflows u64 once scaled
+        assert!(parse_ui_amount("18446744073709551615", 1).is_err());
+    }
+
+    #[test]
+    fn test_ui_amount_to_base_units() {
+        assert_eq!(ui_amount_to_base_units(5, 9).unwrap(), 5_000_000_000);
+        assert_eq!(ui_amount_to_base_units(5, 0).unwrap(), 5);
+        // Would wrap in release builds
+        assert!(ui_amount_to_base_units(u64::MAX / 1_000, 9).is_err());
+        assert!(ui_amount_to_base_units(1, 20).is_err());
+    }
+
+    #[test]
+    fn test_decimal_csv_parsing() {
+        let path = PathBuf::from("./test_fixtures/test_csv_decimals.csv");
+        let entries = DecimalCsvEntry::new_from_file(&path).expect("Failed to parse CSV");
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].amount_unlocked, "10.5");
+        assert_eq!(entries[0].amount_locked, "0.25");
+        assert_eq!(entries[2].category, AirdropCategory::Searcher);
+    }
+
+    #[test]
+    fn test_json_parsing_matches_csv() {
+        let csv_entries = CsvEntry::new_from_file(&PathBuf::from("./test_fixtures/test_csv.csv"))
+            .expect("Failed to parse CSV");
+        let json_entries =
+            CsvEntry::new_from_json_file(&PathBuf::from("./test_fixtures/test_entries.json"))
+                .expect("Failed to parse JSON");
+        assert_eq!(json_entries, csv_entries);
+    }
+
+    #[test]
+    fn test_json_parsing_rejects_half_schedule() {
+        let path = PathBuf::from("./test_fixtures/test_entries_half_schedule.json");
+        assert!(CsvEntry::new_from_json_file(&path).is_err());
+    }
+
+    #[test]
+    fn test_csv_parsing_with_unlock_schedules() {
+        let path = PathBuf::from("./test_fixtures/test_csv_unlock_schedules.csv");
+        let entries = CsvEntry::new_from_file(&path).expect("Failed to parse CSV");
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].start_ts, Some(1_700_000_000));
+        assert_eq!(entries[0].end_ts, Some(1_800_000_000));
+        assert_eq!(entries[1].start_ts, None);
+        assert_eq!(entries[1].end_ts, None);
+        assert_eq!(entries[2].start_ts, Some(1_750_000_000));
+        assert_eq!(entries[2].end_ts, Some(1_760_000_000));
     }
 }
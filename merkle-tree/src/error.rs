@@ -1,3 +1,4 @@
+use solana_program::pubkey::Pubkey;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -10,4 +11,110 @@ pub enum MerkleTreeError {
     IoError(#[from] std::io::Error),
     #[error("Serde Error: {0}")]
     SerdeError(#[from] serde_json::Error),
+    #[error("Bincode Error: {0}")]
+    BincodeError(#[from] bincode::Error),
+}
+
+/// Top-level error for integrators wrapping the distributor tooling.
+/// Every variant has a stable numeric code, see [DistributorError::code].
+#[derive(Error, Debug)]
+pub enum DistributorError {
+    #[error("Rpc Error: {0}")]
+    RpcError(String),
+    #[error("Indexer has not caught up: {0}")]
+    IndexerLag(String),
+    #[error("Wallet {0} is not eligible for this airdrop: it is not in the merkle tree")]
+    ProofNotFound(Pubkey),
+    #[error("Merkle tree mismatch: {0}")]
+    TreeMismatch(String),
+    #[error("Insufficient funds: {0}")]
+    InsufficientFunds(String),
+    #[error("Claimant {0} has already claimed")]
+    AlreadyClaimed(Pubkey),
+    #[error("Program error {code}: {message}")]
+    ProgramError { code: u32, message: String },
+    #[error("Merkle Tree Error: {0}")]
+    MerkleTreeError(#[from] MerkleTreeError),
+    #[error("io Error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+impl DistributorError {
+    /// Stable discriminant for programmatic matching. Never reorder or reuse these values.
+    pub fn code(&self) -> u32 {
+        match self {
+            DistributorError::RpcError(_) => 1,
+            DistributorError::IndexerLag(_) => 2,
+            DistributorError::ProofNotFound(_) => 3,
+            DistributorError::TreeMismatch(_) => 4,
+            DistributorError::InsufficientFunds(_) => 5,
+            DistributorError::AlreadyClaimed(_) => 6,
+            DistributorError::ProgramError { .. } => 7,
+            DistributorError::MerkleTreeError(_) => 8,
+            DistributorError::IoError(_) => 9,
+        }
+    }
+}
+
+impl From<serde_json::Error> for DistributorError {
+    fn from(e: serde_json::Error) -> Self {
+        DistributorError::MerkleTreeError(MerkleTreeError::SerdeError(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distributor_error_codes_are_stable() {
+        let claimant = Pubkey::new_unique();
+        let cases = [
+            (DistributorError::RpcError("timeout".to_string()), 1),
+            (DistributorError::IndexerLag("behind".to_string()), 2),
+            (DistributorError::ProofNotFound(claimant), 3),
+            (DistributorError::TreeMismatch("root".to_string()), 4),
+            (DistributorError::InsufficientFunds("vault".to_string()), 5),
+            (DistributorError::AlreadyClaimed(claimant), 6),
+            (
+                DistributorError::ProgramError {
+                    code: 6000,
+                    message: "custom".to_string(),
+                },
+                7,
+            ),
+            (
+                DistributorError::MerkleTreeError(MerkleTreeError::MerkleRootError),
+                8,
+            ),
+            (
+                DistributorError::IoError(std::io::Error::from(std::io::ErrorKind::NotFound)),
+                9,
+            ),
+        ];
+
+        for (error, code) in cases {
+            assert_eq!(error.code(), code, "unexpected code for {error}");
+        }
+    }
+
+    #[test]
+    fn test_merkle_tree_errors_map_to_merkle_tree_variant() {
+        let error: DistributorError =
+            MerkleTreeError::MerkleValidationError("Duplicate claimants found".to_string()).into();
+        assert!(matches!(
+            error,
+            DistributorError::MerkleTreeError(MerkleTreeError::MerkleValidationError(_))
+        ));
+
+        let serde_error = serde_json::from_str::<u64>("not a number").unwrap_err();
+        let error: DistributorError = serde_error.into();
+        assert!(matches!(
+            error,
+            DistributorError::MerkleTreeError(MerkleTreeError::SerdeError(_))
+        ));
+
+        let error: DistributorError = std::io::Error::from(std::io::ErrorKind::NotFound).into();
+        assert!(matches!(error, DistributorError::IoError(_)));
+    }
 }
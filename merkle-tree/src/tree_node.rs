@@ -4,10 +4,18 @@ use serde::{Deserialize, Serialize};
 use solana_program::{hash::hashv, pubkey::Pubkey};
 use solana_sdk::hash::Hash;
 
-use crate::csv_entry::{AirdropCategory, CsvEntry};
+use crate::{
+    csv_entry::{
+        parse_ui_amount, ui_amount_to_base_units, AirdropCategory, CsvEntry, DecimalCsvEntry,
+    },
+    error::{DistributorError, MerkleTreeError::MerkleValidationError},
+};
+/// Decimals of the mint whose whole tokens [CsvEntry] amounts are given in.
 pub const MINT_DECIMALS: u32 = 9;
 
-/// Represents the claim information for an account.
+/// Represents the claim information for an account. Every amount is in base units of the mint,
+/// exactly as the program hashes it into the leaf and pays it out; UI amounts are scaled when
+/// entries are read, see [TreeNode::from_csv_entry] and [TreeNode::from_decimal_csv_entry].
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub struct TreeNode {
     /// Pubkey of the claimant; will be responsible for signing the claim
@@ -26,15 +34,36 @@ pub struct TreeNode {
     pub total_unlocked_validator: u64,
     /// Total amount locked under validator allocation
     pub total_locked_validator: u64,
+    /// Optional vesting window overriding the distributor's global schedule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unlock_schedule: Option<UnlockSchedule>,
+}
+
+/// Per-node vesting window, committed to in the leaf hash.
+#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct UnlockSchedule {
+    /// Timestamp when locked tokens start unlocking for this claimant
+    pub start_ts: i64,
+    /// Timestamp when all locked tokens are unlocked for this claimant
+    pub end_ts: i64,
 }
 
 impl TreeNode {
     pub fn hash(&self) -> Hash {
-        hashv(&[
-            &self.claimant.to_bytes(),
-            &self.amount_unlocked().to_le_bytes(),
-            &self.amount_locked().to_le_bytes(),
-        ])
+        match self.unlock_schedule {
+            Some(schedule) => hashv(&[
+                &self.claimant.to_bytes(),
+                &self.amount_unlocked().to_le_bytes(),
+                &self.amount_locked().to_le_bytes(),
+                &schedule.start_ts.to_le_bytes(),
+                &schedule.end_ts.to_le_bytes(),
+            ]),
+            None => hashv(&[
+                &self.claimant.to_bytes(),
+                &self.amount_unlocked().to_le_bytes(),
+                &self.amount_locked().to_le_bytes(),
+            ]),
+        }
     }
 
     /// Return total amount of locked and unlocked amount for this claimant
@@ -44,7 +73,13 @@ impl TreeNode {
             .unwrap()
     }
 
-    /// Get total amount of unlocked tokens for this claimant
+    /// Whether the node allocates nothing, locked or unlocked, in any category
+    pub fn is_empty(&self) -> bool {
+        self.amount_unlocked() == 0 && self.amount_locked() == 0
+    }
+
+    /// Get total amount of unlocked tokens for this claimant, in base units. Only sums the
+    /// categories, no decimals are applied
     pub fn amount_unlocked(&self) -> u64 {
         self.total_unlocked_searcher
             .checked_add(self.total_unlocked_validator)
@@ -53,7 +88,8 @@ impl TreeNode {
             .unwrap()
     }
 
-    /// Get total amount of locked tokens for this claimant
+    /// Get total amount of locked tokens for this claimant, in base units. Only sums the
+    /// categories, no decimals are applied
     pub fn amount_locked(&self) -> u64 {
         self.total_locked_searcher
             .checked_add(self.total_locked_validator)
@@ -63,15 +99,52 @@ impl TreeNode {
     }
 }
 
-/// Converts a ui amount to a token amount (with decimals)
-fn ui_amount_to_token_amount(amount: u64) -> u64 {
-    amount * 10u64.checked_pow(MINT_DECIMALS).unwrap()
-}
+impl TreeNode {
+    /// Builds a node from a CSV entry with whole-token amounts, scaled by the mint's
+    /// `decimals`, usually [MINT_DECIMALS].
+    pub fn from_csv_entry(entry: CsvEntry, decimals: u32) -> Result<Self, DistributorError> {
+        let claimant = Pubkey::from_str(entry.pubkey.as_str()).map_err(|e| {
+            MerkleValidationError(format!("Invalid claimant {}: {e}", entry.pubkey))
+        })?;
+        Ok(Self::new_for_category(
+            claimant,
+            entry.category,
+            ui_amount_to_base_units(entry.amount_unlocked, decimals)?,
+            ui_amount_to_base_units(entry.amount_locked, decimals)?,
+            entry.start_ts,
+            entry.end_ts,
+        ))
+    }
+
+    /// Builds a node from a CSV entry with decimal amounts, scaled by the mint's `decimals`.
+    pub fn from_decimal_csv_entry(
+        entry: DecimalCsvEntry,
+        decimals: u32,
+    ) -> Result<Self, DistributorError> {
+        let claimant = Pubkey::from_str(entry.pubkey.as_str()).map_err(|e| {
+            MerkleValidationError(format!("Invalid claimant {}: {e}", entry.pubkey))
+        })?;
+        Ok(Self::new_for_category(
+            claimant,
+            entry.category,
+            parse_ui_amount(&entry.amount_unlocked, decimals)?,
+            parse_ui_amount(&entry.amount_locked, decimals)?,
+            entry.start_ts,
+            entry.end_ts,
+        ))
+    }
 
-impl From<CsvEntry> for TreeNode {
-    fn from(entry: CsvEntry) -> Self {
+    /// A node holding the given native amounts under `category`.
+    fn new_for_category(
+        claimant: Pubkey,
+        category: AirdropCategory,
+        amount_unlocked: u64,
+        amount_locked: u64,
+        start_ts: Option<i64>,
+        end_ts: Option<i64>,
+    ) -> Self {
         let mut node = Self {
-            claimant: Pubkey::from_str(entry.pubkey.as_str()).unwrap(),
+            claimant,
             proof: None,
             total_unlocked_staker: 0,
             total_locked_staker: 0,
@@ -79,12 +152,13 @@ impl From<CsvEntry> for TreeNode {
             total_locked_searcher: 0,
             total_unlocked_validator: 0,
             total_locked_validator: 0,
+            unlock_schedule: match (start_ts, end_ts) {
+                (Some(start_ts), Some(end_ts)) => Some(UnlockSchedule { start_ts, end_ts }),
+                _ => None,
+            },
         };
 
-        // CSV entry uses UI amounts; we convert to native amounts here
-        let amount_unlocked = ui_amount_to_token_amount(entry.amount_unlocked);
-        let amount_locked = ui_amount_to_token_amount(entry.amount_locked);
-        match entry.category {
+        match category {
             AirdropCategory::Staker => {
                 node.total_unlocked_staker = amount_unlocked;
                 node.total_locked_staker = amount_locked;
@@ -117,16 +191,88 @@ mod tests {
             total_locked_searcher: 0,
             total_unlocked_validator: 0,
             total_locked_validator: 0,
+            unlock_schedule: None,
+        };
+        let serialized = serde_json::to_string(&tree_node).unwrap();
+        let deserialized: TreeNode = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(tree_node, deserialized);
+        assert!(!serialized.contains("unlock_schedule"));
+    }
+
+    #[test]
+    fn test_unlock_schedule_changes_hash() {
+        let mut tree_node = TreeNode {
+            claimant: Pubkey::new_unique(),
+            proof: None,
+            total_unlocked_staker: 10,
+            total_locked_staker: 20,
+            total_unlocked_searcher: 0,
+            total_locked_searcher: 0,
+            total_unlocked_validator: 0,
+            total_locked_validator: 0,
+            unlock_schedule: None,
         };
+        let global_hash = tree_node.hash();
+
+        tree_node.unlock_schedule = Some(UnlockSchedule {
+            start_ts: 100,
+            end_ts: 200,
+        });
+        let custom_hash = tree_node.hash();
+        assert_ne!(global_hash, custom_hash);
+
+        tree_node.unlock_schedule = Some(UnlockSchedule {
+            start_ts: 100,
+            end_ts: 300,
+        });
+        assert_ne!(custom_hash, tree_node.hash());
+
         let serialized = serde_json::to_string(&tree_node).unwrap();
         let deserialized: TreeNode = serde_json::from_str(&serialized).unwrap();
         assert_eq!(tree_node, deserialized);
     }
 
     #[test]
-    fn test_ui_amount_to_token_amount() {
-        let ui_amount = 5;
-        let token_amount = ui_amount_to_token_amount(ui_amount);
-        assert_eq!(token_amount, 5_000_000_000);
+    fn test_ui_amounts_round_trip_to_hashed_base_units() {
+        let claimant = Pubkey::new_unique();
+        let whole = TreeNode::from_csv_entry(
+            CsvEntry {
+                pubkey: claimant.to_string(),
+                amount_unlocked: 5,
+                amount_locked: 2,
+                category: AirdropCategory::Staker,
+                start_ts: None,
+                end_ts: None,
+            },
+            MINT_DECIMALS,
+        )
+        .unwrap();
+        let decimal = |amount_unlocked: &str, amount_locked: &str| {
+            TreeNode::from_decimal_csv_entry(
+                DecimalCsvEntry {
+                    pubkey: claimant.to_string(),
+                    amount_unlocked: amount_unlocked.to_string(),
+                    amount_locked: amount_locked.to_string(),
+                    category: AirdropCategory::Staker,
+                    start_ts: None,
+                    end_ts: None,
+                },
+                MINT_DECIMALS,
+            )
+            .unwrap()
+        };
+
+        assert_eq!(whole.amount_unlocked(), 5_000_000_000);
+        assert_eq!(whole.amount_locked(), 2_000_000_000);
+        // The leaf new_claim recomputes from the amounts it is passed
+        let program_leaf = hashv(&[
+            &claimant.to_bytes(),
+            &5_000_000_000u64.to_le_bytes(),
+            &2_000_000_000u64.to_le_bytes(),
+        ]);
+        assert_eq!(whole.hash(), program_leaf);
+        assert_eq!(decimal("5", "2").hash(), program_leaf);
+        assert_eq!(decimal("5.000000000", "2.0").hash(), program_leaf);
+        assert_ne!(decimal("5.000000001", "2").hash(), program_leaf);
     }
 }
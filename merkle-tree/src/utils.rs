@@ -1,7 +1,128 @@
+use std::collections::HashSet;
+#[cfg(feature = "parallel")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use solana_program::pubkey::Pubkey;
 
 use crate::{merkle_tree::MerkleTree, tree_node::TreeNode};
 
+/// Nodes hashed or proven between two progress reports.
+pub const PROGRESS_CHUNK: usize = 4096;
+
+/// Pass over the nodes a [TreeBuildProgress] report is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeBuildStage {
+    /// Hashing the leaves
+    Hashing,
+    /// Extracting each node's proof
+    Proofs,
+    /// Checking every proof against the root
+    Verifying,
+}
+
+/// Nodes of a stage done so far, reported every [PROGRESS_CHUNK] nodes and at the end of the
+/// stage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TreeBuildProgress {
+    pub stage: TreeBuildStage,
+    pub done: usize,
+    pub total: usize,
+}
+
+/// Receives the progress of a tree build. It may be called from several threads with the
+/// `parallel` feature, so reports of a stage can arrive slightly out of order.
+pub type ProgressCallback<'a> = &'a (dyn Fn(TreeBuildProgress) + Sync);
+
+/// Builds the merkle tree over `tree_nodes` and stores each node's proof in it.
+pub fn build_merkle_tree(tree_nodes: &mut [TreeNode]) -> MerkleTree {
+    build_merkle_tree_with_progress(tree_nodes, &|_| {})
+}
+
+/// [build_merkle_tree] reporting its progress to `progress`.
+pub fn build_merkle_tree_with_progress(
+    tree_nodes: &mut [TreeNode],
+    progress: ProgressCallback,
+) -> MerkleTree {
+    let total = tree_nodes.len();
+    let mut hashed_nodes = Vec::with_capacity(total);
+    for chunk in tree_nodes.chunks(PROGRESS_CHUNK) {
+        hashed_nodes.extend(chunk.iter().map(|claim_info| claim_info.hash().to_bytes()));
+        progress(TreeBuildProgress {
+            stage: TreeBuildStage::Hashing,
+            done: hashed_nodes.len(),
+            total,
+        });
+    }
+
+    let tree = MerkleTree::new(&hashed_nodes[..], true);
+
+    for (chunk_index, chunk) in tree_nodes.chunks_mut(PROGRESS_CHUNK).enumerate() {
+        let offset = chunk_index * PROGRESS_CHUNK;
+        for (i, tree_node) in chunk.iter_mut().enumerate() {
+            tree_node.proof = Some(get_proof(&tree, offset + i));
+        }
+        progress(TreeBuildProgress {
+            stage: TreeBuildStage::Proofs,
+            done: offset + chunk.len(),
+            total,
+        });
+    }
+    tree
+}
+
+/// [build_merkle_tree] with leaf hashing and proof extraction spread across threads.
+/// Produces the same root and proofs.
+#[cfg(feature = "parallel")]
+pub fn build_merkle_tree_parallel(tree_nodes: &mut [TreeNode]) -> MerkleTree {
+    build_merkle_tree_parallel_with_progress(tree_nodes, &|_| {})
+}
+
+/// [build_merkle_tree_parallel] reporting its progress to `progress`.
+#[cfg(feature = "parallel")]
+pub fn build_merkle_tree_parallel_with_progress(
+    tree_nodes: &mut [TreeNode],
+    progress: ProgressCallback,
+) -> MerkleTree {
+    let total = tree_nodes.len();
+    let hashed = AtomicUsize::new(0);
+    let hashed_nodes = tree_nodes
+        .par_chunks(PROGRESS_CHUNK)
+        .flat_map_iter(|chunk| {
+            let hashes = chunk
+                .iter()
+                .map(|claim_info| claim_info.hash().to_bytes())
+                .collect::<Vec<_>>();
+            progress(TreeBuildProgress {
+                stage: TreeBuildStage::Hashing,
+                done: hashed.fetch_add(chunk.len(), Ordering::Relaxed) + chunk.len(),
+                total,
+            });
+            hashes
+        })
+        .collect::<Vec<_>>();
+
+    let tree = MerkleTree::new(&hashed_nodes[..], true);
+
+    let proven = AtomicUsize::new(0);
+    tree_nodes
+        .par_chunks_mut(PROGRESS_CHUNK)
+        .enumerate()
+        .for_each(|(chunk_index, chunk)| {
+            let offset = chunk_index * PROGRESS_CHUNK;
+            for (i, tree_node) in chunk.iter_mut().enumerate() {
+                tree_node.proof = Some(get_proof(&tree, offset + i));
+            }
+            progress(TreeBuildProgress {
+                stage: TreeBuildStage::Proofs,
+                done: proven.fetch_add(chunk.len(), Ordering::Relaxed) + chunk.len(),
+                total,
+            });
+        });
+    tree
+}
+
 pub fn get_proof(merkle_tree: &MerkleTree, index: usize) -> Vec<[u8; 32]> {
     let mut proof = Vec::new();
     let path = merkle_tree.find_path(index).expect("path to index");
@@ -40,19 +161,93 @@ pub fn get_merkle_distributor_pda(
     )
 }
 
+/// System-owned PDA holding the lamports of a native SOL distributor.
+pub fn get_sol_vault_pda(program_id: &Pubkey, distributor: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"SolVault".as_ref(), distributor.as_ref()], program_id)
+}
+
+/// How an airdrop version, one of the distributor PDA seeds, is interpreted.
+/// Any u64 is a valid version; the scheme only affects how versions are displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionScheme {
+    /// Incrementing counter starting at 0
+    Sequential,
+    /// Calendar date written as YYYYMMDD, e.g. 20250301
+    Date { year: u64, month: u64, day: u64 },
+    /// Unix timestamp in seconds
+    Timestamp,
+    /// Anything else, e.g. a campaign id
+    Opaque,
+}
+
+impl VersionScheme {
+    const MAX_SEQUENTIAL: u64 = 1_000_000;
+    const MIN_TIMESTAMP: u64 = 1_000_000_000;
+    const MAX_TIMESTAMP: u64 = 9_999_999_999;
+
+    pub fn classify(version: u64) -> Self {
+        let (year, month, day) = (version / 10_000, version / 100 % 100, version % 100);
+        if version < Self::MAX_SEQUENTIAL {
+            VersionScheme::Sequential
+        } else if (2000..=2999).contains(&year)
+            && (1..=12).contains(&month)
+            && (1..=31).contains(&day)
+        {
+            VersionScheme::Date { year, month, day }
+        } else if (Self::MIN_TIMESTAMP..=Self::MAX_TIMESTAMP).contains(&version) {
+            VersionScheme::Timestamp
+        } else {
+            VersionScheme::Opaque
+        }
+    }
+}
+
+impl std::fmt::Display for VersionScheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VersionScheme::Sequential => write!(f, "sequential"),
+            VersionScheme::Date { year, month, day } => {
+                write!(f, "date {year:04}-{month:02}-{day:02}")
+            }
+            VersionScheme::Timestamp => write!(f, "unix timestamp"),
+            VersionScheme::Opaque => write!(f, "opaque id"),
+        }
+    }
+}
+
+/// Returns the smallest version >= `from` that is not in `used_versions`,
+/// or None if every version up to u64::MAX is taken.
+pub fn next_free_version(used_versions: &[u64], from: u64) -> Option<u64> {
+    let used: HashSet<u64> = used_versions.iter().copied().collect();
+    (from..=u64::MAX).find(|version| !used.contains(version))
+}
+
 pub fn get_claim_status_pda(
     program_id: &Pubkey,
     claimant: &Pubkey,
     distributor: &Pubkey,
 ) -> ([u8; 32], [u8; 32]) {
-    let address_merkle_tree_pubkey =
-        Pubkey::new_from_array(light_sdk::constants::ADDRESS_TREE_V2);
+    get_claim_status_address(
+        program_id,
+        claimant,
+        distributor,
+        &Pubkey::new_from_array(light_sdk::constants::ADDRESS_TREE_V2),
+    )
+}
+
+/// Address and address seed of the ClaimStatus compressed account in `address_merkle_tree_pubkey`.
+pub fn get_claim_status_address(
+    program_id: &Pubkey,
+    claimant: &Pubkey,
+    distributor: &Pubkey,
+    address_merkle_tree_pubkey: &Pubkey,
+) -> ([u8; 32], [u8; 32]) {
     let c_bytes = claimant.to_bytes();
     let d_bytes = distributor.to_bytes();
     let seeds: [&[u8]; 3] = [b"ClaimStatus", c_bytes.as_ref(), d_bytes.as_ref()];
     // v2 address derivation uses bn254 hash
     let (address, address_seed) =
-        light_sdk::address::v2::derive_address(&seeds, &address_merkle_tree_pubkey, program_id);
+        light_sdk::address::v2::derive_address(&seeds, address_merkle_tree_pubkey, program_id);
     (address, address_seed.0)
 }
 
@@ -64,6 +259,46 @@ pub struct MerkleValidationError {
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn test_build_merkle_tree_reports_progress() {
+        let mut nodes: Vec<TreeNode> = (0..PROGRESS_CHUNK as u64 * 2 + 10)
+            .map(|i| create_node(Pubkey::new_unique(), i, 0, 0, 0, 0, 0))
+            .collect();
+        let total = nodes.len();
+        let reports = std::sync::Mutex::new(vec![]);
+        build_merkle_tree_with_progress(&mut nodes, &|progress: TreeBuildProgress| {
+            reports.lock().unwrap().push(progress)
+        });
+
+        let reports = reports.into_inner().unwrap();
+        let done = |stage| {
+            reports
+                .iter()
+                .filter(|p| p.stage == stage)
+                .map(|p| p.done)
+                .collect::<Vec<_>>()
+        };
+        let expected = vec![PROGRESS_CHUNK, PROGRESS_CHUNK * 2, total];
+        assert_eq!(done(TreeBuildStage::Hashing), expected);
+        assert_eq!(done(TreeBuildStage::Proofs), expected);
+        assert!(reports.iter().all(|p| p.total == total));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_build_matches_serial() {
+        let mut serial_nodes: Vec<TreeNode> = (0..10_000)
+            .map(|i| create_node(Pubkey::new_unique(), i, 2 * i, 0, 0, 0, 0))
+            .collect();
+        let mut parallel_nodes = serial_nodes.clone();
+
+        let serial = build_merkle_tree(&mut serial_nodes);
+        let parallel = build_merkle_tree_parallel(&mut parallel_nodes);
+
+        assert_eq!(serial.get_root(), parallel.get_root());
+        assert_eq!(serial_nodes, parallel_nodes);
+    }
     // Helper function to create a tree node
     fn create_node(
         claimant: Pubkey,
@@ -83,6 +318,7 @@ mod tests {
             total_locked_searcher,
             total_unlocked_validator,
             total_locked_validator,
+            unlock_schedule: None,
         }
     }
 
@@ -97,6 +333,66 @@ mod tests {
         assert_eq!(total, 1000); // 100 + 200 + 300 + 400
     }
 
+    #[test]
+    fn test_classify_version_schemes() {
+        assert_eq!(VersionScheme::classify(0), VersionScheme::Sequential);
+        assert_eq!(VersionScheme::classify(42), VersionScheme::Sequential);
+        assert_eq!(
+            VersionScheme::classify(20250301),
+            VersionScheme::Date {
+                year: 2025,
+                month: 3,
+                day: 1
+            }
+        );
+        assert_eq!(VersionScheme::classify(20251301), VersionScheme::Opaque);
+        assert_eq!(
+            VersionScheme::classify(1_740_787_200),
+            VersionScheme::Timestamp
+        );
+        assert_eq!(VersionScheme::classify(u64::MAX), VersionScheme::Opaque);
+        assert_eq!(
+            VersionScheme::classify(20250301).to_string(),
+            "date 2025-03-01"
+        );
+    }
+
+    #[test]
+    fn test_next_free_version_detects_collisions() {
+        assert_eq!(next_free_version(&[], 0), Some(0));
+        assert_eq!(next_free_version(&[0, 1, 2], 0), Some(3));
+        assert_eq!(next_free_version(&[0, 2], 0), Some(1));
+        // requested version is free
+        assert_eq!(next_free_version(&[0, 1], 5), Some(5));
+        // requested version collides, skip to the next free one
+        assert_eq!(
+            next_free_version(&[20250301, 20250302], 20250301),
+            Some(20250303)
+        );
+        assert_eq!(next_free_version(&[u64::MAX], u64::MAX), None);
+    }
+
+    #[test]
+    fn test_claim_status_address_depends_on_address_tree() {
+        let program_id = Pubkey::new_unique();
+        let claimant = Pubkey::new_unique();
+        let distributor = Pubkey::new_unique();
+        let default_tree = Pubkey::new_from_array(light_sdk::constants::ADDRESS_TREE_V2);
+        let other_tree = Pubkey::new_unique();
+
+        assert_eq!(
+            get_claim_status_pda(&program_id, &claimant, &distributor),
+            get_claim_status_address(&program_id, &claimant, &distributor, &default_tree)
+        );
+        let (default_address, default_seed) =
+            get_claim_status_address(&program_id, &claimant, &distributor, &default_tree);
+        let (other_address, other_seed) =
+            get_claim_status_address(&program_id, &claimant, &distributor, &other_tree);
+        assert_ne!(default_address, other_address);
+        // the address seed only depends on the claimant and distributor
+        assert_eq!(default_seed, other_seed);
+    }
+
     #[test]
     #[should_panic(expected = "Option::unwrap()` on a `None` value")]
     fn test_get_max_total_claim_overflow() {
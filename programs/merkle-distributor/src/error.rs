@@ -1,4 +1,4 @@
-use anchor_lang::error_code;
+use anchor_lang::{error_code, prelude::*};
 
 /// Error codes.
 #[error_code]
@@ -45,4 +45,61 @@ pub enum ErrorCode {
     LightAccountCreationFailed,
     #[msg("Failed to invoke Light system program")]
     LightCpiFailed,
+    #[msg("Claimant signature instruction missing")]
+    MissingClaimantSignature,
+    #[msg("Claimant signature does not authorize this claim")]
+    InvalidClaimantSignature,
+    #[msg("Cliff must be between vesting start and end")]
+    CliffOutsideVesting,
+    #[msg("Distributor is paused")]
+    Paused,
+    #[msg("Distributor has not been clawed back")]
+    NotClawedBack,
+    #[msg("Token vault still holds tokens")]
+    VaultNotEmpty,
+    #[msg("Packed account index is out of range of the remaining accounts")]
+    InvalidAccountLayout,
+    #[msg("Minimum claim interval cannot be negative")]
+    InvalidClaimInterval,
+    #[msg("Minimum claim interval has not elapsed since the last claim")]
+    ClaimTooSoon,
+    #[msg("Admin has been renounced")]
+    AdminRenounced,
+    #[msg("New clawback start must be later than the current one")]
+    ClawbackStartNotLater,
+    #[msg("Category amounts do not add up to the claimed amounts")]
+    CategoryBreakdownMismatch,
+    #[msg("Instruction does not match the distributor's distribution mode")]
+    WrongDistributionMode,
+    #[msg("Vesting interval must be positive")]
+    InvalidVestingInterval,
+    #[msg("Vault balance is below the amount to pay out, fund the vault")]
+    InsufficientVaultBalance,
+    #[msg("Clawback would leave less in the vault than the unclaimed allocations")]
+    ClawbackExceedsSurplus,
+    #[msg("Maximum number of nodes must be positive")]
+    InvalidMaxNumNodes,
+    #[msg("Maximum total claim must be positive")]
+    InvalidMaxTotalClaim,
+    #[msg("Distributor does not allow claim reassignment")]
+    ReassignmentDisabled,
+    #[msg("New claimant must differ from the old claimant")]
+    ReassignToSameClaimant,
+    #[msg("Proof length does not match the distributor's tree depth, the tree file may be stale")]
+    ProofLengthMismatch,
+    #[msg("New root must not lower the maximum total claim or number of nodes")]
+    CapDecreased,
+    #[msg("Recipient token account is frozen, ask the mint's freeze authority to thaw it")]
+    RecipientAccountFrozen,
+    #[msg("Distributor already has the current account layout")]
+    DistributorAlreadyMigrated,
+    #[msg("Unpause the distributor before renouncing the admin")]
+    RenounceWhilePaused,
+}
+
+/// Logs a Light SDK error and replaces it with `code`, so the transaction fails with a
+/// distributor error instead of the SDK's raw program error.
+pub fn light_sdk_error(e: ProgramError, code: ErrorCode) -> ErrorCode {
+    msg!("Light SDK error: {}", e);
+    code
 }
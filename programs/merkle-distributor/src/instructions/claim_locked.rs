@@ -1,11 +1,11 @@
 use anchor_lang::{
     accounts::{account::Account, signer::Signer},
-    context::{Context, CpiContext},
+    context::Context,
     emit,
     prelude::*,
     Accounts, Result, ToAccountInfo,
 };
-use anchor_spl::token::{self, Token, TokenAccount};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 use light_sdk::{
     account::LightAccount,
@@ -17,12 +17,13 @@ use light_sdk::{
 };
 
 use crate::{
-    error::ErrorCode,
+    error::{light_sdk_error, ErrorCode},
     state::{
         claim_status::{ClaimStatus, ClaimStatusInstructionData},
         claimed_event::ClaimedEvent,
         merkle_distributor::MerkleDistributor,
     },
+    vault::Vault,
     LIGHT_CPI_SIGNER,
 };
 
@@ -37,28 +38,44 @@ pub struct ClaimLocked<'info> {
         mut,
         associated_token::mint = distributor.mint,
         associated_token::authority = distributor.key(),
+        associated_token::token_program = token_program,
         address = distributor.token_vault,
     )]
-    pub from: Account<'info, TokenAccount>,
+    pub from: InterfaceAccount<'info, TokenAccount>,
     /// Account to send the claimed tokens to.
     /// Claimant must sign the transaction and can only claim on behalf of themself
-    #[account(mut, token::authority = claimant.key())]
-    pub to: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::authority = claimant.key(),
+        token::token_program = token_program
+    )]
+    pub to: InterfaceAccount<'info, TokenAccount>,
 
     /// Who is claiming the tokens.
     #[account(mut, address = to.owner @ ErrorCode::OwnerMismatch)]
     pub claimant: Signer<'info>,
 
-    /// SPL [Token] program.
-    pub token_program: Program<'info, Token>,
+    /// The distributor's mint, needed for its decimals by `transfer_checked`.
+    #[account(address = distributor.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// SPL Token or Token-2022 program owning the mint.
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 /// Claim locked tokens as they become unlocked.
+/// Vesting follows the claimant's per-node schedule when one was proven at new_claim,
+/// otherwise the distributor's global schedule.
 /// Check:
 ///     1. The claim window has not expired and the distributor has not been clawed back
-///     2. The withdraw-able amount is greater than 0
-///     3. The locked amount withdrawn is ≤ than the locked amount
-///     4. The distributor amount claimed is ≤ than the max total claim
+///     2. The distributor is not paused
+///     3. The distributor's minimum claim interval has elapsed since the last claim_locked
+///     4. The withdraw-able amount is greater than 0
+///     5. The locked amount withdrawn is ≤ than the locked amount
+///     6. The distributor amount claimed is ≤ than the max total claim
+///     7. The distributor pays out tokens, not native SOL
+///     8. The vault holds the amount to pay out
+///     9. The recipient token account is not frozen
 #[allow(clippy::result_large_err)]
 pub fn handle_claim_locked<'info>(
     ctx: Context<'_, '_, '_, 'info, ClaimLocked<'info>>,
@@ -66,51 +83,89 @@ pub fn handle_claim_locked<'info>(
     claim_status_data: ClaimStatusInstructionData,
     validity_proof: ValidityProof,
 ) -> Result<()> {
-    let claim_status = claim_status_data.into_claim_status(ctx.accounts.claimant.key());
+    withdraw_locked(
+        &mut ctx.accounts.distributor,
+        Vault::Token {
+            from: &ctx.accounts.from,
+            to: &ctx.accounts.to,
+            mint: &ctx.accounts.mint,
+            token_program: &ctx.accounts.token_program,
+        },
+        ctx.accounts.claimant.as_ref(),
+        ctx.remaining_accounts,
+        input_account_meta,
+        claim_status_data,
+        validity_proof,
+    )
+}
+
+/// Shared by [handle_claim_locked] and [crate::instructions::handle_claim_locked_native]:
+/// pays the vested locked amount of `claimant` from `vault` and updates its claim status.
+#[allow(clippy::result_large_err)]
+pub(crate) fn withdraw_locked<'info>(
+    distributor: &mut Account<'info, MerkleDistributor>,
+    vault: Vault<'_, 'info>,
+    claimant: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    input_account_meta: CompressedAccountMeta,
+    claim_status_data: ClaimStatusInstructionData,
+    validity_proof: ValidityProof,
+) -> Result<()> {
+    let claim_status = claim_status_data.into_claim_status(claimant.key());
     let mut claim_status =
-        LightAccount::<ClaimStatus>::new_mut(&crate::ID, &input_account_meta, claim_status)?;
-    let distributor = &ctx.accounts.distributor;
+        LightAccount::<ClaimStatus>::new_mut(&crate::ID, &input_account_meta, claim_status)
+            .map_err(|e| light_sdk_error(e.into(), ErrorCode::LightAccountCreationFailed))?;
 
     let curr_ts = Clock::get()?.unix_timestamp;
 
+    require!(
+        distributor.distribution_mode == vault.mode(),
+        ErrorCode::WrongDistributionMode
+    );
     require!(!distributor.clawed_back, ErrorCode::ClaimExpired);
-
-    let amount =
-        claim_status.amount_withdrawable(curr_ts, distributor.start_ts, distributor.end_ts)?;
+    require!(!distributor.paused, ErrorCode::Paused);
+    if claim_status.last_claimed_ts > 0 {
+        let since_last_claim = curr_ts
+            .checked_sub(claim_status.last_claimed_ts)
+            .ok_or(ErrorCode::ArithmeticError)?;
+        require!(
+            since_last_claim >= distributor.min_claim_interval,
+            ErrorCode::ClaimTooSoon
+        );
+    }
+
+    let (start_ts, end_ts) = claim_status.unlock_window(distributor.start_ts, distributor.end_ts);
+    let amount = claim_status.amount_withdrawable(
+        curr_ts,
+        start_ts,
+        end_ts,
+        distributor.cliff_ts,
+        distributor.vesting_interval_secs,
+    )?;
 
     require!(amount > 0, ErrorCode::InsufficientUnlockedTokens);
 
-    let seeds = [
-        b"MerkleDistributor".as_ref(),
-        &distributor.mint.to_bytes(),
-        &distributor.version.to_le_bytes(),
-        &[ctx.accounts.distributor.bump],
-    ];
-
-    token::transfer(
-        CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            token::Transfer {
-                from: ctx.accounts.from.to_account_info(),
-                to: ctx.accounts.to.to_account_info(),
-                authority: ctx.accounts.distributor.to_account_info(),
-            },
-        )
-        .with_signer(&[&seeds[..]]),
-        amount,
-    )?;
+    // Send enough to cover a Token-2022 transfer fee so the claimant receives amount
+    let transfer_fee = vault.transfer_fee(amount)?;
+    let transfer_amount = amount
+        .checked_add(transfer_fee)
+        .ok_or(ErrorCode::ArithmeticError)?;
+
+    vault.require_available(transfer_amount)?;
+    vault.require_recipient_not_frozen()?;
+    vault.transfer(distributor, transfer_amount)?;
 
     claim_status.locked_amount_withdrawn = claim_status
         .locked_amount_withdrawn
         .checked_add(amount)
         .ok_or(ErrorCode::ArithmeticError)?;
+    claim_status.last_claimed_ts = curr_ts;
 
     require!(
         claim_status.locked_amount_withdrawn <= claim_status.locked_amount,
         ErrorCode::ExceededMaxClaim
     );
 
-    let distributor = &mut ctx.accounts.distributor;
     distributor.total_amount_claimed = distributor
         .total_amount_claimed
         .checked_add(amount)
@@ -121,8 +176,8 @@ pub fn handle_claim_locked<'info>(
         ErrorCode::ExceededMaxClaim
     );
 
-    let remaining_seconds = match curr_ts < distributor.end_ts {
-        true => distributor.end_ts - curr_ts,
+    let remaining_seconds = match curr_ts < end_ts {
+        true => end_ts - curr_ts,
         false => 0,
     };
 
@@ -130,15 +185,21 @@ pub fn handle_claim_locked<'info>(
     let seconds_after_days = remaining_seconds % (24 * 60 * 60); // Remaining seconds after subtracting full days
 
     // Create CPI accounts and invoke Light system program
-    let light_cpi_accounts = CpiAccounts::new(
-        ctx.accounts.claimant.as_ref(),
-        ctx.remaining_accounts,
-        LIGHT_CPI_SIGNER,
+    let light_cpi_accounts = CpiAccounts::new(claimant, remaining_accounts, LIGHT_CPI_SIGNER);
+
+    #[cfg(feature = "trace-cpi")]
+    msg!(
+        "trace-cpi: input_account_meta {:?} remaining_accounts {} proof {}",
+        input_account_meta,
+        remaining_accounts.len(),
+        validity_proof.0.is_some(),
     );
 
     LightSystemProgramCpi::new_cpi(LIGHT_CPI_SIGNER, validity_proof)
-        .with_light_account(claim_status)?
-        .invoke(light_cpi_accounts)?;
+        .with_light_account(claim_status)
+        .map_err(|e| light_sdk_error(e, ErrorCode::LightAccountCreationFailed))?
+        .invoke(light_cpi_accounts)
+        .map_err(|e| light_sdk_error(e, ErrorCode::LightCpiFailed))?;
 
     // Note: might get truncated, do not rely on
     msg!(
@@ -148,8 +209,9 @@ pub fn handle_claim_locked<'info>(
         seconds_after_days,
     );
     emit!(ClaimedEvent {
-        claimant: ctx.accounts.claimant.key(),
+        claimant: claimant.key(),
         amount,
+        transfer_fee,
     });
     Ok(())
 }
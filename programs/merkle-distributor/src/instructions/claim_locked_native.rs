@@ -0,0 +1,60 @@
+use anchor_lang::{context::Context, prelude::*, Accounts, Key, Result};
+use light_sdk::instruction::{account_meta::CompressedAccountMeta, ValidityProof};
+
+use crate::{
+    instructions::claim_locked::withdraw_locked,
+    state::{claim_status::ClaimStatusInstructionData, merkle_distributor::MerkleDistributor},
+    vault::{Vault, SOL_VAULT_SEED},
+};
+
+/// [merkle_distributor::claim_locked_native] accounts.
+#[derive(Accounts)]
+pub struct ClaimLockedNative<'info> {
+    /// The [MerkleDistributor].
+    #[account(mut)]
+    pub distributor: Account<'info, MerkleDistributor>,
+
+    /// SOL vault containing the lamports to distribute.
+    #[account(
+        mut,
+        seeds = [SOL_VAULT_SEED, distributor.key().as_ref()],
+        bump,
+        address = distributor.token_vault
+    )]
+    pub from: SystemAccount<'info>,
+
+    /// Who is claiming, receives the lamports.
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// [crate::instructions::handle_claim_locked] for a distributor paying out native SOL.
+///
+/// CHECK:
+///     1. The distributor pays out native SOL
+///     2. Everything else [crate::instructions::handle_claim_locked] checks
+#[allow(clippy::result_large_err)]
+pub fn handle_claim_locked_native<'info>(
+    ctx: Context<'_, '_, '_, 'info, ClaimLockedNative<'info>>,
+    input_account_meta: CompressedAccountMeta,
+    claim_status_data: ClaimStatusInstructionData,
+    validity_proof: ValidityProof,
+) -> Result<()> {
+    withdraw_locked(
+        &mut ctx.accounts.distributor,
+        Vault::Native {
+            from: &ctx.accounts.from,
+            from_bump: ctx.bumps.from,
+            to: ctx.accounts.claimant.as_ref(),
+            system_program: &ctx.accounts.system_program,
+        },
+        ctx.accounts.claimant.as_ref(),
+        ctx.remaining_accounts,
+        input_account_meta,
+        claim_status_data,
+        validity_proof,
+    )
+}
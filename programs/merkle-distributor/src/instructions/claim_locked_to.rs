@@ -0,0 +1,77 @@
+use anchor_lang::{context::Context, prelude::*, Accounts, Key, Result};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use light_sdk::instruction::{account_meta::CompressedAccountMeta, ValidityProof};
+
+use crate::{
+    instructions::claim_locked::withdraw_locked,
+    state::{claim_status::ClaimStatusInstructionData, merkle_distributor::MerkleDistributor},
+    vault::Vault,
+};
+
+/// [merkle_distributor::claim_locked_to] accounts.
+#[derive(Accounts)]
+pub struct ClaimLockedTo<'info> {
+    /// The [MerkleDistributor].
+    #[account(mut)]
+    pub distributor: Account<'info, MerkleDistributor>,
+
+    /// Distributor ATA containing the tokens to distribute.
+    #[account(
+        mut,
+        associated_token::mint = distributor.mint,
+        associated_token::authority = distributor.key(),
+        associated_token::token_program = token_program,
+        address = distributor.token_vault,
+    )]
+    pub from: InterfaceAccount<'info, TokenAccount>,
+
+    /// Recipient token account chosen by the claimant, owned by anyone.
+    #[account(
+        mut,
+        token::mint = distributor.mint,
+        token::token_program = token_program
+    )]
+    pub to: InterfaceAccount<'info, TokenAccount>,
+
+    /// Who is claiming the tokens, authorizing the transfer to `to` by signing.
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+
+    /// The distributor's mint, needed for its decimals by `transfer_checked`.
+    #[account(address = distributor.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// SPL Token or Token-2022 program owning the mint.
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// [crate::instructions::handle_claim_locked] sending the vested tokens to a token account of
+/// the claimant's choosing. The recipient may differ from the one of new_claim_to and from one
+/// withdrawal to the next.
+///
+/// CHECK:
+///     1. The claimant signed
+///     2. Everything else [crate::instructions::handle_claim_locked] checks, except the owner
+///        of the to account
+#[allow(clippy::result_large_err)]
+pub fn handle_claim_locked_to<'info>(
+    ctx: Context<'_, '_, '_, 'info, ClaimLockedTo<'info>>,
+    input_account_meta: CompressedAccountMeta,
+    claim_status_data: ClaimStatusInstructionData,
+    validity_proof: ValidityProof,
+) -> Result<()> {
+    withdraw_locked(
+        &mut ctx.accounts.distributor,
+        Vault::Token {
+            from: &ctx.accounts.from,
+            to: &ctx.accounts.to,
+            mint: &ctx.accounts.mint,
+            token_program: &ctx.accounts.token_program,
+        },
+        ctx.accounts.claimant.as_ref(),
+        ctx.remaining_accounts,
+        input_account_meta,
+        claim_status_data,
+        validity_proof,
+    )
+}
@@ -1,9 +1,15 @@
 // Instruction to clawback funds once they have expired
 
-use anchor_lang::{context::Context, prelude::*, Accounts, Key, Result};
-use anchor_spl::token::{self, Token, TokenAccount};
+use anchor_lang::{context::Context, emit, prelude::*, Accounts, Key, Result};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface};
 
-use crate::{error::ErrorCode, state::merkle_distributor::MerkleDistributor};
+use crate::{
+    error::ErrorCode,
+    state::{
+        claimed_event::ClawbackEvent,
+        merkle_distributor::{DistributionMode, MerkleDistributor},
+    },
+};
 
 /// [merkle_distributor::clawback] accounts.
 #[derive(Accounts)]
@@ -17,13 +23,14 @@ pub struct Clawback<'info> {
         mut,
         associated_token::mint = distributor.mint,
         associated_token::authority = distributor.key(),
+        associated_token::token_program = token_program,
         address = distributor.token_vault
     )]
-    pub from: Account<'info, TokenAccount>,
+    pub from: InterfaceAccount<'info, TokenAccount>,
 
     /// The Clawback token account.
     #[account(mut, address = distributor.clawback_receiver)]
-    pub to: Account<'info, TokenAccount>,
+    pub to: InterfaceAccount<'info, TokenAccount>,
 
     /// Claimant account
     /// Anyone can claw back the funds
@@ -32,8 +39,12 @@ pub struct Clawback<'info> {
     /// The [System] program.
     pub system_program: Program<'info, System>,
 
-    /// SPL [Token] program.
-    pub token_program: Program<'info, Token>,
+    /// The distributor's mint, needed for its decimals by `transfer_checked`.
+    #[account(address = distributor.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// SPL Token or Token-2022 program owning the mint.
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 /// Claws back unclaimed tokens by:
@@ -43,10 +54,15 @@ pub struct Clawback<'info> {
 ///
 /// CHECK:
 ///     1. The distributor has not already been clawed back
+///     2. The distributor pays out tokens, not native SOL
 #[allow(clippy::result_large_err)]
 pub fn handle_clawback(ctx: Context<Clawback>) -> Result<()> {
     let distributor = &ctx.accounts.distributor;
 
+    require!(
+        distributor.distribution_mode == DistributionMode::Token,
+        ErrorCode::WrongDistributionMode
+    );
     require!(!distributor.clawed_back, ErrorCode::ClawbackAlreadyClaimed);
 
     let curr_ts = Clock::get()?.unix_timestamp;
@@ -55,6 +71,7 @@ pub fn handle_clawback(ctx: Context<Clawback>) -> Result<()> {
         return Err(ErrorCode::ClawbackBeforeStart.into());
     }
 
+    let amount = ctx.accounts.from.amount;
     let seeds = [
         b"MerkleDistributor".as_ref(),
         &distributor.mint.to_bytes(),
@@ -62,23 +79,31 @@ pub fn handle_clawback(ctx: Context<Clawback>) -> Result<()> {
         &[ctx.accounts.distributor.bump],
     ];
 
-    #[allow(deprecated)]
-    token::transfer(
+    token_interface::transfer_checked(
         CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
-            token::Transfer {
+            token_interface::TransferChecked {
                 from: ctx.accounts.from.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
                 to: ctx.accounts.to.to_account_info(),
                 authority: ctx.accounts.distributor.to_account_info(),
             },
         )
         .with_signer(&[&seeds[..]]),
-        ctx.accounts.from.amount,
+        amount,
+        ctx.accounts.mint.decimals,
     )?;
 
     let distributor = &mut ctx.accounts.distributor;
 
     distributor.clawed_back = true;
 
+    emit!(ClawbackEvent {
+        distributor: distributor.key(),
+        receiver: ctx.accounts.to.key(),
+        amount,
+        timestamp: curr_ts,
+    });
+
     Ok(())
 }
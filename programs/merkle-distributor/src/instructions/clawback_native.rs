@@ -0,0 +1,86 @@
+// Instruction to clawback lamports once they have expired
+
+use anchor_lang::{context::Context, emit, prelude::*, Accounts, Key, Result};
+
+use crate::{
+    error::ErrorCode,
+    state::{
+        claimed_event::ClawbackEvent,
+        merkle_distributor::{DistributionMode, MerkleDistributor},
+    },
+    vault::{Vault, SOL_VAULT_SEED},
+};
+
+/// [merkle_distributor::clawback_native] accounts.
+#[derive(Accounts)]
+pub struct ClawbackNative<'info> {
+    /// The [MerkleDistributor].
+    #[account(mut)]
+    pub distributor: Account<'info, MerkleDistributor>,
+
+    /// SOL vault containing the lamports to distribute.
+    #[account(
+        mut,
+        seeds = [SOL_VAULT_SEED, distributor.key().as_ref()],
+        bump,
+        address = distributor.token_vault
+    )]
+    pub from: SystemAccount<'info>,
+
+    /// The clawback receiver wallet.
+    #[account(mut, address = distributor.clawback_receiver)]
+    pub to: SystemAccount<'info>,
+
+    /// Claimant account
+    /// Anyone can claw back the funds
+    pub claimant: Signer<'info>,
+
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// [crate::instructions::handle_clawback] for a distributor paying out native SOL. Every
+/// lamport of the vault goes to the clawback receiver, its rent-exempt minimum included, which
+/// closes the vault.
+///
+/// CHECK:
+///     1. The distributor pays out native SOL
+///     2. The distributor has not already been clawed back
+#[allow(clippy::result_large_err)]
+pub fn handle_clawback_native(ctx: Context<ClawbackNative>) -> Result<()> {
+    let distributor = &ctx.accounts.distributor;
+
+    require!(
+        distributor.distribution_mode == DistributionMode::Native,
+        ErrorCode::WrongDistributionMode
+    );
+    require!(!distributor.clawed_back, ErrorCode::ClawbackAlreadyClaimed);
+
+    let curr_ts = Clock::get()?.unix_timestamp;
+
+    if curr_ts < distributor.clawback_start_ts {
+        return Err(ErrorCode::ClawbackBeforeStart.into());
+    }
+
+    let amount = ctx.accounts.from.lamports();
+    let vault = Vault::Native {
+        from: &ctx.accounts.from,
+        from_bump: ctx.bumps.from,
+        to: ctx.accounts.to.as_ref(),
+        system_program: &ctx.accounts.system_program,
+    };
+    vault.transfer(&ctx.accounts.distributor, amount)?;
+
+    let distributor = &mut ctx.accounts.distributor;
+
+    distributor.clawed_back = true;
+
+    emit!(ClawbackEvent {
+        distributor: distributor.key(),
+        receiver: ctx.accounts.to.key(),
+        amount,
+        timestamp: curr_ts,
+    });
+
+    Ok(())
+}
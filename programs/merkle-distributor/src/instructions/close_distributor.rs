@@ -0,0 +1,83 @@
+// Instruction to reclaim rent once a distributor has been clawed back
+
+use anchor_lang::{context::Context, prelude::*, Accounts, Key, Result};
+use anchor_spl::token_interface::{self, TokenAccount, TokenInterface};
+
+use crate::{
+    error::ErrorCode,
+    state::merkle_distributor::{DistributionMode, MerkleDistributor},
+};
+
+/// [merkle_distributor::close_distributor] accounts.
+#[derive(Accounts)]
+pub struct CloseDistributor<'info> {
+    /// The [MerkleDistributor], closed into the admin.
+    #[account(
+        mut,
+        close = admin,
+        constraint = !distributor.admin_renounced @ ErrorCode::AdminRenounced
+    )]
+    pub distributor: Account<'info, MerkleDistributor>,
+
+    /// Distributor ATA, must be empty.
+    #[account(
+        mut,
+        associated_token::mint = distributor.mint,
+        associated_token::authority = distributor.key(),
+        associated_token::token_program = token_program,
+        address = distributor.token_vault
+    )]
+    pub token_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Admin signer, receives the rent of both accounts
+    #[account(mut, address = distributor.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    /// SPL Token or Token-2022 program owning the vault.
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Closes the token vault and the distributor, returning their rent to the admin.
+///
+/// CHECK:
+///     1. The distributor has been clawed back
+///     2. The token vault is empty
+///     3. The distributor pays out tokens, not native SOL
+#[allow(clippy::result_large_err)]
+pub fn handle_close_distributor(ctx: Context<CloseDistributor>) -> Result<()> {
+    let distributor = &ctx.accounts.distributor;
+
+    require!(
+        distributor.distribution_mode == DistributionMode::Token,
+        ErrorCode::WrongDistributionMode
+    );
+    require!(distributor.clawed_back, ErrorCode::NotClawedBack);
+    require!(
+        ctx.accounts.token_vault.amount == 0,
+        ErrorCode::VaultNotEmpty
+    );
+
+    let seeds = [
+        b"MerkleDistributor".as_ref(),
+        &distributor.mint.to_bytes(),
+        &distributor.version.to_le_bytes(),
+        &[ctx.accounts.distributor.bump],
+    ];
+
+    token_interface::close_account(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token_interface::CloseAccount {
+                account: ctx.accounts.token_vault.to_account_info(),
+                destination: ctx.accounts.admin.to_account_info(),
+                authority: ctx.accounts.distributor.to_account_info(),
+            },
+        )
+        .with_signer(&[&seeds[..]]),
+    )?;
+
+    // Note: might get truncated, do not rely on
+    msg!("closed distributor {}", ctx.accounts.distributor.key());
+
+    Ok(())
+}
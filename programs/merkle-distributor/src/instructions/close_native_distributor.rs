@@ -0,0 +1,47 @@
+// Instruction to reclaim rent once a native SOL distributor has been clawed back
+
+use anchor_lang::{context::Context, prelude::*, Accounts, Key, Result};
+
+use crate::{
+    error::ErrorCode,
+    state::merkle_distributor::{DistributionMode, MerkleDistributor},
+};
+
+/// [merkle_distributor::close_native_distributor] accounts.
+#[derive(Accounts)]
+pub struct CloseNativeDistributor<'info> {
+    /// The [MerkleDistributor], closed into the admin.
+    #[account(
+        mut,
+        close = admin,
+        constraint = !distributor.admin_renounced @ ErrorCode::AdminRenounced
+    )]
+    pub distributor: Account<'info, MerkleDistributor>,
+
+    /// Admin signer, receives the rent of the distributor
+    #[account(mut, address = distributor.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+/// Closes a native SOL distributor, returning its rent to the admin. Clawback already emptied
+/// and thereby closed the vault, so unlike [crate::instructions::handle_close_distributor]
+/// there is no vault to close. Lamports sent to the vault afterwards stay there.
+///
+/// CHECK:
+///     1. The distributor pays out native SOL
+///     2. The distributor has been clawed back
+#[allow(clippy::result_large_err)]
+pub fn handle_close_native_distributor(ctx: Context<CloseNativeDistributor>) -> Result<()> {
+    let distributor = &ctx.accounts.distributor;
+
+    require!(
+        distributor.distribution_mode == DistributionMode::Native,
+        ErrorCode::WrongDistributionMode
+    );
+    require!(distributor.clawed_back, ErrorCode::NotClawedBack);
+
+    // Note: might get truncated, do not rely on
+    msg!("closed distributor {}", ctx.accounts.distributor.key());
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use anchor_lang::{accounts::signer::Signer, context::Context, prelude::*, Accounts, Result};
+use light_sdk::{
+    account::LightAccount,
+    cpi::{
+        v2::{CpiAccounts, LightSystemProgramCpi},
+        InvokeLightSystemProgram, LightCpiInstruction,
+    },
+    instruction::{account_meta::CompressedAccountMeta, ValidityProof},
+};
+
+use crate::{
+    error::{light_sdk_error, ErrorCode},
+    state::claim_status::{ClaimStatus, LegacyClaimStatus},
+    LIGHT_CPI_SIGNER,
+};
+
+/// [merkle_distributor::migrate_claim_status] accounts.
+#[derive(Accounts)]
+pub struct MigrateClaimStatus<'info> {
+    /// Pays the Light system program, anyone can migrate a claim status
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+/// Rewrites a ClaimStatus written in the [LegacyClaimStatus] layout in the current
+/// [ClaimStatus] layout, keeping its address and amounts. claim_locked cannot prove a legacy
+/// account, so claimants from before the layout change run this once first.
+/// CHECK:
+///     1. `claim_status_data` is the data of the input account, which the Light system
+///        program verifies against its hash in the state tree
+#[allow(clippy::result_large_err)]
+pub fn handle_migrate_claim_status<'info>(
+    ctx: Context<'_, '_, '_, 'info, MigrateClaimStatus<'info>>,
+    input_account_meta: CompressedAccountMeta,
+    claim_status_data: LegacyClaimStatus,
+    validity_proof: ValidityProof,
+) -> Result<()> {
+    // The input is hashed in the layout it was written in and the output in the current one
+    let legacy_claim_status = LightAccount::<LegacyClaimStatus>::new_mut(
+        &crate::ID,
+        &input_account_meta,
+        claim_status_data,
+    )
+    .and_then(|account| account.to_account_info())
+    .map_err(|e| light_sdk_error(e, ErrorCode::LightAccountCreationFailed))?;
+    let mut claim_status = LightAccount::<ClaimStatus>::new_mut(
+        &crate::ID,
+        &input_account_meta,
+        ClaimStatus::from(claim_status_data),
+    )
+    .and_then(|account| account.to_account_info())
+    .map_err(|e| light_sdk_error(e, ErrorCode::LightAccountCreationFailed))?;
+    claim_status.input = legacy_claim_status.input;
+
+    let light_cpi_accounts = CpiAccounts::new(
+        ctx.accounts.payer.as_ref(),
+        ctx.remaining_accounts,
+        LIGHT_CPI_SIGNER,
+    );
+    LightSystemProgramCpi::new_cpi(LIGHT_CPI_SIGNER, validity_proof)
+        .with_account_infos(&[claim_status])
+        .invoke(light_cpi_accounts)
+        .map_err(|e| light_sdk_error(e, ErrorCode::LightCpiFailed))?;
+
+    // Note: might get truncated, do not rely on
+    msg!("migrated claim status of {}", claim_status_data.claimant);
+
+    Ok(())
+}
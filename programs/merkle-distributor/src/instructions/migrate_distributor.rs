@@ -0,0 +1,75 @@
+use anchor_lang::{
+    context::{Context, CpiContext},
+    prelude::*,
+    system_program::{self, Transfer},
+    Accounts, Discriminator, Result, ToAccountInfo,
+};
+
+use crate::{
+    error::ErrorCode,
+    state::merkle_distributor::{LegacyMerkleDistributor, MerkleDistributor},
+};
+
+/// [merkle_distributor::migrate_distributor] accounts.
+#[derive(Accounts)]
+pub struct MigrateDistributor<'info> {
+    /// CHECK: A [MerkleDistributor] in the [LegacyMerkleDistributor] layout, which Anchor
+    /// cannot deserialize. The handler checks the discriminator and length.
+    #[account(mut, owner = crate::ID)]
+    pub distributor: UncheckedAccount<'info>,
+
+    /// Pays the rent for the extra space, anyone can migrate a distributor
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Extends a distributor created before `cliff_ts` and the fields after it were added to
+/// [MerkleDistributor::LEN], filling the new fields with their legacy behaviour. Other
+/// instructions cannot load such a distributor until it is migrated.
+/// CHECK:
+///     1. The account is a [MerkleDistributor] of this program
+///     2. The account is shorter than [MerkleDistributor::LEN], i.e. not migrated yet
+#[allow(clippy::result_large_err)]
+pub fn handle_migrate_distributor(ctx: Context<MigrateDistributor>) -> Result<()> {
+    let distributor = ctx.accounts.distributor.to_account_info();
+
+    let legacy = {
+        let data = distributor.try_borrow_data()?;
+        require!(
+            data.starts_with(MerkleDistributor::DISCRIMINATOR),
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+        require!(
+            data.len() < MerkleDistributor::LEN,
+            ErrorCode::DistributorAlreadyMigrated
+        );
+        LegacyMerkleDistributor::deserialize(&mut &data[8..])?
+    };
+
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(MerkleDistributor::LEN);
+    let top_up = rent_exempt_minimum.saturating_sub(distributor.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: distributor.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    distributor.resize(MerkleDistributor::LEN)?;
+
+    let migrated = MerkleDistributor::from(legacy);
+    migrated.try_serialize(&mut &mut distributor.try_borrow_mut_data()?[..])?;
+
+    // Note: might get truncated, do not rely on
+    msg!("migrated distributor version {}", migrated.version);
+
+    Ok(())
+}
@@ -1,13 +1,47 @@
 pub use claim_locked::*;
+pub use claim_locked_native::*;
+pub use claim_locked_to::*;
 pub use clawback::*;
+pub use clawback_native::*;
+pub use close_distributor::*;
+pub use close_native_distributor::*;
+pub use migrate_claim_status::*;
+pub use migrate_distributor::*;
 pub use new_claim::*;
+pub use new_claim_native::*;
+pub use new_claim_signed::*;
+pub use new_claim_to::*;
 pub use new_distributor::*;
+pub use new_native_distributor::*;
+pub use partial_clawback::*;
+pub use reassign_claim::*;
+pub use renounce_admin::*;
 pub use set_admin::*;
 pub use set_clawback_receiver::*;
+pub use set_paused::*;
+pub use update_clawback_start_ts::*;
+pub use update_root::*;
 pub mod claim_locked;
+pub mod claim_locked_native;
+pub mod claim_locked_to;
 pub mod clawback;
+pub mod clawback_native;
+pub mod close_distributor;
+pub mod close_native_distributor;
+pub mod migrate_claim_status;
+pub mod migrate_distributor;
 pub mod new_claim;
+pub mod new_claim_native;
+pub mod new_claim_signed;
+pub mod new_claim_to;
 pub mod new_distributor;
+pub mod new_native_distributor;
+pub mod partial_clawback;
+pub mod reassign_claim;
+pub mod renounce_admin;
 
 pub mod set_admin;
 pub mod set_clawback_receiver;
+pub mod set_paused;
+pub mod update_clawback_start_ts;
+pub mod update_root;
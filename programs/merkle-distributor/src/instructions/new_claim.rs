@@ -1,12 +1,12 @@
 use anchor_lang::{
     context::Context, prelude::*, solana_program::hash::hashv, Accounts, Key, Result,
 };
-use anchor_spl::token::{self, Token, TokenAccount};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 use jito_merkle_verify::verify;
 use light_sdk::{
     account::LightAccount,
-    address::v2::derive_address,
+    address::{v2::derive_address, AddressSeed},
     cpi::{
         v2::{CpiAccounts, LightSystemProgramCpi},
         InvokeLightSystemProgram, LightCpiInstruction,
@@ -15,11 +15,13 @@ use light_sdk::{
 };
 
 use crate::{
-    error::ErrorCode,
+    error::{light_sdk_error, ErrorCode},
     state::{
-        claim_status::ClaimStatus, claimed_event::NewClaimEvent,
+        claim_status::{CategoryBreakdown, ClaimStatus, UnlockSchedule},
+        claimed_event::NewClaimEvent,
         merkle_distributor::MerkleDistributor,
     },
+    vault::Vault,
     LIGHT_CPI_SIGNER,
 };
 
@@ -40,24 +42,30 @@ pub struct NewClaim<'info> {
         mut,
         associated_token::mint = distributor.mint,
         associated_token::authority = distributor.key(),
+        associated_token::token_program = token_program,
         address = distributor.token_vault
     )]
-    pub from: Account<'info, TokenAccount>,
+    pub from: InterfaceAccount<'info, TokenAccount>,
 
     /// Account to send the claimed tokens to.
     #[account(
         mut,
         token::mint=distributor.mint,
-        token::authority = claimant.key()
+        token::authority = claimant.key(),
+        token::token_program = token_program
     )]
-    pub to: Account<'info, TokenAccount>,
+    pub to: InterfaceAccount<'info, TokenAccount>,
 
     /// Who is claiming the tokens.
     #[account(mut, address = to.owner @ ErrorCode::OwnerMismatch)]
     pub claimant: Signer<'info>,
 
-    /// SPL [Token] program.
-    pub token_program: Program<'info, Token>,
+    /// The distributor's mint, needed for its decimals by `transfer_checked`.
+    #[account(address = distributor.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// SPL Token or Token-2022 program owning the mint.
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 /// Initializes a new claim from the [MerkleDistributor].
@@ -68,24 +76,89 @@ pub struct NewClaim<'info> {
 ///
 /// CHECK:
 ///     1. The claim window has not expired and the distributor has not been clawed back
-///     2. The claimant is the owner of the to account
-///     3. Num nodes claimed is less than max_num_nodes
-///     4. The merkle proof is valid
+///     2. The distributor is not paused
+///     3. The claimant is the owner of the to account
+///     4. The merkle proof has the distributor's tree depth and is valid
+///     5. A per-node unlock schedule, if any, is part of the proven leaf and has start < end
+///     6. A category breakdown, if any, adds up to the proven amounts
+///     7. Num nodes claimed, counting this claim once its proof verified, is at most
+///        max_num_nodes
+///     8. The distributor pays out tokens, not native SOL
+///     9. The vault holds the amount to pay out
+///     10. The recipient token account is not frozen
 #[allow(clippy::result_large_err)]
 pub fn handle_new_claim<'info>(
     ctx: Context<'_, '_, '_, 'info, NewClaim<'info>>,
     amount_unlocked: u64,
     amount_locked: u64,
     proof: Vec<[u8; 32]>,
+    unlock_schedule: Option<UnlockSchedule>,
+    category_breakdown: Option<CategoryBreakdown>,
     validity_proof: ValidityProof,
     address_tree_info: PackedAddressTreeInfo,
     output_state_tree_index: u8,
 ) -> Result<()> {
-    let distributor = &mut ctx.accounts.distributor;
+    create_claim(
+        &mut ctx.accounts.distributor,
+        Vault::Token {
+            from: &ctx.accounts.from,
+            to: &ctx.accounts.to,
+            mint: &ctx.accounts.mint,
+            token_program: &ctx.accounts.token_program,
+        },
+        ctx.accounts.claimant.key(),
+        ctx.accounts.claimant.as_ref(),
+        ctx.remaining_accounts,
+        amount_unlocked,
+        amount_locked,
+        proof,
+        unlock_schedule,
+        category_breakdown,
+        validity_proof,
+        address_tree_info,
+        output_state_tree_index,
+    )
+}
 
+/// Shared by [handle_new_claim], [crate::instructions::handle_new_claim_signed] and
+/// [crate::instructions::handle_new_claim_native] once the claimant has been authenticated.
+/// `vault` pays the unlocked amount, `fee_payer` pays for the Light system program CPI.
+#[allow(clippy::result_large_err)]
+pub(crate) fn create_claim<'info>(
+    distributor: &mut Account<'info, MerkleDistributor>,
+    vault: Vault<'_, 'info>,
+    claimant: Pubkey,
+    fee_payer: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    amount_unlocked: u64,
+    amount_locked: u64,
+    proof: Vec<[u8; 32]>,
+    unlock_schedule: Option<UnlockSchedule>,
+    category_breakdown: Option<CategoryBreakdown>,
+    validity_proof: ValidityProof,
+    address_tree_info: PackedAddressTreeInfo,
+    output_state_tree_index: u8,
+) -> Result<()> {
     let curr_ts = Clock::get()?.unix_timestamp;
+    require!(
+        distributor.distribution_mode == vault.mode(),
+        ErrorCode::WrongDistributionMode
+    );
     require!(!distributor.clawed_back, ErrorCode::ClaimExpired);
+    require!(!distributor.paused, ErrorCode::Paused);
+
+    verify_claim_proof(
+        distributor.root,
+        distributor.tree_depth,
+        claimant,
+        amount_unlocked,
+        amount_locked,
+        unlock_schedule,
+        category_breakdown,
+        proof,
+    )?;
 
+    // Only counted once the proof verified, so the counter reflects valid claims alone
     distributor.num_nodes_claimed = distributor
         .num_nodes_claimed
         .checked_add(1)
@@ -96,60 +169,28 @@ pub fn handle_new_claim<'info>(
         ErrorCode::MaxNodesExceeded
     );
 
-    let claimant_account = &ctx.accounts.claimant;
-
-    // Verify the merkle proof.
-    let node = hashv(&[
-        &claimant_account.key().to_bytes(),
-        &amount_unlocked.to_le_bytes(),
-        &amount_locked.to_le_bytes(),
-    ]);
-
-    let distributor = &ctx.accounts.distributor;
-    let node = hashv(&[LEAF_PREFIX, &node.to_bytes()]);
-
-    require!(
-        verify(proof, distributor.root, node.to_bytes()),
-        ErrorCode::InvalidProof
-    );
-
     // Create CPI accounts for Light system program
-    let light_cpi_accounts = CpiAccounts::new(
-        ctx.accounts.claimant.as_ref(),
-        ctx.remaining_accounts,
-        LIGHT_CPI_SIGNER,
-    );
-
-    // Derive v2 address for ClaimStatus compressed account
-    let address_seeds: [&[u8]; 3] = [
-        b"ClaimStatus",
-        &ctx.accounts.claimant.key().to_bytes(),
-        &ctx.accounts.distributor.key().to_bytes(),
-    ];
+    let light_cpi_accounts = CpiAccounts::new(fee_payer, remaining_accounts, LIGHT_CPI_SIGNER);
 
-    let address_tree_pubkey = address_tree_info
-        .get_tree_pubkey(&light_cpi_accounts)
-        .map_err(|_| ErrorCode::InvalidAddressTree)?;
-
-    // Validate address tree matches expected v2 tree
-    if address_tree_pubkey.to_bytes() != light_sdk::constants::ADDRESS_TREE_V2 {
-        return Err(ErrorCode::InvalidAddressTree.into());
-    }
-
-    let (address, address_seed) = derive_address(
-        &address_seeds,
-        &address_tree_pubkey,
-        &crate::ID,
-    );
+    let (address, address_seed) = claim_status_address(
+        claimant,
+        distributor.key(),
+        &address_tree_info,
+        &light_cpi_accounts,
+    )?;
 
     // assigned_account_index = 0 because the address is assigned to the first (and only) output account
     let new_address_params = address_tree_info.into_new_address_params_assigned_packed(address_seed, Some(0));
 
+    // Send enough to cover a Token-2022 transfer fee so the claimant receives amount_unlocked
+    let transfer_fee = vault.transfer_fee(amount_unlocked)?;
+    let transfer_amount = amount_unlocked
+        .checked_add(transfer_fee)
+        .ok_or(ErrorCode::ArithmeticError)?;
+
     // Validate vault has sufficient balance before creating compressed account
-    require!(
-        ctx.accounts.from.amount >= amount_unlocked,
-        ErrorCode::InsufficientUnlockedTokens
-    );
+    vault.require_available(transfer_amount)?;
+    vault.require_recipient_not_frozen()?;
 
     // Initialize ClaimStatus compressed account
     let mut claim_status = LightAccount::<ClaimStatus>::new_init(
@@ -157,38 +198,33 @@ pub fn handle_new_claim<'info>(
         Some(address),
         output_state_tree_index,
     );
-    claim_status.claimant = ctx.accounts.claimant.key();
+    claim_status.claimant = claimant;
     claim_status.locked_amount = amount_locked;
     claim_status.unlocked_amount = amount_unlocked;
     claim_status.locked_amount_withdrawn = 0;
+    claim_status.unlock_schedule = unlock_schedule;
+    claim_status.last_claimed_ts = 0;
+    claim_status.category_breakdown = category_breakdown;
+
+    #[cfg(feature = "trace-cpi")]
+    msg!(
+        "trace-cpi: new_address_params {:?} output_state_tree_index {} remaining_accounts {} proof {}",
+        new_address_params,
+        output_state_tree_index,
+        remaining_accounts.len(),
+        validity_proof.0.is_some(),
+    );
 
     // Invoke Light system program via CPI
     LightSystemProgramCpi::new_cpi(LIGHT_CPI_SIGNER, validity_proof)
-        .with_light_account(claim_status)?
+        .with_light_account(claim_status)
+        .map_err(|e| light_sdk_error(e, ErrorCode::LightAccountCreationFailed))?
         .with_new_addresses(&[new_address_params])
-        .invoke(light_cpi_accounts)?;
-
-    let seeds = [
-        b"MerkleDistributor".as_ref(),
-        &distributor.mint.to_bytes(),
-        &distributor.version.to_le_bytes(),
-        &[ctx.accounts.distributor.bump],
-    ];
+        .invoke(light_cpi_accounts)
+        .map_err(|e| light_sdk_error(e, ErrorCode::LightCpiFailed))?;
 
-    token::transfer(
-        CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            token::Transfer {
-                from: ctx.accounts.from.to_account_info(),
-                to: ctx.accounts.to.to_account_info(),
-                authority: ctx.accounts.distributor.to_account_info(),
-            },
-        )
-        .with_signer(&[&seeds[..]]),
-        amount_unlocked,
-    )?;
+    vault.transfer(distributor, transfer_amount)?;
 
-    let distributor = &mut ctx.accounts.distributor;
     distributor.total_amount_claimed = distributor
         .total_amount_claimed
         .checked_add(amount_unlocked)
@@ -200,17 +236,124 @@ pub fn handle_new_claim<'info>(
     );
 
     // Note: might get truncated, do not rely on
+    let (start_ts, end_ts) = match unlock_schedule {
+        Some(schedule) => (schedule.start_ts, schedule.end_ts),
+        None => (distributor.start_ts, distributor.end_ts),
+    };
     msg!(
         "Created new claim with locked {} and {} unlocked with lockup start:{} end:{}",
         amount_locked,
         amount_unlocked,
-        distributor.start_ts,
-        distributor.end_ts,
+        start_ts,
+        end_ts,
     );
     emit!(NewClaimEvent {
-        claimant: claimant_account.key(),
-        timestamp: curr_ts
+        claimant,
+        timestamp: curr_ts,
+        amount: amount_unlocked,
+        transfer_fee,
+        category_breakdown,
     });
 
     Ok(())
 }
+
+/// Fails with `ProofLengthMismatch` unless `proof` has `tree_depth` hashes, with
+/// `InvalidProof` unless it leads from the leaf of `claimant` to `root`, and with
+/// `CategoryBreakdownMismatch` unless a breakdown adds up to the proven amounts. A
+/// `tree_depth` of 0, which distributors migrated from the legacy layout have, skips the
+/// length check.
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::result_large_err)]
+pub(crate) fn verify_claim_proof(
+    root: [u8; 32],
+    tree_depth: u8,
+    claimant: Pubkey,
+    amount_unlocked: u64,
+    amount_locked: u64,
+    unlock_schedule: Option<UnlockSchedule>,
+    category_breakdown: Option<CategoryBreakdown>,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    // A proof for a tree of another size can never verify, so say why instead of InvalidProof
+    if tree_depth != 0 && proof.len() != usize::from(tree_depth) {
+        msg!(
+            "Proof has {} hashes, the distributor's tree has depth {}",
+            proof.len(),
+            tree_depth
+        );
+        return err!(ErrorCode::ProofLengthMismatch);
+    }
+
+    // Nodes with a custom unlock schedule commit to it in the leaf.
+    let node = match unlock_schedule {
+        Some(schedule) => {
+            require!(
+                schedule.start_ts < schedule.end_ts,
+                ErrorCode::StartTimestampAfterEnd
+            );
+            hashv(&[
+                &claimant.to_bytes(),
+                &amount_unlocked.to_le_bytes(),
+                &amount_locked.to_le_bytes(),
+                &schedule.start_ts.to_le_bytes(),
+                &schedule.end_ts.to_le_bytes(),
+            ])
+        }
+        None => hashv(&[
+            &claimant.to_bytes(),
+            &amount_unlocked.to_le_bytes(),
+            &amount_locked.to_le_bytes(),
+        ]),
+    };
+
+    let node = hashv(&[LEAF_PREFIX, &node.to_bytes()]);
+
+    require!(
+        verify(proof, root, node.to_bytes()),
+        ErrorCode::InvalidProof
+    );
+
+    // The breakdown is not in the leaf, so it is only as trustworthy as its totals
+    if let Some(breakdown) = category_breakdown {
+        require!(
+            breakdown.matches(amount_unlocked, amount_locked),
+            ErrorCode::CategoryBreakdownMismatch
+        );
+    }
+    Ok(())
+}
+
+/// Address of the ClaimStatus of `claimant` in the v2 address tree `address_tree_info` points
+/// at, with the seed creating it.
+#[allow(clippy::result_large_err)]
+pub(crate) fn claim_status_address(
+    claimant: Pubkey,
+    distributor: Pubkey,
+    address_tree_info: &PackedAddressTreeInfo,
+    light_cpi_accounts: &CpiAccounts<'_, '_>,
+) -> Result<([u8; 32], AddressSeed)> {
+    // Derive v2 address for ClaimStatus compressed account
+    let address_seeds: [&[u8]; 3] = [
+        b"ClaimStatus",
+        &claimant.to_bytes(),
+        &distributor.to_bytes(),
+    ];
+
+    // The tree is looked up by the packed index from the instruction args, not by a fixed
+    // position in the remaining accounts
+    let address_tree_pubkey = address_tree_info
+        .get_tree_pubkey(light_cpi_accounts)
+        .map_err(|_| ErrorCode::InvalidAccountLayout)?;
+
+    // Validate address tree matches expected v2 tree
+    if address_tree_pubkey.to_bytes() != light_sdk::constants::ADDRESS_TREE_V2 {
+        return Err(ErrorCode::InvalidAddressTree.into());
+    }
+
+    Ok(derive_address(
+        &address_seeds,
+        &address_tree_pubkey,
+        &crate::ID,
+    ))
+}
@@ -0,0 +1,75 @@
+use anchor_lang::{context::Context, prelude::*, Accounts, Key, Result};
+use light_sdk::instruction::{PackedAddressTreeInfo, ValidityProof};
+
+use crate::{
+    instructions::new_claim::create_claim,
+    state::{
+        claim_status::{CategoryBreakdown, UnlockSchedule},
+        merkle_distributor::MerkleDistributor,
+    },
+    vault::{Vault, SOL_VAULT_SEED},
+};
+
+/// [merkle_distributor::new_claim_native] accounts.
+#[derive(Accounts)]
+pub struct NewClaimNative<'info> {
+    /// The [MerkleDistributor].
+    #[account(mut)]
+    pub distributor: Account<'info, MerkleDistributor>,
+
+    /// SOL vault containing the lamports to distribute.
+    #[account(
+        mut,
+        seeds = [SOL_VAULT_SEED, distributor.key().as_ref()],
+        bump,
+        address = distributor.token_vault
+    )]
+    pub from: SystemAccount<'info>,
+
+    /// Who is claiming, receives the lamports.
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// [crate::instructions::handle_new_claim] for a distributor paying out native SOL: the
+/// unlocked amount is sent to the claimant's wallet in lamports.
+///
+/// CHECK:
+///     1. The distributor pays out native SOL
+///     2. Everything else [crate::instructions::handle_new_claim] checks
+#[allow(clippy::result_large_err)]
+pub fn handle_new_claim_native<'info>(
+    ctx: Context<'_, '_, '_, 'info, NewClaimNative<'info>>,
+    amount_unlocked: u64,
+    amount_locked: u64,
+    proof: Vec<[u8; 32]>,
+    unlock_schedule: Option<UnlockSchedule>,
+    category_breakdown: Option<CategoryBreakdown>,
+    validity_proof: ValidityProof,
+    address_tree_info: PackedAddressTreeInfo,
+    output_state_tree_index: u8,
+) -> Result<()> {
+    create_claim(
+        &mut ctx.accounts.distributor,
+        Vault::Native {
+            from: &ctx.accounts.from,
+            from_bump: ctx.bumps.from,
+            to: ctx.accounts.claimant.as_ref(),
+            system_program: &ctx.accounts.system_program,
+        },
+        ctx.accounts.claimant.key(),
+        ctx.accounts.claimant.as_ref(),
+        ctx.remaining_accounts,
+        amount_unlocked,
+        amount_locked,
+        proof,
+        unlock_schedule,
+        category_breakdown,
+        validity_proof,
+        address_tree_info,
+        output_state_tree_index,
+    )
+}
@@ -0,0 +1,241 @@
+use anchor_lang::{
+    context::Context,
+    prelude::*,
+    solana_program::{
+        ed25519_program,
+        instruction::Instruction,
+        sysvar::instructions::{get_instruction_relative, ID as INSTRUCTIONS_SYSVAR_ID},
+    },
+    Accounts, Key, Result,
+};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use light_sdk::instruction::{PackedAddressTreeInfo, ValidityProof};
+
+use crate::{
+    error::ErrorCode,
+    instructions::new_claim::create_claim,
+    state::{
+        claim_status::{CategoryBreakdown, UnlockSchedule},
+        merkle_distributor::MerkleDistributor,
+    },
+    vault::Vault,
+};
+
+/// Ed25519 instruction data layout, see `solana_ed25519_program`.
+const SIGNATURE_OFFSETS_START: usize = 2;
+const SIGNATURE_OFFSETS_SERIALIZED_SIZE: usize = 14;
+const PUBKEY_SERIALIZED_SIZE: usize = 32;
+/// Instruction index meaning "the ed25519 instruction itself".
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// [merkle_distributor::new_claim_signed] accounts.
+#[derive(Accounts)]
+pub struct NewClaimSigned<'info> {
+    /// The [MerkleDistributor].
+    #[account(mut)]
+    pub distributor: Account<'info, MerkleDistributor>,
+
+    /// Distributor ATA containing the tokens to distribute.
+    #[account(
+        mut,
+        associated_token::mint = distributor.mint,
+        associated_token::authority = distributor.key(),
+        associated_token::token_program = token_program,
+        address = distributor.token_vault
+    )]
+    pub from: InterfaceAccount<'info, TokenAccount>,
+
+    /// Account to send the claimed tokens to.
+    #[account(
+        mut,
+        token::mint=distributor.mint,
+        token::authority = claimant.key(),
+        token::token_program = token_program
+    )]
+    pub to: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Who is claiming the tokens. Authenticated by the ed25519 instruction preceding
+    /// this one instead of a transaction signature.
+    #[account(address = to.owner @ ErrorCode::OwnerMismatch)]
+    pub claimant: UncheckedAccount<'info>,
+
+    /// Sponsor paying the transaction and Light system program fees.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Instructions sysvar, used to find the claimant's ed25519 signature.
+    #[account(address = INSTRUCTIONS_SYSVAR_ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    /// The distributor's mint, needed for its decimals by `transfer_checked`.
+    #[account(address = distributor.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// SPL Token or Token-2022 program owning the mint.
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Message the claimant signs off-chain to authorize a sponsored claim from `distributor`.
+pub fn claim_message(distributor: &Pubkey) -> Vec<u8> {
+    format!("claim from distributor {distributor}").into_bytes()
+}
+
+/// Public key and message of an ed25519 program instruction holding exactly one signature,
+/// or None if it is anything else. The ed25519 program has already verified the signature
+/// by the time this instruction runs.
+pub fn ed25519_signed_message(ix: &Instruction) -> Option<(Pubkey, &[u8])> {
+    if ix.program_id != ed25519_program::ID {
+        return None;
+    }
+    let data = ix.data.as_slice();
+    if data.len() < SIGNATURE_OFFSETS_START + SIGNATURE_OFFSETS_SERIALIZED_SIZE || data[0] != 1 {
+        return None;
+    }
+
+    let offset = |i: usize| {
+        let start = SIGNATURE_OFFSETS_START + i * 2;
+        u16::from_le_bytes([data[start], data[start + 1]])
+    };
+    let signature_instruction_index = offset(1);
+    let public_key_offset = offset(2) as usize;
+    let public_key_instruction_index = offset(3);
+    let message_data_offset = offset(4) as usize;
+    let message_data_size = offset(5) as usize;
+    let message_instruction_index = offset(6);
+
+    // Data in other instructions could be swapped out from under the signature check
+    if signature_instruction_index != CURRENT_INSTRUCTION
+        || public_key_instruction_index != CURRENT_INSTRUCTION
+        || message_instruction_index != CURRENT_INSTRUCTION
+    {
+        return None;
+    }
+
+    let pubkey = data.get(public_key_offset..public_key_offset + PUBKEY_SERIALIZED_SIZE)?;
+    let message = data.get(message_data_offset..message_data_offset + message_data_size)?;
+    Some((Pubkey::try_from(pubkey).ok()?, message))
+}
+
+/// Like [crate::instructions::handle_new_claim], but the claimant authorizes the claim by
+/// signing [claim_message] off-chain instead of signing the transaction, so a sponsor can
+/// submit it. The ed25519 program instruction verifying that signature must come immediately
+/// before this instruction.
+///
+/// CHECK:
+///     1. The previous instruction verifies the claimant's signature over [claim_message]
+///     2. Everything [crate::instructions::handle_new_claim] checks
+#[allow(clippy::result_large_err)]
+pub fn handle_new_claim_signed<'info>(
+    ctx: Context<'_, '_, '_, 'info, NewClaimSigned<'info>>,
+    amount_unlocked: u64,
+    amount_locked: u64,
+    proof: Vec<[u8; 32]>,
+    unlock_schedule: Option<UnlockSchedule>,
+    category_breakdown: Option<CategoryBreakdown>,
+    validity_proof: ValidityProof,
+    address_tree_info: PackedAddressTreeInfo,
+    output_state_tree_index: u8,
+) -> Result<()> {
+    let signature_ix = get_instruction_relative(-1, &ctx.accounts.instructions_sysvar)
+        .map_err(|_| ErrorCode::MissingClaimantSignature)?;
+    let (signer, message) =
+        ed25519_signed_message(&signature_ix).ok_or(ErrorCode::MissingClaimantSignature)?;
+    require!(
+        signer == ctx.accounts.claimant.key()
+            && message == claim_message(&ctx.accounts.distributor.key()).as_slice(),
+        ErrorCode::InvalidClaimantSignature
+    );
+
+    create_claim(
+        &mut ctx.accounts.distributor,
+        Vault::Token {
+            from: &ctx.accounts.from,
+            to: &ctx.accounts.to,
+            mint: &ctx.accounts.mint,
+            token_program: &ctx.accounts.token_program,
+        },
+        ctx.accounts.claimant.key(),
+        ctx.accounts.payer.as_ref(),
+        ctx.remaining_accounts,
+        amount_unlocked,
+        amount_locked,
+        proof,
+        unlock_schedule,
+        category_breakdown,
+        validity_proof,
+        address_tree_info,
+        output_state_tree_index,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds ed25519 instruction data like `new_ed25519_instruction_with_signature`,
+    /// with a dummy signature since only the layout is parsed here.
+    fn ed25519_ix(pubkey: &Pubkey, message: &[u8], instruction_index: u16) -> Instruction {
+        let public_key_offset = SIGNATURE_OFFSETS_START + SIGNATURE_OFFSETS_SERIALIZED_SIZE;
+        let signature_offset = public_key_offset + PUBKEY_SERIALIZED_SIZE;
+        let message_data_offset = signature_offset + 64;
+
+        let mut data = vec![1, 0];
+        for offset in [
+            signature_offset as u16,
+            instruction_index,
+            public_key_offset as u16,
+            instruction_index,
+            message_data_offset as u16,
+            message.len() as u16,
+            instruction_index,
+        ] {
+            data.extend_from_slice(&offset.to_le_bytes());
+        }
+        data.extend_from_slice(pubkey.as_ref());
+        data.extend_from_slice(&[0; 64]);
+        data.extend_from_slice(message);
+
+        Instruction {
+            program_id: ed25519_program::ID,
+            accounts: vec![],
+            data,
+        }
+    }
+
+    #[test]
+    fn test_ed25519_signed_message() {
+        let claimant = Pubkey::new_unique();
+        let message = claim_message(&Pubkey::new_unique());
+        let ix = ed25519_ix(&claimant, &message, CURRENT_INSTRUCTION);
+
+        assert_eq!(
+            ed25519_signed_message(&ix),
+            Some((claimant, message.as_slice()))
+        );
+    }
+
+    #[test]
+    fn test_ed25519_signed_message_rejects_other_layouts() {
+        let claimant = Pubkey::new_unique();
+        let message = claim_message(&Pubkey::new_unique());
+
+        // not the ed25519 program
+        let mut ix = ed25519_ix(&claimant, &message, CURRENT_INSTRUCTION);
+        ix.program_id = Pubkey::new_unique();
+        assert_eq!(ed25519_signed_message(&ix), None);
+
+        // signature data taken from another instruction
+        let ix = ed25519_ix(&claimant, &message, 0);
+        assert_eq!(ed25519_signed_message(&ix), None);
+
+        // more than one signature
+        let mut ix = ed25519_ix(&claimant, &message, CURRENT_INSTRUCTION);
+        ix.data[0] = 2;
+        assert_eq!(ed25519_signed_message(&ix), None);
+
+        // truncated message
+        let mut ix = ed25519_ix(&claimant, &message, CURRENT_INSTRUCTION);
+        ix.data.truncate(ix.data.len() - 1);
+        assert_eq!(ed25519_signed_message(&ix), None);
+    }
+}
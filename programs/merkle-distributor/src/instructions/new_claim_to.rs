@@ -0,0 +1,91 @@
+use anchor_lang::{context::Context, prelude::*, Accounts, Key, Result};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use light_sdk::instruction::{PackedAddressTreeInfo, ValidityProof};
+
+use crate::{
+    instructions::new_claim::create_claim,
+    state::{
+        claim_status::{CategoryBreakdown, UnlockSchedule},
+        merkle_distributor::MerkleDistributor,
+    },
+    vault::Vault,
+};
+
+/// [merkle_distributor::new_claim_to] accounts.
+#[derive(Accounts)]
+pub struct NewClaimTo<'info> {
+    /// The [MerkleDistributor].
+    #[account(mut)]
+    pub distributor: Account<'info, MerkleDistributor>,
+
+    /// Distributor ATA containing the tokens to distribute.
+    #[account(
+        mut,
+        associated_token::mint = distributor.mint,
+        associated_token::authority = distributor.key(),
+        associated_token::token_program = token_program,
+        address = distributor.token_vault
+    )]
+    pub from: InterfaceAccount<'info, TokenAccount>,
+
+    /// Recipient token account chosen by the claimant, owned by anyone.
+    #[account(
+        mut,
+        token::mint = distributor.mint,
+        token::token_program = token_program
+    )]
+    pub to: InterfaceAccount<'info, TokenAccount>,
+
+    /// Who is claiming the tokens, authorizing the transfer to `to` by signing.
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+
+    /// The distributor's mint, needed for its decimals by `transfer_checked`.
+    #[account(address = distributor.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// SPL Token or Token-2022 program owning the mint.
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// [crate::instructions::handle_new_claim] sending the unlocked amount to a token account of
+/// the claimant's choosing, e.g. a cold wallet or a custodian. The claim status is still that
+/// of the claimant, so eligibility and later withdrawals are unchanged.
+///
+/// CHECK:
+///     1. The claimant signed
+///     2. Everything else [crate::instructions::handle_new_claim] checks, except the owner of
+///        the to account
+#[allow(clippy::result_large_err)]
+pub fn handle_new_claim_to<'info>(
+    ctx: Context<'_, '_, '_, 'info, NewClaimTo<'info>>,
+    amount_unlocked: u64,
+    amount_locked: u64,
+    proof: Vec<[u8; 32]>,
+    unlock_schedule: Option<UnlockSchedule>,
+    category_breakdown: Option<CategoryBreakdown>,
+    validity_proof: ValidityProof,
+    address_tree_info: PackedAddressTreeInfo,
+    output_state_tree_index: u8,
+) -> Result<()> {
+    create_claim(
+        &mut ctx.accounts.distributor,
+        Vault::Token {
+            from: &ctx.accounts.from,
+            to: &ctx.accounts.to,
+            mint: &ctx.accounts.mint,
+            token_program: &ctx.accounts.token_program,
+        },
+        ctx.accounts.claimant.key(),
+        ctx.accounts.claimant.as_ref(),
+        ctx.remaining_accounts,
+        amount_unlocked,
+        amount_locked,
+        proof,
+        unlock_schedule,
+        category_breakdown,
+        validity_proof,
+        address_tree_info,
+        output_state_tree_index,
+    )
+}
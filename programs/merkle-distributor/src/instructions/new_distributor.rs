@@ -1,14 +1,17 @@
 use anchor_lang::{account, context::Context, prelude::*, Accounts, Key, ToAccountInfo};
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{Mint, Token, TokenAccount},
+    token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
-use crate::{error::ErrorCode, state::merkle_distributor::MerkleDistributor};
+use crate::{
+    error::ErrorCode,
+    state::merkle_distributor::{DistributionMode, MerkleDistributor},
+};
 
 const SECONDS_PER_HOUR: i64 = 3600; // 60 minutes * 60 seconds
 const HOURS_PER_DAY: i64 = 24;
-const SECONDS_PER_DAY: i64 = SECONDS_PER_HOUR * HOURS_PER_DAY; // 24 hours * 3600 seconds
+pub(crate) const SECONDS_PER_DAY: i64 = SECONDS_PER_HOUR * HOURS_PER_DAY; // 24 hours * 3600 seconds
 
 /// Accounts for [merkle_distributor::handle_new_distributor].
 #[derive(Accounts)]
@@ -29,20 +32,21 @@ pub struct NewDistributor<'info> {
     pub distributor: Account<'info, MerkleDistributor>,
 
     /// Clawback receiver token account
-    #[account(mut, token::mint = mint)]
-    pub clawback_receiver: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub clawback_receiver: InterfaceAccount<'info, TokenAccount>,
 
-    /// The mint to distribute.
-    pub mint: Account<'info, Mint>,
+    /// The mint to distribute, owned by SPL Token or Token-2022.
+    pub mint: InterfaceAccount<'info, Mint>,
 
     /// Token vault
     #[account(
         init,
         associated_token::mint = mint,
         associated_token::authority=distributor,
+        associated_token::token_program = token_program,
         payer = admin,
     )]
-    pub token_vault: Account<'info, TokenAccount>,
+    pub token_vault: InterfaceAccount<'info, TokenAccount>,
 
     /// Admin wallet, responsible for creating the distributor and paying for the transaction.
     /// Also has the authority to set the clawback receiver and change itself.
@@ -55,18 +59,30 @@ pub struct NewDistributor<'info> {
     /// The [Associated Token] program.
     pub associated_token_program: Program<'info, AssociatedToken>,
 
-    /// The [Token] program.
-    pub token_program: Program<'info, Token>,
+    /// The SPL Token or Token-2022 program owning the mint.
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 /// Creates a new [MerkleDistributor].
 /// After creating this [MerkleDistributor],
 /// the token_vault should be seeded with max_total_claim tokens.
+///
+/// The program cannot see the tree behind `root`, so the caller is responsible for passing
+/// the `max_num_nodes` and `max_total_claim` of that tree; claims past either cap fail.
 /// CHECK:
 ///     1. The start timestamp is before the end timestamp
 ///     2. The clawback timestamp is after the end timestamp
 ///     3. The start, end, and clawback_start timestamps are all in the future
 ///     4. The clawback start is at least one day after end timestamp
+///     5. The cliff is between the start and end timestamps
+///     6. The minimum claim interval is not negative
+///     7. The vesting interval is positive
+///     8. `max_num_nodes` and `max_total_claim` are positive
+///
+/// `allow_reassignment` lets the admin move unclaimed allocations to other wallets with
+/// [crate::instructions::handle_reassign_claim]; it cannot be changed later. `tree_depth` is
+/// the proof length of the tree behind `root`; claims with a proof of another length fail
+/// with `ProofLengthMismatch` rather than `InvalidProof`.
 #[allow(clippy::too_many_arguments)]
 #[allow(clippy::result_large_err)]
 pub fn handle_new_distributor(
@@ -77,7 +93,64 @@ pub fn handle_new_distributor(
     max_num_nodes: u64,
     start_vesting_ts: i64,
     end_vesting_ts: i64,
+    cliff_ts: i64,
+    clawback_start_ts: i64,
+    min_claim_interval: i64,
+    vesting_interval_secs: i64,
+    allow_reassignment: bool,
+    tree_depth: u8,
+) -> Result<()> {
+    let mint = ctx.accounts.mint.key();
+    let token_vault = ctx.accounts.token_vault.key();
+    let clawback_receiver = ctx.accounts.clawback_receiver.key();
+    let admin = ctx.accounts.admin.key();
+    init_distributor(
+        &mut ctx.accounts.distributor,
+        ctx.bumps.distributor,
+        version,
+        root,
+        mint,
+        token_vault,
+        clawback_receiver,
+        admin,
+        max_total_claim,
+        max_num_nodes,
+        start_vesting_ts,
+        end_vesting_ts,
+        cliff_ts,
+        clawback_start_ts,
+        min_claim_interval,
+        vesting_interval_secs,
+        DistributionMode::Token,
+        allow_reassignment,
+        tree_depth,
+    )
+}
+
+/// Checks the schedule and initializes `distributor`. Shared by [handle_new_distributor] and
+/// [crate::instructions::handle_new_native_distributor], see them for the checks.
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::result_large_err)]
+pub(crate) fn init_distributor(
+    distributor: &mut Account<MerkleDistributor>,
+    bump: u8,
+    version: u64,
+    root: [u8; 32],
+    mint: Pubkey,
+    token_vault: Pubkey,
+    clawback_receiver: Pubkey,
+    admin: Pubkey,
+    max_total_claim: u64,
+    max_num_nodes: u64,
+    start_vesting_ts: i64,
+    end_vesting_ts: i64,
+    cliff_ts: i64,
     clawback_start_ts: i64,
+    min_claim_interval: i64,
+    vesting_interval_secs: i64,
+    distribution_mode: DistributionMode,
+    allow_reassignment: bool,
+    tree_depth: u8,
 ) -> Result<()> {
     let curr_ts = Clock::get()?.unix_timestamp;
 
@@ -85,6 +158,15 @@ pub fn handle_new_distributor(
         start_vesting_ts < end_vesting_ts,
         ErrorCode::StartTimestampAfterEnd
     );
+    require!(
+        start_vesting_ts <= cliff_ts && cliff_ts <= end_vesting_ts,
+        ErrorCode::CliffOutsideVesting
+    );
+    require!(min_claim_interval >= 0, ErrorCode::InvalidClaimInterval);
+    require!(vesting_interval_secs > 0, ErrorCode::InvalidVestingInterval);
+    // A zero cap would reject every claim, leaving the vault to the clawback
+    require!(max_num_nodes > 0, ErrorCode::InvalidMaxNumNodes);
+    require!(max_total_claim > 0, ErrorCode::InvalidMaxTotalClaim);
     // New distributor parameters must all be set in the future
     require!(
         start_vesting_ts > curr_ts && end_vesting_ts > curr_ts && clawback_start_ts > curr_ts,
@@ -105,34 +187,41 @@ pub fn handle_new_distributor(
         ErrorCode::InsufficientClawbackDelay
     );
 
-    let distributor = &mut ctx.accounts.distributor;
-
-    distributor.bump = ctx.bumps.distributor;
+    distributor.bump = bump;
     distributor.version = version;
     distributor.root = root;
-    distributor.mint = ctx.accounts.mint.key();
-    distributor.token_vault = ctx.accounts.token_vault.key();
+    distributor.mint = mint;
+    distributor.token_vault = token_vault;
     distributor.max_total_claim = max_total_claim;
     distributor.max_num_nodes = max_num_nodes;
     distributor.total_amount_claimed = 0;
     distributor.num_nodes_claimed = 0;
     distributor.start_ts = start_vesting_ts;
     distributor.end_ts = end_vesting_ts;
+    distributor.cliff_ts = cliff_ts;
     distributor.clawback_start_ts = clawback_start_ts;
-    distributor.clawback_receiver = ctx.accounts.clawback_receiver.key();
-    distributor.admin = ctx.accounts.admin.key();
+    distributor.clawback_receiver = clawback_receiver;
+    distributor.admin = admin;
     distributor.clawed_back = false;
+    distributor.paused = false;
+    distributor.min_claim_interval = min_claim_interval;
+    distributor.admin_renounced = false;
+    distributor.distribution_mode = distribution_mode;
+    distributor.vesting_interval_secs = vesting_interval_secs;
+    distributor.allow_reassignment = allow_reassignment;
+    distributor.tree_depth = tree_depth;
 
     // Note: might get truncated, do not rely on
     msg! {
-        "New distributor created with version = {}, mint={}, vault={} max_total_claim={}, max_nodes: {}, start_ts: {}, end_ts: {}, clawback_start: {}, clawback_receiver: {}",
+        "New distributor created with version = {}, mint={}, vault={} max_total_claim={}, max_nodes: {}, start_ts: {}, end_ts: {}, cliff_ts: {}, clawback_start: {}, clawback_receiver: {}",
             distributor.version,
             distributor.mint,
-            ctx.accounts.token_vault.key(),
+            distributor.token_vault,
             distributor.max_total_claim,
             distributor.max_num_nodes,
             distributor.start_ts,
             distributor.end_ts,
+            distributor.cliff_ts,
             distributor.clawback_start_ts,
             distributor.clawback_receiver
     };
@@ -0,0 +1,117 @@
+use anchor_lang::{
+    context::{Context, CpiContext},
+    prelude::*,
+    system_program::{self, Transfer},
+    Accounts, Key, ToAccountInfo,
+};
+use anchor_spl::token::spl_token::native_mint;
+
+use crate::{
+    instructions::new_distributor::init_distributor,
+    state::merkle_distributor::{DistributionMode, MerkleDistributor},
+    vault::SOL_VAULT_SEED,
+};
+
+/// Accounts for [merkle_distributor::handle_new_native_distributor].
+#[derive(Accounts)]
+#[instruction(version: u64)]
+pub struct NewNativeDistributor<'info> {
+    /// [MerkleDistributor], at the address of a distributor of the native mint.
+    #[account(
+        init,
+        seeds = [
+            b"MerkleDistributor".as_ref(),
+            native_mint::ID.as_ref(),
+            version.to_le_bytes().as_ref()
+        ],
+        bump,
+        space = MerkleDistributor::LEN,
+        payer = admin
+    )]
+    pub distributor: Account<'info, MerkleDistributor>,
+
+    /// Clawback receiver wallet
+    pub clawback_receiver: SystemAccount<'info>,
+
+    /// SOL vault, funded with its rent-exempt minimum here
+    #[account(
+        mut,
+        seeds = [SOL_VAULT_SEED, distributor.key().as_ref()],
+        bump,
+    )]
+    pub sol_vault: SystemAccount<'info>,
+
+    /// Admin wallet, responsible for creating the distributor and paying for the transaction.
+    /// Also has the authority to change itself.
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+/// Creates a new [MerkleDistributor] paying out native SOL.
+/// After creating this [MerkleDistributor],
+/// the sol_vault should be seeded with max_total_claim lamports.
+/// CHECK:
+///     1. Everything [crate::instructions::handle_new_distributor] checks
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::result_large_err)]
+pub fn handle_new_native_distributor(
+    ctx: Context<NewNativeDistributor>,
+    version: u64,
+    root: [u8; 32],
+    max_total_claim: u64,
+    max_num_nodes: u64,
+    start_vesting_ts: i64,
+    end_vesting_ts: i64,
+    cliff_ts: i64,
+    clawback_start_ts: i64,
+    min_claim_interval: i64,
+    vesting_interval_secs: i64,
+    tree_depth: u8,
+) -> Result<()> {
+    let sol_vault = ctx.accounts.sol_vault.key();
+    let clawback_receiver = ctx.accounts.clawback_receiver.key();
+    let admin = ctx.accounts.admin.key();
+    init_distributor(
+        &mut ctx.accounts.distributor,
+        ctx.bumps.distributor,
+        version,
+        root,
+        native_mint::ID,
+        sol_vault,
+        clawback_receiver,
+        admin,
+        max_total_claim,
+        max_num_nodes,
+        start_vesting_ts,
+        end_vesting_ts,
+        cliff_ts,
+        clawback_start_ts,
+        min_claim_interval,
+        vesting_interval_secs,
+        DistributionMode::Native,
+        // reassign_claim only pays out tokens
+        false,
+        tree_depth,
+    )?;
+
+    // Claims only pay out lamports above the rent-exempt minimum, which keeps the vault alive
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(0);
+    let top_up = rent_exempt_minimum.saturating_sub(ctx.accounts.sol_vault.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.admin.to_account_info(),
+                    to: ctx.accounts.sol_vault.to_account_info(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,113 @@
+// Instruction to claw back part of the vault while claims stay open
+
+use anchor_lang::{context::Context, emit, prelude::*, Accounts, Key, Result};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    error::ErrorCode,
+    state::{
+        claimed_event::ClawbackEvent,
+        merkle_distributor::{DistributionMode, MerkleDistributor},
+    },
+    vault::Vault,
+};
+
+/// [merkle_distributor::partial_clawback] accounts.
+#[derive(Accounts)]
+pub struct PartialClawback<'info> {
+    /// The [MerkleDistributor].
+    pub distributor: Account<'info, MerkleDistributor>,
+
+    /// Distributor ATA containing the tokens to distribute.
+    #[account(
+        mut,
+        associated_token::mint = distributor.mint,
+        associated_token::authority = distributor.key(),
+        associated_token::token_program = token_program,
+        address = distributor.token_vault
+    )]
+    pub from: InterfaceAccount<'info, TokenAccount>,
+
+    /// The Clawback token account.
+    #[account(mut, address = distributor.clawback_receiver)]
+    pub to: InterfaceAccount<'info, TokenAccount>,
+
+    /// Claimant account
+    /// Anyone can claw back the funds
+    pub claimant: Signer<'info>,
+
+    /// The [System] program.
+    pub system_program: Program<'info, System>,
+
+    /// The distributor's mint, needed for its decimals by `transfer_checked`.
+    #[account(address = distributor.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// SPL Token or Token-2022 program owning the mint.
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Claws back `amount` of the vault by:
+/// 1. Checking that the lockup has expired
+/// 2. Checking that the vault keeps enough to pay every allocation not claimed yet
+/// 3. Transferring `amount` from the vault to the clawback receiver
+///
+/// Unlike [merkle_distributor::clawback], the distributor is not marked as clawed back, so
+/// claims carry on.
+///
+/// The floor does not cover Token-2022 transfer fees. Each claim and locked withdrawal pays
+/// its own fee, rounded up and capped per transfer, and the number of withdrawals still to
+/// come is unknown, so fees on a fee-bearing mint are left to whoever funds the vault.
+///
+/// CHECK:
+///     1. The distributor has not already been clawed back
+///     2. The distributor pays out tokens, not native SOL
+///     3. The vault holds at least `max_total_claim - total_amount_claimed` afterwards
+#[allow(clippy::result_large_err)]
+pub fn handle_partial_clawback(ctx: Context<PartialClawback>, amount: u64) -> Result<()> {
+    let distributor = &ctx.accounts.distributor;
+
+    require!(
+        distributor.distribution_mode == DistributionMode::Token,
+        ErrorCode::WrongDistributionMode
+    );
+    require!(!distributor.clawed_back, ErrorCode::ClawbackAlreadyClaimed);
+
+    let curr_ts = Clock::get()?.unix_timestamp;
+
+    if curr_ts < distributor.clawback_start_ts {
+        return Err(ErrorCode::ClawbackBeforeStart.into());
+    }
+
+    let vault = Vault::Token {
+        from: &ctx.accounts.from,
+        to: &ctx.accounts.to,
+        mint: &ctx.accounts.mint,
+        token_program: &ctx.accounts.token_program,
+    };
+
+    let obligation = distributor
+        .max_total_claim
+        .checked_sub(distributor.total_amount_claimed)
+        .ok_or(ErrorCode::ArithmeticError)?;
+    let surplus = vault.available()?.saturating_sub(obligation);
+    if amount > surplus {
+        msg!(
+            "Unclaimed allocations need {} in the vault, at most {} can be clawed back",
+            obligation,
+            surplus
+        );
+        return err!(ErrorCode::ClawbackExceedsSurplus);
+    }
+
+    vault.transfer(distributor, amount)?;
+
+    emit!(ClawbackEvent {
+        distributor: distributor.key(),
+        receiver: ctx.accounts.to.key(),
+        amount,
+        timestamp: curr_ts,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,254 @@
+// Instruction to move an unclaimed allocation to a new claimant
+
+use anchor_lang::{context::Context, emit, prelude::*, Accounts, Key, Result};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use light_sdk::{
+    account::LightAccount,
+    cpi::{
+        v2::{CpiAccounts, LightSystemProgramCpi},
+        InvokeLightSystemProgram, LightCpiInstruction,
+    },
+    instruction::{PackedAddressTreeInfo, ValidityProof},
+};
+
+use crate::{
+    error::{light_sdk_error, ErrorCode},
+    instructions::new_claim::{claim_status_address, verify_claim_proof},
+    state::{
+        claim_status::{CategoryBreakdown, ClaimStatus, UnlockSchedule},
+        claimed_event::{ClaimReassignedEvent, NewClaimEvent},
+        merkle_distributor::{DistributionMode, MerkleDistributor},
+    },
+    vault::Vault,
+    LIGHT_CPI_SIGNER,
+};
+
+/// [merkle_distributor::reassign_claim] accounts.
+#[derive(Accounts)]
+#[instruction(old_claimant: Pubkey, new_claimant: Pubkey)]
+pub struct ReassignClaim<'info> {
+    /// The [MerkleDistributor].
+    #[account(mut, constraint = !distributor.admin_renounced @ ErrorCode::AdminRenounced)]
+    pub distributor: Account<'info, MerkleDistributor>,
+
+    /// Distributor ATA containing the tokens to distribute.
+    #[account(
+        mut,
+        associated_token::mint = distributor.mint,
+        associated_token::authority = distributor.key(),
+        associated_token::token_program = token_program,
+        address = distributor.token_vault
+    )]
+    pub from: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token account of the new claimant, receiving the unlocked amount.
+    #[account(
+        mut,
+        token::mint = distributor.mint,
+        token::authority = new_claimant,
+        token::token_program = token_program
+    )]
+    pub to: InterfaceAccount<'info, TokenAccount>,
+
+    /// Admin signer, also pays for the Light system program CPI
+    #[account(mut, address = distributor.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    /// The distributor's mint, needed for its decimals by `transfer_checked`.
+    #[account(address = distributor.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// SPL Token or Token-2022 program owning the mint.
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Issues the unclaimed allocation of `old_claimant` to `new_claimant`, e.g. when the key of
+/// an employee wallet was lost:
+/// 1. Creates an empty ClaimStatus at the address of `old_claimant`, so that key can never
+///    claim the node, nor can it be reassigned again
+/// 2. Creates the ClaimStatus of `new_claimant` with the node's amounts and schedule, from
+///    which `new_claimant` withdraws locked tokens with claim_locked as if it had claimed
+/// 3. Transfers the unlocked amount to `new_claimant` and counts the node as claimed
+///
+/// Both addresses are created in one Light system program CPI, whose validity proof shows
+/// that neither exists yet. A node that was already claimed or reassigned, or a
+/// `new_claimant` that already holds a claim, fails the CPI.
+///
+/// The program cannot tell whether `new_claimant` has an unclaimed leaf of its own: once its
+/// ClaimStatus exists, that leaf can never be claimed and its allocation stays in the vault
+/// until clawback. Callers must pick a `new_claimant` that is not in the tree.
+///
+/// CHECK:
+///     1. The distributor allows reassignment
+///     2. The distributor pays out tokens, not native SOL
+///     3. The distributor has not been clawed back
+///     4. The distributor is not paused
+///     5. The new claimant differs from the old claimant
+///     6. The merkle proof has the distributor's tree depth and is valid for the leaf of
+///        `old_claimant`
+///     7. A category breakdown, if any, adds up to the proven amounts
+///     8. Num nodes claimed, counting this node once its proof verified, is at most
+///        max_num_nodes
+///     9. The vault holds the amount to pay out
+///     10. The new claimant's token account is not frozen
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::result_large_err)]
+pub fn handle_reassign_claim<'info>(
+    ctx: Context<'_, '_, '_, 'info, ReassignClaim<'info>>,
+    old_claimant: Pubkey,
+    new_claimant: Pubkey,
+    amount_unlocked: u64,
+    amount_locked: u64,
+    proof: Vec<[u8; 32]>,
+    unlock_schedule: Option<UnlockSchedule>,
+    category_breakdown: Option<CategoryBreakdown>,
+    validity_proof: ValidityProof,
+    old_address_tree_info: PackedAddressTreeInfo,
+    new_address_tree_info: PackedAddressTreeInfo,
+    output_state_tree_index: u8,
+) -> Result<()> {
+    let distributor = &mut ctx.accounts.distributor;
+    let curr_ts = Clock::get()?.unix_timestamp;
+
+    require!(
+        distributor.allow_reassignment,
+        ErrorCode::ReassignmentDisabled
+    );
+    require!(
+        distributor.distribution_mode == DistributionMode::Token,
+        ErrorCode::WrongDistributionMode
+    );
+    require!(!distributor.clawed_back, ErrorCode::ClaimExpired);
+    require!(!distributor.paused, ErrorCode::Paused);
+    require!(
+        old_claimant != new_claimant,
+        ErrorCode::ReassignToSameClaimant
+    );
+
+    verify_claim_proof(
+        distributor.root,
+        distributor.tree_depth,
+        old_claimant,
+        amount_unlocked,
+        amount_locked,
+        unlock_schedule,
+        category_breakdown,
+        proof,
+    )?;
+
+    distributor.num_nodes_claimed = distributor
+        .num_nodes_claimed
+        .checked_add(1)
+        .ok_or(ErrorCode::ArithmeticError)?;
+
+    require!(
+        distributor.num_nodes_claimed <= distributor.max_num_nodes,
+        ErrorCode::MaxNodesExceeded
+    );
+
+    let light_cpi_accounts = CpiAccounts::new(
+        ctx.accounts.admin.as_ref(),
+        ctx.remaining_accounts,
+        LIGHT_CPI_SIGNER,
+    );
+    let (old_address, old_address_seed) = claim_status_address(
+        old_claimant,
+        distributor.key(),
+        &old_address_tree_info,
+        &light_cpi_accounts,
+    )?;
+    let (new_address, new_address_seed) = claim_status_address(
+        new_claimant,
+        distributor.key(),
+        &new_address_tree_info,
+        &light_cpi_accounts,
+    )?;
+    let new_address_params = [
+        old_address_tree_info.into_new_address_params_assigned_packed(old_address_seed, Some(0)),
+        new_address_tree_info.into_new_address_params_assigned_packed(new_address_seed, Some(1)),
+    ];
+
+    let vault = Vault::Token {
+        from: &ctx.accounts.from,
+        to: &ctx.accounts.to,
+        mint: &ctx.accounts.mint,
+        token_program: &ctx.accounts.token_program,
+    };
+
+    // Send enough to cover a Token-2022 transfer fee so the new claimant receives
+    // amount_unlocked
+    let transfer_fee = vault.transfer_fee(amount_unlocked)?;
+    let transfer_amount = amount_unlocked
+        .checked_add(transfer_fee)
+        .ok_or(ErrorCode::ArithmeticError)?;
+
+    vault.require_available(transfer_amount)?;
+    vault.require_recipient_not_frozen()?;
+
+    // Nothing is left to withdraw from the old claimant's status: claim_locked with the lost
+    // key finds no unlocked tokens
+    let mut old_claim_status = LightAccount::<ClaimStatus>::new_init(
+        &crate::ID,
+        Some(old_address),
+        output_state_tree_index,
+    );
+    old_claim_status.claimant = old_claimant;
+
+    let mut new_claim_status = LightAccount::<ClaimStatus>::new_init(
+        &crate::ID,
+        Some(new_address),
+        output_state_tree_index,
+    );
+    new_claim_status.claimant = new_claimant;
+    new_claim_status.locked_amount = amount_locked;
+    new_claim_status.unlocked_amount = amount_unlocked;
+    new_claim_status.locked_amount_withdrawn = 0;
+    new_claim_status.unlock_schedule = unlock_schedule;
+    new_claim_status.last_claimed_ts = 0;
+    new_claim_status.category_breakdown = category_breakdown;
+
+    LightSystemProgramCpi::new_cpi(LIGHT_CPI_SIGNER, validity_proof)
+        .with_light_account(old_claim_status)
+        .map_err(|e| light_sdk_error(e, ErrorCode::LightAccountCreationFailed))?
+        .with_light_account(new_claim_status)
+        .map_err(|e| light_sdk_error(e, ErrorCode::LightAccountCreationFailed))?
+        .with_new_addresses(&new_address_params)
+        .invoke(light_cpi_accounts)
+        .map_err(|e| light_sdk_error(e, ErrorCode::LightCpiFailed))?;
+
+    vault.transfer(distributor, transfer_amount)?;
+
+    distributor.total_amount_claimed = distributor
+        .total_amount_claimed
+        .checked_add(amount_unlocked)
+        .ok_or(ErrorCode::ArithmeticError)?;
+
+    require!(
+        distributor.total_amount_claimed <= distributor.max_total_claim,
+        ErrorCode::ExceededMaxClaim
+    );
+
+    // Note: might get truncated, do not rely on
+    msg!(
+        "Reassigned claim of {} to {} with locked {} and {} unlocked",
+        old_claimant,
+        new_claimant,
+        amount_locked,
+        amount_unlocked,
+    );
+    emit!(ClaimReassignedEvent {
+        distributor: distributor.key(),
+        old_claimant,
+        new_claimant,
+        timestamp: curr_ts,
+    });
+    emit!(NewClaimEvent {
+        claimant: new_claimant,
+        timestamp: curr_ts,
+        amount: amount_unlocked,
+        transfer_fee,
+        category_breakdown,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use anchor_lang::{
+    accounts::{account::Account, signer::Signer},
+    context::Context,
+    emit,
+    prelude::*,
+    Accounts, Result,
+};
+
+use crate::{
+    error::ErrorCode,
+    state::{claimed_event::AdminRenouncedEvent, merkle_distributor::MerkleDistributor},
+};
+
+/// [merkle_distributor::renounce_admin] accounts.
+#[derive(Accounts)]
+pub struct RenounceAdmin<'info> {
+    /// The [MerkleDistributor].
+    #[account(mut, constraint = !distributor.admin_renounced @ ErrorCode::AdminRenounced)]
+    pub distributor: Account<'info, MerkleDistributor>,
+
+    /// Admin signer
+    #[account(mut, address = distributor.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+/// Gives up admin control for good. set_admin, set_paused, set_clawback_receiver,
+/// update_clawback_start_ts, update_root, reassign_claim, close_distributor and
+/// close_native_distributor fail with `AdminRenounced` from then on; claims and clawback are
+/// unaffected. Nobody could unpause the distributor afterwards, so it must not be paused.
+/// CHECK:
+///     1. The admin has not been renounced yet
+///     2. The distributor is not paused
+#[allow(clippy::result_large_err)]
+pub fn handle_renounce_admin(ctx: Context<RenounceAdmin>) -> Result<()> {
+    let distributor = &mut ctx.accounts.distributor;
+
+    require!(!distributor.paused, ErrorCode::RenounceWhilePaused);
+
+    distributor.admin_renounced = true;
+
+    // Note: might get truncated, do not rely on
+    msg!("renounced admin {}", distributor.admin);
+    emit!(AdminRenouncedEvent {
+        distributor: distributor.key(),
+        admin: distributor.admin,
+    });
+
+    Ok(())
+}
@@ -1,17 +1,21 @@
 use anchor_lang::{
     accounts::{account::Account, signer::Signer},
     context::Context,
+    emit,
     prelude::*,
     Accounts, Result,
 };
 
-use crate::{error::ErrorCode, state::merkle_distributor::MerkleDistributor};
+use crate::{
+    error::ErrorCode,
+    state::{claimed_event::SetAdminEvent, merkle_distributor::MerkleDistributor},
+};
 
 /// [merkle_distributor::set_clawback_receiver] accounts.
 #[derive(Accounts)]
 pub struct SetAdmin<'info> {
     /// The [MerkleDistributor].
-    #[account(mut)]
+    #[account(mut, constraint = !distributor.admin_renounced @ ErrorCode::AdminRenounced)]
     pub distributor: Account<'info, MerkleDistributor>,
 
     /// Admin signer
@@ -36,10 +40,16 @@ pub fn handle_set_admin(ctx: Context<SetAdmin>) -> Result<()> {
         ErrorCode::SameAdmin
     );
 
+    let old_admin = distributor.admin;
     distributor.admin = ctx.accounts.new_admin.key();
 
     // Note: might get truncated, do not rely on
     msg!("set new admin to {}", ctx.accounts.new_admin.key());
+    emit!(SetAdminEvent {
+        distributor: distributor.key(),
+        old_admin,
+        new_admin: distributor.admin,
+    });
 
     Ok(())
 }
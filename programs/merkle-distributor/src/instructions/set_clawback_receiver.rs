@@ -1,23 +1,30 @@
 use anchor_lang::{
     accounts::{account::Account, signer::Signer},
     context::Context,
+    emit,
     prelude::*,
     Accounts, Result, ToAccountInfo,
 };
-use anchor_spl::token::TokenAccount;
+use anchor_spl::token_interface::TokenAccount;
 
-use crate::{error::ErrorCode, state::merkle_distributor::MerkleDistributor};
+use crate::{
+    error::ErrorCode,
+    state::{
+        claimed_event::ClawbackReceiverChangedEvent,
+        merkle_distributor::{DistributionMode, MerkleDistributor},
+    },
+};
 
 /// [merkle_distributor::set_clawback_receiver] accounts.
 #[derive(Accounts)]
 pub struct SetClawbackReceiver<'info> {
     /// The [MerkleDistributor].
-    #[account(mut)]
+    #[account(mut, constraint = !distributor.admin_renounced @ ErrorCode::AdminRenounced)]
     pub distributor: Account<'info, MerkleDistributor>,
 
     /// New clawback account
     #[account(token::mint=distributor.mint)]
-    pub new_clawback_account: Account<'info, TokenAccount>,
+    pub new_clawback_account: InterfaceAccount<'info, TokenAccount>,
 
     /// Admin signer
     #[account(mut, address = distributor.admin @ ErrorCode::Unauthorized)]
@@ -26,9 +33,21 @@ pub struct SetClawbackReceiver<'info> {
 
 /// Sets new clawback receiver token account
 /// CHECK:
-///     1. The new clawback receiver is not the same as the old one
+///     1. The distributor has not been clawed back yet
+///     2. The new clawback receiver is not the same as the old one
+///     3. The new clawback receiver is a token account for the distributor's mint
+///     4. The distributor pays out tokens: a native SOL distributor claws back to a wallet,
+///        which cannot be changed
 #[allow(clippy::result_large_err)]
 pub fn handle_set_clawback_receiver(ctx: Context<SetClawbackReceiver>) -> Result<()> {
+    require!(
+        ctx.accounts.distributor.distribution_mode == DistributionMode::Token,
+        ErrorCode::WrongDistributionMode
+    );
+    require!(
+        !ctx.accounts.distributor.clawed_back,
+        ErrorCode::ClawbackAlreadyClaimed
+    );
     require!(
         ctx.accounts.distributor.clawback_receiver.key() != ctx.accounts.new_clawback_account.key(),
         ErrorCode::SameClawbackReceiver
@@ -38,6 +57,7 @@ pub fn handle_set_clawback_receiver(ctx: Context<SetClawbackReceiver>) -> Result
 
     let new_clawback_account = *ctx.accounts.new_clawback_account.to_account_info().key;
 
+    let old_clawback_account = distributor.clawback_receiver;
     distributor.clawback_receiver = new_clawback_account;
 
     // Note: might get truncated, do not rely on
@@ -46,6 +66,11 @@ pub fn handle_set_clawback_receiver(ctx: Context<SetClawbackReceiver>) -> Result
         new_clawback_account,
         ctx.accounts.new_clawback_account.owner
     );
+    emit!(ClawbackReceiverChangedEvent {
+        distributor: distributor.key(),
+        old_receiver: old_clawback_account,
+        new_receiver: new_clawback_account,
+    });
 
     Ok(())
 }
@@ -0,0 +1,33 @@
+use anchor_lang::{
+    accounts::{account::Account, signer::Signer},
+    context::Context,
+    prelude::*,
+    Accounts, Result,
+};
+
+use crate::{error::ErrorCode, state::merkle_distributor::MerkleDistributor};
+
+/// [merkle_distributor::set_paused] accounts.
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    /// The [MerkleDistributor].
+    #[account(mut, constraint = !distributor.admin_renounced @ ErrorCode::AdminRenounced)]
+    pub distributor: Account<'info, MerkleDistributor>,
+
+    /// Admin signer
+    #[account(mut, address = distributor.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+/// Pauses or resumes claims. While paused, new_claim, new_claim_signed and claim_locked fail.
+#[allow(clippy::result_large_err)]
+pub fn handle_set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+    let distributor = &mut ctx.accounts.distributor;
+
+    distributor.paused = paused;
+
+    // Note: might get truncated, do not rely on
+    msg!("set paused to {}", paused);
+
+    Ok(())
+}
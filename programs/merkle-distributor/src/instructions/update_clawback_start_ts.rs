@@ -0,0 +1,69 @@
+use anchor_lang::{
+    accounts::{account::Account, signer::Signer},
+    context::Context,
+    emit,
+    prelude::*,
+    Accounts, Result,
+};
+
+use crate::{
+    error::ErrorCode,
+    instructions::new_distributor::SECONDS_PER_DAY,
+    state::{claimed_event::ClawbackStartUpdatedEvent, merkle_distributor::MerkleDistributor},
+};
+
+/// [merkle_distributor::update_clawback_start_ts] accounts.
+#[derive(Accounts)]
+pub struct UpdateClawbackStartTs<'info> {
+    /// The [MerkleDistributor].
+    #[account(mut, constraint = !distributor.admin_renounced @ ErrorCode::AdminRenounced)]
+    pub distributor: Account<'info, MerkleDistributor>,
+
+    /// Admin signer
+    #[account(mut, address = distributor.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+/// Postpones the clawback start
+/// CHECK:
+///     1. The distributor has not been clawed back yet
+///     2. The new clawback start is later than the current one
+///     3. The new clawback start is at least one day after the vesting end
+#[allow(clippy::result_large_err)]
+pub fn handle_update_clawback_start_ts(
+    ctx: Context<UpdateClawbackStartTs>,
+    clawback_start_ts: i64,
+) -> Result<()> {
+    let distributor = &mut ctx.accounts.distributor;
+
+    require!(!distributor.clawed_back, ErrorCode::ClawbackAlreadyClaimed);
+    require!(
+        clawback_start_ts > distributor.clawback_start_ts,
+        ErrorCode::ClawbackStartNotLater
+    );
+    require!(
+        clawback_start_ts
+            >= distributor
+                .end_ts
+                .checked_add(SECONDS_PER_DAY)
+                .ok_or(ErrorCode::ArithmeticError)?,
+        ErrorCode::InsufficientClawbackDelay
+    );
+
+    let old_clawback_start_ts = distributor.clawback_start_ts;
+    distributor.clawback_start_ts = clawback_start_ts;
+
+    // Note: might get truncated, do not rely on
+    msg!(
+        "moved clawback start from {} to {}",
+        old_clawback_start_ts,
+        clawback_start_ts
+    );
+    emit!(ClawbackStartUpdatedEvent {
+        distributor: distributor.key(),
+        old_clawback_start_ts,
+        new_clawback_start_ts: clawback_start_ts,
+    });
+
+    Ok(())
+}
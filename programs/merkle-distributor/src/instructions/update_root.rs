@@ -0,0 +1,75 @@
+use anchor_lang::{
+    accounts::{account::Account, signer::Signer},
+    context::Context,
+    emit,
+    prelude::*,
+    Accounts, Result,
+};
+
+use crate::{
+    error::ErrorCode,
+    state::{claimed_event::RootUpdatedEvent, merkle_distributor::MerkleDistributor},
+};
+
+/// [merkle_distributor::update_root] accounts.
+#[derive(Accounts)]
+pub struct UpdateRoot<'info> {
+    /// The [MerkleDistributor].
+    #[account(mut, constraint = !distributor.admin_renounced @ ErrorCode::AdminRenounced)]
+    pub distributor: Account<'info, MerkleDistributor>,
+
+    /// Admin signer
+    #[account(mut, address = distributor.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+/// Replaces the merkle root with the root of a larger tree, for airdrops that add recipients
+/// in waves. The new tree must contain every node of the current one unchanged, which the
+/// program cannot check: claim statuses already created keep their amounts, and a node
+/// missing from the new tree can no longer be claimed.
+/// CHECK:
+///     1. The distributor has not been clawed back yet
+///     2. Neither cap is lower than the current one, so neither drops below what has been
+///        claimed
+#[allow(clippy::result_large_err)]
+pub fn handle_update_root(
+    ctx: Context<UpdateRoot>,
+    root: [u8; 32],
+    max_total_claim: u64,
+    max_num_nodes: u64,
+    tree_depth: u8,
+) -> Result<()> {
+    let distributor = &mut ctx.accounts.distributor;
+
+    require!(!distributor.clawed_back, ErrorCode::ClawbackAlreadyClaimed);
+    require!(
+        max_total_claim >= distributor.max_total_claim,
+        ErrorCode::CapDecreased
+    );
+    require!(
+        max_num_nodes >= distributor.max_num_nodes,
+        ErrorCode::CapDecreased
+    );
+
+    let old_root = distributor.root;
+    distributor.root = root;
+    distributor.max_total_claim = max_total_claim;
+    distributor.max_num_nodes = max_num_nodes;
+    distributor.tree_depth = tree_depth;
+
+    // Note: might get truncated, do not rely on
+    msg!(
+        "updated root, caps now {} tokens over {} nodes",
+        max_total_claim,
+        max_num_nodes
+    );
+    emit!(RootUpdatedEvent {
+        distributor: distributor.key(),
+        old_root,
+        new_root: root,
+        max_total_claim,
+        max_num_nodes,
+    });
+
+    Ok(())
+}
@@ -18,13 +18,17 @@ use solana_security_txt::security_txt;
 pub mod error;
 pub mod instructions;
 pub mod state;
+pub mod transfer_fee;
+pub mod vault;
 
 use light_sdk::{
     cpi::{derive_light_cpi_signer, CpiSigner},
     instruction::{account_meta::CompressedAccountMeta, PackedAddressTreeInfo, ValidityProof},
 };
 
-use crate::state::claim_status::ClaimStatusInstructionData;
+use crate::state::claim_status::{
+    CategoryBreakdown, ClaimStatusInstructionData, LegacyClaimStatus, UnlockSchedule,
+};
 
 security_txt! {
     // Required fields
@@ -75,7 +79,12 @@ pub mod merkle_distributor {
         max_num_nodes: u64,
         start_vesting_ts: i64,
         end_vesting_ts: i64,
+        cliff_ts: i64,
         clawback_start_ts: i64,
+        min_claim_interval: i64,
+        vesting_interval_secs: i64,
+        allow_reassignment: bool,
+        tree_depth: u8,
     ) -> Result<()> {
         handle_new_distributor(
             ctx,
@@ -85,7 +94,45 @@ pub mod merkle_distributor {
             max_num_nodes,
             start_vesting_ts,
             end_vesting_ts,
+            cliff_ts,
+            clawback_start_ts,
+            min_claim_interval,
+            vesting_interval_secs,
+            allow_reassignment,
+            tree_depth,
+        )
+    }
+
+    /// Like `new_distributor`, for an airdrop of native SOL held in the distributor's SOL vault.
+    /// The same frontrunning caveats apply.
+    #[allow(clippy::result_large_err)]
+    pub fn new_native_distributor(
+        ctx: Context<NewNativeDistributor>,
+        version: u64,
+        root: [u8; 32],
+        max_total_claim: u64,
+        max_num_nodes: u64,
+        start_vesting_ts: i64,
+        end_vesting_ts: i64,
+        cliff_ts: i64,
+        clawback_start_ts: i64,
+        min_claim_interval: i64,
+        vesting_interval_secs: i64,
+        tree_depth: u8,
+    ) -> Result<()> {
+        handle_new_native_distributor(
+            ctx,
+            version,
+            root,
+            max_total_claim,
+            max_num_nodes,
+            start_vesting_ts,
+            end_vesting_ts,
+            cliff_ts,
             clawback_start_ts,
+            min_claim_interval,
+            vesting_interval_secs,
+            tree_depth,
         )
     }
 
@@ -95,6 +142,8 @@ pub mod merkle_distributor {
         amount_unlocked: u64,
         amount_locked: u64,
         proof: Vec<[u8; 32]>,
+        unlock_schedule: Option<UnlockSchedule>,
+        category_breakdown: Option<CategoryBreakdown>,
         validity_proof: ValidityProof,
         address_tree_info: PackedAddressTreeInfo,
         output_state_tree_index: u8,
@@ -104,6 +153,87 @@ pub mod merkle_distributor {
             amount_unlocked,
             amount_locked,
             proof,
+            unlock_schedule,
+            category_breakdown,
+            validity_proof,
+            address_tree_info,
+            output_state_tree_index,
+        )
+    }
+
+    /// Sponsored variant of `new_claim`: the claimant signs an off-chain message instead of the
+    /// transaction, verified through an ed25519 program instruction placed right before this one.
+    #[allow(clippy::result_large_err)]
+    pub fn new_claim_signed<'info>(
+        ctx: Context<'_, '_, '_, 'info, NewClaimSigned<'info>>,
+        amount_unlocked: u64,
+        amount_locked: u64,
+        proof: Vec<[u8; 32]>,
+        unlock_schedule: Option<UnlockSchedule>,
+        category_breakdown: Option<CategoryBreakdown>,
+        validity_proof: ValidityProof,
+        address_tree_info: PackedAddressTreeInfo,
+        output_state_tree_index: u8,
+    ) -> Result<()> {
+        handle_new_claim_signed(
+            ctx,
+            amount_unlocked,
+            amount_locked,
+            proof,
+            unlock_schedule,
+            category_breakdown,
+            validity_proof,
+            address_tree_info,
+            output_state_tree_index,
+        )
+    }
+
+    /// `new_claim` paying a token account chosen by the claimant instead of its own.
+    #[allow(clippy::result_large_err)]
+    pub fn new_claim_to<'info>(
+        ctx: Context<'_, '_, '_, 'info, NewClaimTo<'info>>,
+        amount_unlocked: u64,
+        amount_locked: u64,
+        proof: Vec<[u8; 32]>,
+        unlock_schedule: Option<UnlockSchedule>,
+        category_breakdown: Option<CategoryBreakdown>,
+        validity_proof: ValidityProof,
+        address_tree_info: PackedAddressTreeInfo,
+        output_state_tree_index: u8,
+    ) -> Result<()> {
+        handle_new_claim_to(
+            ctx,
+            amount_unlocked,
+            amount_locked,
+            proof,
+            unlock_schedule,
+            category_breakdown,
+            validity_proof,
+            address_tree_info,
+            output_state_tree_index,
+        )
+    }
+
+    /// `new_claim` of a native SOL distributor, paying the claimant lamports.
+    #[allow(clippy::result_large_err)]
+    pub fn new_claim_native<'info>(
+        ctx: Context<'_, '_, '_, 'info, NewClaimNative<'info>>,
+        amount_unlocked: u64,
+        amount_locked: u64,
+        proof: Vec<[u8; 32]>,
+        unlock_schedule: Option<UnlockSchedule>,
+        category_breakdown: Option<CategoryBreakdown>,
+        validity_proof: ValidityProof,
+        address_tree_info: PackedAddressTreeInfo,
+        output_state_tree_index: u8,
+    ) -> Result<()> {
+        handle_new_claim_native(
+            ctx,
+            amount_unlocked,
+            amount_locked,
+            proof,
+            unlock_schedule,
+            category_breakdown,
             validity_proof,
             address_tree_info,
             output_state_tree_index,
@@ -120,11 +250,91 @@ pub mod merkle_distributor {
         handle_claim_locked(ctx, input_account_meta, claim_status_data, validity_proof)
     }
 
+    /// `claim_locked` paying a token account chosen by the claimant instead of its own.
+    #[allow(clippy::result_large_err)]
+    pub fn claim_locked_to<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimLockedTo<'info>>,
+        input_account_meta: CompressedAccountMeta,
+        claim_status_data: ClaimStatusInstructionData,
+        validity_proof: ValidityProof,
+    ) -> Result<()> {
+        handle_claim_locked_to(ctx, input_account_meta, claim_status_data, validity_proof)
+    }
+
+    /// `claim_locked` of a native SOL distributor, paying the claimant lamports.
+    #[allow(clippy::result_large_err)]
+    pub fn claim_locked_native<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimLockedNative<'info>>,
+        input_account_meta: CompressedAccountMeta,
+        claim_status_data: ClaimStatusInstructionData,
+        validity_proof: ValidityProof,
+    ) -> Result<()> {
+        handle_claim_locked_native(ctx, input_account_meta, claim_status_data, validity_proof)
+    }
+
     #[allow(clippy::result_large_err)]
     pub fn clawback(ctx: Context<Clawback>) -> Result<()> {
         handle_clawback(ctx)
     }
 
+    /// `clawback` of a native SOL distributor, sending the vault's lamports to the receiver.
+    #[allow(clippy::result_large_err)]
+    pub fn clawback_native(ctx: Context<ClawbackNative>) -> Result<()> {
+        handle_clawback_native(ctx)
+    }
+
+    /// Claws back `amount` of the vault, keeping what the unclaimed allocations still need.
+    /// Claims stay open afterwards.
+    #[allow(clippy::result_large_err)]
+    pub fn partial_clawback(ctx: Context<PartialClawback>, amount: u64) -> Result<()> {
+        handle_partial_clawback(ctx, amount)
+    }
+
+    /// Issues the unclaimed allocation of `old_claimant` to `new_claimant`, for a lost key.
+    /// Admin-only, on distributors created with `allow_reassignment`.
+    #[allow(clippy::result_large_err)]
+    pub fn reassign_claim<'info>(
+        ctx: Context<'_, '_, '_, 'info, ReassignClaim<'info>>,
+        old_claimant: Pubkey,
+        new_claimant: Pubkey,
+        amount_unlocked: u64,
+        amount_locked: u64,
+        proof: Vec<[u8; 32]>,
+        unlock_schedule: Option<UnlockSchedule>,
+        category_breakdown: Option<CategoryBreakdown>,
+        validity_proof: ValidityProof,
+        old_address_tree_info: PackedAddressTreeInfo,
+        new_address_tree_info: PackedAddressTreeInfo,
+        output_state_tree_index: u8,
+    ) -> Result<()> {
+        handle_reassign_claim(
+            ctx,
+            old_claimant,
+            new_claimant,
+            amount_unlocked,
+            amount_locked,
+            proof,
+            unlock_schedule,
+            category_breakdown,
+            validity_proof,
+            old_address_tree_info,
+            new_address_tree_info,
+            output_state_tree_index,
+        )
+    }
+
+    /// Closes a clawed back distributor and its empty vault, returning the rent to the admin.
+    #[allow(clippy::result_large_err)]
+    pub fn close_distributor(ctx: Context<CloseDistributor>) -> Result<()> {
+        handle_close_distributor(ctx)
+    }
+
+    /// Closes a clawed back native SOL distributor, returning the rent to the admin.
+    #[allow(clippy::result_large_err)]
+    pub fn close_native_distributor(ctx: Context<CloseNativeDistributor>) -> Result<()> {
+        handle_close_native_distributor(ctx)
+    }
+
     #[allow(clippy::result_large_err)]
     pub fn set_clawback_receiver(ctx: Context<SetClawbackReceiver>) -> Result<()> {
         handle_set_clawback_receiver(ctx)
@@ -134,6 +344,55 @@ pub mod merkle_distributor {
     pub fn set_admin(ctx: Context<SetAdmin>) -> Result<()> {
         handle_set_admin(ctx)
     }
+
+    #[allow(clippy::result_large_err)]
+    pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+        handle_set_paused(ctx, paused)
+    }
+
+    /// Permanently disables the admin-only instructions of the distributor.
+    #[allow(clippy::result_large_err)]
+    pub fn renounce_admin(ctx: Context<RenounceAdmin>) -> Result<()> {
+        handle_renounce_admin(ctx)
+    }
+
+    /// Moves the clawback start later, keeping it at least one day after vesting ends.
+    #[allow(clippy::result_large_err)]
+    pub fn update_clawback_start_ts(
+        ctx: Context<UpdateClawbackStartTs>,
+        clawback_start_ts: i64,
+    ) -> Result<()> {
+        handle_update_clawback_start_ts(ctx, clawback_start_ts)
+    }
+
+    /// Replaces the root with that of a superset tree, never lowering the caps.
+    #[allow(clippy::result_large_err)]
+    pub fn update_root(
+        ctx: Context<UpdateRoot>,
+        root: [u8; 32],
+        max_total_claim: u64,
+        max_num_nodes: u64,
+        tree_depth: u8,
+    ) -> Result<()> {
+        handle_update_root(ctx, root, max_total_claim, max_num_nodes, tree_depth)
+    }
+
+    /// Extends a distributor created before the current account layout to it.
+    #[allow(clippy::result_large_err)]
+    pub fn migrate_distributor(ctx: Context<MigrateDistributor>) -> Result<()> {
+        handle_migrate_distributor(ctx)
+    }
+
+    /// Rewrites a claim status written before the current layout in it.
+    #[allow(clippy::result_large_err)]
+    pub fn migrate_claim_status<'info>(
+        ctx: Context<'_, '_, '_, 'info, MigrateClaimStatus<'info>>,
+        input_account_meta: CompressedAccountMeta,
+        claim_status_data: LegacyClaimStatus,
+        validity_proof: ValidityProof,
+    ) -> Result<()> {
+        handle_migrate_claim_status(ctx, input_account_meta, claim_status_data, validity_proof)
+    }
 }
 
 #[cfg(test)]
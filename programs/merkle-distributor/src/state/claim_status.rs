@@ -15,6 +15,87 @@ pub struct ClaimStatus {
     pub locked_amount_withdrawn: u64,
     /// Unlocked amount
     pub unlocked_amount: u64,
+    /// Per-node vesting window, overrides the distributor's global schedule when set
+    pub unlock_schedule: Option<UnlockSchedule>,
+    /// Time of the last claim_locked (Unix Timestamp), 0 until the first one
+    pub last_claimed_ts: i64,
+    /// Amounts per airdrop category, when the claim supplied them
+    pub category_breakdown: Option<CategoryBreakdown>,
+}
+
+/// Layout of a [ClaimStatus] written before `unlock_schedule`, `last_claimed_ts` and
+/// `category_breakdown` were added. migrate_claim_status proves an account in this layout and
+/// rewrites it in the current one, at the same address and under the same discriminator.
+#[derive(Clone, Copy, Default, Debug, AnchorDeserialize, AnchorSerialize)]
+pub struct LegacyClaimStatus {
+    pub claimant: Pubkey,
+    pub locked_amount: u64,
+    pub locked_amount_withdrawn: u64,
+    pub unlocked_amount: u64,
+}
+
+impl LightDiscriminator for LegacyClaimStatus {
+    const LIGHT_DISCRIMINATOR: [u8; 8] = ClaimStatus::LIGHT_DISCRIMINATOR;
+    const LIGHT_DISCRIMINATOR_SLICE: &'static [u8] = ClaimStatus::LIGHT_DISCRIMINATOR_SLICE;
+}
+
+impl From<LegacyClaimStatus> for ClaimStatus {
+    /// A legacy claim follows the distributor's schedule, has no claim_locked on record and
+    /// no category breakdown.
+    fn from(legacy: LegacyClaimStatus) -> Self {
+        ClaimStatus {
+            claimant: legacy.claimant,
+            locked_amount: legacy.locked_amount,
+            locked_amount_withdrawn: legacy.locked_amount_withdrawn,
+            unlocked_amount: legacy.unlocked_amount,
+            unlock_schedule: None,
+            last_claimed_ts: 0,
+            category_breakdown: None,
+        }
+    }
+}
+
+/// Vesting window for a single claimant, committed to in the merkle leaf.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, AnchorDeserialize, AnchorSerialize)]
+pub struct UnlockSchedule {
+    /// Lockup time start (Unix Timestamp)
+    pub start_ts: i64,
+    /// Lockup time end (Unix Timestamp)
+    pub end_ts: i64,
+}
+
+/// Unlocked and locked amounts per airdrop category, as listed in the tree file. Not part of
+/// the merkle leaf: new_claim only checks that the categories add up to the proven totals.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, AnchorDeserialize, AnchorSerialize)]
+pub struct CategoryBreakdown {
+    pub unlocked_staker: u64,
+    pub locked_staker: u64,
+    pub unlocked_searcher: u64,
+    pub locked_searcher: u64,
+    pub unlocked_validator: u64,
+    pub locked_validator: u64,
+}
+
+impl CategoryBreakdown {
+    /// Sum of the unlocked amounts, None on overflow.
+    pub fn unlocked_amount(&self) -> Option<u64> {
+        self.unlocked_staker
+            .checked_add(self.unlocked_searcher)?
+            .checked_add(self.unlocked_validator)
+    }
+
+    /// Sum of the locked amounts, None on overflow.
+    pub fn locked_amount(&self) -> Option<u64> {
+        self.locked_staker
+            .checked_add(self.locked_searcher)?
+            .checked_add(self.locked_validator)
+    }
+
+    /// Whether the categories add up to `amount_unlocked` and `amount_locked`.
+    pub fn matches(&self, amount_unlocked: u64, amount_locked: u64) -> bool {
+        self.unlocked_amount() == Some(amount_unlocked)
+            && self.locked_amount() == Some(amount_locked)
+    }
 }
 
 /// Instruction data for ClaimStatus without claimant field.
@@ -24,6 +105,9 @@ pub struct ClaimStatusInstructionData {
     pub locked_amount: u64,
     pub locked_amount_withdrawn: u64,
     pub unlocked_amount: u64,
+    pub unlock_schedule: Option<UnlockSchedule>,
+    pub last_claimed_ts: i64,
+    pub category_breakdown: Option<CategoryBreakdown>,
 }
 
 impl ClaimStatusInstructionData {
@@ -33,6 +117,9 @@ impl ClaimStatusInstructionData {
             locked_amount: self.locked_amount,
             locked_amount_withdrawn: self.locked_amount_withdrawn,
             unlocked_amount: self.unlocked_amount,
+            unlock_schedule: self.unlock_schedule,
+            last_claimed_ts: self.last_claimed_ts,
+            category_breakdown: self.category_breakdown,
         }
     }
 }
@@ -40,12 +127,28 @@ impl ClaimStatusInstructionData {
 impl ClaimStatus {
     pub const LEN: usize = 8 + std::mem::size_of::<ClaimStatus>();
 
+    /// Returns the vesting window for this claim, falling back to the distributor's
+    /// global schedule when the node has no override.
+    pub fn unlock_window(&self, start_ts: i64, end_ts: i64) -> (i64, i64) {
+        match self.unlock_schedule {
+            Some(schedule) => (schedule.start_ts, schedule.end_ts),
+            None => (start_ts, end_ts),
+        }
+    }
+
     /// Returns amount withdrawable, factoring in unlocked tokens and previous withdraws.
     /// payout is difference between the amount unlocked and the amount withdrawn
     #[allow(clippy::result_large_err)]
-    pub fn amount_withdrawable(&self, curr_ts: i64, start_ts: i64, end_ts: i64) -> Result<u64> {
+    pub fn amount_withdrawable(
+        &self,
+        curr_ts: i64,
+        start_ts: i64,
+        end_ts: i64,
+        cliff_ts: i64,
+        vesting_interval_secs: i64,
+    ) -> Result<u64> {
         let amount = self
-            .unlocked_amount(curr_ts, start_ts, end_ts)?
+            .unlocked_amount(curr_ts, start_ts, end_ts, cliff_ts, vesting_interval_secs)?
             .checked_sub(self.locked_amount_withdrawn)
             .ok_or(ArithmeticError)?;
 
@@ -54,35 +157,59 @@ impl ClaimStatus {
 
     /// Total amount unlocked
     /// Equal to (time_into_unlock / total_unlock_time) * locked_amount
+    /// The schedule is inclusive at `end_ts`: once `curr_ts == end_ts` the full
+    /// `locked_amount` is unlocked.
+    /// Cliff:
+    ///     Nothing unlocks before `cliff_ts`. From the cliff on the linear amount since
+    ///     `start_ts` is unlocked, so the share that accrued before the cliff unlocks at once.
+    ///     A cliff at or before `start_ts` leaves the linear schedule unchanged.
+    /// Steps:
+    ///     Only whole `vesting_interval_secs` since `start_ts` count as time into unlock, so
+    ///     tokens unlock in steps at each interval boundary, e.g. monthly tranches. An interval
+    ///     of 1 is plain linear vesting. The rest of the amount unlocks at `end_ts`, even when
+    ///     the window is not a whole number of intervals. An interval of 0 is an
+    ///     `ArithmeticError`
     /// Multiplication safety:
-    ///    The maximum possible product is (2^64 -1) * (2^64 -1) = 2^128 - 2^65 + 1
-    ///    which is less than 2^128 - 1 (the maximum value of a u128), meaning that
-    ///    the multiplication will never overflow
-    /// Truncation from u128 to u64:
-    ///     Casting a u128 to a u64 will truncate the 64 higher order bits, which rounds
-    ///     down from the user.
-    ///     in order to avoid truncation, the final result must be less than 2^64 - 1.
-    ///     Rewriting the terms, we get (time_into_unlock * locked_amount) / total_unlock_time < 2^64 - 1
-    ///     We know time_into_unlock and total_unlock_time are both approximately the same size, so we can
-    ///     approximate the above as:
-    ///         b < 2^64 -1.
-    ///     Since b is a i64, this is always true, so no truncation can occur
+    ///    The product is computed in u128. The maximum possible product is
+    ///    (2^64 - 1) * (2^64 - 1) = 2^128 - 2^65 + 1, which is less than 2^128 - 1 (the maximum
+    ///    value of a u128), meaning that the multiplication will never overflow
+    /// Conversion from u128 to u64:
+    ///     time_into_unlock < total_unlock_time, so the quotient is below locked_amount and
+    ///     always fits a u64. The conversion is still checked: any overflow, including in the
+    ///     timestamp differences, fails with `ArithmeticError` instead of wrapping
     #[allow(clippy::result_large_err)]
-    pub fn unlocked_amount(&self, curr_ts: i64, start_ts: i64, end_ts: i64) -> Result<u64> {
+    pub fn unlocked_amount(
+        &self,
+        curr_ts: i64,
+        start_ts: i64,
+        end_ts: i64,
+        cliff_ts: i64,
+        vesting_interval_secs: i64,
+    ) -> Result<u64> {
+        if curr_ts < cliff_ts {
+            return Ok(0);
+        }
         if curr_ts >= start_ts {
             if curr_ts >= end_ts {
                 Ok(self.locked_amount)
             } else {
                 let time_into_unlock = curr_ts.checked_sub(start_ts).ok_or(ArithmeticError)?;
+                let time_into_unlock = time_into_unlock
+                    .checked_sub(
+                        time_into_unlock
+                            .checked_rem(vesting_interval_secs)
+                            .ok_or(ArithmeticError)?,
+                    )
+                    .ok_or(ArithmeticError)?;
                 let total_unlock_time = end_ts.checked_sub(start_ts).ok_or(ArithmeticError)?;
 
-                let amount = ((time_into_unlock as u128)
+                let amount = (time_into_unlock as u128)
                     .checked_mul(self.locked_amount as u128)
-                    .ok_or(ArithmeticError)?)
-                .checked_div(total_unlock_time as u128)
-                .ok_or(ArithmeticError)? as u64;
+                    .ok_or(ArithmeticError)?
+                    .checked_div(total_unlock_time as u128)
+                    .ok_or(ArithmeticError)?;
 
-                Ok(amount)
+                Ok(u64::try_from(amount).map_err(|_| ArithmeticError)?)
             }
         } else {
             Ok(0)
@@ -101,12 +228,15 @@ mod tests {
             locked_amount: 100,
             unlocked_amount: 0,
             locked_amount_withdrawn: 0,
+            unlock_schedule: None,
+            last_claimed_ts: 0,
+            category_breakdown: None,
         };
         let curr_ts = 50;
         let start_ts = 0;
         let end_ts = 100;
         assert_eq!(
-            claim_status.unlocked_amount(curr_ts, start_ts, end_ts),
+            claim_status.unlocked_amount(curr_ts, start_ts, end_ts, start_ts, 1),
             Ok(50)
         );
     }
@@ -118,15 +248,33 @@ mod tests {
             locked_amount: 100,
             locked_amount_withdrawn: 0,
             unlocked_amount: 0,
+            unlock_schedule: None,
+            last_claimed_ts: 0,
+            category_breakdown: None,
         };
         let start_ts = 0;
         let end_ts = 100;
 
-        assert_eq!(claim_status.unlocked_amount(0, start_ts, end_ts), Ok(0));
-        assert_eq!(claim_status.unlocked_amount(25, start_ts, end_ts), Ok(25));
-        assert_eq!(claim_status.unlocked_amount(50, start_ts, end_ts), Ok(50));
-        assert_eq!(claim_status.unlocked_amount(75, start_ts, end_ts), Ok(75));
-        assert_eq!(claim_status.unlocked_amount(100, start_ts, end_ts), Ok(100));
+        assert_eq!(
+            claim_status.unlocked_amount(0, start_ts, end_ts, start_ts, 1),
+            Ok(0)
+        );
+        assert_eq!(
+            claim_status.unlocked_amount(25, start_ts, end_ts, start_ts, 1),
+            Ok(25)
+        );
+        assert_eq!(
+            claim_status.unlocked_amount(50, start_ts, end_ts, start_ts, 1),
+            Ok(50)
+        );
+        assert_eq!(
+            claim_status.unlocked_amount(75, start_ts, end_ts, start_ts, 1),
+            Ok(75)
+        );
+        assert_eq!(
+            claim_status.unlocked_amount(100, start_ts, end_ts, start_ts, 1),
+            Ok(100)
+        );
     }
 
     #[test]
@@ -142,6 +290,9 @@ mod tests {
             locked_amount,
             unlocked_amount: 0,
             locked_amount_withdrawn: 0,
+            unlock_schedule: None,
+            last_claimed_ts: 0,
+            category_breakdown: None,
         };
 
         // Use large values for time_into_unlock and total_unlock_time, but ensure they are within i64 range
@@ -155,7 +306,7 @@ mod tests {
 
             // Perform the calculation using the function
             let calculated_amount = claim_status
-                .unlocked_amount(curr_ts, start_ts, end_ts)
+                .unlocked_amount(curr_ts, start_ts, end_ts, start_ts, 1)
                 .unwrap();
 
             // Assert that the calculated amount matches the expected amount and is within u64 bounds
@@ -164,6 +315,68 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_u64_max_locked_amount_over_realistic_window() {
+        // e.g. a 9 decimal mint with billions of tokens, far beyond what u64 products can hold
+        let locked_amount = u64::MAX;
+        let start_ts = 1_700_000_000;
+        let end_ts = start_ts + 4 * 365 * 86_400;
+        let total_unlock_time = (end_ts - start_ts) as u128;
+
+        for curr_ts in [
+            start_ts,
+            start_ts + 1,
+            start_ts + (end_ts - start_ts) / 4,
+            start_ts + (end_ts - start_ts) / 2,
+            end_ts - 1,
+            end_ts,
+        ] {
+            let expected =
+                ((curr_ts - start_ts) as u128 * locked_amount as u128 / total_unlock_time) as u64;
+            for locked_amount_withdrawn in [0, expected / 2, expected] {
+                let claim_status = ClaimStatus {
+                    locked_amount,
+                    locked_amount_withdrawn,
+                    ..Default::default()
+                };
+                assert_eq!(
+                    claim_status.unlocked_amount(curr_ts, start_ts, end_ts, start_ts, 1),
+                    Ok(expected),
+                    "at {curr_ts}"
+                );
+                assert_eq!(
+                    claim_status.amount_withdrawable(curr_ts, start_ts, end_ts, start_ts, 1),
+                    Ok(expected - locked_amount_withdrawn),
+                    "at {curr_ts} with {locked_amount_withdrawn} withdrawn"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_overflow_returns_arithmetic_error() {
+        let claim_status = ClaimStatus {
+            locked_amount: u64::MAX,
+            ..Default::default()
+        };
+        // curr_ts - start_ts does not fit an i64
+        assert_eq!(
+            claim_status.unlocked_amount(1, i64::MIN, i64::MAX, i64::MIN, 1),
+            Err(ArithmeticError.into())
+        );
+
+        // More withdrawn than unlocked
+        let claim_status = ClaimStatus {
+            locked_amount: u64::MAX,
+            locked_amount_withdrawn: u64::MAX,
+            ..Default::default()
+        };
+        assert_eq!(
+            claim_status.amount_withdrawable(50, 0, 100, 0, 1),
+            Err(ArithmeticError.into())
+        );
+    }
+
     #[test]
     fn test_unlocking_after_end_time() {
         let claim_status = ClaimStatus {
@@ -171,16 +384,49 @@ mod tests {
             locked_amount: 100,
             unlocked_amount: 0,
             locked_amount_withdrawn: 0,
+            unlock_schedule: None,
+            last_claimed_ts: 0,
+            category_breakdown: None,
         };
         let curr_ts = 150;
         let start_ts = 0;
         let end_ts = 100;
         assert_eq!(
-            claim_status.unlocked_amount(curr_ts, start_ts, end_ts),
+            claim_status.unlocked_amount(curr_ts, start_ts, end_ts, start_ts, 1),
             Ok(100)
         );
     }
 
+    #[test]
+    fn test_unlocking_exactly_at_end_time() {
+        let claim_status = ClaimStatus {
+            claimant: Pubkey::new_unique(),
+            locked_amount: 1_000,
+            unlocked_amount: 0,
+            locked_amount_withdrawn: 0,
+            unlock_schedule: None,
+            last_claimed_ts: 0,
+            category_breakdown: None,
+        };
+        let start_ts = 0;
+        let end_ts = 3_600;
+
+        // one second before the end some tokens are still locked
+        assert_eq!(
+            claim_status.unlocked_amount(end_ts - 1, start_ts, end_ts, start_ts, 1),
+            Ok(999)
+        );
+        // inclusive at end_ts
+        assert_eq!(
+            claim_status.unlocked_amount(end_ts, start_ts, end_ts, start_ts, 1),
+            Ok(1_000)
+        );
+        assert_eq!(
+            claim_status.amount_withdrawable(end_ts, start_ts, end_ts, start_ts, 1),
+            Ok(1_000)
+        );
+    }
+
     #[test]
     fn test_division_by_zero() {
         let claim_status = ClaimStatus {
@@ -188,12 +434,15 @@ mod tests {
             locked_amount: 100,
             unlocked_amount: 0,
             locked_amount_withdrawn: 0,
+            unlock_schedule: None,
+            last_claimed_ts: 0,
+            category_breakdown: None,
         };
         let curr_ts = 50;
         let start_ts = 100;
         let end_ts = 100;
         assert_eq!(
-            claim_status.unlocked_amount(curr_ts, start_ts, end_ts),
+            claim_status.unlocked_amount(curr_ts, start_ts, end_ts, start_ts, 1),
             Ok(0)
         );
     }
@@ -207,7 +456,10 @@ mod tests {
         let start_ts = 100;
         let end_ts = 50;
 
-        assert_eq!(claim_status.unlocked_amount(75, start_ts, end_ts), Ok(0));
+        assert_eq!(
+            claim_status.unlocked_amount(75, start_ts, end_ts, start_ts, 1),
+            Ok(0)
+        );
     }
 
     #[test]
@@ -227,12 +479,268 @@ mod tests {
                 locked_amount: 100,
                 unlocked_amount: 0,
                 locked_amount_withdrawn,
+                unlock_schedule: None,
+                last_claimed_ts: 0,
+                category_breakdown: None,
             };
 
             assert_eq!(
-                claim_status.amount_withdrawable(curr_ts, 0, 100),
+                claim_status.amount_withdrawable(curr_ts, 0, 100, 0, 1),
                 Ok(expected)
             );
         }
     }
+
+    #[test]
+    fn test_cliff_vesting() {
+        let claim_status = ClaimStatus {
+            locked_amount: 1_000,
+            ..Default::default()
+        };
+        let (start_ts, cliff_ts, end_ts) = (0, 250, 1_000);
+
+        for (curr_ts, expected) in [
+            // before the cliff nothing unlocks, even though linear vesting has begun
+            (0, 0),
+            (100, 0),
+            (cliff_ts - 1, 0),
+            // at the cliff its whole share unlocks at once
+            (cliff_ts, 250),
+            // then vesting continues linearly
+            (500, 500),
+            (999, 999),
+            (end_ts, 1_000),
+            (2_000, 1_000),
+        ] {
+            assert_eq!(
+                claim_status.amount_withdrawable(curr_ts, start_ts, end_ts, cliff_ts, 1),
+                Ok(expected),
+                "at {curr_ts}"
+            );
+        }
+
+        // Withdrawals made after the cliff are subtracted as usual
+        let claim_status = ClaimStatus {
+            locked_amount: 1_000,
+            locked_amount_withdrawn: 250,
+            ..Default::default()
+        };
+        assert_eq!(
+            claim_status.amount_withdrawable(500, start_ts, end_ts, cliff_ts, 1),
+            Ok(250)
+        );
+    }
+
+    #[test]
+    fn test_cliff_at_start_matches_linear_vesting() {
+        let claim_status = ClaimStatus {
+            locked_amount: 100,
+            ..Default::default()
+        };
+        for curr_ts in [-10, 0, 1, 33, 50, 99, 100, 150] {
+            let linear = claim_status.unlocked_amount(curr_ts, 0, 100, i64::MIN, 1);
+            assert_eq!(claim_status.unlocked_amount(curr_ts, 0, 100, 0, 1), linear);
+        }
+    }
+
+    #[test]
+    fn test_monthly_vesting_steps() {
+        let claim_status = ClaimStatus {
+            locked_amount: 1_200,
+            ..Default::default()
+        };
+        let month = 30 * 86_400;
+        let start_ts = 1_700_000_000;
+        let end_ts = start_ts + 12 * month;
+
+        for (curr_ts, expected) in [
+            (start_ts, 0),
+            (start_ts + month - 1, 0),
+            (start_ts + month, 100),
+            (start_ts + month + month / 2, 100),
+            (start_ts + 2 * month - 1, 100),
+            (start_ts + 2 * month, 200),
+            (end_ts - 1, 1_100),
+            (end_ts, 1_200),
+        ] {
+            assert_eq!(
+                claim_status.unlocked_amount(curr_ts, start_ts, end_ts, start_ts, month),
+                Ok(expected),
+                "at {curr_ts}"
+            );
+        }
+
+        // Nothing more unlocks between two step boundaries
+        let claim_status = ClaimStatus {
+            locked_amount: 1_200,
+            locked_amount_withdrawn: 300,
+            ..Default::default()
+        };
+        for curr_ts in (start_ts + 3 * month..start_ts + 4 * month).step_by(86_400) {
+            assert_eq!(
+                claim_status.amount_withdrawable(curr_ts, start_ts, end_ts, start_ts, month),
+                Ok(0),
+                "at {curr_ts}"
+            );
+        }
+        assert_eq!(
+            claim_status.amount_withdrawable(
+                start_ts + 4 * month,
+                start_ts,
+                end_ts,
+                start_ts,
+                month
+            ),
+            Ok(100)
+        );
+    }
+
+    #[test]
+    fn test_vesting_steps_with_partial_last_interval() {
+        let claim_status = ClaimStatus {
+            locked_amount: 1_000,
+            ..Default::default()
+        };
+        // Three whole intervals of 30 and a last one of 10
+        let (start_ts, end_ts, interval) = (0, 100, 30);
+        for (curr_ts, expected) in [
+            (29, 0),
+            (30, 300),
+            (89, 600),
+            (90, 900),
+            (99, 900),
+            (100, 1_000),
+        ] {
+            assert_eq!(
+                claim_status.unlocked_amount(curr_ts, start_ts, end_ts, start_ts, interval),
+                Ok(expected),
+                "at {curr_ts}"
+            );
+        }
+
+        // The cliff unlocks the whole steps accrued before it
+        assert_eq!(
+            claim_status.unlocked_amount(45, start_ts, end_ts, 45, interval),
+            Ok(300)
+        );
+        assert_eq!(
+            claim_status.unlocked_amount(50, start_ts, end_ts, start_ts, 0),
+            Err(ArithmeticError.into())
+        );
+    }
+
+    #[test]
+    fn test_vesting_interval_of_one_is_linear() {
+        let claim_status = ClaimStatus {
+            locked_amount: 1_000,
+            ..Default::default()
+        };
+        let (start_ts, end_ts) = (0, 3_600);
+        for curr_ts in [0, 1, 7, 1_799, 1_800, 3_599, 3_600] {
+            assert_eq!(
+                claim_status.unlocked_amount(curr_ts, start_ts, end_ts, start_ts, 1),
+                Ok((curr_ts as u64 * 1_000) / 3_600),
+                "at {curr_ts}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_category_breakdown_matches_totals() {
+        let breakdown = CategoryBreakdown {
+            unlocked_staker: 100,
+            locked_staker: 50,
+            unlocked_validator: 20,
+            locked_validator: 10,
+            ..Default::default()
+        };
+        assert!(breakdown.matches(120, 60));
+        assert!(!breakdown.matches(120, 59));
+        assert!(!breakdown.matches(150, 30));
+
+        let overflowing = CategoryBreakdown {
+            unlocked_staker: u64::MAX,
+            unlocked_searcher: 1,
+            ..Default::default()
+        };
+        assert_eq!(overflowing.unlocked_amount(), None);
+        assert!(!overflowing.matches(0, 0));
+    }
+
+    #[test]
+    fn test_unlock_window_defaults_to_global_schedule() {
+        let claim_status = ClaimStatus {
+            locked_amount: 100,
+            ..Default::default()
+        };
+        assert_eq!(claim_status.unlock_window(0, 100), (0, 100));
+    }
+
+    #[test]
+    fn test_per_node_unlock_schedules() {
+        let global = (0, 100);
+        let team = ClaimStatus {
+            locked_amount: 100,
+            unlock_schedule: Some(UnlockSchedule {
+                start_ts: 50,
+                end_ts: 250,
+            }),
+            ..Default::default()
+        };
+        let community = ClaimStatus {
+            locked_amount: 100,
+            unlock_schedule: Some(UnlockSchedule {
+                start_ts: 0,
+                end_ts: 50,
+            }),
+            ..Default::default()
+        };
+
+        for (curr_ts, team_expected, community_expected) in [
+            (0, 0, 0),
+            (25, 0, 50),
+            (50, 0, 100),
+            (100, 25, 100),
+            (150, 50, 100),
+            (250, 100, 100),
+        ] {
+            let (start_ts, end_ts) = team.unlock_window(global.0, global.1);
+            assert_eq!(
+                team.amount_withdrawable(curr_ts, start_ts, end_ts, start_ts, 1),
+                Ok(team_expected)
+            );
+            let (start_ts, end_ts) = community.unlock_window(global.0, global.1);
+            assert_eq!(
+                community.amount_withdrawable(curr_ts, start_ts, end_ts, start_ts, 1),
+                Ok(community_expected)
+            );
+        }
+    }
+
+    #[test]
+    fn test_legacy_claim_status_keeps_discriminator_and_amounts() {
+        let legacy = LegacyClaimStatus {
+            claimant: Pubkey::new_unique(),
+            locked_amount: 300,
+            locked_amount_withdrawn: 100,
+            unlocked_amount: 50,
+        };
+        let mut data = vec![];
+        legacy.serialize(&mut data).unwrap();
+        // The layout before unlock_schedule: claimant and three amounts
+        assert_eq!(data.len(), 56);
+        assert_eq!(
+            LegacyClaimStatus::LIGHT_DISCRIMINATOR,
+            ClaimStatus::LIGHT_DISCRIMINATOR
+        );
+
+        let claim_status = ClaimStatus::from(legacy);
+        assert_eq!(claim_status.claimant, legacy.claimant);
+        assert_eq!(claim_status.locked_amount, 300);
+        assert_eq!(claim_status.locked_amount_withdrawn, 100);
+        assert_eq!(claim_status.unlocked_amount, 50);
+        assert_eq!(claim_status.unlock_schedule, None);
+        assert_eq!(claim_status.last_claimed_ts, 0);
+        assert_eq!(claim_status.category_breakdown, None);
+    }
 }
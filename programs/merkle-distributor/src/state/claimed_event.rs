@@ -1,5 +1,7 @@
 use anchor_lang::{event, prelude::*};
 
+use crate::state::claim_status::CategoryBreakdown;
+
 /// Emitted when a new claim is created.
 #[event]
 pub struct NewClaimEvent {
@@ -7,6 +9,96 @@ pub struct NewClaimEvent {
     pub claimant: Pubkey,
     /// Timestamp.
     pub timestamp: i64,
+    /// Unlocked amount delivered to the claimant.
+    pub amount: u64,
+    /// Token-2022 transfer fee paid by the vault on top of `amount`.
+    pub transfer_fee: u64,
+    /// Claimed amounts per airdrop category, when the claim supplied them.
+    pub category_breakdown: Option<CategoryBreakdown>,
+}
+
+/// Emitted when the admin moves an unclaimed allocation to another wallet. A
+/// [NewClaimEvent] for the new claimant follows.
+#[event]
+pub struct ClaimReassignedEvent {
+    /// Distributor the allocation belongs to.
+    pub distributor: Pubkey,
+    /// Claimant of the node in the merkle tree, who can no longer claim.
+    pub old_claimant: Pubkey,
+    /// Claimant the allocation was issued to.
+    pub new_claimant: Pubkey,
+    /// Timestamp.
+    pub timestamp: i64,
+}
+
+/// Emitted when the remaining vault balance is clawed back.
+#[event]
+pub struct ClawbackEvent {
+    /// Distributor that was clawed back.
+    pub distributor: Pubkey,
+    /// Clawback receiver token account.
+    pub receiver: Pubkey,
+    /// Amount of tokens clawed back.
+    pub amount: u64,
+    /// Timestamp.
+    pub timestamp: i64,
+}
+
+/// Emitted when the admin hands over the distributor.
+#[event]
+pub struct SetAdminEvent {
+    /// Distributor whose admin changed.
+    pub distributor: Pubkey,
+    /// Previous admin.
+    pub old_admin: Pubkey,
+    /// New admin.
+    pub new_admin: Pubkey,
+}
+
+/// Emitted when the admin gives up control of the distributor.
+#[event]
+pub struct AdminRenouncedEvent {
+    /// Distributor that no longer has an admin.
+    pub distributor: Pubkey,
+    /// Admin that renounced.
+    pub admin: Pubkey,
+}
+
+/// Emitted when the admin postpones the clawback start.
+#[event]
+pub struct ClawbackStartUpdatedEvent {
+    /// Distributor whose clawback start changed.
+    pub distributor: Pubkey,
+    /// Previous clawback start timestamp.
+    pub old_clawback_start_ts: i64,
+    /// New clawback start timestamp.
+    pub new_clawback_start_ts: i64,
+}
+
+/// Emitted when the admin changes the clawback receiver.
+#[event]
+pub struct ClawbackReceiverChangedEvent {
+    /// Distributor whose receiver changed.
+    pub distributor: Pubkey,
+    /// Previous clawback receiver token account.
+    pub old_receiver: Pubkey,
+    /// New clawback receiver token account.
+    pub new_receiver: Pubkey,
+}
+
+/// Emitted when the admin replaces the merkle root with that of a larger tree.
+#[event]
+pub struct RootUpdatedEvent {
+    /// Distributor whose root changed.
+    pub distributor: Pubkey,
+    /// Previous merkle root.
+    pub old_root: [u8; 32],
+    /// New merkle root.
+    pub new_root: [u8; 32],
+    /// Maximum total claim of the new tree.
+    pub max_total_claim: u64,
+    /// Number of nodes of the new tree.
+    pub max_num_nodes: u64,
 }
 
 /// Emitted when tokens are claimed.
@@ -14,6 +106,8 @@ pub struct NewClaimEvent {
 pub struct ClaimedEvent {
     /// User that claimed.
     pub claimant: Pubkey,
-    /// Amount of tokens to distribute.
+    /// Amount of tokens delivered to the claimant.
     pub amount: u64,
+    /// Token-2022 transfer fee paid by the vault on top of `amount`.
+    pub transfer_fee: u64,
 }
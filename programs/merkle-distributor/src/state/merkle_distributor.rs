@@ -37,8 +37,127 @@ pub struct MerkleDistributor {
     pub admin: Pubkey,
     /// Whether or not the distributor has been clawed back
     pub clawed_back: bool,
+    // Fields below were appended after the first release, accounts created before them are
+    // shorter and have to go through migrate_distributor first
+    /// Cliff (Unix Timestamp), no locked tokens unlock before it. Equal to `start_ts` for
+    /// plain linear vesting
+    pub cliff_ts: i64,
+    /// Whether the admin has paused claims
+    pub paused: bool,
+    /// Minimum seconds between two claim_locked of the same claimant, 0 for no limit
+    pub min_claim_interval: i64,
+    /// Set by renounce_admin; admin-only instructions fail from then on
+    pub admin_renounced: bool,
+    /// Whether the distributor pays out tokens of `mint` or native SOL
+    pub distribution_mode: DistributionMode,
+    /// Locked tokens unlock in steps of this many seconds since `start_ts`, 1 for linear vesting
+    pub vesting_interval_secs: i64,
+    /// Whether the admin may move an unclaimed allocation to another wallet with
+    /// reassign_claim. Fixed at creation
+    pub allow_reassignment: bool,
+    /// Height of the merkle tree above its leaves, the length of every proof in it
+    pub tree_depth: u8,
+}
+
+/// What a [MerkleDistributor] pays out.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, AnchorDeserialize, AnchorSerialize)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum DistributionMode {
+    /// Tokens of the distributor's mint, held in its `token_vault` ATA
+    #[default]
+    Token,
+    /// Lamports, held in the system-owned `SolVault` PDA stored as `token_vault`. The
+    /// distributor's `mint` is the native mint
+    Native,
 }
 
 impl MerkleDistributor {
     pub const LEN: usize = 8 + std::mem::size_of::<MerkleDistributor>();
 }
+
+/// Layout of a [MerkleDistributor] created before `cliff_ts` and the fields after it were
+/// added. migrate_distributor reads it to extend such an account to the current layout.
+#[derive(Default, Debug, AnchorDeserialize, AnchorSerialize)]
+pub struct LegacyMerkleDistributor {
+    pub bump: u8,
+    pub version: u64,
+    pub root: [u8; 32],
+    pub mint: Pubkey,
+    pub token_vault: Pubkey,
+    pub max_total_claim: u64,
+    pub max_num_nodes: u64,
+    pub total_amount_claimed: u64,
+    pub num_nodes_claimed: u64,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub clawback_start_ts: i64,
+    pub clawback_receiver: Pubkey,
+    pub admin: Pubkey,
+    pub clawed_back: bool,
+}
+
+impl From<LegacyMerkleDistributor> for MerkleDistributor {
+    /// Fills the appended fields with the values a distributor created without them behaves
+    /// like: no cliff, linear vesting, no claim interval, token mode, no reassignment, and an
+    /// unknown tree depth.
+    fn from(legacy: LegacyMerkleDistributor) -> Self {
+        MerkleDistributor {
+            bump: legacy.bump,
+            version: legacy.version,
+            root: legacy.root,
+            mint: legacy.mint,
+            token_vault: legacy.token_vault,
+            max_total_claim: legacy.max_total_claim,
+            max_num_nodes: legacy.max_num_nodes,
+            total_amount_claimed: legacy.total_amount_claimed,
+            num_nodes_claimed: legacy.num_nodes_claimed,
+            start_ts: legacy.start_ts,
+            end_ts: legacy.end_ts,
+            clawback_start_ts: legacy.clawback_start_ts,
+            clawback_receiver: legacy.clawback_receiver,
+            admin: legacy.admin,
+            clawed_back: legacy.clawed_back,
+            cliff_ts: legacy.start_ts,
+            paused: false,
+            min_claim_interval: 0,
+            admin_renounced: false,
+            distribution_mode: DistributionMode::Token,
+            vesting_interval_secs: 1,
+            allow_reassignment: false,
+            tree_depth: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_distributor_migrates_to_token_mode() {
+        let legacy = LegacyMerkleDistributor {
+            version: 3,
+            start_ts: 1_000,
+            end_ts: 2_000,
+            clawback_start_ts: 90_000,
+            ..LegacyMerkleDistributor::default()
+        };
+        // A legacy account ends after `clawed_back`, followed by zeroed slack
+        let mut data = vec![];
+        legacy.serialize(&mut data).unwrap();
+        data.resize(std::mem::size_of::<LegacyMerkleDistributor>(), 0);
+
+        let decoded = LegacyMerkleDistributor::deserialize(&mut data.as_slice()).unwrap();
+        let migrated = MerkleDistributor::from(decoded);
+
+        assert_eq!(migrated.version, 3);
+        assert_eq!(migrated.clawback_start_ts, 90_000);
+        assert_eq!(migrated.distribution_mode, DistributionMode::Token);
+        assert_eq!(migrated.cliff_ts, migrated.start_ts);
+        assert_eq!(migrated.vesting_interval_secs, 1);
+    }
+}
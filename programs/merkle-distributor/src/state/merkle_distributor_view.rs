@@ -0,0 +1,74 @@
+//! JSON friendly view of [MerkleDistributor], for tooling reading distributor accounts without
+//! Anchor. Pubkeys are base58 and the root is hex, the same as explorers show them.
+
+use serde::{Deserialize, Serialize};
+
+use crate::state::merkle_distributor::{DistributionMode, MerkleDistributor};
+
+/// [MerkleDistributor] with its fields in serde friendly types. The on-chain layout stays the
+/// Anchor one; this is only what it is shown as.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleDistributorView {
+    pub bump: u8,
+    pub version: u64,
+    /// Hex encoded merkle root
+    pub root: String,
+    pub mint: String,
+    pub token_vault: String,
+    pub max_total_claim: u64,
+    pub max_num_nodes: u64,
+    pub total_amount_claimed: u64,
+    pub num_nodes_claimed: u64,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub cliff_ts: i64,
+    pub clawback_start_ts: i64,
+    pub clawback_receiver: String,
+    pub admin: String,
+    pub clawed_back: bool,
+    pub paused: bool,
+    pub min_claim_interval: i64,
+    pub admin_renounced: bool,
+    pub distribution_mode: DistributionMode,
+    /// Locked tokens unlock in steps of this many seconds, 1 for linear vesting
+    pub vesting_interval_secs: i64,
+    #[serde(default)]
+    pub allow_reassignment: bool,
+    /// Length of every proof in the tree
+    #[serde(default)]
+    pub tree_depth: u8,
+}
+
+impl From<&MerkleDistributor> for MerkleDistributorView {
+    fn from(distributor: &MerkleDistributor) -> Self {
+        Self {
+            bump: distributor.bump,
+            version: distributor.version,
+            root: distributor
+                .root
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect(),
+            mint: distributor.mint.to_string(),
+            token_vault: distributor.token_vault.to_string(),
+            max_total_claim: distributor.max_total_claim,
+            max_num_nodes: distributor.max_num_nodes,
+            total_amount_claimed: distributor.total_amount_claimed,
+            num_nodes_claimed: distributor.num_nodes_claimed,
+            start_ts: distributor.start_ts,
+            end_ts: distributor.end_ts,
+            cliff_ts: distributor.cliff_ts,
+            clawback_start_ts: distributor.clawback_start_ts,
+            clawback_receiver: distributor.clawback_receiver.to_string(),
+            admin: distributor.admin.to_string(),
+            clawed_back: distributor.clawed_back,
+            paused: distributor.paused,
+            min_claim_interval: distributor.min_claim_interval,
+            admin_renounced: distributor.admin_renounced,
+            distribution_mode: distributor.distribution_mode,
+            vesting_interval_secs: distributor.vesting_interval_secs,
+            allow_reassignment: distributor.allow_reassignment,
+            tree_depth: distributor.tree_depth,
+        }
+    }
+}
@@ -1,3 +1,5 @@
 pub mod claim_status;
 pub mod claimed_event;
 pub mod merkle_distributor;
+#[cfg(feature = "serde")]
+pub mod merkle_distributor_view;
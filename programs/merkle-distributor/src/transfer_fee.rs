@@ -0,0 +1,27 @@
+//! Gross-up for Token-2022 mints with the transfer-fee extension.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions},
+    state::Mint,
+};
+
+use crate::error::ErrorCode;
+
+/// Fee withheld by `mint` when `net_amount` has to arrive at the recipient.
+/// SPL Token mints and Token-2022 mints without the transfer-fee extension charge nothing.
+pub fn transfer_fee_for_net_amount(mint: &AccountInfo, net_amount: u64) -> Result<u64> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(0);
+    }
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+    let Ok(config) = mint.get_extension::<TransferFeeConfig>() else {
+        return Ok(0);
+    };
+    let fee = config
+        .calculate_inverse_epoch_fee(Clock::get()?.epoch, net_amount)
+        .ok_or(ErrorCode::ArithmeticError)?;
+    Ok(fee)
+}
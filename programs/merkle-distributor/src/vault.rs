@@ -0,0 +1,149 @@
+//! Where a distributor's funds sit and how they are paid out: an SPL token vault, or a
+//! system-owned PDA holding lamports for native SOL airdrops.
+
+use anchor_lang::{
+    prelude::*,
+    system_program::{self, Transfer},
+};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    error::ErrorCode,
+    state::merkle_distributor::{DistributionMode, MerkleDistributor},
+    transfer_fee::transfer_fee_for_net_amount,
+};
+
+/// Seed of the SOL vault PDA, followed by the distributor address.
+pub const SOL_VAULT_SEED: &[u8] = b"SolVault";
+
+/// The vault of a distributor together with the account receiving a payout.
+pub(crate) enum Vault<'a, 'info> {
+    Token {
+        from: &'a InterfaceAccount<'info, TokenAccount>,
+        to: &'a InterfaceAccount<'info, TokenAccount>,
+        mint: &'a InterfaceAccount<'info, Mint>,
+        token_program: &'a Interface<'info, TokenInterface>,
+    },
+    Native {
+        from: &'a SystemAccount<'info>,
+        from_bump: u8,
+        to: &'a AccountInfo<'info>,
+        system_program: &'a Program<'info, System>,
+    },
+}
+
+impl<'info> Vault<'_, 'info> {
+    pub fn mode(&self) -> DistributionMode {
+        match self {
+            Vault::Token { .. } => DistributionMode::Token,
+            Vault::Native { .. } => DistributionMode::Native,
+        }
+    }
+
+    /// Sent on top of `amount` so the recipient receives all of it: the Token-2022 transfer
+    /// fee, nothing for SOL.
+    pub fn transfer_fee(&self, amount: u64) -> Result<u64> {
+        match self {
+            Vault::Token { mint, .. } => {
+                transfer_fee_for_net_amount(&mint.to_account_info(), amount)
+            }
+            Vault::Native { .. } => Ok(0),
+        }
+    }
+
+    /// Amount the vault can pay out. The SOL vault keeps its rent-exempt minimum, since a
+    /// transfer leaving it between zero and that minimum fails.
+    pub fn available(&self) -> Result<u64> {
+        match self {
+            Vault::Token { from, .. } => Ok(from.amount),
+            Vault::Native { from, .. } => Ok(from
+                .lamports()
+                .saturating_sub(Rent::get()?.minimum_balance(0))),
+        }
+    }
+
+    /// Fails with `InsufficientVaultBalance`, logging the deficit, when the vault cannot pay
+    /// out `amount`, so an underfunded vault does not surface as an opaque transfer error.
+    pub fn require_available(&self, amount: u64) -> Result<()> {
+        let available = self.available()?;
+        if available < amount {
+            msg!(
+                "Vault holds {} but {} is needed, {} short",
+                available,
+                amount,
+                amount - available
+            );
+            return err!(ErrorCode::InsufficientVaultBalance);
+        }
+        Ok(())
+    }
+
+    /// Fails with `RecipientAccountFrozen` when the recipient token account is frozen, e.g. by
+    /// the freeze authority of a regulated Token-2022 mint, instead of deep inside the transfer.
+    pub fn require_recipient_not_frozen(&self) -> Result<()> {
+        match self {
+            Vault::Token { to, .. } if to.is_frozen() => {
+                msg!("Recipient token account {} is frozen", to.key());
+                err!(ErrorCode::RecipientAccountFrozen)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Pays `amount` from the vault to the recipient.
+    pub fn transfer(
+        &self,
+        distributor: &Account<'info, MerkleDistributor>,
+        amount: u64,
+    ) -> Result<()> {
+        match self {
+            Vault::Token {
+                from,
+                to,
+                mint,
+                token_program,
+            } => {
+                let seeds = [
+                    b"MerkleDistributor".as_ref(),
+                    &distributor.mint.to_bytes(),
+                    &distributor.version.to_le_bytes(),
+                    &[distributor.bump],
+                ];
+                token_interface::transfer_checked(
+                    CpiContext::new(
+                        token_program.to_account_info(),
+                        token_interface::TransferChecked {
+                            from: from.to_account_info(),
+                            mint: mint.to_account_info(),
+                            to: to.to_account_info(),
+                            authority: distributor.to_account_info(),
+                        },
+                    )
+                    .with_signer(&[&seeds[..]]),
+                    amount,
+                    mint.decimals,
+                )
+            }
+            Vault::Native {
+                from,
+                from_bump,
+                to,
+                system_program,
+            } => {
+                let distributor_key = distributor.key();
+                let seeds = [SOL_VAULT_SEED, distributor_key.as_ref(), &[*from_bump]];
+                system_program::transfer(
+                    CpiContext::new(
+                        system_program.to_account_info(),
+                        Transfer {
+                            from: from.to_account_info(),
+                            to: (*to).clone(),
+                        },
+                    )
+                    .with_signer(&[&seeds[..]]),
+                    amount,
+                )
+            }
+        }
+    }
+}
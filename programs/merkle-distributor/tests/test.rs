@@ -11,7 +11,8 @@ use light_program_test::{
 use light_sdk::instruction::{PackedAccounts, SystemAccountMetaConfig};
 use solana_program::program_pack::Pack;
 use spl_associated_token_account::{
-    get_associated_token_address, instruction::create_associated_token_account,
+    get_associated_token_address, get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account,
 };
 
 use solana_sdk::{
@@ -25,14 +26,15 @@ fn test_merkle_tree_creation() {
     let (merkle_tree, _test_keypairs) = create_test_merkle_tree();
 
     // Verify merkle tree properties
-    assert_eq!(merkle_tree.tree_nodes.len(), 2);
+    assert_eq!(merkle_tree.tree_nodes().len(), 2);
     assert_eq!(merkle_tree.max_num_nodes, 2);
 
     // Test the first node in the tree
-    let first_node = &merkle_tree.tree_nodes[0];
+    let first_node = &merkle_tree.tree_nodes()[0];
 
-    // Verify node has correct amounts (stored as UI amounts, converted to base units when used)
-    assert_eq!(first_node.amount_unlocked(), 1000); // UI amounts
+    // Node amounts are base units, as the program hashes and pays them out. The test nodes are
+    // built directly, so no decimals were applied
+    assert_eq!(first_node.amount_unlocked(), 1000);
     assert_eq!(first_node.amount_locked(), 500);
     assert!(first_node.proof.is_some());
 
@@ -153,6 +155,9 @@ async fn test_distributor_integration_with_light_program_test() {
         start_vesting_ts,
         end_vesting_ts,
         clawback_start_ts,
+        0,
+        false,
+        &spl_token::id(),
     );
 
     send_transaction(&mut rpc, &[new_distributor_ix], &[&payer])
@@ -185,7 +190,7 @@ async fn test_distributor_integration_with_light_program_test() {
         .unwrap();
 
     // Get the claimant's node from merkle tree
-    let claimant_node = merkle_tree.get_node(&claimant_keypair.pubkey());
+    let claimant_node = merkle_tree.get_node_unchecked(&claimant_keypair.pubkey());
 
     // Get v2 tree addresses
     let address_tree = rpc.test_accounts.v2_address_trees[0];
@@ -259,6 +264,8 @@ async fn test_distributor_integration_with_light_program_test() {
         &distributor_token_account,
         &claimant_ata,
         &claimant_keypair.pubkey(),
+        &mint,
+        &spl_token::id(),
         packed_account_metas,
         &claimant_node,
         proof.proof,
@@ -288,6 +295,10 @@ async fn test_distributor_integration_with_light_program_test() {
         claimant_node.amount_unlocked()
     );
     assert_eq!(claim_status.locked_amount, claimant_node.amount_locked());
+    assert_eq!(
+        claim_status.category_breakdown,
+        Some(category_breakdown(claimant_node))
+    );
 
     // Verify tokens were transferred to claimant
     let claimant_token_account = rpc.get_account(claimant_ata).await.unwrap();
@@ -314,122 +325,3730 @@ async fn test_distributor_integration_with_light_program_test() {
     );
 }
 
-#[test]
-fn test_merkle_proof_verification() {
-    // Create merkle tree directly
-    let (merkle_tree, _test_keypairs) = create_test_merkle_tree();
+#[tokio::test]
+async fn test_claim_locked_exactly_at_end_ts_unlocks_full_amount() {
+    use anchor_lang::AnchorDeserialize;
+    use merkle_distributor::{state::claim_status::ClaimStatus, ID as PROGRAM_ID};
+    use solana_program::clock::Clock;
 
-    // Test proof verification for each node in the tree
-    for node in &merkle_tree.tree_nodes {
-        let proof = node.proof.as_ref().unwrap();
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
 
-        // The proof should not be empty for a tree with multiple nodes
-        assert!(!proof.is_empty());
+    let (merkle_tree, test_keypairs) = create_test_merkle_tree();
+    let claimant_keypair = &test_keypairs[0];
+    let claimant_node = merkle_tree.get_node_unchecked(&claimant_keypair.pubkey());
 
-        // Each proof should be valid (this is tested internally by the merkle tree library)
-        println!("✅ Proof verified for claimant: {}", node.claimant);
-    }
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let start_vesting_ts = current_time + 10;
+    let end_vesting_ts = current_time + 3600;
+    let clawback_start_ts = end_vesting_ts + 86400;
 
-    println!("✅ Merkle proof verification test completed successfully!");
+    let (mint, distributor_pda, distributor_token_account) = setup_funded_distributor(
+        &mut rpc,
+        &payer,
+        &merkle_tree,
+        start_vesting_ts,
+        end_vesting_ts,
+        clawback_start_ts,
+    )
+    .await;
+    let claimant_ata = new_claim(
+        &mut rpc,
+        &payer,
+        claimant_keypair,
+        &claimant_node,
+        &mint,
+        &distributor_pda,
+        &distributor_token_account,
+    )
+    .await;
+
+    // Warp to exactly end_ts: vesting is inclusive, so everything is unlocked
+    let mut clock = rpc.context.get_sysvar::<Clock>();
+    clock.unix_timestamp = end_vesting_ts;
+    rpc.context.set_sysvar(&clock);
+
+    claim_locked(
+        &mut rpc,
+        claimant_keypair,
+        &distributor_pda,
+        &distributor_token_account,
+        &claimant_ata,
+    )
+    .await;
+
+    let (claim_status_address, _) = get_claim_status_pda(
+        &PROGRAM_ID,
+        &claimant_keypair.pubkey(),
+        &distributor_pda,
+    );
+    let claim_status_account = rpc
+        .get_compressed_account(claim_status_address, None)
+        .await
+        .unwrap()
+        .value
+        .expect("Claim status account not found");
+    let claim_status =
+        ClaimStatus::deserialize(&mut claim_status_account.data.as_ref().unwrap().data.as_slice())
+            .unwrap();
+    assert_eq!(
+        claim_status.locked_amount_withdrawn,
+        claimant_node.amount_locked()
+    );
+
+    let claimant_token_account = rpc.get_account(claimant_ata).await.unwrap();
+    let claimant_token_data =
+        spl_token::state::Account::unpack(&claimant_token_account.unwrap().data).unwrap();
+    assert_eq!(claimant_token_data.amount, claimant_node.total_amount());
 }
 
-async fn send_transaction(
-    rpc: &mut LightProgramTest,
-    instructions: &[solana_program::instruction::Instruction],
-    signers: &[&Keypair],
-) -> Result<(), Box<dyn std::error::Error>> {
-    let (blockhash, _) = rpc.get_latest_blockhash().await?;
-    let transaction = Transaction::new_signed_with_payer(
-        instructions,
-        Some(&signers[0].pubkey()),
-        signers,
-        blockhash,
+#[tokio::test]
+async fn test_claim_locked_recreates_closed_claimant_ata() {
+    use merkle_distributor::ID as PROGRAM_ID;
+    use solana_program::clock::Clock;
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, test_keypairs) = create_test_merkle_tree();
+    let claimant_keypair = &test_keypairs[0];
+    let claimant_node = merkle_tree.get_node_unchecked(&claimant_keypair.pubkey());
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let start_vesting_ts = current_time + 10;
+    let end_vesting_ts = current_time + 3600;
+    let clawback_start_ts = end_vesting_ts + 86400;
+
+    let (mint, distributor_pda, distributor_token_account) = setup_funded_distributor(
+        &mut rpc,
+        &payer,
+        &merkle_tree,
+        start_vesting_ts,
+        end_vesting_ts,
+        clawback_start_ts,
+    )
+    .await;
+    let claimant_ata = new_claim(
+        &mut rpc,
+        &payer,
+        claimant_keypair,
+        &claimant_node,
+        &mint,
+        &distributor_pda,
+        &distributor_token_account,
+    )
+    .await;
+
+    // The claimant empties and closes its token account between claims
+    let burn_ix = spl_token::instruction::burn(
+        &spl_token::id(),
+        &claimant_ata,
+        &mint,
+        &claimant_keypair.pubkey(),
+        &[],
+        claimant_node.amount_unlocked(),
+    )
+    .unwrap();
+    let close_ix = spl_token::instruction::close_account(
+        &spl_token::id(),
+        &claimant_ata,
+        &claimant_keypair.pubkey(),
+        &claimant_keypair.pubkey(),
+        &[],
+    )
+    .unwrap();
+    send_transaction(&mut rpc, &[burn_ix, close_ix], &[claimant_keypair])
+        .await
+        .unwrap();
+    assert!(rpc.get_account(claimant_ata).await.unwrap().is_none());
+
+    let mut clock = rpc.context.get_sysvar::<Clock>();
+    clock.unix_timestamp = end_vesting_ts;
+    rpc.context.set_sysvar(&clock);
+
+    // What the CLI sends: the account is recreated ahead of claim_locked
+    let create_ata_ix = create_associated_token_account(
+        &claimant_keypair.pubkey(),
+        &claimant_keypair.pubkey(),
+        &mint,
+        &spl_token::id(),
     );
-    rpc.process_transaction(transaction).await?;
-    Ok(())
+    let claim_locked_ix = claim_locked_instruction(
+        &mut rpc,
+        claimant_keypair,
+        &distributor_pda,
+        &distributor_token_account,
+        &claimant_ata,
+    )
+    .await;
+    send_transaction(
+        &mut rpc,
+        &[create_ata_ix, claim_locked_ix],
+        &[claimant_keypair],
+    )
+    .await
+    .unwrap();
+
+    let claimant_token_account = rpc.get_account(claimant_ata).await.unwrap();
+    let claimant_token_data =
+        spl_token::state::Account::unpack(&claimant_token_account.unwrap().data).unwrap();
+    assert_eq!(claimant_token_data.amount, claimant_node.amount_locked());
 }
 
-fn create_distributor_instruction(
-    program_id: &solana_sdk::pubkey::Pubkey,
-    distributor_pda: &solana_sdk::pubkey::Pubkey,
-    admin: &solana_sdk::pubkey::Pubkey,
-    mint: &solana_sdk::pubkey::Pubkey,
-    token_vault: &solana_sdk::pubkey::Pubkey,
-    clawback_receiver: &solana_sdk::pubkey::Pubkey,
-    merkle_tree: &AirdropMerkleTree,
-    start_vesting_ts: i64,
-    end_vesting_ts: i64,
-    clawback_start_ts: i64,
-) -> solana_program::instruction::Instruction {
+#[tokio::test]
+async fn test_token_2022_mint_claims_and_claws_back() {
     use anchor_lang::{InstructionData, ToAccountMetas};
+    use merkle_distributor::ID as PROGRAM_ID;
+    use solana_program::clock::Clock;
+    use spl_token_2022::{extension::StateWithExtensions, state::Account as TokenAccount};
 
-    solana_program::instruction::Instruction {
-        program_id: *program_id,
-        accounts: merkle_distributor::accounts::NewDistributor {
-            distributor: *distributor_pda,
-            admin: *admin,
-            mint: *mint,
-            token_vault: *token_vault,
-            clawback_receiver: *clawback_receiver,
-            system_program: solana_program::system_program::ID,
-            token_program: spl_token::id(),
-            associated_token_program: spl_associated_token_account::id(),
-        }
-        .to_account_metas(None),
-        data: merkle_distributor::instruction::NewDistributor {
-            version: 0,
-            root: merkle_tree.merkle_root,
-            max_total_claim: merkle_tree.max_total_claim,
-            max_num_nodes: merkle_tree.max_num_nodes,
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, test_keypairs) = create_test_merkle_tree();
+    let claimant_keypair = &test_keypairs[0];
+    let claimant_node = merkle_tree.get_node_unchecked(&claimant_keypair.pubkey());
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let start_vesting_ts = current_time + 10;
+    let end_vesting_ts = current_time + 3600;
+    let clawback_start_ts = end_vesting_ts + 86400;
+
+    let (mint, distributor_pda, distributor_token_account) =
+        setup_funded_distributor_with_token_program(
+            &mut rpc,
+            &payer,
+            &merkle_tree,
             start_vesting_ts,
             end_vesting_ts,
             clawback_start_ts,
-        }
-        .data(),
-    }
-}
+            &spl_token_2022::id(),
+        )
+        .await;
+    let claimant_ata = new_claim(
+        &mut rpc,
+        &payer,
+        claimant_keypair,
+        &claimant_node,
+        &mint,
+        &distributor_pda,
+        &distributor_token_account,
+    )
+    .await;
 
-fn create_new_claim_instruction(
-    program_id: &solana_sdk::pubkey::Pubkey,
-    distributor_pda: &solana_sdk::pubkey::Pubkey,
-    from: &solana_sdk::pubkey::Pubkey,
-    to: &solana_sdk::pubkey::Pubkey,
-    claimant: &solana_sdk::pubkey::Pubkey,
-    packed_account_metas: Vec<solana_program::instruction::AccountMeta>,
-    claimant_node: &jito_merkle_tree::tree_node::TreeNode,
-    validity_proof: light_sdk::instruction::ValidityProof,
-    address_tree_info: light_sdk::instruction::PackedAddressTreeInfo,
-    output_state_tree_index: u8,
-) -> solana_program::instruction::Instruction {
-    use anchor_lang::{InstructionData, ToAccountMetas};
+    let claimant_account = rpc.get_account(claimant_ata).await.unwrap().unwrap();
+    assert_eq!(claimant_account.owner, spl_token_2022::id());
+    let claimant_data = StateWithExtensions::<TokenAccount>::unpack(&claimant_account.data)
+        .unwrap()
+        .base;
+    assert_eq!(claimant_data.amount, claimant_node.amount_unlocked());
 
-    solana_program::instruction::Instruction {
-        program_id: *program_id,
-        accounts: [
-            merkle_distributor::accounts::NewClaim {
-                distributor: *distributor_pda,
-                from: *from,
-                to: *to,
-                claimant: *claimant,
-                token_program: spl_token::id(),
-            }
-            .to_account_metas(None),
-            packed_account_metas,
-        ]
-        .concat(),
-        data: merkle_distributor::instruction::NewClaim {
-            amount_unlocked: claimant_node.amount_unlocked(),
-            amount_locked: claimant_node.amount_locked(),
-            proof: claimant_node.proof.clone().expect("proof not found"),
-            validity_proof,
-            address_tree_info,
-            output_state_tree_index,
+    let mut clock = rpc.context.get_sysvar::<Clock>();
+    clock.unix_timestamp = end_vesting_ts;
+    rpc.context.set_sysvar(&clock);
+
+    claim_locked(
+        &mut rpc,
+        claimant_keypair,
+        &distributor_pda,
+        &distributor_token_account,
+        &claimant_ata,
+    )
+    .await;
+
+    let claimant_account = rpc.get_account(claimant_ata).await.unwrap().unwrap();
+    let claimant_data = StateWithExtensions::<TokenAccount>::unpack(&claimant_account.data)
+        .unwrap()
+        .base;
+    assert_eq!(claimant_data.amount, claimant_node.total_amount());
+
+    // The other claimant's allocation is clawed back through the Token-2022 program too
+    let mut clock = rpc.context.get_sysvar::<Clock>();
+    clock.unix_timestamp = clawback_start_ts + 1;
+    rpc.context.set_sysvar(&clock);
+
+    let clawback_token_account =
+        get_associated_token_address_with_program_id(&payer.pubkey(), &mint, &spl_token_2022::id());
+    let clawback_ix = solana_program::instruction::Instruction {
+        program_id: PROGRAM_ID,
+        accounts: merkle_distributor::accounts::Clawback {
+            distributor: distributor_pda,
+            from: distributor_token_account,
+            to: clawback_token_account,
+            claimant: payer.pubkey(),
+            system_program: solana_program::system_program::ID,
+            mint,
+            token_program: spl_token_2022::id(),
         }
-        .data(),
-    }
+        .to_account_metas(None),
+        data: merkle_distributor::instruction::Clawback {}.data(),
+    };
+    send_transaction(&mut rpc, &[clawback_ix], &[&payer])
+        .await
+        .unwrap();
+
+    let clawback_account = rpc
+        .get_account(clawback_token_account)
+        .await
+        .unwrap()
+        .unwrap();
+    let clawback_data = StateWithExtensions::<TokenAccount>::unpack(&clawback_account.data)
+        .unwrap()
+        .base;
+    assert_eq!(
+        clawback_data.amount,
+        merkle_tree.max_total_claim - claimant_node.total_amount()
+    );
+}
+
+#[tokio::test]
+async fn test_transfer_fee_mint_delivers_full_allocation() {
+    use anchor_lang::AccountDeserialize;
+    use merkle_distributor::{state::merkle_distributor::MerkleDistributor, ID as PROGRAM_ID};
+    use solana_program::clock::Clock;
+    use spl_token_2022::{
+        extension::{
+            transfer_fee::TransferFeeAmount, BaseStateWithExtensions, StateWithExtensions,
+        },
+        state::Account as TokenAccount,
+    };
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, test_keypairs) = create_test_merkle_tree();
+    let claimant_keypair = &test_keypairs[0];
+    let claimant_node = merkle_tree.get_node_unchecked(&claimant_keypair.pubkey());
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let start_vesting_ts = current_time + 10;
+    let end_vesting_ts = current_time + 3600;
+    let clawback_start_ts = end_vesting_ts + 86400;
+
+    // 1% transfer fee
+    let mint = create_mint(&mut rpc, &payer, &spl_token_2022::id(), Some(100)).await;
+    let (distributor_pda, distributor_token_account) = setup_funded_distributor_with_mint(
+        &mut rpc,
+        &payer,
+        &merkle_tree,
+        start_vesting_ts,
+        end_vesting_ts,
+        clawback_start_ts,
+        0,
+        false,
+        &mint,
+    )
+    .await;
+    // The vault pays the fees on top of the allocations
+    let top_up_ix = spl_token_2022::instruction::mint_to(
+        &spl_token_2022::id(),
+        &mint,
+        &distributor_token_account,
+        &payer.pubkey(),
+        &[],
+        merkle_tree.max_total_claim / 10,
+    )
+    .unwrap();
+    send_transaction(&mut rpc, &[top_up_ix], &[&payer])
+        .await
+        .unwrap();
+
+    let claimant_ata = new_claim(
+        &mut rpc,
+        &payer,
+        claimant_keypair,
+        &claimant_node,
+        &mint,
+        &distributor_pda,
+        &distributor_token_account,
+    )
+    .await;
+
+    let claimant_account = rpc.get_account(claimant_ata).await.unwrap().unwrap();
+    let claimant_state =
+        StateWithExtensions::<TokenAccount>::unpack(&claimant_account.data).unwrap();
+    assert_eq!(claimant_state.base.amount, claimant_node.amount_unlocked());
+
+    let mut clock = rpc.context.get_sysvar::<Clock>();
+    clock.unix_timestamp = end_vesting_ts;
+    rpc.context.set_sysvar(&clock);
+
+    claim_locked(
+        &mut rpc,
+        claimant_keypair,
+        &distributor_pda,
+        &distributor_token_account,
+        &claimant_ata,
+    )
+    .await;
+
+    let claimant_account = rpc.get_account(claimant_ata).await.unwrap().unwrap();
+    let claimant_state =
+        StateWithExtensions::<TokenAccount>::unpack(&claimant_account.data).unwrap();
+    assert_eq!(claimant_state.base.amount, claimant_node.total_amount());
+    let withheld = claimant_state
+        .get_extension::<TransferFeeAmount>()
+        .unwrap()
+        .withheld_amount;
+    assert!(u64::from(withheld) > 0);
+
+    // Claims are accounted in allocation terms, excluding the fees
+    let distributor_account = rpc.get_account(distributor_pda).await.unwrap().unwrap();
+    let distributor =
+        MerkleDistributor::try_deserialize(&mut distributor_account.data.as_slice()).unwrap();
+    assert_eq!(distributor.total_amount_claimed, claimant_node.total_amount());
+}
+
+#[tokio::test]
+async fn test_claim_locked_enforces_min_claim_interval() {
+    use anchor_lang::AnchorDeserialize;
+    use merkle_distributor::{
+        error::ErrorCode, state::claim_status::ClaimStatus, ID as PROGRAM_ID,
+    };
+    use solana_program::clock::Clock;
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, test_keypairs) = create_test_merkle_tree();
+    let claimant_keypair = &test_keypairs[0];
+    let claimant_node = merkle_tree.get_node_unchecked(&claimant_keypair.pubkey());
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let start_vesting_ts = current_time + 10;
+    let end_vesting_ts = current_time + 3600;
+    let clawback_start_ts = end_vesting_ts + 86400;
+    let min_claim_interval = 600;
+
+    let mint = create_mint(&mut rpc, &payer, &spl_token::id(), None).await;
+    let (distributor_pda, distributor_token_account) = setup_funded_distributor_with_mint(
+        &mut rpc,
+        &payer,
+        &merkle_tree,
+        start_vesting_ts,
+        end_vesting_ts,
+        clawback_start_ts,
+        min_claim_interval,
+        false,
+        &mint,
+    )
+    .await;
+    let claimant_ata = new_claim(
+        &mut rpc,
+        &payer,
+        claimant_keypair,
+        &claimant_node,
+        &mint,
+        &distributor_pda,
+        &distributor_token_account,
+    )
+    .await;
+
+    // The first locked withdrawal is not rate limited
+    let mut clock = rpc.context.get_sysvar::<Clock>();
+    clock.unix_timestamp = start_vesting_ts + 1000;
+    rpc.context.set_sysvar(&clock);
+    claim_locked(
+        &mut rpc,
+        claimant_keypair,
+        &distributor_pda,
+        &distributor_token_account,
+        &claimant_ata,
+    )
+    .await;
+
+    let (claim_status_address, _) = get_claim_status_pda(
+        &PROGRAM_ID,
+        &claimant_keypair.pubkey(),
+        &distributor_pda,
+    );
+    let claim_status_account = rpc
+        .get_compressed_account(claim_status_address, None)
+        .await
+        .unwrap()
+        .value
+        .expect("Claim status account not found");
+    let claim_status =
+        ClaimStatus::deserialize(&mut claim_status_account.data.as_ref().unwrap().data.as_slice())
+            .unwrap();
+    assert_eq!(claim_status.last_claimed_ts, start_vesting_ts + 1000);
+
+    // A second withdrawal inside the interval is rejected
+    clock.unix_timestamp = start_vesting_ts + 1100;
+    rpc.context.set_sysvar(&clock);
+    let claim_locked_ix = claim_locked_instruction(
+        &mut rpc,
+        claimant_keypair,
+        &distributor_pda,
+        &distributor_token_account,
+        &claimant_ata,
+    )
+    .await;
+    let err = send_transaction(&mut rpc, &[claim_locked_ix], &[claimant_keypair])
+        .await
+        .unwrap_err();
+    let claim_too_soon = u32::from(ErrorCode::ClaimTooSoon);
+    assert!(
+        format!("{err:?}").contains(&format!("Custom({claim_too_soon})")),
+        "expected ClaimTooSoon, got {err:?}"
+    );
+
+    // Once the interval has elapsed the claimant can withdraw again
+    clock.unix_timestamp = start_vesting_ts + 1000 + min_claim_interval;
+    rpc.context.set_sysvar(&clock);
+    claim_locked(
+        &mut rpc,
+        claimant_keypair,
+        &distributor_pda,
+        &distributor_token_account,
+        &claimant_ata,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_claim_locked_with_missing_light_accounts_returns_light_cpi_failed() {
+    use merkle_distributor::{error::ErrorCode, ID as PROGRAM_ID};
+    use solana_program::clock::Clock;
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, test_keypairs) = create_test_merkle_tree();
+    let claimant_keypair = &test_keypairs[0];
+    let claimant_node = merkle_tree.get_node_unchecked(&claimant_keypair.pubkey());
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let start_vesting_ts = current_time + 10;
+    let end_vesting_ts = current_time + 3600;
+    let clawback_start_ts = end_vesting_ts + 86400;
+
+    let (mint, distributor_pda, distributor_token_account) = setup_funded_distributor(
+        &mut rpc,
+        &payer,
+        &merkle_tree,
+        start_vesting_ts,
+        end_vesting_ts,
+        clawback_start_ts,
+    )
+    .await;
+    let claimant_ata = new_claim(
+        &mut rpc,
+        &payer,
+        claimant_keypair,
+        &claimant_node,
+        &mint,
+        &distributor_pda,
+        &distributor_token_account,
+    )
+    .await;
+
+    let mut clock = rpc.context.get_sysvar::<Clock>();
+    clock.unix_timestamp = end_vesting_ts;
+    rpc.context.set_sysvar(&clock);
+
+    let mut claim_locked_ix = claim_locked_instruction(
+        &mut rpc,
+        claimant_keypair,
+        &distributor_pda,
+        &distributor_token_account,
+        &claimant_ata,
+    )
+    .await;
+    // Keep the 6 ClaimLocked accounts and only the first Light system account
+    claim_locked_ix.accounts.truncate(7);
+
+    let err = send_transaction(&mut rpc, &[claim_locked_ix], &[claimant_keypair])
+        .await
+        .unwrap_err();
+    let light_cpi_failed = u32::from(ErrorCode::LightCpiFailed);
+    assert!(
+        format!("{err:?}").contains(&format!("Custom({light_cpi_failed})")),
+        "expected LightCpiFailed, got {err:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_new_claim_with_out_of_range_tree_index_returns_invalid_account_layout() {
+    use merkle_distributor::{error::ErrorCode, ID as PROGRAM_ID};
+    use solana_program::clock::Clock;
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, test_keypairs) = create_test_merkle_tree();
+    let claimant_keypair = &test_keypairs[0];
+    let claimant_node = merkle_tree.get_node_unchecked(&claimant_keypair.pubkey());
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let start_vesting_ts = current_time + 10;
+    let end_vesting_ts = current_time + 3600;
+    let clawback_start_ts = end_vesting_ts + 86400;
+
+    let (mint, distributor_pda, distributor_token_account) = setup_funded_distributor(
+        &mut rpc,
+        &payer,
+        &merkle_tree,
+        start_vesting_ts,
+        end_vesting_ts,
+        clawback_start_ts,
+    )
+    .await;
+
+    let claimant_ata = get_associated_token_address(&claimant_keypair.pubkey(), &mint);
+    let create_claimant_ata_ix = create_associated_token_account(
+        &payer.pubkey(),
+        &claimant_keypair.pubkey(),
+        &mint,
+        &spl_token::id(),
+    );
+    send_transaction(&mut rpc, &[create_claimant_ata_ix], &[&payer])
+        .await
+        .unwrap();
+
+    let (packed_account_metas, proof, mut address_tree_info, output_state_tree_index) =
+        new_claim_light_inputs(&mut rpc, &claimant_keypair.pubkey(), &distributor_pda).await;
+    address_tree_info.address_merkle_tree_pubkey_index = u8::MAX;
+
+    let new_claim_ix = create_new_claim_instruction(
+        &PROGRAM_ID,
+        &distributor_pda,
+        &distributor_token_account,
+        &claimant_ata,
+        &claimant_keypair.pubkey(),
+        &mint,
+        &spl_token::id(),
+        packed_account_metas,
+        &claimant_node,
+        proof,
+        address_tree_info,
+        output_state_tree_index,
+    );
+    let err = send_transaction(&mut rpc, &[new_claim_ix], &[&payer, claimant_keypair])
+        .await
+        .unwrap_err();
+    let invalid_account_layout = u32::from(ErrorCode::InvalidAccountLayout);
+    assert!(
+        format!("{err:?}").contains(&format!("Custom({invalid_account_layout})")),
+        "expected InvalidAccountLayout, got {err:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_clawback_drains_vault_and_blocks_new_claims() {
+    use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+    use merkle_distributor::{
+        error::ErrorCode, state::merkle_distributor::MerkleDistributor, ID as PROGRAM_ID,
+    };
+    use solana_program::clock::Clock;
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, test_keypairs) = create_test_merkle_tree();
+    let claimant_keypair = &test_keypairs[0];
+    let claimant_node = merkle_tree.get_node_unchecked(&claimant_keypair.pubkey());
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let start_vesting_ts = current_time + 10;
+    let end_vesting_ts = current_time + 3600;
+    let clawback_start_ts = end_vesting_ts + 86400;
+
+    let (mint, distributor_pda, distributor_token_account) = setup_funded_distributor(
+        &mut rpc,
+        &payer,
+        &merkle_tree,
+        start_vesting_ts,
+        end_vesting_ts,
+        clawback_start_ts,
+    )
+    .await;
+    // setup_funded_distributor uses the payer's ATA as clawback receiver
+    let clawback_token_account = get_associated_token_address(&payer.pubkey(), &mint);
+
+    let mut clock = rpc.context.get_sysvar::<Clock>();
+    clock.unix_timestamp = clawback_start_ts + 1;
+    rpc.context.set_sysvar(&clock);
+
+    let clawback_ix = solana_program::instruction::Instruction {
+        program_id: PROGRAM_ID,
+        accounts: merkle_distributor::accounts::Clawback {
+            distributor: distributor_pda,
+            from: distributor_token_account,
+            to: clawback_token_account,
+            claimant: payer.pubkey(),
+            system_program: solana_program::system_program::ID,
+            mint,
+            token_program: spl_token::id(),
+        }
+        .to_account_metas(None),
+        data: merkle_distributor::instruction::Clawback {}.data(),
+    };
+    send_transaction(&mut rpc, &[clawback_ix], &[&payer])
+        .await
+        .unwrap();
+
+    let vault_account = rpc.get_account(distributor_token_account).await.unwrap();
+    let vault_data = spl_token::state::Account::unpack(&vault_account.unwrap().data).unwrap();
+    assert_eq!(vault_data.amount, 0);
+
+    let clawback_account = rpc.get_account(clawback_token_account).await.unwrap();
+    let clawback_data = spl_token::state::Account::unpack(&clawback_account.unwrap().data).unwrap();
+    assert_eq!(clawback_data.amount, merkle_tree.max_total_claim);
+
+    let distributor_account = rpc.get_account(distributor_pda).await.unwrap().unwrap();
+    let distributor =
+        MerkleDistributor::try_deserialize(&mut distributor_account.data.as_slice()).unwrap();
+    assert!(distributor.clawed_back);
+
+    let err = try_new_claim(
+        &mut rpc,
+        &payer,
+        claimant_keypair,
+        &claimant_node,
+        &mint,
+        &distributor_pda,
+        &distributor_token_account,
+    )
+    .await
+    .unwrap_err();
+    let claim_expired = u32::from(ErrorCode::ClaimExpired);
+    assert!(
+        format!("{err:?}").contains(&format!("Custom({claim_expired})")),
+        "expected ClaimExpired, got {err:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_partial_clawback_keeps_unclaimed_allocations() {
+    use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+    use merkle_distributor::{
+        error::ErrorCode, state::merkle_distributor::MerkleDistributor, ID as PROGRAM_ID,
+    };
+    use solana_program::clock::Clock;
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, test_keypairs) = create_test_merkle_tree();
+    let claimant_keypair = &test_keypairs[0];
+    let claimant_node = merkle_tree.get_node_unchecked(&claimant_keypair.pubkey());
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let start_vesting_ts = current_time + 10;
+    let end_vesting_ts = current_time + 3600;
+    let clawback_start_ts = end_vesting_ts + 86400;
+
+    let (mint, distributor_pda, distributor_token_account) = setup_funded_distributor(
+        &mut rpc,
+        &payer,
+        &merkle_tree,
+        start_vesting_ts,
+        end_vesting_ts,
+        clawback_start_ts,
+    )
+    .await;
+    // setup_funded_distributor uses the payer's ATA as clawback receiver
+    let clawback_token_account = get_associated_token_address(&payer.pubkey(), &mint);
+    let partial_clawback_ix = |amount: u64| solana_program::instruction::Instruction {
+        program_id: PROGRAM_ID,
+        accounts: merkle_distributor::accounts::PartialClawback {
+            distributor: distributor_pda,
+            from: distributor_token_account,
+            to: clawback_token_account,
+            claimant: payer.pubkey(),
+            system_program: solana_program::system_program::ID,
+            mint,
+            token_program: spl_token::id(),
+        }
+        .to_account_metas(None),
+        data: merkle_distributor::instruction::PartialClawback { amount }.data(),
+    };
+
+    new_claim(
+        &mut rpc,
+        &payer,
+        claimant_keypair,
+        &claimant_node,
+        &mint,
+        &distributor_pda,
+        &distributor_token_account,
+    )
+    .await;
+    // Everyone else's allocation plus the claimant's locked tokens
+    let obligation = merkle_tree.max_total_claim - claimant_node.amount_unlocked();
+    let surplus = 500;
+    set_token_account_amount(&mut rpc, &distributor_token_account, obligation + surplus).await;
+
+    let err = send_transaction(&mut rpc, &[partial_clawback_ix(surplus)], &[&payer])
+        .await
+        .unwrap_err();
+    let clawback_before_start = u32::from(ErrorCode::ClawbackBeforeStart);
+    assert!(
+        format!("{err:?}").contains(&format!("Custom({clawback_before_start})")),
+        "expected ClawbackBeforeStart, got {err:?}"
+    );
+
+    let mut clock = rpc.context.get_sysvar::<Clock>();
+    clock.unix_timestamp = clawback_start_ts + 1;
+    rpc.context.set_sysvar(&clock);
+
+    // One token more than the surplus would dip into the unclaimed allocations
+    let err = send_transaction(&mut rpc, &[partial_clawback_ix(surplus + 1)], &[&payer])
+        .await
+        .unwrap_err();
+    let clawback_exceeds_surplus = u32::from(ErrorCode::ClawbackExceedsSurplus);
+    assert!(
+        format!("{err:?}").contains(&format!("Custom({clawback_exceeds_surplus})")),
+        "expected ClawbackExceedsSurplus, got {err:?}"
+    );
+
+    let clawback_account = rpc.get_account(clawback_token_account).await.unwrap();
+    let receiver_before = spl_token::state::Account::unpack(&clawback_account.unwrap().data)
+        .unwrap()
+        .amount;
+    send_transaction(&mut rpc, &[partial_clawback_ix(surplus)], &[&payer])
+        .await
+        .unwrap();
+
+    let vault_account = rpc.get_account(distributor_token_account).await.unwrap();
+    let vault_data = spl_token::state::Account::unpack(&vault_account.unwrap().data).unwrap();
+    assert_eq!(vault_data.amount, obligation);
+    let clawback_account = rpc.get_account(clawback_token_account).await.unwrap();
+    let clawback_data = spl_token::state::Account::unpack(&clawback_account.unwrap().data).unwrap();
+    assert_eq!(clawback_data.amount, receiver_before + surplus);
+
+    // Claims stay open, and nothing is left above the obligation
+    let distributor_account = rpc.get_account(distributor_pda).await.unwrap().unwrap();
+    let distributor =
+        MerkleDistributor::try_deserialize(&mut distributor_account.data.as_slice()).unwrap();
+    assert!(!distributor.clawed_back);
+    let err = send_transaction(&mut rpc, &[partial_clawback_ix(1)], &[&payer])
+        .await
+        .unwrap_err();
+    assert!(
+        format!("{err:?}").contains(&format!("Custom({clawback_exceeds_surplus})")),
+        "expected ClawbackExceedsSurplus, got {err:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_partial_clawback_floor_excludes_capped_transfer_fees() {
+    use anchor_lang::{InstructionData, ToAccountMetas};
+    use merkle_distributor::{error::ErrorCode, ID as PROGRAM_ID};
+    use solana_program::clock::Clock;
+    use spl_token_2022::{extension::StateWithExtensions, state::Account as TokenAccount};
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, _test_keypairs) = create_test_merkle_tree();
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let start_vesting_ts = current_time + 10;
+    let end_vesting_ts = current_time + 3600;
+    let clawback_start_ts = end_vesting_ts + 86400;
+
+    // 1% transfer fee, capped at 5 per transfer
+    let maximum_fee = 5;
+    let mint =
+        create_mint_with_fee_cap(&mut rpc, &payer, &spl_token_2022::id(), 100, maximum_fee).await;
+    let (distributor_pda, distributor_token_account) = setup_funded_distributor_with_mint(
+        &mut rpc,
+        &payer,
+        &merkle_tree,
+        start_vesting_ts,
+        end_vesting_ts,
+        clawback_start_ts,
+        0,
+        false,
+        &mint,
+    )
+    .await;
+    let clawback_token_account =
+        get_associated_token_address_with_program_id(&payer.pubkey(), &mint, &spl_token_2022::id());
+    let partial_clawback_ix = |amount: u64| solana_program::instruction::Instruction {
+        program_id: PROGRAM_ID,
+        accounts: merkle_distributor::accounts::PartialClawback {
+            distributor: distributor_pda,
+            from: distributor_token_account,
+            to: clawback_token_account,
+            claimant: payer.pubkey(),
+            system_program: solana_program::system_program::ID,
+            mint,
+            token_program: spl_token_2022::id(),
+        }
+        .to_account_metas(None),
+        data: merkle_distributor::instruction::PartialClawback { amount }.data(),
+    };
+
+    // Every unclaimed node pays a capped fee on new_claim and on at least one claim_locked
+    let fee_reserve = maximum_fee * 2 * merkle_tree.max_num_nodes;
+    let top_up_ix = spl_token_2022::instruction::mint_to(
+        &spl_token_2022::id(),
+        &mint,
+        &distributor_token_account,
+        &payer.pubkey(),
+        &[],
+        fee_reserve,
+    )
+    .unwrap();
+    send_transaction(&mut rpc, &[top_up_ix], &[&payer])
+        .await
+        .unwrap();
+
+    let mut clock = rpc.context.get_sysvar::<Clock>();
+    clock.unix_timestamp = clawback_start_ts + 1;
+    rpc.context.set_sysvar(&clock);
+
+    let err = send_transaction(&mut rpc, &[partial_clawback_ix(fee_reserve + 1)], &[&payer])
+        .await
+        .unwrap_err();
+    let clawback_exceeds_surplus = u32::from(ErrorCode::ClawbackExceedsSurplus);
+    assert!(
+        format!("{err:?}").contains(&format!("Custom({clawback_exceeds_surplus})")),
+        "expected ClawbackExceedsSurplus, got {err:?}"
+    );
+
+    // The floor is the unclaimed allocations alone, so the fee reserve can be taken as well:
+    // whoever funds a fee-bearing mint has to leave the fees in the vault
+    send_transaction(&mut rpc, &[partial_clawback_ix(fee_reserve)], &[&payer])
+        .await
+        .unwrap();
+
+    let vault_account = rpc
+        .get_account(distributor_token_account)
+        .await
+        .unwrap()
+        .unwrap();
+    let vault_state = StateWithExtensions::<TokenAccount>::unpack(&vault_account.data).unwrap();
+    assert_eq!(vault_state.base.amount, merkle_tree.max_total_claim);
+}
+
+#[tokio::test]
+async fn test_underfunded_vault_returns_insufficient_vault_balance() {
+    use merkle_distributor::{error::ErrorCode, ID as PROGRAM_ID};
+    use solana_program::clock::Clock;
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, test_keypairs) = create_test_merkle_tree();
+    let claimant_keypair = &test_keypairs[0];
+    let claimant_node = merkle_tree.get_node_unchecked(&claimant_keypair.pubkey());
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let start_vesting_ts = current_time + 10;
+    let end_vesting_ts = current_time + 3600;
+    let clawback_start_ts = end_vesting_ts + 86400;
+
+    let (mint, distributor_pda, distributor_token_account) = setup_funded_distributor(
+        &mut rpc,
+        &payer,
+        &merkle_tree,
+        start_vesting_ts,
+        end_vesting_ts,
+        clawback_start_ts,
+    )
+    .await;
+    let insufficient_vault_balance = u32::from(ErrorCode::InsufficientVaultBalance);
+
+    // One token short of the unlocked amount
+    set_token_account_amount(
+        &mut rpc,
+        &distributor_token_account,
+        claimant_node.amount_unlocked() - 1,
+    )
+    .await;
+    let err = try_new_claim(
+        &mut rpc,
+        &payer,
+        claimant_keypair,
+        &claimant_node,
+        &mint,
+        &distributor_pda,
+        &distributor_token_account,
+    )
+    .await
+    .unwrap_err();
+    assert!(
+        format!("{err:?}").contains(&format!("Custom({insufficient_vault_balance})")),
+        "expected InsufficientVaultBalance, got {err:?}"
+    );
+
+    // Exactly the unlocked amount, which leaves nothing for the locked tokens
+    set_token_account_amount(
+        &mut rpc,
+        &distributor_token_account,
+        claimant_node.amount_unlocked(),
+    )
+    .await;
+    let claimant_ata = new_claim(
+        &mut rpc,
+        &payer,
+        claimant_keypair,
+        &claimant_node,
+        &mint,
+        &distributor_pda,
+        &distributor_token_account,
+    )
+    .await;
+
+    let mut clock = rpc.context.get_sysvar::<Clock>();
+    clock.unix_timestamp = start_vesting_ts + 1000;
+    rpc.context.set_sysvar(&clock);
+    let claim_locked_ix = claim_locked_instruction(
+        &mut rpc,
+        claimant_keypair,
+        &distributor_pda,
+        &distributor_token_account,
+        &claimant_ata,
+    )
+    .await;
+    let err = send_transaction(&mut rpc, &[claim_locked_ix], &[claimant_keypair])
+        .await
+        .unwrap_err();
+    assert!(
+        format!("{err:?}").contains(&format!("Custom({insufficient_vault_balance})")),
+        "expected InsufficientVaultBalance, got {err:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_frozen_recipient_returns_recipient_account_frozen() {
+    use merkle_distributor::{error::ErrorCode, ID as PROGRAM_ID};
+    use solana_program::clock::Clock;
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, test_keypairs) = create_test_merkle_tree();
+    let claimant_keypair = &test_keypairs[0];
+    let claimant_node = merkle_tree.get_node_unchecked(&claimant_keypair.pubkey());
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let end_vesting_ts = current_time + 3600;
+    let (mint, distributor_pda, distributor_token_account) = setup_funded_distributor(
+        &mut rpc,
+        &payer,
+        &merkle_tree,
+        current_time + 10,
+        end_vesting_ts,
+        end_vesting_ts + 86400,
+    )
+    .await;
+    let claimant_ata = new_claim(
+        &mut rpc,
+        &payer,
+        claimant_keypair,
+        &claimant_node,
+        &mint,
+        &distributor_pda,
+        &distributor_token_account,
+    )
+    .await;
+
+    // The payer is the mint's freeze authority
+    let freeze_ix = spl_token::instruction::freeze_account(
+        &spl_token::id(),
+        &claimant_ata,
+        &mint,
+        &payer.pubkey(),
+        &[],
+    )
+    .unwrap();
+    send_transaction(&mut rpc, &[freeze_ix], &[&payer])
+        .await
+        .unwrap();
+
+    let mut clock = rpc.context.get_sysvar::<Clock>();
+    clock.unix_timestamp = end_vesting_ts;
+    rpc.context.set_sysvar(&clock);
+    let claim_locked_ix = claim_locked_instruction(
+        &mut rpc,
+        claimant_keypair,
+        &distributor_pda,
+        &distributor_token_account,
+        &claimant_ata,
+    )
+    .await;
+    let err = send_transaction(&mut rpc, &[claim_locked_ix], &[claimant_keypair])
+        .await
+        .unwrap_err();
+    let recipient_account_frozen = u32::from(ErrorCode::RecipientAccountFrozen);
+    assert!(
+        format!("{err:?}").contains(&format!("Custom({recipient_account_frozen})")),
+        "expected RecipientAccountFrozen, got {err:?}"
+    );
+
+    // Once thawed the locked tokens arrive
+    let thaw_ix = spl_token::instruction::thaw_account(
+        &spl_token::id(),
+        &claimant_ata,
+        &mint,
+        &payer.pubkey(),
+        &[],
+    )
+    .unwrap();
+    send_transaction(&mut rpc, &[thaw_ix], &[&payer])
+        .await
+        .unwrap();
+    claim_locked(
+        &mut rpc,
+        claimant_keypair,
+        &distributor_pda,
+        &distributor_token_account,
+        &claimant_ata,
+    )
+    .await;
+    let claimant_token_account = rpc.get_account(claimant_ata).await.unwrap();
+    let claimant_token_data =
+        spl_token::state::Account::unpack(&claimant_token_account.unwrap().data).unwrap();
+    assert_eq!(claimant_token_data.amount, claimant_node.total_amount());
+}
+
+#[tokio::test]
+async fn test_new_claim_with_invalid_proof_is_not_counted() {
+    use anchor_lang::AccountDeserialize;
+    use merkle_distributor::{
+        error::ErrorCode, state::merkle_distributor::MerkleDistributor, ID as PROGRAM_ID,
+    };
+    use solana_program::clock::Clock;
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, test_keypairs) = create_test_merkle_tree();
+    let claimant_keypair = &test_keypairs[0];
+    let claimant_node = merkle_tree.get_node_unchecked(&claimant_keypair.pubkey());
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let end_vesting_ts = current_time + 3600;
+    let (mint, distributor_pda, distributor_token_account) = setup_funded_distributor(
+        &mut rpc,
+        &payer,
+        &merkle_tree,
+        current_time + 10,
+        end_vesting_ts,
+        end_vesting_ts + 86400,
+    )
+    .await;
+
+    // One token more than the leaf commits to
+    let mut inflated_node = claimant_node.clone();
+    inflated_node.total_unlocked_staker += 1;
+    let err = try_new_claim(
+        &mut rpc,
+        &payer,
+        claimant_keypair,
+        &inflated_node,
+        &mint,
+        &distributor_pda,
+        &distributor_token_account,
+    )
+    .await
+    .unwrap_err();
+    let invalid_proof = u32::from(ErrorCode::InvalidProof);
+    assert!(
+        format!("{err:?}").contains(&format!("Custom({invalid_proof})")),
+        "expected InvalidProof, got {err:?}"
+    );
+
+    let distributor_account = rpc.get_account(distributor_pda).await.unwrap().unwrap();
+    let distributor =
+        MerkleDistributor::try_deserialize(&mut distributor_account.data.as_slice()).unwrap();
+    assert_eq!(distributor.num_nodes_claimed, 0);
+    assert_eq!(distributor.total_amount_claimed, 0);
+    let (claim_status_address, _) = get_claim_status_pda(
+        &PROGRAM_ID,
+        &claimant_keypair.pubkey(),
+        &distributor_pda,
+    );
+    // The indexer may answer a missing address with an error or with no account
+    let claim_status_account = rpc.get_compressed_account(claim_status_address, None).await;
+    assert!(
+        !matches!(&claim_status_account, Ok(response) if response.value.is_some()),
+        "no claim status should exist, got {claim_status_account:?}"
+    );
+
+    // The real allocation can still be claimed, and is counted once
+    new_claim(
+        &mut rpc,
+        &payer,
+        claimant_keypair,
+        &claimant_node,
+        &mint,
+        &distributor_pda,
+        &distributor_token_account,
+    )
+    .await;
+    let distributor_account = rpc.get_account(distributor_pda).await.unwrap().unwrap();
+    let distributor =
+        MerkleDistributor::try_deserialize(&mut distributor_account.data.as_slice()).unwrap();
+    assert_eq!(distributor.num_nodes_claimed, 1);
+}
+
+#[tokio::test]
+async fn test_new_claim_with_wrong_proof_length() {
+    use anchor_lang::AccountDeserialize;
+    use merkle_distributor::{
+        error::ErrorCode, state::merkle_distributor::MerkleDistributor, ID as PROGRAM_ID,
+    };
+    use solana_program::clock::Clock;
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, test_keypairs) = create_test_merkle_tree();
+    let claimant_keypair = &test_keypairs[0];
+    let claimant_node = merkle_tree.get_node_unchecked(&claimant_keypair.pubkey());
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let end_vesting_ts = current_time + 3600;
+    let (mint, distributor_pda, distributor_token_account) = setup_funded_distributor(
+        &mut rpc,
+        &payer,
+        &merkle_tree,
+        current_time + 10,
+        end_vesting_ts,
+        end_vesting_ts + 86400,
+    )
+    .await;
+
+    let distributor_account = rpc.get_account(distributor_pda).await.unwrap().unwrap();
+    let distributor =
+        MerkleDistributor::try_deserialize(&mut distributor_account.data.as_slice()).unwrap();
+    assert_eq!(distributor.tree_depth, merkle_tree.tree_depth());
+
+    let proof = claimant_node.proof.clone().unwrap();
+    // From a smaller tree, and from a larger one
+    let too_short = proof[..proof.len() - 1].to_vec();
+    let too_long = [proof.clone(), vec![[7; 32]]].concat();
+    let proof_length_mismatch = u32::from(ErrorCode::ProofLengthMismatch);
+    for wrong_proof in [too_short, too_long] {
+        let mut node = claimant_node.clone();
+        node.proof = Some(wrong_proof);
+        let err = try_new_claim(
+            &mut rpc,
+            &payer,
+            claimant_keypair,
+            &node,
+            &mint,
+            &distributor_pda,
+            &distributor_token_account,
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            format!("{err:?}").contains(&format!("Custom({proof_length_mismatch})")),
+            "expected ProofLengthMismatch, got {err:?}"
+        );
+    }
+
+    // A proof of the right length that does not verify is still InvalidProof
+    let mut node = claimant_node.clone();
+    node.proof = Some(vec![[7; 32]; proof.len()]);
+    let err = try_new_claim(
+        &mut rpc,
+        &payer,
+        claimant_keypair,
+        &node,
+        &mint,
+        &distributor_pda,
+        &distributor_token_account,
+    )
+    .await
+    .unwrap_err();
+    let invalid_proof = u32::from(ErrorCode::InvalidProof);
+    assert!(
+        format!("{err:?}").contains(&format!("Custom({invalid_proof})")),
+        "expected InvalidProof, got {err:?}"
+    );
+    let distributor_account = rpc.get_account(distributor_pda).await.unwrap().unwrap();
+    let distributor =
+        MerkleDistributor::try_deserialize(&mut distributor_account.data.as_slice()).unwrap();
+    assert_eq!(distributor.num_nodes_claimed, 0);
+
+    new_claim(
+        &mut rpc,
+        &payer,
+        claimant_keypair,
+        &claimant_node,
+        &mint,
+        &distributor_pda,
+        &distributor_token_account,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_close_distributor_after_clawback_returns_rent() {
+    use anchor_lang::{InstructionData, ToAccountMetas};
+    use merkle_distributor::{error::ErrorCode, ID as PROGRAM_ID};
+    use solana_program::clock::Clock;
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, _test_keypairs) = create_test_merkle_tree();
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let start_vesting_ts = current_time + 10;
+    let end_vesting_ts = current_time + 3600;
+    let clawback_start_ts = end_vesting_ts + 86400;
+
+    let (mint, distributor_pda, distributor_token_account) = setup_funded_distributor(
+        &mut rpc,
+        &payer,
+        &merkle_tree,
+        start_vesting_ts,
+        end_vesting_ts,
+        clawback_start_ts,
+    )
+    .await;
+
+    // A separate fee payer, so the admin's balance only changes by the reclaimed rent
+    let fee_payer = Keypair::new();
+    let fund_fee_payer_ix = solana_program::system_instruction::transfer(
+        &payer.pubkey(),
+        &fee_payer.pubkey(),
+        1_000_000_000,
+    );
+    send_transaction(&mut rpc, &[fund_fee_payer_ix], &[&payer])
+        .await
+        .unwrap();
+
+    let close_distributor_ix = solana_program::instruction::Instruction {
+        program_id: PROGRAM_ID,
+        accounts: merkle_distributor::accounts::CloseDistributor {
+            distributor: distributor_pda,
+            token_vault: distributor_token_account,
+            admin: payer.pubkey(),
+            token_program: spl_token::id(),
+        }
+        .to_account_metas(None),
+        data: merkle_distributor::instruction::CloseDistributor {}.data(),
+    };
+
+    // Still funded and not clawed back
+    let err = send_transaction(&mut rpc, &[close_distributor_ix.clone()], &[&payer])
+        .await
+        .unwrap_err();
+    let not_clawed_back = u32::from(ErrorCode::NotClawedBack);
+    assert!(
+        format!("{err:?}").contains(&format!("Custom({not_clawed_back})")),
+        "expected NotClawedBack, got {err:?}"
+    );
+
+    let mut clock = rpc.context.get_sysvar::<Clock>();
+    clock.unix_timestamp = clawback_start_ts + 1;
+    rpc.context.set_sysvar(&clock);
+
+    let clawback_ix = solana_program::instruction::Instruction {
+        program_id: PROGRAM_ID,
+        accounts: merkle_distributor::accounts::Clawback {
+            distributor: distributor_pda,
+            from: distributor_token_account,
+            to: get_associated_token_address(&payer.pubkey(), &mint),
+            claimant: fee_payer.pubkey(),
+            system_program: solana_program::system_program::ID,
+            mint,
+            token_program: spl_token::id(),
+        }
+        .to_account_metas(None),
+        data: merkle_distributor::instruction::Clawback {}.data(),
+    };
+    send_transaction(&mut rpc, &[clawback_ix], &[&fee_payer])
+        .await
+        .unwrap();
+
+    let distributor_rent = rpc.get_account(distributor_pda).await.unwrap().unwrap().lamports;
+    let vault_rent = rpc
+        .get_account(distributor_token_account)
+        .await
+        .unwrap()
+        .unwrap()
+        .lamports;
+    let admin_before = rpc.get_account(payer.pubkey()).await.unwrap().unwrap().lamports;
+
+    send_transaction(&mut rpc, &[close_distributor_ix], &[&fee_payer, &payer])
+        .await
+        .unwrap();
+
+    assert!(rpc.get_account(distributor_pda).await.unwrap().is_none());
+    assert!(rpc
+        .get_account(distributor_token_account)
+        .await
+        .unwrap()
+        .is_none());
+    let admin_after = rpc.get_account(payer.pubkey()).await.unwrap().unwrap().lamports;
+    assert_eq!(admin_after, admin_before + distributor_rent + vault_rent);
+}
+
+#[tokio::test]
+async fn test_set_clawback_receiver_redirects_clawback() {
+    use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+    use merkle_distributor::{
+        error::ErrorCode, state::merkle_distributor::MerkleDistributor, ID as PROGRAM_ID,
+    };
+    use solana_program::clock::Clock;
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, _test_keypairs) = create_test_merkle_tree();
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let start_vesting_ts = current_time + 10;
+    let end_vesting_ts = current_time + 3600;
+    let clawback_start_ts = end_vesting_ts + 86400;
+
+    let (mint, distributor_pda, distributor_token_account) = setup_funded_distributor(
+        &mut rpc,
+        &payer,
+        &merkle_tree,
+        start_vesting_ts,
+        end_vesting_ts,
+        clawback_start_ts,
+    )
+    .await;
+
+    let treasury = Keypair::new();
+    let treasury_token_account = get_associated_token_address(&treasury.pubkey(), &mint);
+    let create_treasury_ata_ix = create_associated_token_account(
+        &payer.pubkey(),
+        &treasury.pubkey(),
+        &mint,
+        &spl_token::id(),
+    );
+    send_transaction(&mut rpc, &[create_treasury_ata_ix], &[&payer])
+        .await
+        .unwrap();
+
+    let set_clawback_receiver_ix = |new_clawback_account| solana_program::instruction::Instruction {
+        program_id: PROGRAM_ID,
+        accounts: merkle_distributor::accounts::SetClawbackReceiver {
+            distributor: distributor_pda,
+            new_clawback_account,
+            admin: payer.pubkey(),
+        }
+        .to_account_metas(None),
+        data: merkle_distributor::instruction::SetClawbackReceiver {}.data(),
+    };
+    send_transaction(
+        &mut rpc,
+        &[set_clawback_receiver_ix(treasury_token_account)],
+        &[&payer],
+    )
+    .await
+    .unwrap();
+
+    let distributor_account = rpc.get_account(distributor_pda).await.unwrap().unwrap();
+    let distributor =
+        MerkleDistributor::try_deserialize(&mut distributor_account.data.as_slice()).unwrap();
+    assert_eq!(distributor.clawback_receiver, treasury_token_account);
+
+    let mut clock = rpc.context.get_sysvar::<Clock>();
+    clock.unix_timestamp = clawback_start_ts + 1;
+    rpc.context.set_sysvar(&clock);
+
+    let clawback_ix = solana_program::instruction::Instruction {
+        program_id: PROGRAM_ID,
+        accounts: merkle_distributor::accounts::Clawback {
+            distributor: distributor_pda,
+            from: distributor_token_account,
+            to: treasury_token_account,
+            claimant: payer.pubkey(),
+            system_program: solana_program::system_program::ID,
+            mint,
+            token_program: spl_token::id(),
+        }
+        .to_account_metas(None),
+        data: merkle_distributor::instruction::Clawback {}.data(),
+    };
+    send_transaction(&mut rpc, &[clawback_ix], &[&payer])
+        .await
+        .unwrap();
+
+    let treasury_account = rpc.get_account(treasury_token_account).await.unwrap();
+    let treasury_data = spl_token::state::Account::unpack(&treasury_account.unwrap().data).unwrap();
+    assert_eq!(treasury_data.amount, merkle_tree.max_total_claim);
+
+    // The receiver is frozen once the distributor has been clawed back
+    let original_receiver = get_associated_token_address(&payer.pubkey(), &mint);
+    let err = send_transaction(
+        &mut rpc,
+        &[set_clawback_receiver_ix(original_receiver)],
+        &[&payer],
+    )
+    .await
+    .unwrap_err();
+    let already_clawed_back = u32::from(ErrorCode::ClawbackAlreadyClaimed);
+    assert!(
+        format!("{err:?}").contains(&format!("Custom({already_clawed_back})")),
+        "expected ClawbackAlreadyClaimed, got {err:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_paused_distributor_rejects_claims_until_resumed() {
+    use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+    use merkle_distributor::{
+        error::ErrorCode, state::merkle_distributor::MerkleDistributor, ID as PROGRAM_ID,
+    };
+    use solana_program::clock::Clock;
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, test_keypairs) = create_test_merkle_tree();
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let start_vesting_ts = current_time + 10;
+    let end_vesting_ts = current_time + 3600;
+    let clawback_start_ts = end_vesting_ts + 86400;
+
+    let (mint, distributor_pda, distributor_token_account) = setup_funded_distributor(
+        &mut rpc,
+        &payer,
+        &merkle_tree,
+        start_vesting_ts,
+        end_vesting_ts,
+        clawback_start_ts,
+    )
+    .await;
+
+    let set_paused_ix = |paused: bool| solana_program::instruction::Instruction {
+        program_id: PROGRAM_ID,
+        accounts: merkle_distributor::accounts::SetPaused {
+            distributor: distributor_pda,
+            admin: payer.pubkey(),
+        }
+        .to_account_metas(None),
+        data: merkle_distributor::instruction::SetPaused { paused }.data(),
+    };
+    send_transaction(&mut rpc, &[set_paused_ix(true)], &[&payer])
+        .await
+        .unwrap();
+
+    let paused_claimant = &test_keypairs[0];
+    let err = try_new_claim(
+        &mut rpc,
+        &payer,
+        paused_claimant,
+        &merkle_tree.get_node_unchecked(&paused_claimant.pubkey()),
+        &mint,
+        &distributor_pda,
+        &distributor_token_account,
+    )
+    .await
+    .unwrap_err();
+    let paused = u32::from(ErrorCode::Paused);
+    assert!(
+        format!("{err:?}").contains(&format!("Custom({paused})")),
+        "expected Paused, got {err:?}"
+    );
+
+    send_transaction(&mut rpc, &[set_paused_ix(false)], &[&payer])
+        .await
+        .unwrap();
+
+    let claimant = &test_keypairs[1];
+    let claimant_node = merkle_tree.get_node_unchecked(&claimant.pubkey());
+    let claimant_ata = new_claim(
+        &mut rpc,
+        &payer,
+        claimant,
+        &claimant_node,
+        &mint,
+        &distributor_pda,
+        &distributor_token_account,
+    )
+    .await;
+
+    let claimant_account = rpc.get_account(claimant_ata).await.unwrap().unwrap();
+    let claimant_data = spl_token::state::Account::unpack(&claimant_account.data).unwrap();
+    assert_eq!(claimant_data.amount, claimant_node.amount_unlocked());
+
+    let distributor_account = rpc.get_account(distributor_pda).await.unwrap().unwrap();
+    let distributor =
+        MerkleDistributor::try_deserialize(&mut distributor_account.data.as_slice()).unwrap();
+    assert!(!distributor.paused);
+    assert_eq!(distributor.num_nodes_claimed, 1);
+}
+
+#[tokio::test]
+async fn test_renounced_admin_blocks_admin_instructions() {
+    use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+    use merkle_distributor::{
+        error::ErrorCode, state::merkle_distributor::MerkleDistributor, ID as PROGRAM_ID,
+    };
+    use solana_program::clock::Clock;
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, test_keypairs) = create_test_merkle_tree();
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let start_vesting_ts = current_time + 10;
+    let end_vesting_ts = current_time + 3600;
+    let clawback_start_ts = end_vesting_ts + 86400;
+
+    let (mint, distributor_pda, distributor_token_account) = setup_funded_distributor(
+        &mut rpc,
+        &payer,
+        &merkle_tree,
+        start_vesting_ts,
+        end_vesting_ts,
+        clawback_start_ts,
+    )
+    .await;
+
+    let renounce_admin_ix = solana_program::instruction::Instruction {
+        program_id: PROGRAM_ID,
+        accounts: merkle_distributor::accounts::RenounceAdmin {
+            distributor: distributor_pda,
+            admin: payer.pubkey(),
+        }
+        .to_account_metas(None),
+        data: merkle_distributor::instruction::RenounceAdmin {}.data(),
+    };
+    send_transaction(&mut rpc, &[renounce_admin_ix.clone()], &[&payer])
+        .await
+        .unwrap();
+
+    let distributor_account = rpc.get_account(distributor_pda).await.unwrap().unwrap();
+    let distributor =
+        MerkleDistributor::try_deserialize(&mut distributor_account.data.as_slice()).unwrap();
+    assert!(distributor.admin_renounced);
+    assert_eq!(distributor.admin, payer.pubkey());
+
+    let set_paused_ix = solana_program::instruction::Instruction {
+        program_id: PROGRAM_ID,
+        accounts: merkle_distributor::accounts::SetPaused {
+            distributor: distributor_pda,
+            admin: payer.pubkey(),
+        }
+        .to_account_metas(None),
+        data: merkle_distributor::instruction::SetPaused { paused: true }.data(),
+    };
+    let set_admin_ix = solana_program::instruction::Instruction {
+        program_id: PROGRAM_ID,
+        accounts: merkle_distributor::accounts::SetAdmin {
+            distributor: distributor_pda,
+            admin: payer.pubkey(),
+            new_admin: Keypair::new().pubkey(),
+        }
+        .to_account_metas(None),
+        data: merkle_distributor::instruction::SetAdmin {}.data(),
+    };
+    let set_clawback_receiver_ix = solana_program::instruction::Instruction {
+        program_id: PROGRAM_ID,
+        accounts: merkle_distributor::accounts::SetClawbackReceiver {
+            distributor: distributor_pda,
+            new_clawback_account: distributor_token_account,
+            admin: payer.pubkey(),
+        }
+        .to_account_metas(None),
+        data: merkle_distributor::instruction::SetClawbackReceiver {}.data(),
+    };
+    let update_clawback_start_ts_ix = solana_program::instruction::Instruction {
+        program_id: PROGRAM_ID,
+        accounts: merkle_distributor::accounts::UpdateClawbackStartTs {
+            distributor: distributor_pda,
+            admin: payer.pubkey(),
+        }
+        .to_account_metas(None),
+        data: merkle_distributor::instruction::UpdateClawbackStartTs {
+            clawback_start_ts: clawback_start_ts + 86400,
+        }
+        .data(),
+    };
+    let admin_renounced = u32::from(ErrorCode::AdminRenounced);
+    for ix in [
+        set_paused_ix,
+        set_admin_ix,
+        set_clawback_receiver_ix,
+        update_clawback_start_ts_ix,
+        renounce_admin_ix,
+    ] {
+        let err = send_transaction(&mut rpc, &[ix], &[&payer])
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{err:?}").contains(&format!("Custom({admin_renounced})")),
+            "expected AdminRenounced, got {err:?}"
+        );
+    }
+
+    // Claims do not depend on the admin
+    let claimant = &test_keypairs[0];
+    let claimant_node = merkle_tree.get_node_unchecked(&claimant.pubkey());
+    let claimant_ata = new_claim(
+        &mut rpc,
+        &payer,
+        claimant,
+        &claimant_node,
+        &mint,
+        &distributor_pda,
+        &distributor_token_account,
+    )
+    .await;
+    let claimant_account = rpc.get_account(claimant_ata).await.unwrap().unwrap();
+    let claimant_data = spl_token::state::Account::unpack(&claimant_account.data).unwrap();
+    assert_eq!(claimant_data.amount, claimant_node.amount_unlocked());
+}
+
+#[tokio::test]
+async fn test_renounce_admin_fails_while_paused() {
+    use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+    use merkle_distributor::{
+        error::ErrorCode, state::merkle_distributor::MerkleDistributor, ID as PROGRAM_ID,
+    };
+    use solana_program::clock::Clock;
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, _test_keypairs) = create_test_merkle_tree();
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let start_vesting_ts = current_time + 10;
+    let end_vesting_ts = current_time + 3600;
+    let clawback_start_ts = end_vesting_ts + 86400;
+
+    let (_mint, distributor_pda, _distributor_token_account) = setup_funded_distributor(
+        &mut rpc,
+        &payer,
+        &merkle_tree,
+        start_vesting_ts,
+        end_vesting_ts,
+        clawback_start_ts,
+    )
+    .await;
+
+    let set_paused_ix = |paused: bool| solana_program::instruction::Instruction {
+        program_id: PROGRAM_ID,
+        accounts: merkle_distributor::accounts::SetPaused {
+            distributor: distributor_pda,
+            admin: payer.pubkey(),
+        }
+        .to_account_metas(None),
+        data: merkle_distributor::instruction::SetPaused { paused }.data(),
+    };
+    let renounce_admin_ix = solana_program::instruction::Instruction {
+        program_id: PROGRAM_ID,
+        accounts: merkle_distributor::accounts::RenounceAdmin {
+            distributor: distributor_pda,
+            admin: payer.pubkey(),
+        }
+        .to_account_metas(None),
+        data: merkle_distributor::instruction::RenounceAdmin {}.data(),
+    };
+
+    send_transaction(&mut rpc, &[set_paused_ix(true)], &[&payer])
+        .await
+        .unwrap();
+    // Nobody could unpause the distributor once the admin is gone
+    let err = send_transaction(&mut rpc, &[renounce_admin_ix.clone()], &[&payer])
+        .await
+        .unwrap_err();
+    let renounce_while_paused = u32::from(ErrorCode::RenounceWhilePaused);
+    assert!(
+        format!("{err:?}").contains(&format!("Custom({renounce_while_paused})")),
+        "expected RenounceWhilePaused, got {err:?}"
+    );
+
+    send_transaction(&mut rpc, &[set_paused_ix(false)], &[&payer])
+        .await
+        .unwrap();
+    send_transaction(&mut rpc, &[renounce_admin_ix], &[&payer])
+        .await
+        .unwrap();
+
+    let distributor_account = rpc.get_account(distributor_pda).await.unwrap().unwrap();
+    let distributor =
+        MerkleDistributor::try_deserialize(&mut distributor_account.data.as_slice()).unwrap();
+    assert!(distributor.admin_renounced);
+    assert!(!distributor.paused);
+}
+
+#[tokio::test]
+async fn test_update_clawback_start_ts_only_moves_later() {
+    use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+    use merkle_distributor::{
+        error::ErrorCode, state::merkle_distributor::MerkleDistributor, ID as PROGRAM_ID,
+    };
+    use solana_program::clock::Clock;
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, _test_keypairs) = create_test_merkle_tree();
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let start_vesting_ts = current_time + 10;
+    let end_vesting_ts = current_time + 3600;
+    let clawback_start_ts = end_vesting_ts + 86400;
+
+    let (_mint, distributor_pda, _distributor_token_account) = setup_funded_distributor(
+        &mut rpc,
+        &payer,
+        &merkle_tree,
+        start_vesting_ts,
+        end_vesting_ts,
+        clawback_start_ts,
+    )
+    .await;
+
+    let update_clawback_start_ts_ix =
+        |clawback_start_ts| solana_program::instruction::Instruction {
+            program_id: PROGRAM_ID,
+            accounts: merkle_distributor::accounts::UpdateClawbackStartTs {
+                distributor: distributor_pda,
+                admin: payer.pubkey(),
+            }
+            .to_account_metas(None),
+            data: merkle_distributor::instruction::UpdateClawbackStartTs { clawback_start_ts }
+                .data(),
+        };
+
+    // Moving the clawback start earlier, or leaving it in place, is rejected
+    let not_later = u32::from(ErrorCode::ClawbackStartNotLater);
+    for earlier_ts in [clawback_start_ts - 1, clawback_start_ts] {
+        let err = send_transaction(
+            &mut rpc,
+            &[update_clawback_start_ts_ix(earlier_ts)],
+            &[&payer],
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            format!("{err:?}").contains(&format!("Custom({not_later})")),
+            "expected ClawbackStartNotLater, got {err:?}"
+        );
+    }
+
+    let postponed_ts = clawback_start_ts + 7 * 86400;
+    send_transaction(
+        &mut rpc,
+        &[update_clawback_start_ts_ix(postponed_ts)],
+        &[&payer],
+    )
+    .await
+    .unwrap();
+    let mut distributor_account = rpc.get_account(distributor_pda).await.unwrap().unwrap();
+    let mut distributor =
+        MerkleDistributor::try_deserialize(&mut distributor_account.data.as_slice()).unwrap();
+    assert_eq!(distributor.clawback_start_ts, postponed_ts);
+
+    // new_distributor never stores a clawback start less than a day after vesting ends, so write
+    // one directly to check that a later value still has to respect the minimum delay
+    distributor.clawback_start_ts = end_vesting_ts + 60;
+    let mut data = vec![];
+    distributor.try_serialize(&mut data).unwrap();
+    distributor_account.data[..data.len()].copy_from_slice(&data);
+    rpc.context
+        .set_account(distributor_pda, distributor_account)
+        .unwrap();
+
+    let err = send_transaction(
+        &mut rpc,
+        &[update_clawback_start_ts_ix(end_vesting_ts + 86400 - 1)],
+        &[&payer],
+    )
+    .await
+    .unwrap_err();
+    let insufficient_delay = u32::from(ErrorCode::InsufficientClawbackDelay);
+    assert!(
+        format!("{err:?}").contains(&format!("Custom({insufficient_delay})")),
+        "expected InsufficientClawbackDelay, got {err:?}"
+    );
+    send_transaction(
+        &mut rpc,
+        &[update_clawback_start_ts_ix(end_vesting_ts + 86400)],
+        &[&payer],
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_update_root_only_raises_caps() {
+    use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+    use jito_merkle_tree::tree_node::TreeNode;
+    use merkle_distributor::{
+        error::ErrorCode, state::merkle_distributor::MerkleDistributor, ID as PROGRAM_ID,
+    };
+    use solana_program::clock::Clock;
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, test_keypairs) = create_test_merkle_tree();
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let end_vesting_ts = current_time + 3600;
+    let (mint, distributor_pda, distributor_token_account) = setup_funded_distributor(
+        &mut rpc,
+        &payer,
+        &merkle_tree,
+        current_time + 10,
+        end_vesting_ts,
+        end_vesting_ts + 86400,
+    )
+    .await;
+
+    let first_node = merkle_tree.get_node_unchecked(&test_keypairs[0].pubkey());
+    new_claim(
+        &mut rpc,
+        &payer,
+        &test_keypairs[0],
+        &first_node,
+        &mint,
+        &distributor_pda,
+        &distributor_token_account,
+    )
+    .await;
+
+    // Second wave: the same nodes plus a new recipient
+    let new_keypair = Keypair::new();
+    let mut tree_nodes = merkle_tree.tree_nodes().to_vec();
+    tree_nodes.push(TreeNode {
+        claimant: new_keypair.pubkey(),
+        total_unlocked_staker: 300,
+        total_locked_staker: 200,
+        total_unlocked_searcher: 0,
+        total_locked_searcher: 0,
+        total_unlocked_validator: 0,
+        total_locked_validator: 0,
+        proof: None,
+        unlock_schedule: None,
+    });
+    let superset = AirdropMerkleTree::new(tree_nodes).unwrap();
+
+    let update_root_ix =
+        |max_total_claim, max_num_nodes| solana_program::instruction::Instruction {
+            program_id: PROGRAM_ID,
+            accounts: merkle_distributor::accounts::UpdateRoot {
+                distributor: distributor_pda,
+                admin: payer.pubkey(),
+            }
+            .to_account_metas(None),
+            data: merkle_distributor::instruction::UpdateRoot {
+                root: superset.merkle_root,
+                max_total_claim,
+                max_num_nodes,
+                tree_depth: superset.tree_depth(),
+            }
+            .data(),
+        };
+
+    // Lowering either cap is rejected
+    let cap_decreased = u32::from(ErrorCode::CapDecreased);
+    for (max_total_claim, max_num_nodes) in [
+        (merkle_tree.max_total_claim - 1, superset.max_num_nodes),
+        (superset.max_total_claim, merkle_tree.max_num_nodes - 1),
+    ] {
+        let err = send_transaction(
+            &mut rpc,
+            &[update_root_ix(max_total_claim, max_num_nodes)],
+            &[&payer],
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            format!("{err:?}").contains(&format!("Custom({cap_decreased})")),
+            "expected CapDecreased, got {err:?}"
+        );
+    }
+
+    send_transaction(
+        &mut rpc,
+        &[update_root_ix(
+            superset.max_total_claim,
+            superset.max_num_nodes,
+        )],
+        &[&payer],
+    )
+    .await
+    .unwrap();
+    let distributor_account = rpc.get_account(distributor_pda).await.unwrap().unwrap();
+    let distributor =
+        MerkleDistributor::try_deserialize(&mut distributor_account.data.as_slice()).unwrap();
+    assert_eq!(distributor.root, superset.merkle_root);
+    assert_eq!(distributor.max_total_claim, superset.max_total_claim);
+    assert_eq!(distributor.max_num_nodes, superset.max_num_nodes);
+    assert_eq!(distributor.tree_depth, superset.tree_depth());
+    assert_eq!(distributor.num_nodes_claimed, 1);
+    assert_eq!(
+        distributor.total_amount_claimed,
+        first_node.amount_unlocked()
+    );
+
+    // Top the vault up for the new allocation
+    let token_program = rpc.get_account(mint).await.unwrap().unwrap().owner;
+    let mint_to_ix = spl_token_2022::instruction::mint_to(
+        &token_program,
+        &mint,
+        &distributor_token_account,
+        &payer.pubkey(),
+        &[],
+        superset.max_total_claim - merkle_tree.max_total_claim,
+    )
+    .unwrap();
+    send_transaction(&mut rpc, &[mint_to_ix], &[&payer])
+        .await
+        .unwrap();
+
+    // Proofs from the old tree file no longer fit the root
+    let stale_node = merkle_tree.get_node_unchecked(&test_keypairs[1].pubkey());
+    let err = try_new_claim(
+        &mut rpc,
+        &payer,
+        &test_keypairs[1],
+        &stale_node,
+        &mint,
+        &distributor_pda,
+        &distributor_token_account,
+    )
+    .await
+    .unwrap_err();
+    let proof_length_mismatch = u32::from(ErrorCode::ProofLengthMismatch);
+    assert!(
+        format!("{err:?}").contains(&format!("Custom({proof_length_mismatch})")),
+        "expected ProofLengthMismatch, got {err:?}"
+    );
+
+    for keypair in [&test_keypairs[1], &new_keypair] {
+        new_claim(
+            &mut rpc,
+            &payer,
+            keypair,
+            &superset.get_node_unchecked(&keypair.pubkey()),
+            &mint,
+            &distributor_pda,
+            &distributor_token_account,
+        )
+        .await;
+    }
+    let distributor_account = rpc.get_account(distributor_pda).await.unwrap().unwrap();
+    let distributor =
+        MerkleDistributor::try_deserialize(&mut distributor_account.data.as_slice()).unwrap();
+    assert_eq!(distributor.num_nodes_claimed, 3);
+}
+
+#[tokio::test]
+async fn test_migrate_distributor_extends_legacy_layout() {
+    use anchor_lang::{
+        AccountDeserialize, AnchorSerialize, Discriminator, InstructionData, ToAccountMetas,
+    };
+    use merkle_distributor::{
+        error::ErrorCode,
+        state::merkle_distributor::{DistributionMode, LegacyMerkleDistributor, MerkleDistributor},
+        ID as PROGRAM_ID,
+    };
+    use solana_program::clock::Clock;
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, test_keypairs) = create_test_merkle_tree();
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let start_vesting_ts = current_time + 10;
+    let end_vesting_ts = current_time + 3600;
+    let clawback_start_ts = end_vesting_ts + 86400;
+
+    let (mint, distributor_pda, distributor_token_account) = setup_funded_distributor(
+        &mut rpc,
+        &payer,
+        &merkle_tree,
+        start_vesting_ts,
+        end_vesting_ts,
+        clawback_start_ts,
+    )
+    .await;
+
+    // Rewrite the distributor as the first release stored it: the legacy fields only, in an
+    // account sized by the legacy struct
+    let mut distributor_account = rpc.get_account(distributor_pda).await.unwrap().unwrap();
+    let distributor =
+        MerkleDistributor::try_deserialize(&mut distributor_account.data.as_slice()).unwrap();
+    let legacy = LegacyMerkleDistributor {
+        bump: distributor.bump,
+        version: distributor.version,
+        root: distributor.root,
+        mint: distributor.mint,
+        token_vault: distributor.token_vault,
+        max_total_claim: distributor.max_total_claim,
+        max_num_nodes: distributor.max_num_nodes,
+        total_amount_claimed: distributor.total_amount_claimed,
+        num_nodes_claimed: distributor.num_nodes_claimed,
+        start_ts: distributor.start_ts,
+        end_ts: distributor.end_ts,
+        clawback_start_ts: distributor.clawback_start_ts,
+        clawback_receiver: distributor.clawback_receiver,
+        admin: distributor.admin,
+        clawed_back: distributor.clawed_back,
+    };
+    let legacy_len = 8 + std::mem::size_of::<LegacyMerkleDistributor>();
+    let mut data = MerkleDistributor::DISCRIMINATOR.to_vec();
+    legacy.serialize(&mut data).unwrap();
+    data.resize(legacy_len, 0);
+    distributor_account.lamports = rpc
+        .get_minimum_balance_for_rent_exemption(legacy_len)
+        .await
+        .unwrap();
+    distributor_account.data = data;
+    rpc.context
+        .set_account(distributor_pda, distributor_account)
+        .unwrap();
+
+    let set_paused_ix = solana_program::instruction::Instruction {
+        program_id: PROGRAM_ID,
+        accounts: merkle_distributor::accounts::SetPaused {
+            distributor: distributor_pda,
+            admin: payer.pubkey(),
+        }
+        .to_account_metas(None),
+        data: merkle_distributor::instruction::SetPaused { paused: false }.data(),
+    };
+    let err = send_transaction(&mut rpc, &[set_paused_ix.clone()], &[&payer])
+        .await
+        .unwrap_err();
+    let did_not_deserialize = u32::from(anchor_lang::error::ErrorCode::AccountDidNotDeserialize);
+    assert!(
+        format!("{err:?}").contains(&format!("Custom({did_not_deserialize})")),
+        "expected AccountDidNotDeserialize, got {err:?}"
+    );
+
+    let migrate_distributor_ix = solana_program::instruction::Instruction {
+        program_id: PROGRAM_ID,
+        accounts: merkle_distributor::accounts::MigrateDistributor {
+            distributor: distributor_pda,
+            payer: payer.pubkey(),
+            system_program: solana_program::system_program::id(),
+        }
+        .to_account_metas(None),
+        data: merkle_distributor::instruction::MigrateDistributor {}.data(),
+    };
+    send_transaction(&mut rpc, &[migrate_distributor_ix.clone()], &[&payer])
+        .await
+        .unwrap();
+
+    let distributor_account = rpc.get_account(distributor_pda).await.unwrap().unwrap();
+    assert_eq!(distributor_account.data.len(), MerkleDistributor::LEN);
+    assert!(
+        distributor_account.lamports
+            >= rpc
+                .get_minimum_balance_for_rent_exemption(MerkleDistributor::LEN)
+                .await
+                .unwrap()
+    );
+    let migrated =
+        MerkleDistributor::try_deserialize(&mut distributor_account.data.as_slice()).unwrap();
+    assert_eq!(migrated.root, distributor.root);
+    assert_eq!(migrated.max_total_claim, distributor.max_total_claim);
+    assert_eq!(migrated.clawback_start_ts, distributor.clawback_start_ts);
+    assert_eq!(migrated.admin, distributor.admin);
+    assert_eq!(migrated.cliff_ts, distributor.start_ts);
+    assert_eq!(migrated.vesting_interval_secs, 1);
+    assert_eq!(migrated.distribution_mode, DistributionMode::Token);
+    assert!(!migrated.paused && !migrated.allow_reassignment);
+    assert_eq!(migrated.tree_depth, 0);
+
+    send_transaction(&mut rpc, &[set_paused_ix], &[&payer])
+        .await
+        .unwrap();
+
+    let err = send_transaction(&mut rpc, &[migrate_distributor_ix], &[&payer])
+        .await
+        .unwrap_err();
+    let already_migrated = u32::from(ErrorCode::DistributorAlreadyMigrated);
+    assert!(
+        format!("{err:?}").contains(&format!("Custom({already_migrated})")),
+        "expected DistributorAlreadyMigrated, got {err:?}"
+    );
+
+    // Without a stored tree depth, proofs of any length are checked against the root alone
+    let claimant = &test_keypairs[0];
+    let claimant_node = merkle_tree.get_node_unchecked(&claimant.pubkey());
+    assert!(!claimant_node.proof.as_ref().unwrap().is_empty());
+    let claimant_ata = new_claim(
+        &mut rpc,
+        &payer,
+        claimant,
+        &claimant_node,
+        &mint,
+        &distributor_pda,
+        &distributor_token_account,
+    )
+    .await;
+    let claimant_account = rpc.get_account(claimant_ata).await.unwrap().unwrap();
+    let claimant_data = spl_token::state::Account::unpack(&claimant_account.data).unwrap();
+    assert_eq!(claimant_data.amount, claimant_node.amount_unlocked());
+}
+
+#[tokio::test]
+async fn test_new_distributor_rejects_invalid_timestamps() {
+    use merkle_distributor::{error::ErrorCode, ID as PROGRAM_ID};
+    use solana_program::clock::Clock;
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, _test_keypairs) = create_test_merkle_tree();
+
+    let mint = create_mint(&mut rpc, &payer, &spl_token::id(), None).await;
+    let (distributor_pda, _bump) = get_merkle_distributor_pda(&PROGRAM_ID, &mint, 0);
+    let distributor_token_account = get_associated_token_address(&distributor_pda, &mint);
+    let clawback_token_account = get_associated_token_address(&payer.pubkey(), &mint);
+    let create_clawback_ata_ix =
+        create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &mint, &spl_token::id());
+    send_transaction(&mut rpc, &[create_clawback_ata_ix], &[&payer])
+        .await
+        .unwrap();
+
+    let new_distributor_ix = |start_vesting_ts, end_vesting_ts, clawback_start_ts| {
+        create_distributor_instruction(
+            &PROGRAM_ID,
+            &distributor_pda,
+            &payer.pubkey(),
+            &mint,
+            &distributor_token_account,
+            &clawback_token_account,
+            &merkle_tree,
+            start_vesting_ts,
+            end_vesting_ts,
+            clawback_start_ts,
+            0,
+            false,
+            &spl_token::id(),
+        )
+    };
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let start_vesting_ts = current_time + 10;
+    let end_vesting_ts = current_time + 3600;
+
+    for (start_ts, end_ts, clawback_start_ts, expected_error) in [
+        // Vesting has to last at least a second
+        (
+            end_vesting_ts,
+            end_vesting_ts,
+            end_vesting_ts + 86400,
+            ErrorCode::StartTimestampAfterEnd,
+        ),
+        (
+            end_vesting_ts + 1,
+            end_vesting_ts,
+            end_vesting_ts + 86400,
+            ErrorCode::StartTimestampAfterEnd,
+        ),
+        (
+            start_vesting_ts,
+            end_vesting_ts,
+            end_vesting_ts,
+            ErrorCode::ClawbackDuringVesting,
+        ),
+        // One second short of the minimum clawback delay
+        (
+            start_vesting_ts,
+            end_vesting_ts,
+            end_vesting_ts + 86400 - 1,
+            ErrorCode::InsufficientClawbackDelay,
+        ),
+    ] {
+        let err = send_transaction(
+            &mut rpc,
+            &[new_distributor_ix(start_ts, end_ts, clawback_start_ts)],
+            &[&payer],
+        )
+        .await
+        .unwrap_err();
+        let code = u32::from(expected_error);
+        assert!(
+            format!("{err:?}").contains(&format!("Custom({code})")),
+            "expected {expected_error:?} for start {start_ts}, end {end_ts} and clawback start \
+             {clawback_start_ts}, got {err:?}"
+        );
+    }
+
+    // Both boundaries are inclusive on the valid side
+    send_transaction(
+        &mut rpc,
+        &[new_distributor_ix(
+            end_vesting_ts - 1,
+            end_vesting_ts,
+            end_vesting_ts + 86400,
+        )],
+        &[&payer],
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_new_distributor_rejects_zero_caps() {
+    use merkle_distributor::{error::ErrorCode, ID as PROGRAM_ID};
+    use solana_program::clock::Clock;
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, _test_keypairs) = create_test_merkle_tree();
+
+    let mint = create_mint(&mut rpc, &payer, &spl_token::id(), None).await;
+    let (distributor_pda, _bump) = get_merkle_distributor_pda(&PROGRAM_ID, &mint, 0);
+    let distributor_token_account = get_associated_token_address(&distributor_pda, &mint);
+    let clawback_token_account = get_associated_token_address(&payer.pubkey(), &mint);
+    let create_clawback_ata_ix =
+        create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &mint, &spl_token::id());
+    send_transaction(&mut rpc, &[create_clawback_ata_ix], &[&payer])
+        .await
+        .unwrap();
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let new_distributor_ix = |max_num_nodes, max_total_claim| {
+        let mut tree = merkle_tree.clone();
+        tree.max_num_nodes = max_num_nodes;
+        tree.max_total_claim = max_total_claim;
+        create_distributor_instruction(
+            &PROGRAM_ID,
+            &distributor_pda,
+            &payer.pubkey(),
+            &mint,
+            &distributor_token_account,
+            &clawback_token_account,
+            &tree,
+            current_time + 10,
+            current_time + 3600,
+            current_time + 3600 + 86400,
+            0,
+            false,
+            &spl_token::id(),
+        )
+    };
+
+    for (max_num_nodes, max_total_claim, expected_error) in [
+        (
+            0,
+            merkle_tree.max_total_claim,
+            ErrorCode::InvalidMaxNumNodes,
+        ),
+        (
+            merkle_tree.max_num_nodes,
+            0,
+            ErrorCode::InvalidMaxTotalClaim,
+        ),
+        (0, 0, ErrorCode::InvalidMaxNumNodes),
+    ] {
+        let err = send_transaction(
+            &mut rpc,
+            &[new_distributor_ix(max_num_nodes, max_total_claim)],
+            &[&payer],
+        )
+        .await
+        .unwrap_err();
+        let code = u32::from(expected_error);
+        assert!(
+            format!("{err:?}").contains(&format!("Custom({code})")),
+            "expected {expected_error:?} for max_num_nodes {max_num_nodes} and max_total_claim \
+             {max_total_claim}, got {err:?}"
+        );
+    }
+
+    // The caps of the tree itself are accepted
+    send_transaction(
+        &mut rpc,
+        &[new_distributor_ix(
+            merkle_tree.max_num_nodes,
+            merkle_tree.max_total_claim,
+        )],
+        &[&payer],
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_reassign_claim_moves_unclaimed_allocation() {
+    use anchor_lang::AccountDeserialize;
+    use merkle_distributor::{
+        error::ErrorCode, state::merkle_distributor::MerkleDistributor, ID as PROGRAM_ID,
+    };
+    use solana_program::clock::Clock;
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, test_keypairs) = create_test_merkle_tree();
+    let lost_node = merkle_tree.get_node_unchecked(&test_keypairs[0].pubkey());
+    let claimant = &test_keypairs[1];
+    let claimant_node = merkle_tree.get_node_unchecked(&claimant.pubkey());
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let start_vesting_ts = current_time + 10;
+    let end_vesting_ts = current_time + 3600;
+    let clawback_start_ts = end_vesting_ts + 86400;
+
+    let mint = create_mint(&mut rpc, &payer, &spl_token::id(), None).await;
+    let (distributor_pda, distributor_token_account) = setup_funded_distributor_with_mint(
+        &mut rpc,
+        &payer,
+        &merkle_tree,
+        start_vesting_ts,
+        end_vesting_ts,
+        clawback_start_ts,
+        0,
+        true,
+        &mint,
+    )
+    .await;
+
+    let new_wallet = Keypair::new();
+    let other_wallet = Keypair::new();
+    let mut setup_ixs = vec![solana_program::system_instruction::transfer(
+        &payer.pubkey(),
+        &new_wallet.pubkey(),
+        1_000_000_000,
+    )];
+    for wallet in [&new_wallet, &other_wallet] {
+        setup_ixs.push(create_associated_token_account(
+            &payer.pubkey(),
+            &wallet.pubkey(),
+            &mint,
+            &spl_token::id(),
+        ));
+    }
+    send_transaction(&mut rpc, &setup_ixs, &[&payer])
+        .await
+        .unwrap();
+
+    // Proven while every address is free, sent once they are taken
+    let mut reassign_ixs = vec![];
+    for (old_node, wallet) in [
+        (&lost_node, &new_wallet),
+        (&lost_node, &other_wallet),
+        (&claimant_node, &other_wallet),
+    ] {
+        reassign_ixs.push(
+            reassign_claim_instruction(
+                &mut rpc,
+                &payer.pubkey(),
+                old_node,
+                &wallet.pubkey(),
+                &mint,
+                &distributor_pda,
+                &distributor_token_account,
+            )
+            .await,
+        );
+    }
+    let reassign_claimed_ix = reassign_ixs.pop().unwrap();
+    let reassign_again_ix = reassign_ixs.pop().unwrap();
+    let reassign_lost_ix = reassign_ixs.pop().unwrap();
+
+    send_transaction(&mut rpc, &[reassign_lost_ix], &[&payer])
+        .await
+        .unwrap();
+    let new_wallet_ata = get_associated_token_address(&new_wallet.pubkey(), &mint);
+    let new_wallet_account = rpc.get_account(new_wallet_ata).await.unwrap().unwrap();
+    let new_wallet_data = spl_token::state::Account::unpack(&new_wallet_account.data).unwrap();
+    assert_eq!(new_wallet_data.amount, lost_node.amount_unlocked());
+
+    new_claim(
+        &mut rpc,
+        &payer,
+        claimant,
+        &claimant_node,
+        &mint,
+        &distributor_pda,
+        &distributor_token_account,
+    )
+    .await;
+
+    // Neither a node that was reassigned nor one that was claimed can be reassigned
+    let light_cpi_failed = u32::from(ErrorCode::LightCpiFailed);
+    for ix in [reassign_again_ix, reassign_claimed_ix] {
+        let err = send_transaction(&mut rpc, &[ix], &[&payer])
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{err:?}").contains(&format!("Custom({light_cpi_failed})")),
+            "expected LightCpiFailed, got {err:?}"
+        );
+    }
+
+    let distributor_account = rpc.get_account(distributor_pda).await.unwrap().unwrap();
+    let distributor =
+        MerkleDistributor::try_deserialize(&mut distributor_account.data.as_slice()).unwrap();
+    assert_eq!(distributor.num_nodes_claimed, 2);
+    assert_eq!(
+        distributor.total_amount_claimed,
+        lost_node.amount_unlocked() + claimant_node.amount_unlocked()
+    );
+    let other_wallet_ata = get_associated_token_address(&other_wallet.pubkey(), &mint);
+    let other_wallet_account = rpc.get_account(other_wallet_ata).await.unwrap().unwrap();
+    assert_eq!(
+        spl_token::state::Account::unpack(&other_wallet_account.data)
+            .unwrap()
+            .amount,
+        0
+    );
+
+    // The new wallet withdraws the locked tokens of the node like any claimant
+    let mut clock = rpc.context.get_sysvar::<Clock>();
+    clock.unix_timestamp = end_vesting_ts;
+    rpc.context.set_sysvar(&clock);
+    claim_locked(
+        &mut rpc,
+        &new_wallet,
+        &distributor_pda,
+        &distributor_token_account,
+        &new_wallet_ata,
+    )
+    .await;
+    let new_wallet_account = rpc.get_account(new_wallet_ata).await.unwrap().unwrap();
+    let new_wallet_data = spl_token::state::Account::unpack(&new_wallet_account.data).unwrap();
+    assert_eq!(new_wallet_data.amount, lost_node.total_amount());
+}
+
+#[tokio::test]
+async fn test_reassign_claim_requires_allow_reassignment() {
+    use merkle_distributor::{error::ErrorCode, ID as PROGRAM_ID};
+    use solana_program::clock::Clock;
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, test_keypairs) = create_test_merkle_tree();
+    let lost_node = merkle_tree.get_node_unchecked(&test_keypairs[0].pubkey());
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let end_vesting_ts = current_time + 3600;
+    let (mint, distributor_pda, distributor_token_account) = setup_funded_distributor(
+        &mut rpc,
+        &payer,
+        &merkle_tree,
+        current_time + 10,
+        end_vesting_ts,
+        end_vesting_ts + 86400,
+    )
+    .await;
+
+    let new_wallet = solana_sdk::pubkey::Pubkey::new_unique();
+    let create_ata_ix =
+        create_associated_token_account(&payer.pubkey(), &new_wallet, &mint, &spl_token::id());
+    send_transaction(&mut rpc, &[create_ata_ix], &[&payer])
+        .await
+        .unwrap();
+
+    let reassign_ix = reassign_claim_instruction(
+        &mut rpc,
+        &payer.pubkey(),
+        &lost_node,
+        &new_wallet,
+        &mint,
+        &distributor_pda,
+        &distributor_token_account,
+    )
+    .await;
+    let err = send_transaction(&mut rpc, &[reassign_ix], &[&payer])
+        .await
+        .unwrap_err();
+    let reassignment_disabled = u32::from(ErrorCode::ReassignmentDisabled);
+    assert!(
+        format!("{err:?}").contains(&format!("Custom({reassignment_disabled})")),
+        "expected ReassignmentDisabled, got {err:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_new_claim_signed_by_claimant_off_chain() {
+    use merkle_distributor::ID as PROGRAM_ID;
+    use solana_program::clock::Clock;
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, test_keypairs) = create_test_merkle_tree();
+    let claimant_keypair = &test_keypairs[0];
+    let claimant_node = merkle_tree.get_node_unchecked(&claimant_keypair.pubkey());
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let (mint, distributor_pda, distributor_token_account) = setup_funded_distributor(
+        &mut rpc,
+        &payer,
+        &merkle_tree,
+        current_time + 10,
+        current_time + 3600,
+        current_time + 3600 + 86400,
+    )
+    .await;
+
+    // The claimant holds no SOL and never signs a transaction
+    let claimant_ata = try_new_claim_signed(
+        &mut rpc,
+        &payer,
+        &claimant_keypair.pubkey(),
+        claimant_keypair,
+        &claimant_node,
+        &mint,
+        &distributor_pda,
+        &distributor_token_account,
+    )
+    .await
+    .unwrap();
+
+    let claimant_token_account = rpc.get_account(claimant_ata).await.unwrap();
+    let claimant_token_data =
+        spl_token::state::Account::unpack(&claimant_token_account.unwrap().data).unwrap();
+    assert_eq!(claimant_token_data.amount, claimant_node.amount_unlocked());
+    assert!(rpc
+        .get_account(claimant_keypair.pubkey())
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_new_claim_signed_rejects_signature_from_other_key() {
+    use merkle_distributor::{error::ErrorCode, ID as PROGRAM_ID};
+    use solana_program::clock::Clock;
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, test_keypairs) = create_test_merkle_tree();
+    let claimant_keypair = &test_keypairs[0];
+    let claimant_node = merkle_tree.get_node_unchecked(&claimant_keypair.pubkey());
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let (mint, distributor_pda, distributor_token_account) = setup_funded_distributor(
+        &mut rpc,
+        &payer,
+        &merkle_tree,
+        current_time + 10,
+        current_time + 3600,
+        current_time + 3600 + 86400,
+    )
+    .await;
+
+    // A valid ed25519 signature, but not by the claimant
+    let err = try_new_claim_signed(
+        &mut rpc,
+        &payer,
+        &claimant_keypair.pubkey(),
+        &Keypair::new(),
+        &claimant_node,
+        &mint,
+        &distributor_pda,
+        &distributor_token_account,
+    )
+    .await
+    .unwrap_err();
+    let invalid_signature = u32::from(ErrorCode::InvalidClaimantSignature);
+    assert!(
+        format!("{err:?}").contains(&format!("Custom({invalid_signature})")),
+        "expected InvalidClaimantSignature, got {err:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_claim_to_recipient_keeps_claim_status_of_claimant() {
+    use anchor_lang::{AnchorDeserialize, Discriminator, InstructionData, ToAccountMetas};
+    use merkle_distributor::{state::claim_status::ClaimStatus, ID as PROGRAM_ID};
+    use solana_program::clock::Clock;
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, test_keypairs) = create_test_merkle_tree();
+    let claimant_keypair = &test_keypairs[0];
+    let claimant_node = merkle_tree.get_node_unchecked(&claimant_keypair.pubkey());
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let start_vesting_ts = current_time + 10;
+    let end_vesting_ts = current_time + 3600;
+    let clawback_start_ts = end_vesting_ts + 86400;
+
+    let (mint, distributor_pda, distributor_token_account) = setup_funded_distributor(
+        &mut rpc,
+        &payer,
+        &merkle_tree,
+        start_vesting_ts,
+        end_vesting_ts,
+        clawback_start_ts,
+    )
+    .await;
+
+    // A cold wallet that never signs
+    let recipient = Keypair::new().pubkey();
+    let recipient_ata = get_associated_token_address(&recipient, &mint);
+    let fund_claimant_ix = solana_program::system_instruction::transfer(
+        &payer.pubkey(),
+        &claimant_keypair.pubkey(),
+        1_000_000_000,
+    );
+    let create_recipient_ata_ix =
+        create_associated_token_account(&payer.pubkey(), &recipient, &mint, &spl_token::id());
+    send_transaction(
+        &mut rpc,
+        &[fund_claimant_ix, create_recipient_ata_ix],
+        &[&payer],
+    )
+    .await
+    .unwrap();
+
+    let (packed_account_metas, proof, address_tree_info, output_state_tree_index) =
+        new_claim_light_inputs(&mut rpc, &claimant_keypair.pubkey(), &distributor_pda).await;
+    let new_claim_to_ix = solana_program::instruction::Instruction {
+        program_id: PROGRAM_ID,
+        accounts: [
+            merkle_distributor::accounts::NewClaimTo {
+                distributor: distributor_pda,
+                from: distributor_token_account,
+                to: recipient_ata,
+                claimant: claimant_keypair.pubkey(),
+                mint,
+                token_program: spl_token::id(),
+            }
+            .to_account_metas(None),
+            packed_account_metas,
+        ]
+        .concat(),
+        data: merkle_distributor::instruction::NewClaimTo {
+            amount_unlocked: claimant_node.amount_unlocked(),
+            amount_locked: claimant_node.amount_locked(),
+            proof: claimant_node.proof.clone().expect("proof not found"),
+            unlock_schedule: None,
+            category_breakdown: Some(category_breakdown(&claimant_node)),
+            validity_proof: proof,
+            address_tree_info,
+            output_state_tree_index,
+        }
+        .data(),
+    };
+    send_transaction(&mut rpc, &[new_claim_to_ix], &[&payer, claimant_keypair])
+        .await
+        .unwrap();
+
+    let recipient_account = rpc.get_account(recipient_ata).await.unwrap().unwrap();
+    let recipient_data = spl_token::state::Account::unpack(&recipient_account.data).unwrap();
+    assert_eq!(recipient_data.amount, claimant_node.amount_unlocked());
+    let claimant_ata = get_associated_token_address(&claimant_keypair.pubkey(), &mint);
+    assert!(rpc.get_account(claimant_ata).await.unwrap().is_none());
+
+    let mut clock = rpc.context.get_sysvar::<Clock>();
+    clock.unix_timestamp = end_vesting_ts;
+    rpc.context.set_sysvar(&clock);
+
+    // claim_locked_to takes the same accounts and arguments as claim_locked
+    let mut claim_locked_to_ix = claim_locked_instruction(
+        &mut rpc,
+        claimant_keypair,
+        &distributor_pda,
+        &distributor_token_account,
+        &recipient_ata,
+    )
+    .await;
+    claim_locked_to_ix.data[..8]
+        .copy_from_slice(merkle_distributor::instruction::ClaimLockedTo::DISCRIMINATOR);
+    send_transaction(&mut rpc, &[claim_locked_to_ix], &[claimant_keypair])
+        .await
+        .unwrap();
+
+    let recipient_account = rpc.get_account(recipient_ata).await.unwrap().unwrap();
+    let recipient_data = spl_token::state::Account::unpack(&recipient_account.data).unwrap();
+    assert_eq!(recipient_data.amount, claimant_node.total_amount());
+
+    // The claim status stays keyed on the claimant
+    let (claim_status_address, _) =
+        get_claim_status_pda(&PROGRAM_ID, &claimant_keypair.pubkey(), &distributor_pda);
+    let claim_status_account = rpc
+        .get_compressed_account(claim_status_address, None)
+        .await
+        .unwrap()
+        .value
+        .expect("Claim status account not found");
+    let claim_status =
+        ClaimStatus::deserialize(&mut claim_status_account.data.as_ref().unwrap().data.as_slice())
+            .unwrap();
+    assert_eq!(claim_status.claimant, claimant_keypair.pubkey());
+    assert_eq!(
+        claim_status.locked_amount_withdrawn,
+        claimant_node.amount_locked()
+    );
+}
+
+#[tokio::test]
+async fn test_native_distributor_pays_lamports_and_claws_back() {
+    use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+    use jito_merkle_tree::utils::get_sol_vault_pda;
+    use merkle_distributor::{
+        state::merkle_distributor::{DistributionMode, MerkleDistributor},
+        ID as PROGRAM_ID,
+    };
+    use solana_program::clock::Clock;
+
+    let config = ProgramTestConfig::new_v2(true, Some(vec![("merkle_distributor", PROGRAM_ID)]));
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+
+    let (merkle_tree, test_keypairs) = create_test_merkle_tree();
+    let claimant_keypair = &test_keypairs[0];
+    let claimant_node = merkle_tree.get_node_unchecked(&claimant_keypair.pubkey());
+
+    let current_time = rpc.context.get_sysvar::<Clock>().unix_timestamp;
+    let start_vesting_ts = current_time + 10;
+    let end_vesting_ts = current_time + 3600;
+    let clawback_start_ts = end_vesting_ts + 86400;
+
+    let (distributor_pda, _bump) =
+        get_merkle_distributor_pda(&PROGRAM_ID, &spl_token::native_mint::id(), 0);
+    let (sol_vault, _bump) = get_sol_vault_pda(&PROGRAM_ID, &distributor_pda);
+    let clawback_receiver = Keypair::new().pubkey();
+
+    let new_distributor_ix = solana_program::instruction::Instruction {
+        program_id: PROGRAM_ID,
+        accounts: merkle_distributor::accounts::NewNativeDistributor {
+            distributor: distributor_pda,
+            clawback_receiver,
+            sol_vault,
+            admin: payer.pubkey(),
+            system_program: solana_program::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: merkle_distributor::instruction::NewNativeDistributor {
+            version: 0,
+            root: merkle_tree.merkle_root,
+            max_total_claim: merkle_tree.max_total_claim,
+            max_num_nodes: merkle_tree.max_num_nodes,
+            start_vesting_ts,
+            end_vesting_ts,
+            cliff_ts: start_vesting_ts,
+            clawback_start_ts,
+            min_claim_interval: 0,
+            vesting_interval_secs: 1,
+            tree_depth: merkle_tree.tree_depth(),
+        }
+        .data(),
+    };
+    let fund_vault_ix = solana_program::system_instruction::transfer(
+        &payer.pubkey(),
+        &sol_vault,
+        merkle_tree.max_total_claim,
+    );
+    let fund_claimant_ix = solana_program::system_instruction::transfer(
+        &payer.pubkey(),
+        &claimant_keypair.pubkey(),
+        1_000_000_000,
+    );
+    send_transaction(
+        &mut rpc,
+        &[new_distributor_ix, fund_vault_ix, fund_claimant_ix],
+        &[&payer],
+    )
+    .await
+    .unwrap();
+
+    let distributor_account = rpc.get_account(distributor_pda).await.unwrap().unwrap();
+    let distributor =
+        MerkleDistributor::try_deserialize(&mut distributor_account.data.as_slice()).unwrap();
+    assert_eq!(distributor.distribution_mode, DistributionMode::Native);
+    assert_eq!(distributor.token_vault, sol_vault);
+    let rent_exempt_minimum = rpc.get_minimum_balance_for_rent_exemption(0).await.unwrap();
+    let vault_lamports = rpc.get_account(sol_vault).await.unwrap().unwrap().lamports;
+    assert_eq!(
+        vault_lamports,
+        rent_exempt_minimum + merkle_tree.max_total_claim
+    );
+
+    let (packed_account_metas, proof, address_tree_info, output_state_tree_index) =
+        new_claim_light_inputs(&mut rpc, &claimant_keypair.pubkey(), &distributor_pda).await;
+    let new_claim_ix = solana_program::instruction::Instruction {
+        program_id: PROGRAM_ID,
+        accounts: [
+            merkle_distributor::accounts::NewClaimNative {
+                distributor: distributor_pda,
+                from: sol_vault,
+                claimant: claimant_keypair.pubkey(),
+                system_program: solana_program::system_program::ID,
+            }
+            .to_account_metas(None),
+            packed_account_metas,
+        ]
+        .concat(),
+        data: merkle_distributor::instruction::NewClaimNative {
+            amount_unlocked: claimant_node.amount_unlocked(),
+            amount_locked: claimant_node.amount_locked(),
+            proof: claimant_node.proof.clone().expect("proof not found"),
+            unlock_schedule: None,
+            category_breakdown: Some(category_breakdown(&claimant_node)),
+            validity_proof: proof,
+            address_tree_info,
+            output_state_tree_index,
+        }
+        .data(),
+    };
+    send_transaction(&mut rpc, &[new_claim_ix], &[&payer, claimant_keypair])
+        .await
+        .unwrap();
+
+    // The claimant pays the Light fees of its claim status, so check the vault side
+    let vault_lamports = rpc.get_account(sol_vault).await.unwrap().unwrap().lamports;
+    assert_eq!(
+        vault_lamports,
+        rent_exempt_minimum + merkle_tree.max_total_claim - claimant_node.amount_unlocked()
+    );
+
+    let mut clock = rpc.context.get_sysvar::<Clock>();
+    clock.unix_timestamp = clawback_start_ts + 1;
+    rpc.context.set_sysvar(&clock);
+
+    let clawback_ix = solana_program::instruction::Instruction {
+        program_id: PROGRAM_ID,
+        accounts: merkle_distributor::accounts::ClawbackNative {
+            distributor: distributor_pda,
+            from: sol_vault,
+            to: clawback_receiver,
+            claimant: payer.pubkey(),
+            system_program: solana_program::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: merkle_distributor::instruction::ClawbackNative {}.data(),
+    };
+    let close_distributor_ix = solana_program::instruction::Instruction {
+        program_id: PROGRAM_ID,
+        accounts: merkle_distributor::accounts::CloseNativeDistributor {
+            distributor: distributor_pda,
+            admin: payer.pubkey(),
+        }
+        .to_account_metas(None),
+        data: merkle_distributor::instruction::CloseNativeDistributor {}.data(),
+    };
+    send_transaction(&mut rpc, &[clawback_ix, close_distributor_ix], &[&payer])
+        .await
+        .unwrap();
+
+    // Clawback takes the rent-exempt minimum too, closing the vault
+    assert!(rpc.get_account(sol_vault).await.unwrap().is_none());
+    let receiver_lamports = rpc
+        .get_account(clawback_receiver)
+        .await
+        .unwrap()
+        .unwrap()
+        .lamports;
+    assert_eq!(
+        receiver_lamports,
+        rent_exempt_minimum + merkle_tree.max_total_claim - claimant_node.amount_unlocked()
+    );
+    assert!(rpc.get_account(distributor_pda).await.unwrap().is_none());
+}
+
+#[test]
+fn test_merkle_proof_verification() {
+    // Create merkle tree directly
+    let (merkle_tree, _test_keypairs) = create_test_merkle_tree();
+
+    // Test proof verification for each node in the tree
+    for node in merkle_tree.tree_nodes() {
+        let proof = node.proof.as_ref().unwrap();
+
+        // The proof should not be empty for a tree with multiple nodes
+        assert!(!proof.is_empty());
+
+        // Each proof should be valid (this is tested internally by the merkle tree library)
+        println!("✅ Proof verified for claimant: {}", node.claimant);
+    }
+
+    println!("✅ Merkle proof verification test completed successfully!");
+}
+
+async fn send_transaction(
+    rpc: &mut LightProgramTest,
+    instructions: &[solana_program::instruction::Instruction],
+    signers: &[&Keypair],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (blockhash, _) = rpc.get_latest_blockhash().await?;
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&signers[0].pubkey()),
+        signers,
+        blockhash,
+    );
+    rpc.process_transaction(transaction).await?;
+    Ok(())
+}
+
+/// Overwrites the balance of an SPL token account, e.g. to underfund a vault.
+async fn set_token_account_amount(
+    rpc: &mut LightProgramTest,
+    token_account: &solana_sdk::pubkey::Pubkey,
+    amount: u64,
+) {
+    let mut account = rpc.get_account(*token_account).await.unwrap().unwrap();
+    let mut token_account_data = spl_token::state::Account::unpack(&account.data).unwrap();
+    token_account_data.amount = amount;
+    spl_token::state::Account::pack(token_account_data, &mut account.data).unwrap();
+    rpc.context.set_account(*token_account, account).unwrap();
+}
+
+/// Create a mint, a distributor for `merkle_tree` and fund its vault with `max_total_claim`.
+/// Returns the mint, distributor PDA and token vault.
+async fn setup_funded_distributor(
+    rpc: &mut LightProgramTest,
+    payer: &Keypair,
+    merkle_tree: &AirdropMerkleTree,
+    start_vesting_ts: i64,
+    end_vesting_ts: i64,
+    clawback_start_ts: i64,
+) -> (
+    solana_sdk::pubkey::Pubkey,
+    solana_sdk::pubkey::Pubkey,
+    solana_sdk::pubkey::Pubkey,
+) {
+    setup_funded_distributor_with_token_program(
+        rpc,
+        payer,
+        merkle_tree,
+        start_vesting_ts,
+        end_vesting_ts,
+        clawback_start_ts,
+        &spl_token::id(),
+    )
+    .await
+}
+
+/// Like [setup_funded_distributor], with the mint owned by `token_program`, either SPL Token
+/// or Token-2022.
+async fn setup_funded_distributor_with_token_program(
+    rpc: &mut LightProgramTest,
+    payer: &Keypair,
+    merkle_tree: &AirdropMerkleTree,
+    start_vesting_ts: i64,
+    end_vesting_ts: i64,
+    clawback_start_ts: i64,
+    token_program: &solana_sdk::pubkey::Pubkey,
+) -> (
+    solana_sdk::pubkey::Pubkey,
+    solana_sdk::pubkey::Pubkey,
+    solana_sdk::pubkey::Pubkey,
+) {
+    let mint = create_mint(rpc, payer, token_program, None).await;
+    let (distributor_pda, distributor_token_account) = setup_funded_distributor_with_mint(
+        rpc,
+        payer,
+        merkle_tree,
+        start_vesting_ts,
+        end_vesting_ts,
+        clawback_start_ts,
+        0,
+        false,
+        &mint,
+    )
+    .await;
+    (mint, distributor_pda, distributor_token_account)
+}
+
+/// Create a mint with 9 decimals and `payer` as mint authority, owned by `token_program`.
+/// `transfer_fee_basis_points` adds the Token-2022 transfer-fee extension, without a fee cap.
+async fn create_mint(
+    rpc: &mut LightProgramTest,
+    payer: &Keypair,
+    token_program: &solana_sdk::pubkey::Pubkey,
+    transfer_fee_basis_points: Option<u16>,
+) -> solana_sdk::pubkey::Pubkey {
+    create_mint_with_transfer_fee(
+        rpc,
+        payer,
+        token_program,
+        transfer_fee_basis_points.map(|basis_points| (basis_points, u64::MAX)),
+    )
+    .await
+}
+
+/// Like [create_mint], with a transfer fee capped at `maximum_fee` per transfer.
+async fn create_mint_with_fee_cap(
+    rpc: &mut LightProgramTest,
+    payer: &Keypair,
+    token_program: &solana_sdk::pubkey::Pubkey,
+    transfer_fee_basis_points: u16,
+    maximum_fee: u64,
+) -> solana_sdk::pubkey::Pubkey {
+    create_mint_with_transfer_fee(
+        rpc,
+        payer,
+        token_program,
+        Some((transfer_fee_basis_points, maximum_fee)),
+    )
+    .await
+}
+
+async fn create_mint_with_transfer_fee(
+    rpc: &mut LightProgramTest,
+    payer: &Keypair,
+    token_program: &solana_sdk::pubkey::Pubkey,
+    fee_config: Option<(u16, u64)>,
+) -> solana_sdk::pubkey::Pubkey {
+    use spl_token_2022::extension::{transfer_fee, ExtensionType};
+
+    let mint_keypair = Keypair::new();
+    let mint = mint_keypair.pubkey();
+    let extensions = match fee_config {
+        Some(_) => vec![ExtensionType::TransferFeeConfig],
+        None => vec![],
+    };
+    let mint_len =
+        ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&extensions)
+            .unwrap();
+    let rent = rpc
+        .get_minimum_balance_for_rent_exemption(mint_len)
+        .await
+        .unwrap();
+    let mut instructions = vec![solana_program::system_instruction::create_account(
+        &payer.pubkey(),
+        &mint,
+        rent,
+        mint_len as u64,
+        token_program,
+    )];
+    if let Some((basis_points, maximum_fee)) = fee_config {
+        instructions.push(
+            transfer_fee::instruction::initialize_transfer_fee_config(
+                token_program,
+                &mint,
+                None,
+                None,
+                basis_points,
+                maximum_fee,
+            )
+            .unwrap(),
+        );
+    }
+    // The spl-token-2022 instruction builders accept either token program
+    instructions.push(
+        spl_token_2022::instruction::initialize_mint(
+            token_program,
+            &mint,
+            &payer.pubkey(),
+            Some(&payer.pubkey()),
+            9,
+        )
+        .unwrap(),
+    );
+    send_transaction(rpc, &instructions, &[payer, &mint_keypair])
+        .await
+        .unwrap();
+    mint
+}
+
+/// Create a distributor for `merkle_tree` over an existing `mint` and fund its vault with
+/// `max_total_claim`. Returns the distributor PDA and token vault.
+async fn setup_funded_distributor_with_mint(
+    rpc: &mut LightProgramTest,
+    payer: &Keypair,
+    merkle_tree: &AirdropMerkleTree,
+    start_vesting_ts: i64,
+    end_vesting_ts: i64,
+    clawback_start_ts: i64,
+    min_claim_interval: i64,
+    allow_reassignment: bool,
+    mint: &solana_sdk::pubkey::Pubkey,
+) -> (solana_sdk::pubkey::Pubkey, solana_sdk::pubkey::Pubkey) {
+    use merkle_distributor::ID as PROGRAM_ID;
+
+    let mint = *mint;
+    let token_program = rpc.get_account(mint).await.unwrap().unwrap().owner;
+
+    let (distributor_pda, _bump) = get_merkle_distributor_pda(&PROGRAM_ID, &mint, 0);
+    let distributor_token_account =
+        get_associated_token_address_with_program_id(&distributor_pda, &mint, &token_program);
+
+    let clawback_token_account =
+        get_associated_token_address_with_program_id(&payer.pubkey(), &mint, &token_program);
+    let create_clawback_ata_ix =
+        create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &mint, &token_program);
+    send_transaction(rpc, &[create_clawback_ata_ix], &[payer])
+        .await
+        .unwrap();
+
+    let new_distributor_ix = create_distributor_instruction(
+        &PROGRAM_ID,
+        &distributor_pda,
+        &payer.pubkey(),
+        &mint,
+        &distributor_token_account,
+        &clawback_token_account,
+        merkle_tree,
+        start_vesting_ts,
+        end_vesting_ts,
+        clawback_start_ts,
+        min_claim_interval,
+        allow_reassignment,
+        &token_program,
+    );
+    send_transaction(rpc, &[new_distributor_ix], &[payer])
+        .await
+        .unwrap();
+
+    let mint_to_ix = spl_token_2022::instruction::mint_to(
+        &token_program,
+        &mint,
+        &distributor_token_account,
+        &payer.pubkey(),
+        &[],
+        merkle_tree.max_total_claim,
+    )
+    .unwrap();
+    send_transaction(rpc, &[mint_to_ix], &[payer])
+        .await
+        .unwrap();
+
+    (distributor_pda, distributor_token_account)
+}
+
+/// Fund the claimant, create its token account and send new_claim. Returns the claimant ATA.
+async fn new_claim(
+    rpc: &mut LightProgramTest,
+    payer: &Keypair,
+    claimant_keypair: &Keypair,
+    claimant_node: &jito_merkle_tree::tree_node::TreeNode,
+    mint: &solana_sdk::pubkey::Pubkey,
+    distributor_pda: &solana_sdk::pubkey::Pubkey,
+    distributor_token_account: &solana_sdk::pubkey::Pubkey,
+) -> solana_sdk::pubkey::Pubkey {
+    try_new_claim(
+        rpc,
+        payer,
+        claimant_keypair,
+        claimant_node,
+        mint,
+        distributor_pda,
+        distributor_token_account,
+    )
+    .await
+    .unwrap()
+}
+
+/// Like [new_claim], but returns the error if the new_claim transaction fails.
+async fn try_new_claim(
+    rpc: &mut LightProgramTest,
+    payer: &Keypair,
+    claimant_keypair: &Keypair,
+    claimant_node: &jito_merkle_tree::tree_node::TreeNode,
+    mint: &solana_sdk::pubkey::Pubkey,
+    distributor_pda: &solana_sdk::pubkey::Pubkey,
+    distributor_token_account: &solana_sdk::pubkey::Pubkey,
+) -> Result<solana_sdk::pubkey::Pubkey, Box<dyn std::error::Error>> {
+    use merkle_distributor::ID as PROGRAM_ID;
+
+    let (packed_account_metas, proof, address_tree_info, output_state_tree_index) =
+        new_claim_light_inputs(rpc, &claimant_keypair.pubkey(), distributor_pda).await;
+
+    let fund_claimant_ix = solana_program::system_instruction::transfer(
+        &payer.pubkey(),
+        &claimant_keypair.pubkey(),
+        1_000_000_000,
+    );
+    let token_program = rpc.get_account(*mint).await?.expect("mint not found").owner;
+    let claimant_ata = get_associated_token_address_with_program_id(
+        &claimant_keypair.pubkey(),
+        mint,
+        &token_program,
+    );
+    let create_claimant_ata_ix = create_associated_token_account(
+        &payer.pubkey(),
+        &claimant_keypair.pubkey(),
+        mint,
+        &token_program,
+    );
+    send_transaction(rpc, &[fund_claimant_ix, create_claimant_ata_ix], &[payer])
+        .await
+        .unwrap();
+
+    let new_claim_ix = create_new_claim_instruction(
+        &PROGRAM_ID,
+        distributor_pda,
+        distributor_token_account,
+        &claimant_ata,
+        &claimant_keypair.pubkey(),
+        mint,
+        &token_program,
+        packed_account_metas,
+        claimant_node,
+        proof,
+        address_tree_info,
+        output_state_tree_index,
+    );
+    send_transaction(rpc, &[new_claim_ix], &[payer, claimant_keypair]).await?;
+
+    Ok(claimant_ata)
+}
+
+/// Light remaining accounts, validity proof, address tree info and output state tree index
+/// for creating the claimant's ClaimStatus.
+async fn new_claim_light_inputs(
+    rpc: &mut LightProgramTest,
+    claimant: &solana_sdk::pubkey::Pubkey,
+    distributor_pda: &solana_sdk::pubkey::Pubkey,
+) -> (
+    Vec<solana_program::instruction::AccountMeta>,
+    light_sdk::instruction::ValidityProof,
+    light_sdk::instruction::PackedAddressTreeInfo,
+    u8,
+) {
+    use merkle_distributor::ID as PROGRAM_ID;
+
+    let address_tree = rpc.test_accounts.v2_address_trees[0];
+    let (claim_status_address, _address_seed) =
+        get_claim_status_pda(&PROGRAM_ID, claimant, distributor_pda);
+    let proof = rpc
+        .get_validity_proof(
+            vec![],
+            vec![AddressWithTree {
+                address: claim_status_address,
+                tree: address_tree,
+            }],
+            None,
+        )
+        .await
+        .unwrap()
+        .value;
+
+    let mut packed_accounts = PackedAccounts::default();
+    packed_accounts
+        .add_system_accounts_v2(SystemAccountMetaConfig::new(PROGRAM_ID))
+        .unwrap();
+    let output_state_tree_index = rpc
+        .get_random_state_tree_info()
+        .unwrap()
+        .pack_output_tree_index(&mut packed_accounts)
+        .unwrap();
+    let address_tree_info = proof.pack_tree_infos(&mut packed_accounts).address_trees[0];
+    let (packed_account_metas, _, _) = packed_accounts.to_account_metas();
+
+    (
+        packed_account_metas,
+        proof.proof,
+        address_tree_info,
+        output_state_tree_index,
+    )
+}
+
+/// reassign_claim of `old_claimant_node` to `new_claimant`, paying the token account of
+/// `new_claimant`, with a validity proof that neither claim status address exists yet.
+async fn reassign_claim_instruction(
+    rpc: &mut LightProgramTest,
+    admin: &solana_sdk::pubkey::Pubkey,
+    old_claimant_node: &jito_merkle_tree::tree_node::TreeNode,
+    new_claimant: &solana_sdk::pubkey::Pubkey,
+    mint: &solana_sdk::pubkey::Pubkey,
+    distributor_pda: &solana_sdk::pubkey::Pubkey,
+    distributor_token_account: &solana_sdk::pubkey::Pubkey,
+) -> solana_program::instruction::Instruction {
+    use anchor_lang::{InstructionData, ToAccountMetas};
+    use merkle_distributor::ID as PROGRAM_ID;
+
+    let address_tree = rpc.test_accounts.v2_address_trees[0];
+    let addresses = [&old_claimant_node.claimant, new_claimant]
+        .into_iter()
+        .map(|claimant| AddressWithTree {
+            address: get_claim_status_pda(&PROGRAM_ID, claimant, distributor_pda).0,
+            tree: address_tree,
+        })
+        .collect();
+    let proof = rpc
+        .get_validity_proof(vec![], addresses, None)
+        .await
+        .unwrap()
+        .value;
+
+    let mut packed_accounts = PackedAccounts::default();
+    packed_accounts
+        .add_system_accounts_v2(SystemAccountMetaConfig::new(PROGRAM_ID))
+        .unwrap();
+    let output_state_tree_index = rpc
+        .get_random_state_tree_info()
+        .unwrap()
+        .pack_output_tree_index(&mut packed_accounts)
+        .unwrap();
+    let address_tree_infos = proof.pack_tree_infos(&mut packed_accounts).address_trees;
+    let (packed_account_metas, _, _) = packed_accounts.to_account_metas();
+
+    let token_program = rpc.get_account(*mint).await.unwrap().unwrap().owner;
+    solana_program::instruction::Instruction {
+        program_id: PROGRAM_ID,
+        accounts: [
+            merkle_distributor::accounts::ReassignClaim {
+                distributor: *distributor_pda,
+                from: *distributor_token_account,
+                to: get_associated_token_address_with_program_id(
+                    new_claimant,
+                    mint,
+                    &token_program,
+                ),
+                admin: *admin,
+                mint: *mint,
+                token_program,
+            }
+            .to_account_metas(None),
+            packed_account_metas,
+        ]
+        .concat(),
+        data: merkle_distributor::instruction::ReassignClaim {
+            old_claimant: old_claimant_node.claimant,
+            new_claimant: *new_claimant,
+            amount_unlocked: old_claimant_node.amount_unlocked(),
+            amount_locked: old_claimant_node.amount_locked(),
+            proof: old_claimant_node.proof.clone().expect("proof not found"),
+            unlock_schedule: None,
+            category_breakdown: Some(category_breakdown(old_claimant_node)),
+            validity_proof: proof.proof,
+            old_address_tree_info: address_tree_infos[0],
+            new_address_tree_info: address_tree_infos[1],
+            output_state_tree_index,
+        }
+        .data(),
+    }
+}
+
+/// Sponsored new_claim: `payer` creates the claimant ATA and sends the transaction, the
+/// claimant only authorizes it through `message_signer`'s off-chain signature.
+async fn try_new_claim_signed(
+    rpc: &mut LightProgramTest,
+    payer: &Keypair,
+    claimant: &solana_sdk::pubkey::Pubkey,
+    message_signer: &Keypair,
+    claimant_node: &jito_merkle_tree::tree_node::TreeNode,
+    mint: &solana_sdk::pubkey::Pubkey,
+    distributor_pda: &solana_sdk::pubkey::Pubkey,
+    distributor_token_account: &solana_sdk::pubkey::Pubkey,
+) -> Result<solana_sdk::pubkey::Pubkey, Box<dyn std::error::Error>> {
+    use anchor_lang::{InstructionData, ToAccountMetas};
+    use merkle_distributor::{instructions::claim_message, ID as PROGRAM_ID};
+    use solana_sdk::ed25519_instruction::new_ed25519_instruction_with_signature;
+
+    let (packed_account_metas, proof, address_tree_info, output_state_tree_index) =
+        new_claim_light_inputs(rpc, claimant, distributor_pda).await;
+
+    let token_program = rpc.get_account(*mint).await?.expect("mint not found").owner;
+    let claimant_ata = get_associated_token_address_with_program_id(claimant, mint, &token_program);
+    if rpc.get_account(claimant_ata).await?.is_none() {
+        let create_claimant_ata_ix =
+            create_associated_token_account(&payer.pubkey(), claimant, mint, &token_program);
+        send_transaction(rpc, &[create_claimant_ata_ix], &[payer])
+            .await
+            .unwrap();
+    }
+
+    let message = claim_message(distributor_pda);
+    let signature = message_signer.sign_message(&message);
+    let ed25519_ix = new_ed25519_instruction_with_signature(
+        &message,
+        signature.as_ref().try_into().unwrap(),
+        &message_signer.pubkey().to_bytes(),
+    );
+
+    let new_claim_signed_ix = solana_program::instruction::Instruction {
+        program_id: PROGRAM_ID,
+        accounts: [
+            merkle_distributor::accounts::NewClaimSigned {
+                distributor: *distributor_pda,
+                from: *distributor_token_account,
+                to: claimant_ata,
+                claimant: *claimant,
+                payer: payer.pubkey(),
+                instructions_sysvar: solana_program::sysvar::instructions::ID,
+                mint: *mint,
+                token_program,
+            }
+            .to_account_metas(None),
+            packed_account_metas,
+        ]
+        .concat(),
+        data: merkle_distributor::instruction::NewClaimSigned {
+            amount_unlocked: claimant_node.amount_unlocked(),
+            amount_locked: claimant_node.amount_locked(),
+            proof: claimant_node.proof.clone().expect("proof not found"),
+            unlock_schedule: claimant_node.unlock_schedule.map(|schedule| {
+                merkle_distributor::state::claim_status::UnlockSchedule {
+                    start_ts: schedule.start_ts,
+                    end_ts: schedule.end_ts,
+                }
+            }),
+            category_breakdown: Some(category_breakdown(claimant_node)),
+            validity_proof: proof,
+            address_tree_info,
+            output_state_tree_index,
+        }
+        .data(),
+    };
+    send_transaction(rpc, &[ed25519_ix, new_claim_signed_ix], &[payer]).await?;
+
+    Ok(claimant_ata)
+}
+
+/// Send claim_locked for an existing compressed ClaimStatus.
+async fn claim_locked(
+    rpc: &mut LightProgramTest,
+    claimant_keypair: &Keypair,
+    distributor_pda: &solana_sdk::pubkey::Pubkey,
+    distributor_token_account: &solana_sdk::pubkey::Pubkey,
+    claimant_ata: &solana_sdk::pubkey::Pubkey,
+) {
+    let claim_locked_ix = claim_locked_instruction(
+        rpc,
+        claimant_keypair,
+        distributor_pda,
+        distributor_token_account,
+        claimant_ata,
+    )
+    .await;
+    send_transaction(rpc, &[claim_locked_ix], &[claimant_keypair])
+        .await
+        .unwrap();
+}
+
+/// claim_locked instruction for an existing compressed ClaimStatus, with a fresh validity proof.
+async fn claim_locked_instruction(
+    rpc: &mut LightProgramTest,
+    claimant_keypair: &Keypair,
+    distributor_pda: &solana_sdk::pubkey::Pubkey,
+    distributor_token_account: &solana_sdk::pubkey::Pubkey,
+    claimant_ata: &solana_sdk::pubkey::Pubkey,
+) -> solana_program::instruction::Instruction {
+    use anchor_lang::{AccountDeserialize, AnchorDeserialize, InstructionData, ToAccountMetas};
+    use light_sdk::instruction::{account_meta::CompressedAccountMeta, PackedStateTreeInfo};
+    use merkle_distributor::{
+        state::{
+            claim_status::{ClaimStatus, ClaimStatusInstructionData},
+            merkle_distributor::MerkleDistributor,
+        },
+        ID as PROGRAM_ID,
+    };
+
+    let distributor_account = rpc.get_account(*distributor_pda).await.unwrap().unwrap();
+    let mint = MerkleDistributor::try_deserialize(&mut distributor_account.data.as_slice())
+        .unwrap()
+        .mint;
+    let token_program = rpc.get_account(mint).await.unwrap().unwrap().owner;
+
+    let (claim_status_address, _) =
+        get_claim_status_pda(&PROGRAM_ID, &claimant_keypair.pubkey(), distributor_pda);
+    let compressed_account = rpc
+        .get_compressed_account(claim_status_address, None)
+        .await
+        .unwrap()
+        .value
+        .expect("Claim status account not found");
+    let claim_status =
+        ClaimStatus::deserialize(&mut compressed_account.data.as_ref().unwrap().data.as_slice())
+            .unwrap();
+
+    let validity_proof = rpc
+        .get_validity_proof(vec![compressed_account.hash], vec![], None)
+        .await
+        .unwrap()
+        .value;
+
+    let mut packed_accounts = PackedAccounts::default();
+    packed_accounts
+        .add_system_accounts_v2(SystemAccountMetaConfig::new(PROGRAM_ID))
+        .unwrap();
+    let merkle_tree_index = packed_accounts.insert_or_get(compressed_account.tree_info.tree);
+    let queue_index = packed_accounts.insert_or_get(compressed_account.tree_info.queue);
+    let input_account_meta = CompressedAccountMeta {
+        tree_info: PackedStateTreeInfo {
+            root_index: validity_proof.accounts[0]
+                .root_index
+                .root_index()
+                .unwrap_or_default(),
+            prove_by_index: validity_proof.accounts[0].root_index.proof_by_index(),
+            merkle_tree_pubkey_index: merkle_tree_index,
+            queue_pubkey_index: queue_index,
+            leaf_index: compressed_account.leaf_index,
+        },
+        address: claim_status_address,
+        output_state_tree_index: queue_index,
+    };
+    let (packed_account_metas, _, _) = packed_accounts.to_account_metas();
+
+    solana_program::instruction::Instruction {
+        program_id: PROGRAM_ID,
+        accounts: [
+            merkle_distributor::accounts::ClaimLocked {
+                distributor: *distributor_pda,
+                from: *distributor_token_account,
+                to: *claimant_ata,
+                claimant: claimant_keypair.pubkey(),
+                mint,
+                token_program,
+            }
+            .to_account_metas(None),
+            packed_account_metas,
+        ]
+        .concat(),
+        data: merkle_distributor::instruction::ClaimLocked {
+            claim_status_data: ClaimStatusInstructionData {
+                locked_amount: claim_status.locked_amount,
+                locked_amount_withdrawn: claim_status.locked_amount_withdrawn,
+                unlocked_amount: claim_status.unlocked_amount,
+                unlock_schedule: claim_status.unlock_schedule,
+                last_claimed_ts: claim_status.last_claimed_ts,
+                category_breakdown: claim_status.category_breakdown,
+            },
+            validity_proof: validity_proof.proof,
+            input_account_meta,
+        }
+        .data(),
+    }
+}
+
+fn create_distributor_instruction(
+    program_id: &solana_sdk::pubkey::Pubkey,
+    distributor_pda: &solana_sdk::pubkey::Pubkey,
+    admin: &solana_sdk::pubkey::Pubkey,
+    mint: &solana_sdk::pubkey::Pubkey,
+    token_vault: &solana_sdk::pubkey::Pubkey,
+    clawback_receiver: &solana_sdk::pubkey::Pubkey,
+    merkle_tree: &AirdropMerkleTree,
+    start_vesting_ts: i64,
+    end_vesting_ts: i64,
+    clawback_start_ts: i64,
+    min_claim_interval: i64,
+    allow_reassignment: bool,
+    token_program: &solana_sdk::pubkey::Pubkey,
+) -> solana_program::instruction::Instruction {
+    use anchor_lang::{InstructionData, ToAccountMetas};
+
+    solana_program::instruction::Instruction {
+        program_id: *program_id,
+        accounts: merkle_distributor::accounts::NewDistributor {
+            distributor: *distributor_pda,
+            admin: *admin,
+            mint: *mint,
+            token_vault: *token_vault,
+            clawback_receiver: *clawback_receiver,
+            system_program: solana_program::system_program::ID,
+            token_program: *token_program,
+            associated_token_program: spl_associated_token_account::id(),
+        }
+        .to_account_metas(None),
+        data: merkle_distributor::instruction::NewDistributor {
+            version: 0,
+            root: merkle_tree.merkle_root,
+            max_total_claim: merkle_tree.max_total_claim,
+            max_num_nodes: merkle_tree.max_num_nodes,
+            start_vesting_ts,
+            end_vesting_ts,
+            cliff_ts: start_vesting_ts,
+            clawback_start_ts,
+            min_claim_interval,
+            vesting_interval_secs: 1,
+            allow_reassignment,
+            tree_depth: merkle_tree.tree_depth(),
+        }
+        .data(),
+    }
+}
+
+fn create_new_claim_instruction(
+    program_id: &solana_sdk::pubkey::Pubkey,
+    distributor_pda: &solana_sdk::pubkey::Pubkey,
+    from: &solana_sdk::pubkey::Pubkey,
+    to: &solana_sdk::pubkey::Pubkey,
+    claimant: &solana_sdk::pubkey::Pubkey,
+    mint: &solana_sdk::pubkey::Pubkey,
+    token_program: &solana_sdk::pubkey::Pubkey,
+    packed_account_metas: Vec<solana_program::instruction::AccountMeta>,
+    claimant_node: &jito_merkle_tree::tree_node::TreeNode,
+    validity_proof: light_sdk::instruction::ValidityProof,
+    address_tree_info: light_sdk::instruction::PackedAddressTreeInfo,
+    output_state_tree_index: u8,
+) -> solana_program::instruction::Instruction {
+    use anchor_lang::{InstructionData, ToAccountMetas};
+
+    solana_program::instruction::Instruction {
+        program_id: *program_id,
+        accounts: [
+            merkle_distributor::accounts::NewClaim {
+                distributor: *distributor_pda,
+                from: *from,
+                to: *to,
+                claimant: *claimant,
+                mint: *mint,
+                token_program: *token_program,
+            }
+            .to_account_metas(None),
+            packed_account_metas,
+        ]
+        .concat(),
+        data: merkle_distributor::instruction::NewClaim {
+            amount_unlocked: claimant_node.amount_unlocked(),
+            amount_locked: claimant_node.amount_locked(),
+            proof: claimant_node.proof.clone().expect("proof not found"),
+            unlock_schedule: claimant_node.unlock_schedule.map(|schedule| {
+                merkle_distributor::state::claim_status::UnlockSchedule {
+                    start_ts: schedule.start_ts,
+                    end_ts: schedule.end_ts,
+                }
+            }),
+            category_breakdown: Some(category_breakdown(claimant_node)),
+            validity_proof,
+            address_tree_info,
+            output_state_tree_index,
+        }
+        .data(),
+    }
+}
+
+/// Create test data and merkle tree without CSV files
+fn category_breakdown(
+    node: &jito_merkle_tree::tree_node::TreeNode,
+) -> merkle_distributor::state::claim_status::CategoryBreakdown {
+    merkle_distributor::state::claim_status::CategoryBreakdown {
+        unlocked_staker: node.total_unlocked_staker,
+        locked_staker: node.total_locked_staker,
+        unlocked_searcher: node.total_unlocked_searcher,
+        locked_searcher: node.total_locked_searcher,
+        unlocked_validator: node.total_unlocked_validator,
+        locked_validator: node.total_locked_validator,
+    }
 }
 
-/// Create test data and merkle tree without CSV files
 fn create_test_merkle_tree() -> (AirdropMerkleTree, Vec<Keypair>) {
     use jito_merkle_tree::tree_node::TreeNode;
 
@@ -447,6 +4066,7 @@ fn create_test_merkle_tree() -> (AirdropMerkleTree, Vec<Keypair>) {
             total_unlocked_validator: 0,
             total_locked_validator: 0,
             proof: None, // Will be set by AirdropMerkleTree::new
+            unlock_schedule: None,
         },
         TreeNode {
             claimant: test_keypairs[1].pubkey(),
@@ -457,6 +4077,7 @@ fn create_test_merkle_tree() -> (AirdropMerkleTree, Vec<Keypair>) {
             total_unlocked_validator: 2000,
             total_locked_validator: 1000,
             proof: None, // Will be set by AirdropMerkleTree::new
+            unlock_schedule: None,
         },
     ];
 